@@ -0,0 +1,7 @@
+fn main() {
+    // The triple `cargo::runner` looks up `CARGO_TARGET_<TRIPLE>_RUNNER` for, which is only
+    // known to build scripts.
+    let target = std::env::var("TARGET").expect("cargo sets TARGET for build scripts");
+    println!("cargo:rustc-env=ASSERT_CMD_TARGET={}", target);
+    println!("cargo:rerun-if-changed=build.rs");
+}
@@ -0,0 +1,303 @@
+//! Group repeated assertion failures, like those from a data-driven loop.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use assert_cmd::aggregate::Failures;
+//! use assert_cmd::Command;
+//!
+//! let mut failures = Failures::new();
+//! for input in &["1", "2", "3"] {
+//!     failures.check(|| {
+//!         Command::cargo_bin("bin_fixture")
+//!             .unwrap()
+//!             .write_stdin(*input)
+//!             .assert()
+//!             .success()
+//!     });
+//! }
+//! failures.finish();
+//! ```
+
+use std::any::Any;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt;
+use std::panic;
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+
+thread_local! {
+    static COLLECTING: Cell<usize> = const { Cell::new(0) };
+}
+
+/// An assertion failure raised while a [`Failures::check`] is running.
+struct Failure {
+    signature: String,
+    dump: String,
+}
+
+/// Report an assertion failure, either by panicking or, within [`Failures::check`], by handing
+/// it to the collector without running the panic hook.
+pub(crate) fn fail(signature: String, dump: String) -> ! {
+    if COLLECTING.with(|c| c.get()) == 0 {
+        let message = format!("{}\n{}", signature, dump);
+        // Held until unwinding starts, so the panic hook has printed all of `message` before
+        // another thread's failure can start printing.
+        let _reporting = REPORTING.lock().unwrap_or_else(PoisonError::into_inner);
+        panic::panic_any(message);
+    }
+    panic::resume_unwind(Box::new(Failure { signature, dump }))
+}
+
+/// Whether failures are being collected rather than reported.
+pub(crate) fn is_collecting() -> bool {
+    COLLECTING.with(|c| c.get()) != 0
+}
+
+/// Run `check`, returning any panic, described like an assertion failure, instead of
+/// panicking.
+pub(crate) fn catch_all<R>(check: impl FnOnce() -> R) -> Result<R, (String, String)> {
+    COLLECTING.with(|c| c.set(c.get() + 1));
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(check));
+    COLLECTING.with(|c| c.set(c.get() - 1));
+    result.map_err(describe)
+}
+
+/// Run `check`, returning its assertion failure as `(signature, dump)` instead of panicking.
+///
+/// Other panics keep unwinding.
+pub(crate) fn catch<R>(check: impl FnOnce() -> R) -> Result<R, (String, String)> {
+    COLLECTING.with(|c| c.set(c.get() + 1));
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(check));
+    COLLECTING.with(|c| c.set(c.get() - 1));
+    match result {
+        Ok(value) => Ok(value),
+        Err(payload) => match payload.downcast::<Failure>() {
+            Ok(failure) => Err((failure.signature, failure.dump)),
+            Err(payload) => panic::resume_unwind(payload),
+        },
+    }
+}
+
+/// Serializes failure reports across threads.
+///
+/// Poisoning is ignored because the guard is released while panicking.
+static REPORTING: Mutex<()> = Mutex::new(());
+
+/// Collect the failures of many checks, reporting each distinct failure once.
+///
+/// Failures are grouped by their headline (and [`Assert::with_message`]), so the same
+/// assertion failing for every iteration of a loop is reported with a count and a single
+/// representative dump.
+///
+/// Call [`Failures::finish`] after the last check; dropping unfinished `Failures` reports too.
+///
+/// [`Assert::with_message`]: crate::assert::Assert::with_message()
+#[derive(Debug, Default)]
+pub struct Failures {
+    checks: usize,
+    groups: Vec<Group>,
+    index: HashMap<String, usize>,
+    finished: bool,
+}
+
+#[derive(Debug)]
+struct Group {
+    signature: String,
+    dump: String,
+    count: usize,
+    first: usize,
+}
+
+impl Failures {
+    /// Start collecting.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run one check, recording its failure instead of panicking.
+    ///
+    /// Returns the check's result if it passed.
+    pub fn check<R, F>(&mut self, check: F) -> Option<R>
+    where
+        F: FnOnce() -> R,
+    {
+        let check_index = self.checks;
+        self.checks += 1;
+
+        COLLECTING.with(|c| c.set(c.get() + 1));
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(check));
+        COLLECTING.with(|c| c.set(c.get() - 1));
+
+        match result {
+            Ok(value) => Some(value),
+            Err(payload) => {
+                let (signature, dump) = describe(payload);
+                self.record(check_index, signature, dump);
+                None
+            }
+        }
+    }
+
+    /// The number of checks that failed.
+    pub fn failed(&self) -> usize {
+        self.groups.iter().map(|g| g.count).sum()
+    }
+
+    /// Panic with a summary if any check failed.
+    pub fn finish(mut self) {
+        self.finished = true;
+        if !self.groups.is_empty() {
+            panic!("{}", self);
+        }
+    }
+
+    /// Give up on reporting, returning the summary instead.
+    pub(crate) fn into_summary(mut self) -> String {
+        self.finished = true;
+        self.to_string()
+    }
+
+    fn record(&mut self, check_index: usize, signature: String, dump: String) {
+        match self.index.get(&signature) {
+            Some(&i) => self.groups[i].count += 1,
+            None => {
+                self.index.insert(signature.clone(), self.groups.len());
+                self.groups.push(Group {
+                    signature,
+                    dump,
+                    count: 1,
+                    first: check_index,
+                });
+            }
+        }
+    }
+}
+
+/// The pass/fail distribution of a check that is allowed to be flaky.
+///
+/// See [`Command::allow_flaky`].
+///
+/// [`Command::allow_flaky`]: crate::cmd::Command::allow_flaky()
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Flakiness {
+    /// How many times the command was run.
+    pub runs: usize,
+    /// How many of the runs passed the check.
+    pub passed: usize,
+}
+
+impl Flakiness {
+    /// Whether every run passed.
+    pub fn is_stable(&self) -> bool {
+        self.runs == self.passed
+    }
+}
+
+impl fmt::Display for Flakiness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "passed {} of {} runs", self.passed, self.runs)
+    }
+}
+
+fn describe(payload: Box<dyn Any + Send>) -> (String, String) {
+    let payload = match payload.downcast::<Failure>() {
+        Ok(failure) => return (failure.signature, failure.dump),
+        Err(payload) => payload,
+    };
+    let message = if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else if let Some(message) = payload.downcast_ref::<&str>() {
+        (*message).to_owned()
+    } else {
+        "Box<dyn Any>".to_owned()
+    };
+    (message, String::new())
+}
+
+impl fmt::Display for Failures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} checks failed ({} distinct)",
+            self.failed(),
+            self.checks,
+            self.groups.len()
+        )?;
+        for group in &self.groups {
+            writeln!(f)?;
+            writeln!(
+                f,
+                "--- {} times, first at check #{} ---",
+                group.count, group.first
+            )?;
+            write!(f, "{}", group.signature)?;
+            if !group.dump.is_empty() {
+                write!(f, "\n{}", group.dump)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Failures {
+    fn drop(&mut self) {
+        if !self.finished && !self.groups.is_empty() && !thread::panicking() {
+            panic!("{}", self);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn groups_identical_failures() {
+        let mut failures = Failures::new();
+        for i in 0..5 {
+            failures.check(|| fail("Unexpected failure".to_owned(), format!("run={}", i)));
+        }
+        failures.check(|| fail("Unexpected success".to_owned(), String::new()));
+        assert_eq!(failures.check(|| 42), Some(42));
+        assert_eq!(failures.failed(), 6);
+        assert_eq!(
+            failures.to_string(),
+            "6 of 7 checks failed (2 distinct)
+--- 5 times, first at check #0 ---
+Unexpected failure
+run=0
+--- 1 times, first at check #5 ---
+Unexpected success"
+        );
+        failures.finished = true;
+    }
+
+    #[test]
+    fn foreign_panics() {
+        let mut failures = Failures::new();
+        failures.check(|| panic!("boom"));
+        assert_eq!(failures.groups[0].signature, "boom");
+        failures.finished = true;
+    }
+
+    #[test]
+    fn catch_failures_only() {
+        assert_eq!(catch(|| 42), Ok(42));
+        assert_eq!(
+            catch(|| fail("headline".to_owned(), "dump".to_owned())),
+            Err(("headline".to_owned(), "dump".to_owned()))
+        );
+        assert!(panic::catch_unwind(|| catch(|| panic!("boom"))).is_err());
+    }
+
+    #[test]
+    fn outside_check_panics() {
+        for _ in 0..2 {
+            let result = panic::catch_unwind(|| fail("headline".to_owned(), "dump".to_owned()));
+            let payload = result.unwrap_err();
+            assert_eq!(payload.downcast_ref::<String>().unwrap(), "headline\ndump");
+        }
+    }
+}
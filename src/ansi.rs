@@ -0,0 +1,314 @@
+//! Recognize ANSI escape sequences in program output.
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+use bstr::ByteSlice;
+
+const ESC: u8 = 0x1b;
+const BEL: u8 = 0x07;
+
+/// An escape sequence found within a buffer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Sequence<'a> {
+    pub(crate) range: Range<usize>,
+    kind: Kind<'a>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Kind<'a> {
+    /// Control Sequence Introducer, `ESC [ <params> <intermediates> <final>`
+    Csi { params: &'a [u8], final_byte: u8 },
+    /// Operating System Command, `ESC ] ... (BEL | ESC \)`
+    Osc,
+    /// Any other two-byte escape
+    Other,
+}
+
+impl<'a> Sequence<'a> {
+    /// The parameters of a Select Graphic Rendition sequence (`ESC [ ... m`).
+    pub(crate) fn sgr_params(&self) -> Option<&'a [u8]> {
+        match self.kind {
+            Kind::Csi {
+                params,
+                final_byte: b'm',
+            } => Some(params),
+            _ => None,
+        }
+    }
+
+    /// Whether this is an Erase in Line sequence (`ESC [ ... K`), common in progress bars.
+    pub(crate) fn is_erase_line(&self) -> bool {
+        matches!(
+            self.kind,
+            Kind::Csi {
+                final_byte: b'K',
+                ..
+            }
+        )
+    }
+}
+
+/// Iterate over the escape sequences in `data`.
+pub(crate) fn sequences(data: &[u8]) -> Sequences<'_> {
+    Sequences { data, offset: 0 }
+}
+
+pub(crate) struct Sequences<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Iterator for Sequences<'a> {
+    type Item = Sequence<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let data = self.data;
+        let start = self.offset + memchr(ESC, &data[self.offset..])?;
+        let (end, kind) = parse_escape(data, start);
+        self.offset = end;
+        Some(Sequence {
+            range: start..end,
+            kind,
+        })
+    }
+}
+
+/// Remove all escape sequences from `data`.
+pub(crate) fn strip(data: &[u8]) -> Cow<'_, [u8]> {
+    let mut sequences = sequences(data).peekable();
+    if sequences.peek().is_none() {
+        return Cow::Borrowed(data);
+    }
+
+    let mut stripped = Vec::with_capacity(data.len());
+    let mut offset = 0;
+    for seq in sequences {
+        stripped.extend_from_slice(&data[offset..seq.range.start]);
+        offset = seq.range.end;
+    }
+    stripped.extend_from_slice(&data[offset..]);
+    Cow::Owned(stripped)
+}
+
+/// Whether `data` contains in-place updates, like progress bars, rather than plain lines.
+pub(crate) fn has_progress(data: &[u8]) -> Option<usize> {
+    let carriage_return = data
+        .iter()
+        .enumerate()
+        .position(|(i, b)| *b == b'\r' && data.get(i + 1) != Some(&b'\n'));
+    let erase_line = sequences(data)
+        .find(|seq| seq.is_erase_line())
+        .map(|seq| seq.range.start);
+    match (carriage_return, erase_line) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Render `data` as a terminal would, applying carriage returns and line erasure.
+///
+/// Other escape sequences are removed.
+pub(crate) fn render(data: &[u8]) -> String {
+    let mut rendered = String::with_capacity(data.len());
+    let mut line: Vec<char> = Vec::new();
+    let mut cursor = 0;
+    let mut offset = 0;
+    let mut write = |text: &[u8], line: &mut Vec<char>, cursor: &mut usize| {
+        for c in String::from_utf8_lossy(text).chars() {
+            match c {
+                '\r' => *cursor = 0,
+                '\n' => {
+                    rendered.extend(line.drain(..));
+                    rendered.push('\n');
+                    *cursor = 0;
+                }
+                c => {
+                    if *cursor < line.len() {
+                        line[*cursor] = c;
+                    } else {
+                        line.push(c);
+                    }
+                    *cursor += 1;
+                }
+            }
+        }
+    };
+    for seq in sequences(data) {
+        write(&data[offset..seq.range.start], &mut line, &mut cursor);
+        if seq.is_erase_line() {
+            line.truncate(cursor);
+        }
+        offset = seq.range.end;
+    }
+    write(&data[offset..], &mut line, &mut cursor);
+    rendered.extend(line);
+    rendered
+}
+
+/// Find the first byte of `data` that a terminal wouldn't print as text.
+///
+/// Line feeds, tabs, `\r\n`, and color (SGR) sequences are allowed; other escape
+/// sequences, control characters, and invalid UTF-8 are not.  Returns the offset and a
+/// description.
+pub(crate) fn find_unprintable(data: &[u8]) -> Option<(usize, String)> {
+    let mut offset = 0;
+    for seq in sequences(data) {
+        if let Some(found) = find_unprintable_text(data, offset, seq.range.start) {
+            return Some(found);
+        }
+        if seq.sgr_params().is_none() {
+            return Some((
+                seq.range.start,
+                format!("escape sequence {:?}", data[seq.range.clone()].as_bstr()),
+            ));
+        }
+        offset = seq.range.end;
+    }
+    find_unprintable_text(data, offset, data.len())
+}
+
+fn find_unprintable_text(data: &[u8], start: usize, end: usize) -> Option<(usize, String)> {
+    let text = match std::str::from_utf8(&data[start..end]) {
+        Ok(text) => text,
+        Err(err) => {
+            let offset = start + err.valid_up_to();
+            return Some((
+                offset,
+                format!("invalid UTF-8 {:?}", data[offset..(offset + 1)].as_bstr()),
+            ));
+        }
+    };
+    for (i, c) in text.char_indices() {
+        let allowed = match c {
+            '\n' | '\t' => true,
+            '\r' => data.get(start + i + 1) == Some(&b'\n'),
+            c => !c.is_control(),
+        };
+        if !allowed {
+            return Some((start + i, format!("control character {:?}", c)));
+        }
+    }
+    None
+}
+
+fn memchr(needle: u8, haystack: &[u8]) -> Option<usize> {
+    haystack.iter().position(|b| *b == needle)
+}
+
+/// Parse the escape starting at `data[start] == ESC`, returning its end.
+///
+/// Truncated sequences consume the rest of the buffer.
+fn parse_escape(data: &[u8], start: usize) -> (usize, Kind<'_>) {
+    let mut i = start + 1;
+    match data.get(i) {
+        Some(b'[') => {
+            i += 1;
+            let params_start = i;
+            while i < data.len() && (0x30..=0x3f).contains(&data[i]) {
+                i += 1;
+            }
+            let params = &data[params_start..i];
+            while i < data.len() && (0x20..=0x2f).contains(&data[i]) {
+                i += 1;
+            }
+            match data.get(i) {
+                Some(&final_byte) if (0x40..=0x7e).contains(&final_byte) => {
+                    (i + 1, Kind::Csi { params, final_byte })
+                }
+                _ => (i, Kind::Other),
+            }
+        }
+        Some(b']') => {
+            i += 1;
+            while i < data.len() {
+                match data[i] {
+                    BEL => return (i + 1, Kind::Osc),
+                    ESC if data.get(i + 1) == Some(&b'\\') => return (i + 2, Kind::Osc),
+                    _ => i += 1,
+                }
+            }
+            (i, Kind::Osc)
+        }
+        Some(_) => (i + 1, Kind::Other),
+        None => (i, Kind::Other),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn finds_sgr() {
+        let data = b"plain \x1b[1;31mred\x1b[0m";
+        let found: Vec<_> = sequences(data).collect();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].range, 6..13);
+        assert_eq!(found[0].sgr_params(), Some(&b"1;31"[..]));
+        assert_eq!(found[1].sgr_params(), Some(&b"0"[..]));
+    }
+
+    #[test]
+    fn finds_non_sgr() {
+        let data = b"\x1b[2K\x1b]0;title\x07\x1b=";
+        let found: Vec<_> = sequences(data).collect();
+        assert_eq!(found.len(), 3);
+        assert!(found.iter().all(|s| s.sgr_params().is_none()));
+        assert_eq!(found[1].range, 4..14);
+        assert_eq!(found[2].range, 14..16);
+    }
+
+    #[test]
+    fn strip_sequences() {
+        assert_eq!(
+            strip(b"plain \x1b[1;31mred\x1b[0m!").as_ref(),
+            b"plain red!"
+        );
+        assert!(matches!(strip(b"plain"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn render_carriage_returns() {
+        assert_eq!(render(b"10%\r50%\r100%\ndone\n"), "100%\ndone\n");
+        assert_eq!(render(b"downloading\rok"), "okwnloading");
+        assert_eq!(render(b"downloading\r\x1b[2Kok"), "ok");
+        assert_eq!(render(b"line\r\n"), "line\n");
+    }
+
+    #[test]
+    fn detect_progress() {
+        assert_eq!(has_progress(b"plain\r\nlines\n"), None);
+        assert_eq!(has_progress(b"10%\r50%"), Some(3));
+        assert_eq!(has_progress(b"10%\x1b[K"), Some(3));
+    }
+
+    #[test]
+    fn plain_text() {
+        assert_eq!(sequences(b"hello\nworld\n").count(), 0);
+    }
+
+    #[test]
+    fn printable() {
+        assert_eq!(
+            find_unprintable(b"plain\ttext\r\n\x1b[1mbold\x1b[0m\n"),
+            None
+        );
+        assert_eq!(
+            find_unprintable(b"ok\x07"),
+            Some((2, "control character '\\u{7}'".to_owned()))
+        );
+        assert_eq!(
+            find_unprintable(b"50%\r"),
+            Some((3, "control character '\\r'".to_owned()))
+        );
+        assert_eq!(
+            find_unprintable(b"a\x1b[2Kb"),
+            Some((1, "escape sequence \"\\u{1b}[2K\"".to_owned()))
+        );
+        assert_eq!(
+            find_unprintable(b"\x1b[1mab\xff"),
+            Some((6, "invalid UTF-8 \"\\xFF\"".to_owned()))
+        );
+    }
+}
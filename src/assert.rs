@@ -1,15 +1,22 @@
 //! [`std::process::Output`] assertions.
 
 use std::borrow::Cow;
+use std::cell::Cell;
+use std::env;
 use std::fmt;
+use std::path;
 use std::process;
 use std::str;
 
-use predicates::str::PredicateStrExt;
+use bstr::ByteSlice;
 use predicates_tree::CaseTreeExt;
 
-use crate::output::output_fmt;
+#[cfg(not(feature = "predicates"))]
+use crate::lite as preds;
 use crate::output::DebugBytes;
+#[cfg(feature = "predicates")]
+use predicates as preds;
+use preds::str::PredicateStrExt;
 
 /// Assert the state of an [`Output`].
 ///
@@ -47,21 +54,118 @@ pub trait OutputAssertExt {
     fn assert(self) -> Assert;
 }
 
-impl OutputAssertExt for process::Output {
+impl<O: OutputLike> OutputAssertExt for O {
     fn assert(self) -> Assert {
         Assert::new(self)
     }
 }
 
-impl<'c> OutputAssertExt for &'c mut process::Command {
+/// The result of running a program, as needed by [`Assert`].
+///
+/// This is implemented for [`Output`]. Implement it for other ways of running a program, like a
+/// PTY or a remote host, to reuse [`Assert`]'s assertions and failure formatting.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[cfg(unix)] {
+/// use assert_cmd::assert::OutputLike;
+/// use assert_cmd::prelude::*;
+///
+/// use std::os::unix::process::ExitStatusExt;
+/// use std::process::ExitStatus;
+///
+/// struct Replay {
+///     code: i32,
+///     screen: Vec<u8>,
+/// }
+///
+/// impl OutputLike for Replay {
+///     fn status(&self) -> ExitStatus {
+///         ExitStatus::from_raw(self.code << 8)
+///     }
+///
+///     fn stdout(&self) -> &[u8] {
+///         &self.screen
+///     }
+///
+///     fn stderr(&self) -> &[u8] {
+///         &[]
+///     }
+/// }
+///
+/// let replay = Replay {
+///     code: 0,
+///     screen: b"hello\n".to_vec(),
+/// };
+/// replay.assert().success().stdout("hello\n");
+/// # }
+/// ```
+///
+/// [`Output`]: std::process::Output
+pub trait OutputLike {
+    /// How the program exited.
+    fn status(&self) -> process::ExitStatus;
+
+    /// Everything the program wrote to stdout.
+    fn stdout(&self) -> &[u8];
+
+    /// Everything the program wrote to stderr.
+    fn stderr(&self) -> &[u8];
+
+    /// Convert to an [`Output`], which [`Assert::get_output`] exposes.
+    ///
+    /// [`Output`]: std::process::Output
+    fn into_output(self) -> process::Output
+    where
+        Self: Sized,
+    {
+        process::Output {
+            status: self.status(),
+            stdout: self.stdout().to_vec(),
+            stderr: self.stderr().to_vec(),
+        }
+    }
+}
+
+impl OutputLike for process::Output {
+    fn status(&self) -> process::ExitStatus {
+        self.status
+    }
+
+    fn stdout(&self) -> &[u8] {
+        &self.stdout
+    }
+
+    fn stderr(&self) -> &[u8] {
+        &self.stderr
+    }
+
+    fn into_output(self) -> process::Output {
+        self
+    }
+}
+
+impl OutputAssertExt for &mut process::Command {
     fn assert(self) -> Assert {
-        let output = match self.output() {
+        let start = std::time::Instant::now();
+        let output = match crate::budget::track(crate::timing::label(self), || {
+            crate::env_guard::spawning(|| self.output())
+        }) {
             Ok(output) => output,
             Err(err) => {
-                panic!("Failed to spawn {:?}: {}", self, err);
+                panic!(
+                    "Failed to spawn {:?}: {}",
+                    self,
+                    crate::exec::SpawnError::new(self, err)
+                );
             }
         };
-        Assert::new(output).append_context("command", format!("{:?}", self))
+        Assert::new(output)
+            .append_context("command", format!("{:?}", self))
+            .set_cwd(self.get_current_dir().map(std::path::Path::to_owned))
+            .set_invocation(crate::transcript::Invocation::new(self, false))
+            .set_report(crate::report::entry(self, Some(start.elapsed())))
     }
 }
 
@@ -85,20 +189,173 @@ impl<'c> OutputAssertExt for &'c mut process::Command {
 /// [`Output`]: std::process::Output
 pub struct Assert {
     output: process::Output,
-    context: Vec<(&'static str, Box<dyn fmt::Display>)>,
+    context: Vec<(&'static str, std::rc::Rc<dyn fmt::Display>)>,
+    child_env: Option<Vec<(String, String)>>,
+    message: Option<String>,
+    strict_stderr: bool,
+    stderr_asserted: Cell<bool>,
+    timed_out: Option<std::time::Duration>,
+    recording: Option<crate::transcript::Recording>,
+    invocation: Option<crate::transcript::Invocation>,
+    settings: crate::settings::AssertSettings,
+    samples: Option<Vec<crate::resources::Sample>>,
+    usage: Option<crate::resources::ResourceUsage>,
+    #[cfg(unix)]
+    child_signals: Option<crate::signals::SignalState>,
+    temp_leftovers: Option<Vec<std::path::PathBuf>>,
+    /// The [`Command::in_temp_dir`] directory, kept alive for this `Assert`, and whether to keep
+    /// it on failure.
+    ///
+    /// [`Command::in_temp_dir`]: crate::cmd::Command::in_temp_dir()
+    temp_cwd: Option<(std::sync::Arc<crate::temp::TempDir>, bool)>,
+    /// The child's working directory, when not the test's.
+    cwd: Option<std::path::PathBuf>,
+    /// `stdout` and `stderr` as written, when captured as [`Streams::Combined`].
+    ///
+    /// [`Streams::Combined`]: crate::cmd::Streams::Combined
+    combined: Option<Vec<u8>>,
+    /// The files output was written to, with [`Command::capture_to_files`].
+    ///
+    /// [`Command::capture_to_files`]: crate::cmd::Command::capture_to_files()
+    spool: Option<std::sync::Arc<crate::spool::Spool>>,
+    stdin_unread: Option<(usize, usize)>,
+    on_failure: Option<crate::failure::LocalHandler>,
+    /// This run's entry for [`report`], taken once the outcome is known.
+    ///
+    /// [`report`]: crate::report
+    report: Cell<Option<crate::report::Entry>>,
 }
 
 impl Assert {
-    /// Create an `Assert` for a given [`Output`].
+    /// Create an `Assert` for a given [`Output`] or other [`OutputLike`].
     ///
     /// [`Output`]: std::process::Output
-    pub fn new(output: process::Output) -> Self {
+    pub fn new<O: OutputLike>(output: O) -> Self {
+        let output = output.into_output();
+        let mut context: Vec<(&'static str, std::rc::Rc<dyn fmt::Display>)> = vec![];
+        if let Some(failure) = crate::exec::from_output(&output) {
+            context.push(("exec failure", std::rc::Rc::new(failure)));
+        }
         Self {
             output,
-            context: vec![],
+            context,
+            child_env: None,
+            message: None,
+            strict_stderr: matches!(env::var_os(STRICT_STDERR_ENV), Some(v) if !v.is_empty() && v != "0"),
+            stderr_asserted: Cell::new(false),
+            timed_out: None,
+            recording: None,
+            invocation: None,
+            settings: crate::settings::AssertSettings::new(),
+            samples: None,
+            usage: None,
+            #[cfg(unix)]
+            child_signals: None,
+            temp_leftovers: None,
+            temp_cwd: None,
+            cwd: None,
+            combined: None,
+            spool: None,
+            stdin_unread: None,
+            on_failure: None,
+            report: Cell::new(None),
+        }
+    }
+
+    pub(crate) fn set_child_env(mut self, child_env: Option<Vec<(String, String)>>) -> Self {
+        self.child_env = child_env;
+        self
+    }
+
+    pub(crate) fn set_timed_out(mut self, timeout: std::time::Duration) -> Self {
+        self.timed_out = Some(timeout);
+        self
+    }
+
+    pub(crate) fn set_recording(mut self, recording: crate::transcript::Recording) -> Self {
+        self.recording = Some(recording);
+        self
+    }
+
+    /// Record how the child was invoked, reporting it in failures.
+    pub(crate) fn set_invocation(mut self, invocation: crate::transcript::Invocation) -> Self {
+        if invocation.found {
+            self = self.append_context("program", invocation.program().display().to_string());
+        }
+        self = self.append_context("cwd", invocation.cwd().display().to_string());
+        if let Some(env) = invocation.env_summary() {
+            self = self.append_context("env", env);
+        }
+        self.invocation = Some(invocation);
+        self
+    }
+
+    pub(crate) fn set_stdin_unread(mut self, stdin_unread: Option<(usize, usize)>) -> Self {
+        self.stdin_unread = stdin_unread;
+        self
+    }
+
+    pub(crate) fn set_temp_leftovers(mut self, leftovers: Vec<std::path::PathBuf>) -> Self {
+        self.temp_leftovers = Some(leftovers);
+        self
+    }
+
+    pub(crate) fn set_combined(mut self, combined: Option<Vec<u8>>) -> Self {
+        self.combined = combined;
+        self
+    }
+
+    pub(crate) fn set_report(self, entry: Option<crate::report::Entry>) -> Self {
+        self.report.set(entry);
+        self
+    }
+
+    pub(crate) fn set_spool(mut self, spool: std::sync::Arc<crate::spool::Spool>) -> Self {
+        self.spool = Some(spool);
+        self
+    }
+
+    pub(crate) fn set_cwd(mut self, cwd: Option<std::path::PathBuf>) -> Self {
+        self.cwd = cwd;
+        self
+    }
+
+    pub(crate) fn set_temp_cwd(
+        mut self,
+        dir: std::sync::Arc<crate::temp::TempDir>,
+        keep: bool,
+    ) -> Self {
+        let path = dir.path().display().to_string();
+        self.temp_cwd = Some((dir, keep));
+        if keep {
+            self.append_context("temp cwd", format!("{}, kept on failure", path))
+        } else {
+            self.append_context("temp cwd", path)
         }
     }
 
+    pub(crate) fn set_resource_usage(
+        mut self,
+        usage: Option<crate::resources::ResourceUsage>,
+    ) -> Self {
+        self.usage = usage;
+        self
+    }
+
+    pub(crate) fn set_samples(mut self, samples: Option<Vec<crate::resources::Sample>>) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    #[cfg(unix)]
+    pub(crate) fn set_child_signals(
+        mut self,
+        child_signals: Option<crate::signals::SignalState>,
+    ) -> Self {
+        self.child_signals = child_signals;
+        self
+    }
+
     /// Clarify failures with additional context.
     ///
     /// # Examples
@@ -118,18 +375,14 @@ impl Assert {
     where
         D: fmt::Display + 'static,
     {
-        self.context.push((name, Box::new(context)));
+        self.context.push((name, std::rc::Rc::new(context)));
         self
     }
 
-    /// Access the contained [`Output`].
+    /// Lead failure messages with a description of what was expected.
     ///
-    /// [`Output`]: std::process::Output
-    pub fn get_output(&self) -> &process::Output {
-        &self.output
-    }
-
-    /// Ensure the command succeeded.
+    /// The generic headline (e.g. "Unexpected failure") and all captured context are still
+    /// reported, so failures read like requirements rather than mechanics.
     ///
     /// # Examples
     ///
@@ -141,28 +394,19 @@ impl Assert {
     /// Command::cargo_bin("bin_fixture")
     ///     .unwrap()
     ///     .assert()
+    ///     .with_message("config reload should keep serving")
     ///     .success();
     /// ```
-    pub fn success(self) -> Self {
-        if !self.output.status.success() {
-            let actual_code = self.output.status.code().unwrap_or_else(|| {
-                panic!(
-                    "Unexpected failure.\ncode=<interrupted>\nstderr=```{}```\n{}",
-                    DebugBytes::new(&self.output.stderr),
-                    self
-                )
-            });
-            panic!(
-                "Unexpected failure.\ncode-{}\nstderr=```{}```\n{}",
-                actual_code,
-                DebugBytes::new(&self.output.stderr),
-                self
-            );
-        }
+    pub fn with_message<M>(mut self, message: M) -> Self
+    where
+        M: Into<String>,
+    {
+        self.message = Some(message.into());
         self
     }
 
-    /// Ensure the command failed.
+    /// Handle this `Assert`'s failures as data before the panic reporting them, rather than with
+    /// any [`failure::set_handler`] handler.
     ///
     /// # Examples
     ///
@@ -173,132 +417,257 @@ impl Assert {
     ///
     /// Command::cargo_bin("bin_fixture")
     ///     .unwrap()
-    ///     .env("exit", "1")
     ///     .assert()
-    ///     .failure();
+    ///     .on_failure(|failure| eprintln!("{:?}: {}", failure.kind(), failure.headline()))
+    ///     .success();
     /// ```
-    pub fn failure(self) -> Self {
-        if self.output.status.success() {
-            panic!("Unexpected success\n{}", self);
-        }
-        self
-    }
-
-    /// Ensure the command aborted before returning a code.
-    pub fn interrupted(self) -> Self {
-        if self.output.status.code().is_some() {
-            panic!("Unexpected completion\n{}", self);
-        }
+    ///
+    /// [`failure::set_handler`]: crate::failure::set_handler()
+    pub fn on_failure<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(&crate::failure::Failure) + 'static,
+    {
+        self.on_failure = Some(std::rc::Rc::new(handler));
         self
     }
 
-    /// Ensure the command returned the expected code.
+    /// Fail if the command wrote to `stderr` without any `stderr` assertion being made.
     ///
-    /// This uses [`IntoCodePredicate`] to provide short-hands for common cases.
-    ///
-    /// See [`predicates`] for more predicates.
+    /// The check happens when the `Assert` is dropped.  Asserting `stderr` is empty, or making any
+    /// other `stderr` assertion, satisfies it.  To enable this for every `Assert`, set the
+    /// `ASSERT_CMD_STRICT_STDERR=1` environment variable when running the tests.
     ///
     /// # Examples
     ///
-    /// Accepting a predicate:
     /// ```rust,no_run
     /// use assert_cmd::prelude::*;
     ///
     /// use std::process::Command;
-    /// use predicates::prelude::*;
     ///
     /// Command::cargo_bin("bin_fixture")
     ///     .unwrap()
-    ///     .env("exit", "42")
+    ///     .env("stderr", "deprecated flag")
     ///     .assert()
-    ///     .code(predicate::eq(42));
+    ///     .strict_stderr()
+    ///     .success()
+    ///     .stderr("deprecated flag\n");
     /// ```
+    pub fn strict_stderr(mut self) -> Self {
+        self.strict_stderr = true;
+        self
+    }
+
+    /// Make assertions on the partial output, only if a [`Command::timeout`] killed the command.
     ///
-    /// Accepting an exit code:
-    /// ```rust,no_run
-    /// use assert_cmd::prelude::*;
+    /// This distinguishes a command that hung after starting from one that never got going.
     ///
-    /// use std::process::Command;
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    /// use predicates::prelude::*;
     ///
     /// Command::cargo_bin("bin_fixture")
     ///     .unwrap()
-    ///     .env("exit", "42")
+    ///     .env("stdout", "started")
+    ///     .env("sleep", "100")
+    ///     .timeout(std::time::Duration::from_secs(1))
     ///     .assert()
-    ///     .code(42);
+    ///     .on_timeout(|assert| assert.stdout(predicate::str::contains("started")));
     /// ```
     ///
-    /// Accepting multiple exit codes:
+    /// [`Command::timeout`]: crate::cmd::Command::timeout()
+    pub fn on_timeout<F>(self, f: F) -> Self
+    where
+        F: FnOnce(Self) -> Self,
+    {
+        if self.timed_out.is_some() {
+            f(self)
+        } else {
+            self
+        }
+    }
+
+    /// Apply shared filters and display settings.
+    ///
+    /// Filters rewrite `stdout` and `stderr` for all later assertions and failure messages.
+    ///
+    /// # Examples
+    ///
     /// ```rust,no_run
     /// use assert_cmd::prelude::*;
+    /// use assert_cmd::settings::AssertSettings;
     ///
     /// use std::process::Command;
     ///
+    /// let settings = AssertSettings::new().filter(r"\d+ms", "[DURATION]");
+    ///
     /// Command::cargo_bin("bin_fixture")
     ///     .unwrap()
-    ///     .env("exit", "42")
+    ///     .env("stdout", "done in 15ms")
     ///     .assert()
-    ///     .code(&[2, 42] as &[i32]);
+    ///     .with_settings(&settings)
+    ///     .stdout("done in [DURATION]\n");
     /// ```
+    pub fn with_settings(mut self, settings: &crate::settings::AssertSettings) -> Self {
+        self.output.stdout = settings.apply(std::mem::take(&mut self.output.stdout));
+        self.output.stderr = settings.apply(std::mem::take(&mut self.output.stderr));
+        self.combined = self
+            .combined
+            .take()
+            .map(|combined| settings.apply(combined));
+        self.settings = settings.clone();
+        self
+    }
+
+    /// Decode `stdout` from `encoding` to UTF-8, before any later assertion sees it.
     ///
-    pub fn code<I, P>(self, pred: I) -> Self
-    where
-        I: IntoCodePredicate<P>,
-        P: predicates_core::Predicate<i32>,
-    {
-        self.code_impl(&pred.into_code())
+    /// This is reflected in failure messages.  Undecodable data is replaced with `U+FFFD`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::encoding::Encoding;
+    /// use assert_cmd::Command;
+    ///
+    /// Command::new("wmic")
+    ///     .args(["os", "get", "caption"])
+    ///     .assert()
+    ///     .decode_stdout(Encoding::Auto)
+    ///     .stdout_has_line(predicates::str::contains("Windows"));
+    /// ```
+    pub fn decode_stdout(mut self, encoding: crate::encoding::Encoding) -> Self {
+        let (stdout, decoded) = crate::encoding::decode(&self.output.stdout, encoding);
+        self.output.stdout = stdout;
+        match decoded {
+            Some(decoded) => self.append_context("decoded", format!("stdout from {}", decoded)),
+            None => self,
+        }
     }
 
-    fn code_impl(self, pred: &dyn predicates_core::Predicate<i32>) -> Self {
-        let actual_code = self
-            .output
-            .status
-            .code()
-            .unwrap_or_else(|| panic!("Command interrupted\n{}", self));
-        if let Some(case) = pred.find_case(false, &actual_code) {
-            panic!("Unexpected return code, failed {}\n{}", case.tree(), self);
+    /// Decode `stderr` from `encoding` to UTF-8, before any later assertion sees it.
+    ///
+    /// See [`Assert::decode_stdout`].
+    pub fn decode_stderr(mut self, encoding: crate::encoding::Encoding) -> Self {
+        let (stderr, decoded) = crate::encoding::decode(&self.output.stderr, encoding);
+        self.output.stderr = stderr;
+        match decoded {
+            Some(decoded) => self.append_context("decoded", format!("stderr from {}", decoded)),
+            None => self,
         }
-        self
     }
 
-    /// Ensure the command wrote the expected data to `stdout`.
+    /// Access the contained [`Output`].
     ///
-    /// This uses [`IntoOutputPredicate`] to provide short-hands for common cases.
+    /// [`Output`]: std::process::Output
+    pub fn get_output(&self) -> &process::Output {
+        &self.output
+    }
+
+    /// Take the contained [`Output`], for further processing.
     ///
-    /// See [`predicates`] for more predicates.
+    /// This counts as asserting on `stderr` for [`Assert::strict_stderr`].
     ///
     /// # Examples
     ///
-    /// Accepting a bytes predicate:
     /// ```rust,no_run
-    /// use assert_cmd::prelude::*;
+    /// use assert_cmd::Command;
     ///
-    /// use std::process::Command;
-    /// use predicates::prelude::*;
+    /// let output = Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "42")
+    ///     .assert()
+    ///     .success()
+    ///     .into_output();
+    /// let answer: u32 = String::from_utf8(output.stdout).unwrap().trim().parse().unwrap();
+    /// ```
     ///
-    /// Command::cargo_bin("bin_fixture")
+    /// [`Output`]: std::process::Output
+    pub fn into_output(mut self) -> process::Output {
+        self.stderr_asserted.set(true);
+        process::Output {
+            status: self.output.status,
+            stdout: std::mem::take(&mut self.output.stdout),
+            stderr: std::mem::take(&mut self.output.stderr),
+        }
+    }
+
+    /// `stdout`, with invalid UTF-8 replaced.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// let version = Command::cargo_bin("bin_fixture")
     ///     .unwrap()
-    ///     .env("stdout", "hello")
-    ///     .env("stderr", "world")
+    ///     .env("stdout", "tool 1.2.3")
     ///     .assert()
-    ///     .stdout(predicate::eq(b"hello\n" as &[u8]));
+    ///     .success()
+    ///     .stdout_lossy();
+    /// assert_eq!(version.trim(), "tool 1.2.3");
     /// ```
+    pub fn stdout_lossy(&self) -> String {
+        String::from_utf8_lossy(&self.output.stdout).into_owned()
+    }
+
+    /// `stderr`, with invalid UTF-8 replaced.
+    ///
+    /// See [`Assert::stdout_lossy`].
+    pub fn stderr_lossy(&self) -> String {
+        String::from_utf8_lossy(&self.output.stderr).into_owned()
+    }
+
+    /// How the child was invoked: the program as found on `PATH`, the arguments, the working
+    /// directory, and how the environment differed from the test's.
+    ///
+    /// Failures report these too.  This is only available for an `Assert` from a command, not
+    /// from an [`Output`].
+    ///
+    /// # Examples
     ///
-    /// Accepting a `str` predicate:
     /// ```rust,no_run
-    /// use assert_cmd::prelude::*;
+    /// use assert_cmd::Command;
     ///
-    /// use std::process::Command;
-    /// use predicates::prelude::*;
+    /// let assert = Command::new("sh")
+    ///     .args(["-c", "exit 0"])
+    ///     .env("LANG", "C")
+    ///     .assert();
+    /// let invocation = assert.invocation().unwrap();
+    /// assert!(invocation.program().is_absolute());
+    /// assert_eq!(invocation.args(), ["-c", "exit 0"]);
+    /// assert_eq!(invocation.env(), [("LANG".into(), Some("C".into()))]);
+    /// ```
     ///
-    /// Command::cargo_bin("bin_fixture")
+    /// [`Output`]: std::process::Output
+    pub fn invocation(&self) -> Option<&crate::transcript::Invocation> {
+        self.invocation.as_ref()
+    }
+
+    /// The full [`Transcript`] of the run, for snapshot testing.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// let assert = Command::cargo_bin("bin_fixture")
     ///     .unwrap()
     ///     .env("stdout", "hello")
-    ///     .env("stderr", "world")
-    ///     .assert()
-    ///     .stdout(predicate::str::diff("hello\n"));
+    ///     .assert();
+    /// let snapshot = assert.transcript().to_string();
+    /// assert!(snapshot.ends_with("[stdout] hello\nstatus: exit 0"));
     /// ```
     ///
-    /// Accepting bytes:
+    /// [`Transcript`]: crate::transcript::Transcript
+    pub fn transcript(&self) -> crate::transcript::Transcript<'_> {
+        crate::transcript::Transcript::new(self.recording.as_ref(), &self.output)
+    }
+
+    /// Ensure the command succeeded.
+    ///
+    /// # Examples
+    ///
     /// ```rust,no_run
     /// use assert_cmd::prelude::*;
     ///
@@ -306,13 +675,36 @@ impl Assert {
     ///
     /// Command::cargo_bin("bin_fixture")
     ///     .unwrap()
-    ///     .env("stdout", "hello")
-    ///     .env("stderr", "world")
     ///     .assert()
-    ///     .stdout(b"hello\n" as &[u8]);
+    ///     .success();
     /// ```
+    pub fn success(self) -> Self {
+        if let Some(timeout) = self.timed_out {
+            self.fail(format_args!("Command timed out after {:?}", timeout));
+        }
+        if !self.output.status.success() {
+            let actual_code = self
+                .output
+                .status
+                .code()
+                .map(|code| code.to_string())
+                .unwrap_or_else(|| "<interrupted>".to_owned());
+            self.fail(format_args!(
+                "Unexpected failure.\ncode={}\nstderr=```{}```",
+                actual_code,
+                DebugBytes::with_limit(
+                    &self.settings.display(&self.output.stderr),
+                    self.settings.limit()
+                ),
+            ));
+        }
+        self
+    }
+
+    /// Ensure the command failed.
+    ///
+    /// # Examples
     ///
-    /// Accepting a `str`:
     /// ```rust,no_run
     /// use assert_cmd::prelude::*;
     ///
@@ -320,121 +712,3678 @@ impl Assert {
     ///
     /// Command::cargo_bin("bin_fixture")
     ///     .unwrap()
-    ///     .env("stdout", "hello")
+    ///     .env("exit", "1")
+    ///     .assert()
+    ///     .failure();
+    /// ```
+    pub fn failure(self) -> Self {
+        if self.output.status.success() {
+            self.fail("Unexpected success");
+        }
+        self
+    }
+
+    /// Ensure the command succeeded without writing anything to `stderr`.
+    ///
+    /// A shortcut for `.success().stderr("")`, a "clean success".
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "done")
+    ///     .assert()
+    ///     .success_with_empty_stderr();
+    /// ```
+    pub fn success_with_empty_stderr(self) -> Self {
+        self.success().stderr("")
+    }
+
+    /// Ensure the command failed with the expected code.
+    ///
+    /// A shortcut for `.failure().code(pred)`, like for an expected usage error.  This uses
+    /// [`IntoCodePredicate`] to provide short-hands for common cases.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("exit", "2")
+    ///     .assert()
+    ///     .failure_with_code(2);
+    /// ```
+    pub fn failure_with_code<I, P>(self, pred: I) -> Self
+    where
+        I: IntoCodePredicate<P>,
+        P: predicates_core::Predicate<i32>,
+    {
+        self.failure().code(pred)
+    }
+
+    /// Ensure the command didn't exit while [`Command::write_stdin`] input was still being
+    /// written.
+    ///
+    /// A child that exits without reading its input causes a broken pipe.  This is also noted
+    /// in every failure message, to distinguish "ignored its input" from an output mismatch.
+    ///
+    /// [`Command::write_stdin`]: crate::cmd::Command::write_stdin()
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::new("cat")
+    ///     .write_stdin("hello")
+    ///     .assert()
+    ///     .stdin_consumed()
+    ///     .stdout("hello");
+    /// ```
+    pub fn stdin_consumed(self) -> Self {
+        if let Some((written, len)) = self.stdin_unread {
+            self.fail(format_args!(
+                "Child exited before reading stdin, {} of {} bytes were written",
+                written, len
+            ));
+        }
+        self
+    }
+
+    /// Ensure the command exited on its own, as expected of one that may not read all of its
+    /// [`Command::write_stdin`] input.
+    ///
+    /// Whether such a child leaves input unwritten is a race: small inputs fit in the pipe's
+    /// buffer before the child exits.  This drops the "stdin unread" note from failure messages,
+    /// so they don't depend on who won.
+    ///
+    /// [`Command::write_stdin`]: crate::cmd::Command::write_stdin()
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::new("true")
+    ///     .write_stdin(vec![b'x'; 1_000_000])
+    ///     .assert()
+    ///     .expect_early_exit()
+    ///     .success();
+    /// ```
+    pub fn expect_early_exit(mut self) -> Self {
+        if let Some(timeout) = self.timed_out {
+            self.fail(format_args!(
+                "Command timed out after {:?}, rather than exiting early",
+                timeout
+            ));
+        }
+        self.stdin_unread = None;
+        self.context.retain(|(name, _)| *name != "stdin unread");
+        self
+    }
+
+    /// See [`Command::assert_requires_tty`].
+    ///
+    /// [`Command::assert_requires_tty`]: crate::cmd::Command::assert_requires_tty()
+    pub(crate) fn refuses_without_tty(self) -> Self {
+        if let Some(timeout) = self.timed_out {
+            self.fail(format_args!(
+                "Command was still running without a terminal after {:?}, rather than failing fast",
+                timeout
+            ));
+        }
+        if self.output.status.success() {
+            self.fail("Command succeeded without a terminal, expected it to fail");
+        }
+        if self.stderr_bytes().trim().is_empty() {
+            self.fail("Command failed without a terminal, but didn't say why on stderr");
+        }
+        self
+    }
+
+    /// Ensure the command aborted before returning a code.
+    pub fn interrupted(self) -> Self {
+        if self.output.status.code().is_some() {
+            self.fail("Unexpected completion");
+        }
+        self
+    }
+
+    /// Ensure the command was terminated by `signal`, like `libc::SIGSEGV`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::new("sh")
+    ///     .args(["-c", "kill -SEGV $$"])
+    ///     .assert()
+    ///     .interrupted_by(11);
+    /// ```
+    #[cfg(unix)]
+    pub fn interrupted_by(self, signal: i32) -> Self {
+        let actual = self.require_signal();
+        if actual != signal {
+            self.fail(format_args!(
+                "Unexpected signal, expected {}, got {}",
+                crate::signals::describe(signal),
+                crate::signals::describe(actual)
+            ));
+        }
+        self
+    }
+
+    /// Ensure the command was terminated by a signal satisfying `pred`.
+    ///
+    /// This uses [`IntoCodePredicate`] to provide short-hands for common cases.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// // Either way of being killed is fine
+    /// Command::new("sh")
+    ///     .args(["-c", "kill -TERM $$"])
+    ///     .assert()
+    ///     .signal(&[9, 15] as &[i32]);
+    /// ```
+    #[cfg(unix)]
+    pub fn signal<I, P>(self, pred: I) -> Self
+    where
+        I: IntoCodePredicate<P>,
+        P: predicates_core::Predicate<i32>,
+    {
+        self.signal_impl(&pred.into_code())
+    }
+
+    #[cfg(unix)]
+    fn signal_impl(self, pred: &dyn predicates_core::Predicate<i32>) -> Self {
+        let actual = self.require_signal();
+        if let Some(case) = pred.find_case(false, &actual) {
+            self.fail(format_args!(
+                "Unexpected signal {}, failed {}",
+                crate::signals::describe(actual),
+                case.tree()
+            ));
+        }
+        self
+    }
+
+    #[cfg(unix)]
+    fn require_signal(&self) -> i32 {
+        use std::os::unix::process::ExitStatusExt;
+        match (self.output.status.signal(), self.output.status.code()) {
+            (Some(signal), _) => signal,
+            (None, Some(code)) => self.fail(format_args!(
+                "Unexpected completion, exited with code {} rather than a signal",
+                code
+            )),
+            (None, None) => self.fail("Unexpected completion, without a code or a signal"),
+        }
+    }
+
+    /// Ensure the command returned the expected code.
+    ///
+    /// This uses [`IntoCodePredicate`] to provide short-hands for common cases.
+    ///
+    /// See [`predicates`] for more predicates.
+    ///
+    /// # Examples
+    ///
+    /// Accepting a predicate:
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    /// use predicates::prelude::*;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("exit", "42")
+    ///     .assert()
+    ///     .code(predicate::eq(42));
+    /// ```
+    ///
+    /// Accepting an exit code:
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("exit", "42")
+    ///     .assert()
+    ///     .code(42);
+    /// ```
+    ///
+    /// Accepting multiple exit codes:
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("exit", "42")
+    ///     .assert()
+    ///     .code([2, 42]);
+    /// ```
+    ///
+    /// Accepting a range of exit codes:
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("exit", "2")
+    ///     .assert()
+    ///     .code(2..10);
+    /// ```
+    ///
+    pub fn code<I, P>(self, pred: I) -> Self
+    where
+        I: IntoCodePredicate<P>,
+        P: predicates_core::Predicate<i32>,
+    {
+        self.code_impl(&pred.into_code())
+    }
+
+    fn code_impl(self, pred: &dyn predicates_core::Predicate<i32>) -> Self {
+        let actual_code = self
+            .output
+            .status
+            .code()
+            .unwrap_or_else(|| match self.timed_out {
+                Some(timeout) => self.fail(format_args!("Command timed out after {:?}", timeout)),
+                None => self.fail("Command interrupted"),
+            });
+        if let Some(case) = pred.find_case(false, &actual_code) {
+            self.fail(format_args!(
+                "Unexpected return code, failed {}",
+                case.tree()
+            ));
+        }
+        self
+    }
+
+    /// Ensure the command returned the expected platform-specific raw status.
+    ///
+    /// [`code`][Assert::code] is a lossy view of the exit status.  When that isn't enough,
+    /// this asserts on:
+    /// - Unix: the wait status as reported by `waitpid`, see [`ExitStatusExt::into_raw`]
+    /// - Windows: the full exit code, including `NTSTATUS` values like `0xC0000005`
+    ///
+    /// This uses [`IntoCodePredicate`] to provide short-hands for common cases.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// // On Unix, the exit code is stored in the upper byte of the wait status
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("exit", "42")
+    ///     .assert()
+    ///     .raw_code(42 << 8);
+    /// ```
+    ///
+    /// [`ExitStatusExt::into_raw`]: std::os::unix::process::ExitStatusExt::into_raw()
+    #[cfg(any(unix, windows))]
+    pub fn raw_code<I, P>(self, pred: I) -> Self
+    where
+        I: IntoCodePredicate<P>,
+        P: predicates_core::Predicate<i32>,
+    {
+        self.raw_code_impl(&pred.into_code())
+    }
+
+    #[cfg(any(unix, windows))]
+    fn raw_code_impl(self, pred: &dyn predicates_core::Predicate<i32>) -> Self {
+        let actual_code = raw_status(&self.output.status);
+        if let Some(case) = pred.find_case(false, &actual_code) {
+            self.fail(format_args!(
+                "Unexpected raw status, failed {}",
+                case.tree()
+            ));
+        }
+        self
+    }
+
+    /// Ensure the command died from `SIGPIPE`, directly or as reported by a shell (exit code
+    /// `141`).
+    ///
+    /// See [`Command::stdout_closed_after`].
+    ///
+    /// [`Command::stdout_closed_after`]: crate::cmd::Command::stdout_closed_after()
+    #[cfg(unix)]
+    pub fn killed_by_sigpipe(self) -> Self {
+        if shell_code(&self.output.status) != Some(128 + libc::SIGPIPE) {
+            self.fail("Expected the command to be killed by `SIGPIPE`");
+        }
+        self
+    }
+
+    /// Ensure the command crashed, rather than exiting or being stopped.
+    ///
+    /// A crash is:
+    /// - Unix: death by `SIGSEGV`, `SIGBUS`, `SIGILL`, `SIGFPE`, `SIGABRT`, `SIGTRAP`, or
+    ///   `SIGSYS`
+    /// - Windows: an error `NTSTATUS` exit code, like `0xC0000005` for an access violation
+    ///
+    /// See [`Assert::exit_nt_status`] for a specific crash.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("abort", "1")
+    ///     .assert()
+    ///     .crashed();
+    /// ```
+    #[cfg(any(unix, windows))]
+    pub fn crashed(self) -> Self {
+        if let Some(timeout) = self.timed_out {
+            self.fail(format_args!(
+                "Command timed out after {:?}, rather than crashing",
+                timeout
+            ));
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            match (self.output.status.signal(), self.output.status.code()) {
+                (Some(signal), _) if crate::platform::is_crash_signal(signal) => {}
+                (Some(signal), _) => self.fail(format_args!(
+                    "Expected the command to crash, but it was killed by {}",
+                    crate::signals::describe(signal)
+                )),
+                (None, Some(code)) => self.fail(format_args!(
+                    "Expected the command to crash, but it exited with code {}",
+                    code
+                )),
+                (None, None) => self.fail("Expected the command to crash"),
+            }
+        }
+        #[cfg(windows)]
+        {
+            let code = raw_status(&self.output.status) as u32;
+            if !crate::platform::is_crash_code(code) {
+                self.fail(format_args!(
+                    "Expected the command to crash, but it exited with code {} ({:#010X})",
+                    code, code
+                ));
+            }
+        }
+        self
+    }
+
+    /// Ensure the command crashed with the Windows `NTSTATUS` exit code `status`, like
+    /// [`STATUS_ACCESS_VIOLATION`][crate::platform::STATUS_ACCESS_VIOLATION].
+    ///
+    /// On Unix, this expects the equivalent signal, like `SIGSEGV`, for the statuses in
+    /// [`platform`][crate::platform].
+    ///
+    /// # Panics
+    ///
+    /// On Unix, if `status` has no equivalent signal.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::platform::STATUS_STACK_BUFFER_OVERRUN;
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("abort", "1")
+    ///     .assert()
+    ///     .exit_nt_status(STATUS_STACK_BUFFER_OVERRUN);
+    /// ```
+    #[cfg(any(unix, windows))]
+    pub fn exit_nt_status(self, status: u32) -> Self {
+        #[cfg(unix)]
+        {
+            let signal = match crate::platform::nt_status_signal(status) {
+                Some(signal) => signal,
+                None => panic!("`NTSTATUS` {:#010X} has no Unix equivalent", status),
+            };
+            self.interrupted_by(signal)
+        }
+        #[cfg(windows)]
+        {
+            if let Some(timeout) = self.timed_out {
+                self.fail(format_args!("Command timed out after {:?}", timeout));
+            }
+            let code = raw_status(&self.output.status) as u32;
+            if code != status {
+                self.fail(format_args!(
+                    "Unexpected exit status, expected {:#010X}, got {:#010X}",
+                    status, code
+                ));
+            }
+            self
+        }
+    }
+
+    /// Ensure a wrapper command forwarded the exit status of the tool it ran.
+    ///
+    /// This follows the shell conventions: an exit code is passed through as-is and death by
+    /// signal `N` becomes exit code `128 + N`.  Run the wrapped tool directly to get `inner`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// let inner = Command::new("false").output().unwrap().status;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .arg("false")
+    ///     .assert()
+    ///     .code_maps_from_child(&inner);
+    /// ```
+    pub fn code_maps_from_child(self, inner: &process::ExitStatus) -> Self {
+        let expected = match shell_code(inner) {
+            Some(code) => code,
+            None => self.fail(format_args!("Inner status `{}` has no exit code", inner)),
+        };
+        self.code_impl(&EqCodePredicate::new(expected))
+    }
+
+    /// Ensure the command reported that it found a program but couldn't execute it.
+    ///
+    /// This is the shell convention of exit code `126`.
+    pub fn code_not_executable(self) -> Self {
+        self.code_impl(&EqCodePredicate::new(126))
+    }
+
+    /// Ensure the command reported that it couldn't find a program.
+    ///
+    /// This is the shell convention of exit code `127`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::new("sh")
+    ///     .args(["-c", "no-such-program"])
+    ///     .assert()
+    ///     .code_not_found();
+    /// ```
+    pub fn code_not_found(self) -> Self {
+        self.code_impl(&EqCodePredicate::new(127))
+    }
+
+    /// Ensure the command reported that a program it ran died from `signal`.
+    ///
+    /// This is the shell convention of exit code `128 + signal`.
+    pub fn code_from_signal(self, signal: i32) -> Self {
+        self.code_impl(&EqCodePredicate::new(128 + signal))
+    }
+
+    /// Ensure `stdout`, `stderr`, and the exit code match the files expected for the current
+    /// test.
+    ///
+    /// For a test function named `help`, these are `tests/expected/help.stdout`,
+    /// `tests/expected/help.stderr`, and `tests/expected/help.code`, relative to the crate root.
+    /// Set `ASSERT_CMD_OVERWRITE=1` to create or update the files from the actual output, or
+    /// `ASSERT_CMD_PENDING=1` to save mismatches for [`golden::review`].
+    ///
+    /// The test name is taken from the test harness's thread name, so this doesn't work with
+    /// `--test-threads=1`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// #[test]
+    /// fn help() {
+    ///     Command::cargo_bin("bin_fixture")
+    ///         .unwrap()
+    ///         .arg("--help")
+    ///         .assert()
+    ///         .matches_expected();
+    /// }
+    /// ```
+    ///
+    /// [`golden::review`]: crate::golden::review()
+    pub fn matches_expected(self) -> Self {
+        let stem = crate::golden::expected_stem().unwrap_or_else(|err| self.fail(err));
+        let code = match self.output.status.code() {
+            Some(code) => format!("{}\n", code),
+            None => "interrupted\n".to_owned(),
+        };
+        let files = [
+            ("stdout", &self.output.stdout[..]),
+            ("stderr", &self.output.stderr[..]),
+            ("code", code.as_bytes()),
+        ];
+        let errors: Vec<_> = files
+            .iter()
+            .filter_map(|(ext, actual)| {
+                crate::golden::compare(&stem.with_extension(ext), actual).err()
+            })
+            .collect();
+        if !errors.is_empty() {
+            self.fail(errors.join("\n"));
+        }
+        self.stderr_asserted.set(true);
+        self
+    }
+
+    /// Ensure the exit code, `stdout`, and `stderr` match the snapshot `name`.
+    ///
+    /// Snapshots are kept in one file each, under `tests/snapshots` relative to the crate root,
+    /// in a directory per module of the current test: `cli::help` saving `"usage"` uses
+    /// `tests/snapshots/cli/usage.snap`.  On a mismatch, the failure shows the differences; set
+    /// `UPDATE_SNAPSHOTS=1` to create or update the snapshots from the actual output, or
+    /// `ASSERT_CMD_PENDING=1` to save mismatches for [`golden::review`].
+    ///
+    /// With [`Streams::Combined`], the combined output is saved instead of `stdout` and
+    /// `stderr`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .arg("--help")
+    ///     .assert()
+    ///     .matches_snapshot("help");
+    /// ```
+    ///
+    /// [`golden::review`]: crate::golden::review()
+    /// [`Streams::Combined`]: crate::cmd::Streams::Combined
+    pub fn matches_snapshot(self, name: &str) -> Self {
+        let code = self.output.status.code();
+        let snapshot = match self.combined {
+            Some(ref combined) => crate::golden::snapshot(code, &[("combined", combined)]),
+            None => crate::golden::snapshot(
+                code,
+                &[
+                    ("stdout", &self.output.stdout),
+                    ("stderr", &self.output.stderr),
+                ],
+            ),
+        };
+        let path = crate::golden::snapshot_path(name);
+        if let Err(err) =
+            crate::golden::compare_with(&path, &snapshot, crate::golden::UPDATE_SNAPSHOTS_ENV)
+        {
+            self.fail(err);
+        }
+        self.stderr_asserted.set(true);
+        self
+    }
+
+    /// Ensure the exit status, `stdout`, and `stderr` match the TOML document at `path`.
+    ///
+    /// The document keeps the whole result together, like:
+    ///
+    /// ```toml
+    /// status.code = 2
+    /// stdout = ""
+    /// stderr = '''
+    /// error: missing <input>
+    /// '''
+    /// ```
+    ///
+    /// A command killed by a signal has a `status.signal` instead, and with
+    /// [`Streams::Combined`], the combined output is saved as `combined`.  Invalid UTF-8 is
+    /// replaced.  Set `ASSERT_CMD_OVERWRITE=1` to create or update the file from the actual
+    /// result, or `ASSERT_CMD_PENDING=1` to save a mismatch for [`golden::review`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .arg("--help")
+    ///     .assert()
+    ///     .matches_path("tests/expected/help.toml");
+    /// ```
+    ///
+    /// [`golden::review`]: crate::golden::review()
+    /// [`Streams::Combined`]: crate::cmd::Streams::Combined
+    pub fn matches_path<P: AsRef<path::Path>>(self, path: P) -> Self {
+        #[cfg(unix)]
+        let signal = {
+            use std::os::unix::process::ExitStatusExt;
+            self.output.status.signal()
+        };
+        #[cfg(not(unix))]
+        let signal: Option<i32> = None;
+        let status = match (self.output.status.code(), signal) {
+            (Some(code), _) => format!("status.code = {}", code),
+            (None, Some(signal)) => format!("status.signal = {}", signal),
+            (None, None) => "status = \"interrupted\"".to_owned(),
+        };
+        let document = match self.combined {
+            Some(ref combined) => crate::golden::toml_result(&status, &[("combined", combined)]),
+            None => crate::golden::toml_result(
+                &status,
+                &[
+                    ("stdout", &self.output.stdout),
+                    ("stderr", &self.output.stderr),
+                ],
+            ),
+        };
+        if let Err(err) = crate::golden::compare(path.as_ref(), &document) {
+            self.fail(err);
+        }
+        self.stderr_asserted.set(true);
+        self
+    }
+
+    /// Ensure `stdout` matches the contents of the file at `path`.
+    ///
+    /// Set `ASSERT_CMD_OVERWRITE=1` to create or update the file from the actual output, or
+    /// `ASSERT_CMD_PENDING=1` to save a mismatch for [`golden::review`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello")
+    ///     .assert()
+    ///     .stdout_matches_path("tests/expected/hello.stdout");
+    /// ```
+    ///
+    /// [`golden::review`]: crate::golden::review()
+    pub fn stdout_matches_path<P: AsRef<path::Path>>(self, path: P) -> Self {
+        if let Err(err) = crate::golden::compare(path.as_ref(), &self.output.stdout) {
+            self.fail(err);
+        }
+        self
+    }
+
+    /// Ensure `stderr` matches the contents of the file at `path`.
+    ///
+    /// See [`Assert::stdout_matches_path`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stderr", "oops")
+    ///     .assert()
+    ///     .stderr_matches_path("tests/expected/oops.stderr");
+    /// ```
+    pub fn stderr_matches_path<P: AsRef<path::Path>>(self, path: P) -> Self {
+        if let Err(err) = crate::golden::compare(path.as_ref(), &self.output.stderr) {
+            self.fail(err);
+        }
+        self.stderr_asserted.set(true);
+        self
+    }
+
+    /// Ensure `stdout` matches the contents of the file at `path`, overlooking the differences
+    /// `tolerance` allows.
+    ///
+    /// See [`Assert::stdout_matches_path`]; overwriting still saves the output as-is.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::settings::Tolerance;
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello  ")
+    ///     .assert()
+    ///     .stdout_matches_path_tolerant(
+    ///         "tests/expected/hello.stdout",
+    ///         Tolerance::new().trailing_whitespace(),
+    ///     );
+    /// ```
+    pub fn stdout_matches_path_tolerant<P: AsRef<path::Path>>(
+        self,
+        path: P,
+        tolerance: crate::settings::Tolerance,
+    ) -> Self {
+        if let Err(err) =
+            crate::golden::compare_tolerant(path.as_ref(), &self.output.stdout, &tolerance)
+        {
+            self.fail(err);
+        }
+        self
+    }
+
+    /// Ensure `stderr` matches the contents of the file at `path`, overlooking the differences
+    /// `tolerance` allows.
+    ///
+    /// See [`Assert::stdout_matches_path_tolerant`].
+    pub fn stderr_matches_path_tolerant<P: AsRef<path::Path>>(
+        self,
+        path: P,
+        tolerance: crate::settings::Tolerance,
+    ) -> Self {
+        if let Err(err) =
+            crate::golden::compare_tolerant(path.as_ref(), &self.output.stderr, &tolerance)
+        {
+            self.fail(err);
+        }
+        self.stderr_asserted.set(true);
+        self
+    }
+
+    /// Resource usage samples, see [`Command::sample_resources`].
+    ///
+    /// [`Command::sample_resources`]: crate::cmd::Command::sample_resources()
+    pub fn resource_samples(&self) -> &[crate::resources::Sample] {
+        self.samples.as_deref().unwrap_or(&[])
+    }
+
+    /// Ensure the highest resident set size sampled, in bytes, matches `pred`.
+    ///
+    /// Requires [`Command::sample_resources`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    /// use predicates::prelude::*;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .sample_resources(std::time::Duration::from_millis(100))
+    ///     .assert()
+    ///     .peak_rss(predicate::lt(200 * 1024 * 1024));
+    /// ```
+    ///
+    /// [`Command::sample_resources`]: crate::cmd::Command::sample_resources()
+    pub fn peak_rss<P>(self, pred: P) -> Self
+    where
+        P: predicates_core::Predicate<u64>,
+    {
+        self.peak_rss_impl(&pred)
+    }
+
+    fn peak_rss_impl(self, pred: &dyn predicates_core::Predicate<u64>) -> Self {
+        let peak = self
+            .require_samples()
+            .iter()
+            .map(|s| s.rss)
+            .max()
+            .unwrap_or(0);
+        if let Some(case) = pred.find_case(false, &peak) {
+            self.fail(format_args!(
+                "Unexpected peak RSS, failed {}\npeak_rss=`{}`",
+                case.tree(),
+                peak
+            ));
+        }
+        self
+    }
+
+    /// The child's total resource usage, like its peak memory and CPU time, if known.
+    ///
+    /// This is collected when the child exits, for [`Command`] on Unix.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// let assert = Command::cargo_bin("bin_fixture").unwrap().assert();
+    /// let usage = assert.get_resource_usage().unwrap();
+    /// println!("{:?} of CPU, {} bytes", usage.cpu_time(), usage.max_rss);
+    /// ```
+    ///
+    /// [`Command`]: crate::cmd::Command
+    pub fn get_resource_usage(&self) -> Option<crate::resources::ResourceUsage> {
+        self.usage
+    }
+
+    /// Ensure the child's peak resident set size stayed below `bytes`, to guard against memory
+    /// blowups.
+    ///
+    /// See [`Assert::get_resource_usage`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .assert()
+    ///     .success()
+    ///     .max_rss_below(200 * 1024 * 1024);
+    /// ```
+    pub fn max_rss_below(self, bytes: u64) -> Self {
+        let usage = match self.usage {
+            Some(usage) => usage,
+            None => self
+                .fail("No resource usage was collected; it's only available for `Command` on Unix"),
+        };
+        if bytes <= usage.max_rss {
+            self.fail(format_args!(
+                "Peak RSS of {} bytes, expected below {} bytes",
+                usage.max_rss, bytes
+            ));
+        }
+        self
+    }
+
+    /// Ensure the resident set size didn't keep growing, a sign of a leak.
+    ///
+    /// This fails if the RSS never shrank and was still growing through the second half of the
+    /// samples; a jump during startup that levels off is fine.
+    ///
+    /// Requires [`Command::sample_resources`] and at least three samples.
+    ///
+    /// [`Command::sample_resources`]: crate::cmd::Command::sample_resources()
+    pub fn no_monotonic_rss_growth(self) -> Self {
+        const MIN_SAMPLES: usize = 3;
+
+        let samples = self.require_samples();
+        if samples.len() < MIN_SAMPLES {
+            self.fail(format_args!(
+                "Too few resource samples to detect growth, got {} but need {}",
+                samples.len(),
+                MIN_SAMPLES
+            ));
+        }
+        if crate::resources::grows_monotonically(samples) {
+            let series: Vec<_> = samples.iter().map(|s| s.rss.to_string()).collect();
+            self.fail(format_args!(
+                "RSS grew with every sample\nrss=`{}`",
+                series.join(", ")
+            ));
+        }
+        self
+    }
+
+    /// How long the command ran, if known.
+    ///
+    /// This is measured by [`Command`], from spawning the child to having read all of its
+    /// output.
+    ///
+    /// [`Command`]: crate::cmd::Command
+    pub fn get_duration(&self) -> Option<std::time::Duration> {
+        self.transcript().duration()
+    }
+
+    /// Ensure how long the command ran matches `pred`.
+    ///
+    /// Requires running through [`Command`], which records the duration.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    /// use predicates::prelude::*;
+    ///
+    /// use std::time::Duration;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .arg("--help")
+    ///     .assert()
+    ///     .duration(predicate::lt(Duration::from_millis(200)));
+    /// ```
+    ///
+    /// [`Command`]: crate::cmd::Command
+    pub fn duration<P>(self, pred: P) -> Self
+    where
+        P: predicates_core::Predicate<std::time::Duration>,
+    {
+        self.run_duration_impl(&pred)
+    }
+
+    fn run_duration_impl(self, pred: &dyn predicates_core::Predicate<std::time::Duration>) -> Self {
+        let duration = match self.get_duration() {
+            Some(duration) => duration,
+            None => self.fail("No duration recorded, run it with `Command`"),
+        };
+        if let Some(case) = pred.find_case(false, &duration) {
+            self.fail(format_args!(
+                "Unexpected duration, failed {}\nduration=`{:?}`",
+                case.tree(),
+                duration
+            ));
+        }
+        self
+    }
+
+    /// Ensure the command ran for at most `limit`, for performance smoke tests.
+    ///
+    /// See [`Assert::duration`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// use std::time::Duration;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .arg("--help")
+    ///     .assert()
+    ///     .completes_within(Duration::from_millis(200));
+    /// ```
+    pub fn completes_within(self, limit: std::time::Duration) -> Self {
+        let duration = match self.get_duration() {
+            Some(duration) => duration,
+            None => self.fail("No duration recorded, run it with `Command`"),
+        };
+        if limit < duration {
+            self.fail(format_args!(
+                "Command took {:?}, expected to complete within {:?}",
+                duration, limit
+            ));
+        }
+        self
+    }
+
+    /// Ensure spawning the child took at most `limit`.
+    ///
+    /// Requires running through [`Command`], which records [`Timings`].
+    ///
+    /// [`Command`]: crate::cmd::Command
+    /// [`Timings`]: crate::transcript::Timings
+    pub fn spawns_within(self, limit: std::time::Duration) -> Self {
+        self.within_impl("spawn", |t| Some(t.spawn), limit)
+    }
+
+    /// Ensure the first byte of `stdout` was written within `limit` of spawning.
+    ///
+    /// Requires running through [`Command`], which records [`Timings`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// use std::time::Duration;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "ready")
+    ///     .env("sleep", "1")
+    ///     .assert()
+    ///     .first_stdout_within(Duration::from_millis(200));
+    /// ```
+    ///
+    /// [`Command`]: crate::cmd::Command
+    /// [`Timings`]: crate::transcript::Timings
+    pub fn first_stdout_within(self, limit: std::time::Duration) -> Self {
+        self.within_impl("first stdout", |t| t.first_stdout, limit)
+    }
+
+    /// Ensure the first byte of `stderr` was written within `limit` of spawning.
+    ///
+    /// See [`Assert::first_stdout_within`].
+    pub fn first_stderr_within(self, limit: std::time::Duration) -> Self {
+        self.within_impl("first stderr", |t| t.first_stderr, limit)
+    }
+
+    /// Ensure the first byte on either stream was written within `limit` of spawning.
+    ///
+    /// See [`Assert::first_stdout_within`].
+    pub fn first_output_within(self, limit: std::time::Duration) -> Self {
+        self.within_impl("first output", |t| t.first_output(), limit)
+    }
+
+    /// Ensure the child exited, with all of its output read, within `limit` of spawning.
+    ///
+    /// See [`Assert::first_stdout_within`].
+    pub fn exits_within(self, limit: std::time::Duration) -> Self {
+        self.within_impl("exit", |t| Some(t.exit), limit)
+    }
+
+    fn within_impl(
+        self,
+        milestone: &str,
+        at: fn(&crate::transcript::Timings) -> Option<std::time::Duration>,
+        limit: std::time::Duration,
+    ) -> Self {
+        let timings = match self.transcript().timings() {
+            Some(timings) => timings,
+            None => self.fail("No timings recorded, run it with `Command`"),
+        };
+        match at(&timings) {
+            Some(elapsed) if elapsed <= limit => {}
+            Some(elapsed) => self.fail(format_args!(
+                "Unexpected {} latency, took {:?} but expected within {:?}\ntimings=`{:?}`",
+                milestone, elapsed, limit, timings
+            )),
+            None => self.fail(format_args!(
+                "Unexpected {} latency, nothing was written but expected within {:?}",
+                milestone, limit
+            )),
+        }
+        self
+    }
+
+    /// Ensure the child removed everything it created in its temp dir.
+    ///
+    /// Requires [`Command::isolated_temp_dir`].
+    ///
+    /// [`Command::isolated_temp_dir`]: crate::cmd::Command::isolated_temp_dir()
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .isolated_temp_dir(true)
+    ///     .assert()
+    ///     .leaves_no_temp_files();
+    /// ```
+    pub fn leaves_no_temp_files(self) -> Self {
+        let leftovers = match self.temp_leftovers {
+            Some(ref leftovers) => leftovers,
+            None => self.fail("Temp dir wasn't isolated; see `Command::isolated_temp_dir`"),
+        };
+        if !leftovers.is_empty() {
+            let listing: Vec<_> = leftovers
+                .iter()
+                .map(|path| format!("  {}", path.display()))
+                .collect();
+            self.fail(format_args!(
+                "Child left {} entries in its temp dir\n{}",
+                leftovers.len(),
+                listing.join("\n")
+            ));
+        }
+        self
+    }
+
+    /// Ensure the command left the expected data in the file at `path`, relative to its working
+    /// directory.
+    ///
+    /// This uses [`IntoOutputPredicate`] to provide short-hands for common cases.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::new("sh")
+    ///     .args(["-c", "mkdir -p out && echo done > out/report.txt"])
+    ///     .in_temp_dir()
+    ///     .assert()
+    ///     .success()
+    ///     .file("out/report.txt", "done\n")
+    ///     .dir_exists("out");
+    /// ```
+    pub fn file<F, I, P>(self, path: F, pred: I) -> Self
+    where
+        F: AsRef<std::path::Path>,
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        self.file_impl(path.as_ref(), &pred.into_output())
+    }
+
+    fn file_impl(
+        self,
+        path: &std::path::Path,
+        pred: &dyn predicates_core::Predicate<[u8]>,
+    ) -> Self {
+        let path = self.resolve(path);
+        let actual = match std::fs::read(&path) {
+            Ok(actual) => actual,
+            Err(err) => self.fail(format_args!("Unable to read `{}`: {}", path.display(), err)),
+        };
+        if let Some(case) = pred.find_case(false, &actual) {
+            self.fail(format_args!(
+                "Unexpected contents of `{}`, failed {}",
+                path.display(),
+                case.tree()
+            ));
+        }
+        self
+    }
+
+    /// Ensure the command left a directory at `path`, relative to its working directory.
+    ///
+    /// See [`Assert::file`].
+    pub fn dir_exists<F: AsRef<std::path::Path>>(self, path: F) -> Self {
+        let path = self.resolve(path.as_ref());
+        match std::fs::metadata(&path) {
+            Ok(metadata) if metadata.is_dir() => {}
+            Ok(_) => self.fail(format_args!(
+                "Expected a directory at `{}`, found a file",
+                path.display()
+            )),
+            Err(err) => self.fail(format_args!(
+                "Expected a directory at `{}`: {}",
+                path.display(),
+                err
+            )),
+        }
+        self
+    }
+
+    /// `path` relative to the child's working directory.
+    fn resolve(&self, path: &std::path::Path) -> std::path::PathBuf {
+        match self.cwd {
+            Some(ref cwd) => cwd.join(path),
+            None => path.to_owned(),
+        }
+    }
+
+    fn require_samples(&self) -> &[crate::resources::Sample] {
+        match self.samples {
+            Some(ref samples) if !samples.is_empty() => samples,
+            Some(_) => {
+                self.fail("No resource samples were collected; sampling is only supported on Linux")
+            }
+            None => self.fail("Resource sampling wasn't enabled; see `Command::sample_resources`"),
+        }
+    }
+
+    /// Ensure the command wrote the expected data to `stdout`.
+    ///
+    /// This uses [`IntoOutputPredicate`] to provide short-hands for common cases.
+    ///
+    /// See [`predicates`] for more predicates.
+    ///
+    /// # Examples
+    ///
+    /// Accepting a bytes predicate:
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    /// use predicates::prelude::*;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello")
+    ///     .env("stderr", "world")
+    ///     .assert()
+    ///     .stdout(predicate::eq(b"hello\n" as &[u8]));
+    /// ```
+    ///
+    /// Accepting a `str` predicate:
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    /// use predicates::prelude::*;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello")
+    ///     .env("stderr", "world")
+    ///     .assert()
+    ///     .stdout(predicate::str::diff("hello\n"));
+    /// ```
+    ///
+    /// Accepting bytes:
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello")
+    ///     .env("stderr", "world")
+    ///     .assert()
+    ///     .stdout(b"hello\n" as &[u8]);
+    /// ```
+    ///
+    /// Accepting a `str`:
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello")
+    ///     .env("stderr", "world")
+    ///     .assert()
+    ///     .stdout("hello\n");
+    /// ```
+    ///
+    pub fn stdout<I, P>(self, pred: I) -> Self
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        self.stdout_impl(&pred.into_output())
+    }
+
+    fn stdout_impl(self, pred: &dyn predicates_core::Predicate<[u8]>) -> Self {
+        {
+            let actual = &self.output.stdout;
+            if let Some(case) = pred.find_case(false, actual) {
+                self.fail(format_args!("Unexpected stdout, failed {}", case.tree()));
+            }
+        }
+        self
+    }
+
+    /// Ensure the command wrote the expected data to `stderr`.
+    ///
+    /// This uses [`IntoOutputPredicate`] to provide short-hands for common cases.
+    ///
+    /// See [`predicates`] for more predicates.
+    ///
+    /// # Examples
+    ///
+    /// Accepting a bytes predicate:
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    /// use predicates::prelude::*;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello")
+    ///     .env("stderr", "world")
+    ///     .assert()
+    ///     .stderr(predicate::eq(b"world\n" as &[u8]));
+    /// ```
+    ///
+    /// Accepting a `str` predicate:
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    /// use predicates::prelude::*;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello")
+    ///     .env("stderr", "world")
+    ///     .assert()
+    ///     .stderr(predicate::str::diff("world\n"));
+    /// ```
+    ///
+    /// Accepting bytes:
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello")
+    ///     .env("stderr", "world")
+    ///     .assert()
+    ///     .stderr(b"world\n" as &[u8]);
+    /// ```
+    ///
+    /// Accepting a `str`:
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello")
+    ///     .env("stderr", "world")
+    ///     .assert()
+    ///     .stderr("world\n");
+    /// ```
+    ///
+    pub fn stderr<I, P>(self, pred: I) -> Self
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        self.stderr_impl(&pred.into_output())
+    }
+
+    fn stderr_impl(self, pred: &dyn predicates_core::Predicate<[u8]>) -> Self {
+        {
+            let actual = self.stderr_bytes();
+            if let Some(case) = pred.find_case(false, actual) {
+                self.fail(format_args!("Unexpected stderr, failed {}\n", case.tree()));
+            }
+        }
+        self
+    }
+
+    /// Ensure the command wrote the expected data to `stdout` and `stderr`, interleaved as
+    /// written.
+    ///
+    /// Requires capturing [`Streams::Combined`], which leaves [`Assert::stdout`] and
+    /// [`Assert::stderr`] empty.
+    ///
+    /// This uses [`IntoOutputPredicate`] to provide short-hands for common cases.
+    ///
+    /// [`Streams::Combined`]: crate::cmd::Streams::Combined
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::cmd::Streams;
+    /// use assert_cmd::Command;
+    ///
+    /// Command::new("sh")
+    ///     .args(["-c", "echo result; echo progress >&2; echo done"])
+    ///     .capture(Streams::Combined)
+    ///     .assert()
+    ///     .combined_output("result\nprogress\ndone\n");
+    /// ```
+    pub fn combined_output<I, P>(self, pred: I) -> Self
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        self.combined_output_impl(&pred.into_output())
+    }
+
+    fn combined_output_impl(self, pred: &dyn predicates_core::Predicate<[u8]>) -> Self {
+        self.stderr_asserted.set(true);
+        let actual = match self.combined {
+            Some(ref combined) => combined,
+            None => self.fail("Output wasn't combined; see `Streams::Combined`"),
+        };
+        if let Some(case) = pred.find_case(false, actual) {
+            self.fail(format_args!(
+                "Unexpected combined output, failed {}",
+                case.tree()
+            ));
+        }
+        self
+    }
+
+    /// Ensure `stdout` is `expected`, treating `\r\n` and `\n` line endings the same.
+    ///
+    /// Both sides are normalized to `\n` before comparing, for CLIs that write the platform's
+    /// line endings.  Use [`AssertSettings::normalize_line_endings`] to normalize every
+    /// assertion instead.
+    ///
+    /// [`AssertSettings::normalize_line_endings`]: crate::settings::AssertSettings::normalize_line_endings()
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "a\r\nb")
+    ///     .assert()
+    ///     .stdout_normalized("a\nb\n");
+    /// ```
+    pub fn stdout_normalized<S: AsRef<str>>(self, expected: S) -> Self {
+        self.normalized_impl("stdout", expected.as_ref())
+    }
+
+    /// Ensure `stderr` is `expected`, treating `\r\n` and `\n` line endings the same.
+    ///
+    /// See [`Assert::stdout_normalized`].
+    pub fn stderr_normalized<S: AsRef<str>>(self, expected: S) -> Self {
+        self.normalized_impl("stderr", expected.as_ref())
+    }
+
+    fn normalized_impl(self, stream: &str, expected: &str) -> Self {
+        let expected = expected.replace("\r\n", "\n");
+        let actual = crate::settings::crlf_to_lf(self.stream(stream));
+        let pred = StrContentOutputPredicate::from_string(expected);
+        if let Some(case) = predicates_core::Predicate::find_case(&pred, false, &actual) {
+            self.fail(format_args!(
+                "Unexpected {} (line endings normalized), failed {}",
+                stream,
+                case.tree()
+            ));
+        }
+        self
+    }
+
+    /// Ensure `stdout` is `expected`, overlooking the differences `tolerance` allows.
+    ///
+    /// Both sides are normalized by [`Tolerance::apply`] before comparing.
+    ///
+    /// [`Tolerance::apply`]: crate::settings::Tolerance::apply()
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::settings::Tolerance;
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "a  \nb")
+    ///     .assert()
+    ///     .stdout_tolerant("a\nb", Tolerance::new().trailing_whitespace().final_newline());
+    /// ```
+    pub fn stdout_tolerant<S: AsRef<str>>(
+        self,
+        expected: S,
+        tolerance: crate::settings::Tolerance,
+    ) -> Self {
+        self.tolerant_impl("stdout", expected.as_ref(), tolerance)
+    }
+
+    /// Ensure `stderr` is `expected`, overlooking the differences `tolerance` allows.
+    ///
+    /// See [`Assert::stdout_tolerant`].
+    pub fn stderr_tolerant<S: AsRef<str>>(
+        self,
+        expected: S,
+        tolerance: crate::settings::Tolerance,
+    ) -> Self {
+        self.tolerant_impl("stderr", expected.as_ref(), tolerance)
+    }
+
+    fn tolerant_impl(
+        self,
+        stream: &str,
+        expected: &str,
+        tolerance: crate::settings::Tolerance,
+    ) -> Self {
+        let expected = String::from_utf8_lossy(&tolerance.apply(expected.as_bytes())).into_owned();
+        let actual = tolerance.apply(self.stream(stream));
+        let pred = StrContentOutputPredicate::from_string(expected);
+        if let Some(case) = predicates_core::Predicate::find_case(&pred, false, &actual) {
+            self.fail(format_args!(
+                "Unexpected {} (tolerating {}), failed {}",
+                stream,
+                tolerance,
+                case.tree()
+            ));
+        }
+        self
+    }
+
+    /// Ensure `stdout` is valid UTF-8 text matching `pred`.
+    ///
+    /// Unlike [`Assert::stdout`], failures show the text as-is, rather than escaped, and invalid
+    /// UTF-8 is reported with where it starts.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    /// use predicates::prelude::*;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "héllo")
+    ///     .assert()
+    ///     .stdout_str(predicate::str::starts_with("hé"));
+    /// ```
+    pub fn stdout_str<P>(self, pred: P) -> Self
+    where
+        P: predicates_core::Predicate<str>,
+    {
+        self.str_impl("stdout", &pred)
+    }
+
+    /// Ensure `stderr` is valid UTF-8 text matching `pred`.
+    ///
+    /// See [`Assert::stdout_str`].
+    pub fn stderr_str<P>(self, pred: P) -> Self
+    where
+        P: predicates_core::Predicate<str>,
+    {
+        self.str_impl("stderr", &pred)
+    }
+
+    fn str_impl(self, stream: &str, pred: &dyn predicates_core::Predicate<str>) -> Self {
+        let data = self.stream(stream);
+        let text = match str::from_utf8(data) {
+            Ok(text) => text,
+            Err(err) => {
+                let valid = &data[..err.valid_up_to()];
+                let line = valid.iter().filter(|b| **b == b'\n').count() + 1;
+                self.fail(format_args!(
+                    "{} is not valid UTF-8, from byte {} (line {})\n{}=```\n{}```",
+                    stream,
+                    err.valid_up_to(),
+                    line,
+                    stream,
+                    String::from_utf8_lossy(data)
+                ))
+            }
+        };
+        if let Some(case) = pred.find_case(false, text) {
+            self.fail(format_args!(
+                "Unexpected {}, failed {}\n{}=```\n{}```",
+                stream,
+                case.tree(),
+                stream,
+                text
+            ));
+        }
+        self
+    }
+
+    /// Ensure some line of `stdout` matches `pred`.
+    ///
+    /// Lines are split as by [`str::lines`], with invalid UTF-8 replaced.  Failures show the
+    /// first lines, numbered.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    /// use predicates::prelude::*;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "Compiling\nFinished in 2s")
+    ///     .assert()
+    ///     .stdout_has_line(predicate::str::starts_with("Finished"));
+    /// ```
+    pub fn stdout_has_line<P>(self, pred: P) -> Self
+    where
+        P: predicates_core::Predicate<str>,
+    {
+        self.has_line_impl("stdout", &pred)
+    }
+
+    /// Ensure some line of `stderr` matches `pred`.
+    ///
+    /// See [`Assert::stdout_has_line`].
+    pub fn stderr_has_line<P>(self, pred: P) -> Self
+    where
+        P: predicates_core::Predicate<str>,
+    {
+        self.has_line_impl("stderr", &pred)
+    }
+
+    fn has_line_impl(self, stream: &str, pred: &dyn predicates_core::Predicate<str>) -> Self {
+        let text = String::from_utf8_lossy(self.stream(stream));
+        let lines: Vec<_> = text.lines().collect();
+        if !lines.iter().any(|line| pred.eval(line)) {
+            self.fail(format_args!(
+                "No line of {} matched, failed {}\n{}",
+                stream,
+                pred,
+                excerpt(&lines, None)
+            ));
+        }
+        self
+    }
+
+    /// Ensure `stdout` has `count` lines.
+    ///
+    /// A trailing newline doesn't start another line.  See [`Assert::stdout_has_line`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "a\nb\nc\n")
+    ///     .assert()
+    ///     .stdout_line_count(3);
+    /// ```
+    pub fn stdout_line_count(self, count: usize) -> Self {
+        self.line_count_impl("stdout", count)
+    }
+
+    /// Ensure `stderr` has `count` lines.
+    ///
+    /// See [`Assert::stdout_line_count`].
+    pub fn stderr_line_count(self, count: usize) -> Self {
+        self.line_count_impl("stderr", count)
+    }
+
+    fn line_count_impl(self, stream: &str, count: usize) -> Self {
+        let text = String::from_utf8_lossy(self.stream(stream));
+        let lines: Vec<_> = text.lines().collect();
+        if lines.len() != count {
+            self.fail(format_args!(
+                "Unexpected number of lines in {}, expected {}, got {}\n{}",
+                stream,
+                count,
+                lines.len(),
+                excerpt(&lines, None)
+            ));
+        }
+        self
+    }
+
+    /// Ensure line `number` of `stdout`, counting from 1, matches `pred`.
+    ///
+    /// Failures show the lines around it.  See [`Assert::stdout_has_line`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    /// use predicates::prelude::*;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "name: demo\nversion: 1.0")
+    ///     .assert()
+    ///     .stdout_line(2, predicate::str::starts_with("version:"));
+    /// ```
+    pub fn stdout_line<P>(self, number: usize, pred: P) -> Self
+    where
+        P: predicates_core::Predicate<str>,
+    {
+        self.line_impl("stdout", number, &pred)
+    }
+
+    /// Ensure line `number` of `stderr`, counting from 1, matches `pred`.
+    ///
+    /// See [`Assert::stdout_line`].
+    pub fn stderr_line<P>(self, number: usize, pred: P) -> Self
+    where
+        P: predicates_core::Predicate<str>,
+    {
+        self.line_impl("stderr", number, &pred)
+    }
+
+    fn line_impl(
+        self,
+        stream: &str,
+        number: usize,
+        pred: &dyn predicates_core::Predicate<str>,
+    ) -> Self {
+        let text = String::from_utf8_lossy(self.stream(stream));
+        let lines: Vec<_> = text.lines().collect();
+        let line = match number.checked_sub(1).and_then(|i| lines.get(i)) {
+            Some(line) => *line,
+            None => self.fail(format_args!(
+                "No line {} in {}, which has {} lines\n{}",
+                number,
+                stream,
+                lines.len(),
+                excerpt(&lines, None)
+            )),
+        };
+        if let Some(case) = pred.find_case(false, line) {
+            self.fail(format_args!(
+                "Unexpected line {} of {}, failed {}\n{}",
+                number,
+                stream,
+                case.tree(),
+                excerpt(&lines, Some(number))
+            ));
+        }
+        self
+    }
+
+    /// Ensure `stdout` doesn't contain `forbidden`.
+    ///
+    /// Unlike negating a predicate, failures show where `forbidden` occurred, with the lines
+    /// around it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "Compiling\nFinished")
+    ///     .assert()
+    ///     .stdout_does_not_contain("panicked");
+    /// ```
+    pub fn stdout_does_not_contain<S: AsRef<str>>(self, forbidden: S) -> Self {
+        self.does_not_contain_impl("stdout", forbidden.as_ref())
+    }
+
+    /// Ensure `stderr` doesn't contain `forbidden`.
+    ///
+    /// See [`Assert::stdout_does_not_contain`].
+    pub fn stderr_does_not_contain<S: AsRef<str>>(self, forbidden: S) -> Self {
+        self.does_not_contain_impl("stderr", forbidden.as_ref())
+    }
+
+    fn does_not_contain_impl(self, stream: &str, forbidden: &str) -> Self {
+        let found: Vec<_> = self.stream(stream).find_iter(forbidden).collect();
+        self.forbidden_impl(stream, &format!("{:?}", forbidden), &found)
+    }
+
+    /// Ensure no part of `stdout` matches the regular expression `forbidden`.
+    ///
+    /// See [`Assert::stdout_does_not_contain`], and the [`regex` crate](https://docs.rs/regex)
+    /// for the syntax.
+    ///
+    /// # Panics
+    ///
+    /// If `forbidden` is not a valid regular expression.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stderr", "warning: unused")
+    ///     .assert()
+    ///     .stderr_does_not_match(r"(?i)\berror\b");
+    /// ```
+    pub fn stdout_does_not_match(self, forbidden: &str) -> Self {
+        self.does_not_match_impl("stdout", forbidden)
+    }
+
+    /// Ensure no part of `stderr` matches the regular expression `forbidden`.
+    ///
+    /// See [`Assert::stdout_does_not_match`].
+    pub fn stderr_does_not_match(self, forbidden: &str) -> Self {
+        self.does_not_match_impl("stderr", forbidden)
+    }
+
+    /// Ensure the command wrote nothing to `stdout`.
+    ///
+    /// On failure, this reports how much was written, with an excerpt.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stderr", "hello")
+    ///     .assert()
+    ///     .stdout_empty();
+    /// ```
+    pub fn stdout_empty(self) -> Self {
+        self.empty_impl("stdout")
+    }
+
+    /// Ensure the command wrote nothing to `stderr`.
+    ///
+    /// See [`Assert::stdout_empty`].
+    pub fn stderr_empty(self) -> Self {
+        self.empty_impl("stderr")
+    }
+
+    fn empty_impl(self, stream: &str) -> Self {
+        let data = self.stream(stream);
+        if data.is_empty() {
+            return self;
+        }
+        let text = String::from_utf8_lossy(data);
+        let lines: Vec<_> = text.lines().collect();
+        self.fail(format_args!(
+            "Expected no output on {}, got {} bytes\n{}",
+            stream,
+            data.len(),
+            excerpt(&lines, None)
+        ));
+    }
+
+    /// The file `stdout` was written to, with [`Command::capture_to_files`].
+    ///
+    /// [`Command::capture_to_files`]: crate::cmd::Command::capture_to_files()
+    pub fn stdout_path(&self) -> Option<path::PathBuf> {
+        self.spool.as_ref().map(|spool| spool.path("stdout"))
+    }
+
+    /// The file `stderr` was written to, with [`Command::capture_to_files`].
+    ///
+    /// [`Command::capture_to_files`]: crate::cmd::Command::capture_to_files()
+    pub fn stderr_path(&self) -> Option<path::PathBuf> {
+        self.spool.as_ref().map(|spool| spool.path("stderr"))
+    }
+
+    /// Ensure the size in bytes of the file `stdout` was written to matches `pred`.
+    ///
+    /// See [`Command::capture_to_files`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    /// use predicates::prelude::*;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello")
+    ///     .capture_to_files(true)
+    ///     .assert()
+    ///     .stdout_file_size(predicate::eq(6));
+    /// ```
+    ///
+    /// [`Command::capture_to_files`]: crate::cmd::Command::capture_to_files()
+    pub fn stdout_file_size<P>(self, pred: P) -> Self
+    where
+        P: predicates_core::Predicate<u64>,
+    {
+        self.file_size_impl("stdout", &pred)
+    }
+
+    /// Ensure the size in bytes of the file `stderr` was written to matches `pred`.
+    ///
+    /// See [`Assert::stdout_file_size`].
+    pub fn stderr_file_size<P>(self, pred: P) -> Self
+    where
+        P: predicates_core::Predicate<u64>,
+    {
+        self.file_size_impl("stderr", &pred)
+    }
+
+    fn file_size_impl(self, stream: &str, pred: &dyn predicates_core::Predicate<u64>) -> Self {
+        let path = self.spool_path(stream);
+        let size = match std::fs::metadata(&path) {
+            Ok(metadata) => metadata.len(),
+            Err(err) => self.fail(format_args!(
+                "Unable to read {} file `{}`: {}",
+                stream,
+                path.display(),
+                err
+            )),
+        };
+        if let Some(case) = pred.find_case(false, &size) {
+            self.fail(format_args!(
+                "Unexpected size of {} file, failed {}",
+                stream,
+                case.tree()
+            ));
+        }
+        self
+    }
+
+    /// Ensure the SHA-256 of the file `stdout` was written to is `expected`, in hex.
+    ///
+    /// See [`Command::capture_to_files`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello")
+    ///     .capture_to_files(true)
+    ///     .assert()
+    ///     .stdout_file_sha256("5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03");
+    /// ```
+    ///
+    /// [`Command::capture_to_files`]: crate::cmd::Command::capture_to_files()
+    pub fn stdout_file_sha256(self, expected: &str) -> Self {
+        self.file_sha256_impl("stdout", expected)
+    }
+
+    /// Ensure the SHA-256 of the file `stderr` was written to is `expected`, in hex.
+    ///
+    /// See [`Assert::stdout_file_sha256`].
+    pub fn stderr_file_sha256(self, expected: &str) -> Self {
+        self.file_sha256_impl("stderr", expected)
+    }
+
+    fn file_sha256_impl(self, stream: &str, expected: &str) -> Self {
+        let path = self.spool_path(stream);
+        let actual = match std::fs::File::open(&path)
+            .and_then(|file| crate::spool::sha256(std::io::BufReader::new(file)))
+        {
+            Ok(actual) => actual,
+            Err(err) => self.fail(format_args!(
+                "Unable to read {} file `{}`: {}",
+                stream,
+                path.display(),
+                err
+            )),
+        };
+        if !actual.eq_ignore_ascii_case(expected.trim()) {
+            self.fail(format_args!(
+                "Unexpected SHA-256 of {} file, expected {}, got {}",
+                stream, expected, actual
+            ));
+        }
+        self
+    }
+
+    /// Ensure `check` accepts the file `stdout` was written to, reading it as it goes.
+    ///
+    /// See [`Command::capture_to_files`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    /// use std::io::BufRead;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .arg("--generate")
+    ///     .capture_to_files(true)
+    ///     .assert()
+    ///     .stdout_file_check(|reader| reader.lines().all(|line| line.map_or(false, |l| l.starts_with("row "))));
+    /// ```
+    ///
+    /// [`Command::capture_to_files`]: crate::cmd::Command::capture_to_files()
+    pub fn stdout_file_check<F>(self, check: F) -> Self
+    where
+        F: FnOnce(&mut dyn std::io::BufRead) -> bool,
+    {
+        self.file_check_impl("stdout", check)
+    }
+
+    /// Ensure `check` accepts the file `stderr` was written to, reading it as it goes.
+    ///
+    /// See [`Assert::stdout_file_check`].
+    pub fn stderr_file_check<F>(self, check: F) -> Self
+    where
+        F: FnOnce(&mut dyn std::io::BufRead) -> bool,
+    {
+        self.file_check_impl("stderr", check)
+    }
+
+    fn file_check_impl<F>(self, stream: &str, check: F) -> Self
+    where
+        F: FnOnce(&mut dyn std::io::BufRead) -> bool,
+    {
+        let path = self.spool_path(stream);
+        let mut reader = match std::fs::File::open(&path) {
+            Ok(file) => std::io::BufReader::new(file),
+            Err(err) => self.fail(format_args!(
+                "Unable to read {} file `{}`: {}",
+                stream,
+                path.display(),
+                err
+            )),
+        };
+        if !check(&mut reader) {
+            self.fail(format_args!("Unexpected {} file, failed the check", stream));
+        }
+        self
+    }
+
+    /// The file `stream` was written to.
+    fn spool_path(&self, stream: &str) -> path::PathBuf {
+        if stream == "stderr" {
+            self.stderr_asserted.set(true);
+        }
+        match self.spool.as_ref() {
+            Some(spool) => spool.path(stream),
+            None => self.fail("Output wasn't captured to files; see `Command::capture_to_files`"),
+        }
+    }
+
+    fn does_not_match_impl(self, stream: &str, forbidden: &str) -> Self {
+        let re = crate::matches::compile(forbidden, false, false);
+        let found: Vec<_> = re
+            .find_iter(self.stream(stream))
+            .map(|found| found.start())
+            .collect();
+        self.forbidden_impl(stream, &format!("match of regex {:?}", forbidden), &found)
+    }
+
+    /// Fail if `found`, the offsets where `what` occurred, isn't empty.
+    fn forbidden_impl(self, stream: &str, what: &str, found: &[usize]) -> Self {
+        let first = match found.first() {
+            Some(first) => *first,
+            None => return self,
+        };
+        let data = self.stream(stream);
+        let line = data[..first].iter().filter(|b| **b == b'\n').count() + 1;
+        let times = match found.len() {
+            1 => String::new(),
+            n => format!(" ({} times)", n),
+        };
+        let text = String::from_utf8_lossy(data);
+        let lines: Vec<_> = text.lines().collect();
+        self.fail(format_args!(
+            "Unexpected {} in {}{}, first at line {} (byte {})\n{}",
+            what,
+            stream,
+            times,
+            line,
+            first,
+            excerpt(&lines, Some(line))
+        ));
+    }
+
+    /// Ensure the command wrote the expected data to `stdout`, with a different expectation on
+    /// Windows.
+    ///
+    /// `unix` is used on every other platform.  See [`PerOs`] to distinguish more platforms.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello")
+    ///     .assert()
+    ///     .stdout_per_os("hello\r\n", "hello\n");
+    /// ```
+    ///
+    /// [`PerOs`]: crate::platform::PerOs
+    pub fn stdout_per_os<W, WP, U, UP>(self, windows: W, unix: U) -> Self
+    where
+        W: IntoOutputPredicate<WP>,
+        WP: predicates_core::Predicate<[u8]>,
+        U: IntoOutputPredicate<UP>,
+        UP: predicates_core::Predicate<[u8]>,
+    {
+        if cfg!(windows) {
+            self.stdout(windows)
+        } else {
+            self.stdout(unix)
+        }
+    }
+
+    /// Ensure the command wrote the expected data to `stderr`, with a different expectation on
+    /// Windows.
+    ///
+    /// `unix` is used on every other platform.  See [`PerOs`] to distinguish more platforms.
+    ///
+    /// [`PerOs`]: crate::platform::PerOs
+    pub fn stderr_per_os<W, WP, U, UP>(self, windows: W, unix: U) -> Self
+    where
+        W: IntoOutputPredicate<WP>,
+        WP: predicates_core::Predicate<[u8]>,
+        U: IntoOutputPredicate<UP>,
+        UP: predicates_core::Predicate<[u8]>,
+    {
+        if cfg!(windows) {
+            self.stderr(windows)
+        } else {
+            self.stderr(unix)
+        }
+    }
+
+    /// Check every assertion, reporting all of their failures together rather than only the
+    /// first.
+    ///
+    /// See [`SoftAssert`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::assert::Assert;
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello")
+    ///     .assert()
+    ///     .soft()
+    ///     .check(Assert::success)
+    ///     .check(|assert| assert.stdout("hello\n"))
+    ///     .check(|assert| assert.stderr(""))
+    ///     .verify();
+    /// ```
+    pub fn soft(self) -> SoftAssert {
+        SoftAssert {
+            assert: self,
+            failures: crate::aggregate::Failures::new(),
+        }
+    }
+
+    /// A copy to check independently, see [`Assert::soft`].
+    ///
+    /// The copy doesn't check [`Assert::strict_stderr`]; that's left to `self`.
+    fn duplicate(&self) -> Self {
+        Self {
+            output: self.output.clone(),
+            context: self.context.clone(),
+            child_env: self.child_env.clone(),
+            message: self.message.clone(),
+            strict_stderr: false,
+            stderr_asserted: Cell::new(false),
+            timed_out: self.timed_out,
+            recording: self.recording.clone(),
+            invocation: self.invocation.clone(),
+            settings: self.settings.clone(),
+            samples: self.samples.clone(),
+            usage: self.usage,
+            #[cfg(unix)]
+            child_signals: self.child_signals.clone(),
+            temp_leftovers: self.temp_leftovers.clone(),
+            temp_cwd: self.temp_cwd.clone(),
+            cwd: self.cwd.clone(),
+            combined: self.combined.clone(),
+            spool: self.spool.clone(),
+            stdin_unread: self.stdin_unread,
+            on_failure: self.on_failure.clone(),
+            // Reported by `self`, once.
+            report: Cell::new(None),
+        }
+    }
+
+    /// Run `check`, returning its failure instead of panicking.
+    ///
+    /// This is for custom test harnesses, like `libtest-mimic`, and for reporting several
+    /// failures at once.  Any assertion, or chain of them, can be checked this way; the common
+    /// ones have `try_` shorthands, like [`Assert::try_success`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// let err = Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello")
+    ///     .assert()
+    ///     .try_assert(|assert| assert.success().stdout("goodbye\n"))
+    ///     .unwrap_err();
+    /// assert!(err.headline().starts_with("Unexpected stdout"));
+    /// assert_eq!(err.get_output().stdout, b"hello\n");
+    /// ```
+    pub fn try_assert<F>(self, check: F) -> Result<Self, AssertError>
+    where
+        F: FnOnce(Self) -> Self,
+    {
+        let output = self.output.clone();
+        crate::aggregate::catch(|| check(self)).map_err(|(headline, context)| AssertError {
+            headline,
+            context,
+            output,
+        })
+    }
+
+    /// [`Assert::success`], returning the failure instead of panicking.
+    pub fn try_success(self) -> Result<Self, AssertError> {
+        self.try_assert(Self::success)
+    }
+
+    /// [`Assert::failure`], returning the failure instead of panicking.
+    pub fn try_failure(self) -> Result<Self, AssertError> {
+        self.try_assert(Self::failure)
+    }
+
+    /// [`Assert::interrupted`], returning the failure instead of panicking.
+    pub fn try_interrupted(self) -> Result<Self, AssertError> {
+        self.try_assert(Self::interrupted)
+    }
+
+    /// [`Assert::code`], returning the failure instead of panicking.
+    pub fn try_code<I, P>(self, pred: I) -> Result<Self, AssertError>
+    where
+        I: IntoCodePredicate<P>,
+        P: predicates_core::Predicate<i32>,
+    {
+        self.try_assert(|assert| assert.code(pred))
+    }
+
+    /// [`Assert::stdout`], returning the failure instead of panicking.
+    pub fn try_stdout<I, P>(self, pred: I) -> Result<Self, AssertError>
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        self.try_assert(|assert| assert.stdout(pred))
+    }
+
+    /// [`Assert::stderr`], returning the failure instead of panicking.
+    pub fn try_stderr<I, P>(self, pred: I) -> Result<Self, AssertError>
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        self.try_assert(|assert| assert.stderr(pred))
+    }
+
+    /// Ensure the command wrote ANSI escape sequences, like colors, to `stdout`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "\x1b[1;31mhello\x1b[0m")
+    ///     .assert()
+    ///     .stdout_has_ansi();
+    /// ```
+    pub fn stdout_has_ansi(self) -> Self {
+        if let Err(err) = has_ansi("stdout", &self.output.stdout) {
+            self.fail(err);
+        }
+        self
+    }
+
+    /// Ensure the command did not write any ANSI escape sequences to `stdout`.
+    ///
+    /// Useful for verifying color is suppressed when piped or when `NO_COLOR` is set.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("NO_COLOR", "1")
+    ///     .env("stdout", "hello")
+    ///     .assert()
+    ///     .stdout_no_ansi();
+    /// ```
+    pub fn stdout_no_ansi(self) -> Self {
+        if let Err(err) = no_ansi("stdout", &self.output.stdout) {
+            self.fail(err);
+        }
+        self
+    }
+
+    /// Ensure the command wrote a specific Select Graphic Rendition sequence to `stdout`.
+    ///
+    /// `params` are the `;`-separated parameters of the sequence, e.g. `"1;31"` for
+    /// `ESC[1;31m` (bold red).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "\x1b[1;31mhello\x1b[0m")
+    ///     .assert()
+    ///     .stdout_has_sgr("1;31")
+    ///     .stdout_has_sgr("0");
+    /// ```
+    pub fn stdout_has_sgr(self, params: &str) -> Self {
+        if let Err(err) = has_sgr("stdout", &self.output.stdout, params) {
+            self.fail(err);
+        }
+        self
+    }
+
+    /// Ensure the command wrote ANSI escape sequences, like colors, to `stderr`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stderr", "\x1b[1;31mworld\x1b[0m")
+    ///     .assert()
+    ///     .stderr_has_ansi();
+    /// ```
+    pub fn stderr_has_ansi(self) -> Self {
+        if let Err(err) = has_ansi("stderr", self.stderr_bytes()) {
+            self.fail(err);
+        }
+        self
+    }
+
+    /// Ensure the command did not write any ANSI escape sequences to `stderr`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("NO_COLOR", "1")
     ///     .env("stderr", "world")
     ///     .assert()
-    ///     .stdout("hello\n");
+    ///     .stderr_no_ansi();
+    /// ```
+    pub fn stderr_no_ansi(self) -> Self {
+        if let Err(err) = no_ansi("stderr", self.stderr_bytes()) {
+            self.fail(err);
+        }
+        self
+    }
+
+    /// Ensure the command wrote a specific Select Graphic Rendition sequence to `stderr`.
+    ///
+    /// See [`Assert::stdout_has_sgr`].
+    pub fn stderr_has_sgr(self, params: &str) -> Self {
+        if let Err(err) = has_sgr("stderr", self.stderr_bytes(), params) {
+            self.fail(err);
+        }
+        self
+    }
+
+    /// Ensure the child saw the expected value for the environment variable `name`.
+    ///
+    /// Requires the child to have dumped its environment, see [`Command::env_dump`].
+    ///
+    /// This uses [`IntoOutputPredicate`] to provide short-hands for common cases.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    /// use predicates::prelude::*;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("LEVEL", "debug")
+    ///     .env_dump(true)
+    ///     .assert()
+    ///     .child_env("LEVEL", "debug")
+    ///     .child_env("PATH", predicate::str::is_empty().not());
+    /// ```
+    ///
+    /// [`Command::env_dump`]: crate::cmd::Command::env_dump()
+    pub fn child_env<I, P>(self, name: &str, pred: I) -> Self
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        self.child_env_impl(name, &pred.into_output())
+    }
+
+    fn child_env_impl(self, name: &str, pred: &dyn predicates_core::Predicate<[u8]>) -> Self {
+        let value = match self.child_env_value(name) {
+            Ok(Some(value)) => value,
+            Ok(None) => self.fail(format_args!(
+                "Environment variable `{}` was not set in the child",
+                name
+            )),
+            Err(err) => self.fail(err),
+        };
+        if let Some(case) = pred.find_case(false, value.as_bytes()) {
+            self.fail(format_args!(
+                "Unexpected value for child environment variable `{}`, failed {}",
+                name,
+                case.tree(),
+            ));
+        }
+        self
+    }
+
+    /// Ensure the environment variable `name` was not set in the child.
+    ///
+    /// Requires the child to have dumped its environment, see [`Command::env_dump`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env_remove("LEVEL")
+    ///     .env_dump(true)
+    ///     .assert()
+    ///     .child_env_unset("LEVEL");
+    /// ```
+    ///
+    /// [`Command::env_dump`]: crate::cmd::Command::env_dump()
+    pub fn child_env_unset(self, name: &str) -> Self {
+        match self.child_env_value(name) {
+            Ok(None) => {}
+            Ok(Some(value)) => self.fail(format_args!(
+                "Environment variable `{}` was unexpectedly set in the child to {:?}",
+                name, value
+            )),
+            Err(err) => self.fail(err),
+        }
+        self
+    }
+
+    fn child_env_value(&self, name: &str) -> Result<Option<&str>, &'static str> {
+        let child_env = self.child_env.as_ref().ok_or(
+            "Child did not dump its environment; enable `Command::env_dump` and call `assert_cmd::env_dump::dump()` in the child",
+        )?;
+        Ok(child_env
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str()))
+    }
+
+    /// Ensure the command stayed within the hermeticity `rules`.
+    ///
+    /// `stdout` and `stderr` are checked for absolute paths outside the allowed roots.  If the
+    /// rules list allowed environment variables, the child's environment is checked as well,
+    /// which requires [`Command::env_dump`].
+    ///
+    /// Network access is not audited, see the [`hermetic` module documentation][crate::hermetic].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::hermetic::Rules;
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "wrote /tmp/out.txt")
+    ///     .assert()
+    ///     .audit(&Rules::new().allow_path("/tmp"));
+    /// ```
+    ///
+    /// [`Command::env_dump`]: crate::cmd::Command::env_dump()
+    pub fn audit(self, rules: &crate::hermetic::Rules) -> Self {
+        let mut violations = Vec::new();
+        if rules.audits_env() {
+            let child_env = match self.child_env {
+                Some(ref child_env) => child_env,
+                None => self.fail(
+                    "Child did not dump its environment; enable `Command::env_dump` and call `assert_cmd::env_dump::dump()` in the child",
+                ),
+            };
+            violations.extend(rules.check_env(child_env.iter().map(|(key, _)| key.as_str())));
+        }
+        violations.extend(rules.check_output("stdout", &self.output.stdout));
+        violations.extend(rules.check_output("stderr", &self.output.stderr));
+        if !violations.is_empty() {
+            let violations: String = violations.iter().map(|v| format!("\n  {}", v)).collect();
+            self.fail(format_args!("Hermeticity violations:{}", violations));
+        }
+        self
+    }
+
+    /// Ensure the child had no signals blocked or ignored.
+    ///
+    /// Requires the child to have dumped its signal state, see [`Command::signal_dump`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .reset_signals()
+    ///     .signal_dump(true)
+    ///     .assert()
+    ///     .child_signals_default();
+    /// ```
+    ///
+    /// [`Command::signal_dump`]: crate::cmd::Command::signal_dump()
+    #[cfg(unix)]
+    pub fn child_signals_default(self) -> Self {
+        let state = self.require_child_signals();
+        if !state.is_default() {
+            self.fail(format_args!(
+                "Child inherited non-default signal state\n{}",
+                state
+            ));
+        }
+        self
+    }
+
+    /// Ensure the child's signal state satisfies `pred`.
+    ///
+    /// Requires the child to have dumped its signal state, see [`Command::signal_dump`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// const SIGINT: i32 = 2;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .signal_dump(true)
+    ///     .assert()
+    ///     .child_signals(|state| !state.ignored.contains(&SIGINT));
+    /// ```
+    ///
+    /// [`Command::signal_dump`]: crate::cmd::Command::signal_dump()
+    #[cfg(unix)]
+    pub fn child_signals<F>(self, pred: F) -> Self
+    where
+        F: FnOnce(&crate::signals::SignalState) -> bool,
+    {
+        let state = self.require_child_signals();
+        if !pred(state) {
+            self.fail(format_args!("Unexpected child signal state\n{}", state));
+        }
+        self
+    }
+
+    #[cfg(unix)]
+    fn require_child_signals(&self) -> &crate::signals::SignalState {
+        match self.child_signals {
+            Some(ref state) => state,
+            None => self.fail(
+                "Child did not dump its signal state; enable `Command::signal_dump` and call `assert_cmd::signals::dump()` in the child",
+            ),
+        }
+    }
+
+    /// Ensure `stdout`, as rendered by a terminal, matches `pred`.
+    ///
+    /// Carriage returns and "erase line" sequences are applied like a terminal would, so only the
+    /// final state of each progress line is checked.  Other ANSI escape sequences are removed.
+    ///
+    /// This uses [`IntoOutputPredicate`] to provide short-hands for common cases.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "10%\r50%\r100%")
+    ///     .assert()
+    ///     .stdout_rendered("100%\n");
+    /// ```
+    pub fn stdout_rendered<I, P>(self, pred: I) -> Self
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        self.rendered_impl("stdout", &pred.into_output())
+    }
+
+    /// Ensure `stderr`, as rendered by a terminal, matches `pred`.
+    ///
+    /// See [`Assert::stdout_rendered`].
+    pub fn stderr_rendered<I, P>(self, pred: I) -> Self
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        self.rendered_impl("stderr", &pred.into_output())
+    }
+
+    fn rendered_impl(self, stream: &str, pred: &dyn predicates_core::Predicate<[u8]>) -> Self {
+        let data = self.stream(stream);
+        let rendered = crate::ansi::render(data);
+        if let Some(case) = pred.find_case(false, rendered.as_bytes()) {
+            self.fail(format_args!(
+                "Unexpected rendered {}, failed {}\nrendered=```{}```",
+                stream,
+                case.tree(),
+                rendered
+            ));
+        }
+        self
+    }
+
+    /// Ensure neither `stdout` nor `stderr` has debugging output that shouldn't ship.
+    ///
+    /// This catches `dbg!` output, panics and failed (debug) assertions, and paths of the
+    /// build machine, like into the crate's source or the cargo registry.  Run the tests with
+    /// `cargo test --release` to check the binary as it ships.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello")
+    ///     .assert()
+    ///     .no_debug_leaks();
+    /// ```
+    pub fn no_debug_leaks(self) -> Self {
+        let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+        for stream in &["stdout", "stderr"] {
+            if let Some(leak) = crate::leaks::find(self.stream(stream), &[&manifest_dir]) {
+                self.fail(format_args!(
+                    "Debug output in {}, line {} looks like {}: {:?}",
+                    stream, leak.line, leak.kind, leak.text
+                ));
+            }
+        }
+        self
+    }
+
+    /// Ensure `stdout` is printable text, catching binary garbage or stray terminal control.
+    ///
+    /// Line feeds, tabs, `\r\n`, and color escape sequences are allowed.  Any other control
+    /// character, escape sequence, or invalid UTF-8 fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello")
+    ///     .assert()
+    ///     .stdout_printable_only();
     /// ```
+    pub fn stdout_printable_only(self) -> Self {
+        if let Some((offset, found)) = crate::ansi::find_unprintable(&self.output.stdout) {
+            self.fail(format_args!(
+                "Unexpected {} in stdout at byte {}",
+                found, offset
+            ));
+        }
+        self
+    }
+
+    /// Ensure `stderr` is printable text.
     ///
-    pub fn stdout<I, P>(self, pred: I) -> Self
+    /// See [`Assert::stdout_printable_only`].
+    pub fn stderr_printable_only(self) -> Self {
+        if let Some((offset, found)) = crate::ansi::find_unprintable(self.stderr_bytes()) {
+            self.fail(format_args!(
+                "Unexpected {} in stderr at byte {}",
+                found, offset
+            ));
+        }
+        self
+    }
+
+    /// Ensure the command did not write in-place updates, like progress bars, to `stdout`.
+    ///
+    /// Progress output is detected by carriage returns (other than `\r\n`) and "erase line"
+    /// escape sequences.  Polished CLIs skip these when `stdout` is not a terminal.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "done")
+    ///     .assert()
+    ///     .stdout_no_progress();
+    /// ```
+    pub fn stdout_no_progress(self) -> Self {
+        if let Some(offset) = crate::ansi::has_progress(&self.output.stdout) {
+            self.fail(format_args!(
+                "Unexpected progress output in stdout at byte {}",
+                offset
+            ));
+        }
+        self
+    }
+
+    /// Ensure the command did not write in-place updates, like progress bars, to `stderr`.
+    ///
+    /// See [`Assert::stdout_no_progress`].
+    pub fn stderr_no_progress(self) -> Self {
+        if let Some(offset) = crate::ansi::has_progress(self.stderr_bytes()) {
+            self.fail(format_args!(
+                "Unexpected progress output in stderr at byte {}",
+                offset
+            ));
+        }
+        self
+    }
+
+    /// Ensure the command logged no records at `level` to `stderr`.
+    ///
+    /// See the [`logs` module][crate::logs] for the supported formats.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    /// use assert_cmd::logs::Level;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stderr", "[WARN  app] careful")
+    ///     .assert()
+    ///     .stderr_no_logs(Level::Error);
+    /// ```
+    pub fn stderr_no_logs(self, level: crate::logs::Level) -> Self {
+        let records: Vec<_> = crate::logs::parse(self.stderr_bytes())
+            .into_iter()
+            .filter(|r| r.level == level)
+            .collect();
+        if !records.is_empty() {
+            self.fail(format_args!(
+                "Unexpected {} records in stderr:{}",
+                level,
+                format_records(&records)
+            ));
+        }
+        self
+    }
+
+    /// Ensure the command logged exactly `count` records at `level` matching `pred` to `stderr`.
+    ///
+    /// `pred` is evaluated against each record's message.  This uses [`IntoOutputPredicate`] to
+    /// provide short-hands for common cases.
+    ///
+    /// See the [`logs` module][crate::logs] for the supported formats.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    /// use assert_cmd::logs::Level;
+    ///
+    /// use std::process::Command;
+    /// use predicates::prelude::*;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stderr", "[WARN  app] careful")
+    ///     .assert()
+    ///     .stderr_log_count(Level::Warn, predicate::str::contains("care"), 1);
+    /// ```
+    pub fn stderr_log_count<I, P>(self, level: crate::logs::Level, pred: I, count: usize) -> Self
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        self.stderr_log_count_impl(level, &pred.into_output(), count)
+    }
+
+    fn stderr_log_count_impl(
+        self,
+        level: crate::logs::Level,
+        pred: &dyn predicates_core::Predicate<[u8]>,
+        count: usize,
+    ) -> Self {
+        let records: Vec<_> = crate::logs::parse(self.stderr_bytes())
+            .into_iter()
+            .filter(|r| r.level == level)
+            .collect();
+        let matched = records
+            .iter()
+            .filter(|r| pred.eval(r.message.as_bytes()))
+            .count();
+        if matched != count {
+            self.fail(format_args!(
+                "Expected {} {} records matching {} in stderr, found {}; {} records:{}",
+                count,
+                level,
+                pred,
+                matched,
+                level,
+                format_records(&records)
+            ));
+        }
+        self
+    }
+
+    /// Ensure `stdout`, parsed as one JSON document, satisfies `pred`.
+    ///
+    /// Requires the `json` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", r#"{"name":"tool","features":["a","b"]}"#)
+    ///     .assert()
+    ///     .stdout_json(|value| value["features"].as_array().map_or(false, |f| f.len() == 2));
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn stdout_json<F>(self, pred: F) -> Self
+    where
+        F: FnOnce(&crate::json::Value) -> bool,
+    {
+        let value = self.stdout_json_value();
+        if !pred(&value) {
+            self.fail(format_args!(
+                "Unexpected JSON in stdout\nparsed=`{}`",
+                value
+            ));
+        }
+        self
+    }
+
+    /// Ensure the value at the [JSON Pointer] `pointer` in `stdout`, parsed as one JSON
+    /// document, satisfies `pred`.
+    ///
+    /// Requires the `json` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::json::Value;
+    /// use assert_cmd::prelude::*;
+    /// use predicates::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", r#"{"package":{"version":"1.2.3"}}"#)
+    ///     .assert()
+    ///     .stdout_json_at("/package/version", predicate::eq(Value::from("1.2.3")));
+    /// ```
+    ///
+    /// [JSON Pointer]: https://tools.ietf.org/html/rfc6901
+    #[cfg(feature = "json")]
+    pub fn stdout_json_at<P>(self, pointer: &str, pred: P) -> Self
+    where
+        P: predicates_core::Predicate<crate::json::Value>,
+    {
+        self.stdout_json_at_impl(pointer, &pred)
+    }
+
+    #[cfg(feature = "json")]
+    fn stdout_json_at_impl(
+        self,
+        pointer: &str,
+        pred: &dyn predicates_core::Predicate<crate::json::Value>,
+    ) -> Self {
+        let document = self.stdout_json_value();
+        let value = match document.pointer(pointer) {
+            Some(value) => value,
+            None => self.fail(format_args!(
+                "No `{}` in stdout JSON\nparsed=`{}`",
+                pointer, document
+            )),
+        };
+        if let Some(case) = pred.find_case(false, value) {
+            self.fail(format_args!(
+                "Unexpected JSON in stdout at `{}`, failed {}\nvalue=`{}`",
+                pointer,
+                case.tree(),
+                value
+            ));
+        }
+        self
+    }
+
+    #[cfg(feature = "json")]
+    fn stdout_json_value(&self) -> crate::json::Value {
+        serde_json::from_slice(&self.output.stdout)
+            .unwrap_or_else(|err| self.fail(format_args!("Invalid JSON in stdout, {}", err)))
+    }
+
+    /// Ensure the newline-delimited JSON records the command wrote to `stdout` satisfy `pred`.
+    ///
+    /// Requires the `json` feature.  See [`Assert::stderr_jsonl`].
+    #[cfg(feature = "json")]
+    pub fn stdout_jsonl<F>(self, pred: F) -> Self
+    where
+        F: FnOnce(&crate::json::JsonLines) -> bool,
+    {
+        let lines = crate::json::JsonLines::parse(&self.output.stdout)
+            .unwrap_or_else(|err| self.fail(format_args!("Invalid JSON lines in stdout, {}", err)));
+        if !pred(&lines) {
+            self.fail("Unexpected JSON lines in stdout");
+        }
+        self
+    }
+
+    /// Ensure the newline-delimited JSON records the command wrote to `stderr` satisfy `pred`.
+    ///
+    /// Every non-blank line must be valid JSON; the failure pinpoints the first one that isn't.
+    ///
+    /// Requires the `json` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stderr", "{\"level\":\"info\",\"event\":\"started\"}")
+    ///     .assert()
+    ///     .stderr_jsonl(|logs| logs.contains("event", "started") && !logs.contains("level", "error"));
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn stderr_jsonl<F>(self, pred: F) -> Self
+    where
+        F: FnOnce(&crate::json::JsonLines) -> bool,
+    {
+        let lines = crate::json::JsonLines::parse(self.stderr_bytes())
+            .unwrap_or_else(|err| self.fail(format_args!("Invalid JSON lines in stderr, {}", err)));
+        if !pred(&lines) {
+            self.fail("Unexpected JSON lines in stderr");
+        }
+        self
+    }
+
+    /// Ensure `stdout`, split into NUL-delimited records, like from `find -print0`, is
+    /// `expected`.
+    ///
+    /// A trailing NUL terminates the last record rather than starting an empty one.  See
+    /// [`Assert::stdout_nul_records_unordered`] when the order isn't stable.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::new("printf")
+    ///     .arg("a b\\0c\\nd\\0")
+    ///     .assert()
+    ///     .stdout_nul_records(["a b", "c\nd"]);
+    /// ```
+    pub fn stdout_nul_records<I, S>(self, expected: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<[u8]>,
+    {
+        let expected: Vec<_> = expected.into_iter().collect();
+        let expected: Vec<&[u8]> = expected.iter().map(AsRef::as_ref).collect();
+        let actual = nul_records(&self.output.stdout);
+        if actual.len() != expected.len() {
+            self.fail(format_args!(
+                "Expected {} NUL-delimited records in stdout, got {}\nrecords={}",
+                expected.len(),
+                actual.len(),
+                format_nul_records(&actual)
+            ));
+        }
+        if let Some(i) = (0..actual.len()).find(|&i| actual[i] != expected[i]) {
+            self.fail(format_args!(
+                "Unexpected record {} in stdout, expected {:?}, got {:?}\nrecords={}",
+                i,
+                expected[i].as_bstr(),
+                actual[i].as_bstr(),
+                format_nul_records(&actual)
+            ));
+        }
+        self
+    }
+
+    /// Ensure `stdout`, split into NUL-delimited records, has the `expected` records in any
+    /// order.
+    ///
+    /// See [`Assert::stdout_nul_records`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::new("find")
+    ///     .args(["src", "-name", "*.rs", "-print0"])
+    ///     .assert()
+    ///     .stdout_nul_records_unordered(["src/main.rs", "src/lib.rs"]);
+    /// ```
+    pub fn stdout_nul_records_unordered<I, S>(self, expected: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<[u8]>,
+    {
+        let expected: Vec<_> = expected.into_iter().collect();
+        let mut expected: Vec<&[u8]> = expected.iter().map(AsRef::as_ref).collect();
+        let actual = nul_records(&self.output.stdout);
+        let mut unexpected = Vec::new();
+        for record in &actual {
+            match expected.iter().position(|e| e == record) {
+                Some(i) => {
+                    expected.swap_remove(i);
+                }
+                None => unexpected.push(*record),
+            }
+        }
+        if !expected.is_empty() || !unexpected.is_empty() {
+            self.fail(format_args!(
+                "Unexpected NUL-delimited records in stdout, missing {}, unexpected {}\nrecords={}",
+                format_nul_records(&expected),
+                format_nul_records(&unexpected),
+                format_nul_records(&actual)
+            ));
+        }
+        self
+    }
+
+    /// Ensure no line the command wrote to `stdout` is wider than `width` columns.
+    ///
+    /// ANSI escape sequences don't count towards the width and each `char` is assumed to be one
+    /// column wide.  Pair this with the `COLUMNS` environment variable to verify help text and
+    /// tables wrap to the terminal size.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .arg("--help")
+    ///     .env("COLUMNS", "80")
+    ///     .assert()
+    ///     .stdout_max_width(80);
+    /// ```
+    pub fn stdout_max_width(self, width: usize) -> Self {
+        if let Err(err) = max_width("stdout", &self.output.stdout, width) {
+            self.fail(err);
+        }
+        self
+    }
+
+    /// Ensure no line the command wrote to `stderr` is wider than `width` columns.
+    ///
+    /// See [`Assert::stdout_max_width`].
+    pub fn stderr_max_width(self, width: usize) -> Self {
+        if let Err(err) = max_width("stderr", self.stderr_bytes(), width) {
+            self.fail(err);
+        }
+        self
+    }
+
+    /// Ensure `stdout`, parsed as a `T`, matches `pred`.
+    ///
+    /// Surrounding whitespace is trimmed before parsing.  Pair this with [`numeric::approx`] for
+    /// measurements where exact string equality is meaningless.
+    ///
+    /// [`numeric::approx`]: crate::numeric::approx()
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::numeric::approx;
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "2.504")
+    ///     .assert()
+    ///     .stdout_parsed::<f64, _>(approx(2.5).abs(0.01));
+    /// ```
+    pub fn stdout_parsed<T, P>(self, pred: P) -> Self
+    where
+        T: str::FromStr + fmt::Debug,
+        T::Err: fmt::Display,
+        P: predicates_core::Predicate<T>,
+    {
+        self.parsed_impl("stdout", &pred)
+    }
+
+    /// Ensure `stderr`, parsed as a `T`, matches `pred`.
+    ///
+    /// See [`Assert::stdout_parsed`].
+    pub fn stderr_parsed<T, P>(self, pred: P) -> Self
+    where
+        T: str::FromStr + fmt::Debug,
+        T::Err: fmt::Display,
+        P: predicates_core::Predicate<T>,
+    {
+        self.parsed_impl("stderr", &pred)
+    }
+
+    fn parsed_impl<T>(self, stream: &str, pred: &dyn predicates_core::Predicate<T>) -> Self
+    where
+        T: str::FromStr + fmt::Debug,
+        T::Err: fmt::Display,
+    {
+        let data = self.stream(stream);
+        let text = String::from_utf8_lossy(data);
+        let value = match text.trim().parse::<T>() {
+            Ok(value) => value,
+            Err(err) => self.fail(format_args!(
+                "Unparseable {} as `{}`: {}",
+                stream,
+                std::any::type_name::<T>(),
+                err
+            )),
+        };
+        if let Some(case) = pred.find_case(false, &value) {
+            self.fail(format_args!(
+                "Unexpected {}, failed {}\nparsed=`{:?}`",
+                stream,
+                case.tree(),
+                value
+            ));
+        }
+        self
+    }
+
+    /// Ensure the first human-readable duration in `stdout`, like `finished in 2.3s`, matches
+    /// `pred`.
+    ///
+    /// See [`numeric::find_duration`] for the recognized formats.
+    ///
+    /// [`numeric::find_duration`]: crate::numeric::find_duration()
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    /// use predicates::prelude::*;
+    ///
+    /// use std::process::Command;
+    /// use std::time::Duration;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "finished in 2.3s")
+    ///     .assert()
+    ///     .stdout_duration(predicate::lt(Duration::from_secs(5)));
+    /// ```
+    pub fn stdout_duration<P>(self, pred: P) -> Self
+    where
+        P: predicates_core::Predicate<std::time::Duration>,
+    {
+        self.duration_impl("stdout", &pred)
+    }
+
+    /// Ensure the first human-readable duration in `stderr` matches `pred`.
+    ///
+    /// See [`Assert::stdout_duration`].
+    pub fn stderr_duration<P>(self, pred: P) -> Self
     where
-        I: IntoOutputPredicate<P>,
-        P: predicates_core::Predicate<[u8]>,
+        P: predicates_core::Predicate<std::time::Duration>,
     {
-        self.stdout_impl(&pred.into_output())
+        self.duration_impl("stderr", &pred)
     }
 
-    fn stdout_impl(self, pred: &dyn predicates_core::Predicate<[u8]>) -> Self {
-        {
-            let actual = &self.output.stdout;
-            if let Some(case) = pred.find_case(false, &actual) {
-                panic!("Unexpected stdout, failed {}\n{}", case.tree(), self);
-            }
+    fn duration_impl(
+        self,
+        stream: &str,
+        pred: &dyn predicates_core::Predicate<std::time::Duration>,
+    ) -> Self {
+        let data = self.stream(stream);
+        let text = String::from_utf8_lossy(data);
+        let duration = match crate::numeric::find_duration(&text) {
+            Some(duration) => duration,
+            None => self.fail(format_args!("No duration found in {}", stream)),
+        };
+        if let Some(case) = pred.find_case(false, &duration) {
+            self.fail(format_args!(
+                "Unexpected duration in {}, failed {}\nparsed=`{:?}`",
+                stream,
+                case.tree(),
+                duration
+            ));
         }
         self
     }
 
-    /// Ensure the command wrote the expected data to `stderr`.
-    ///
-    /// This uses [`IntoOutputPredicate`] to provide short-hands for common cases.
+    /// Ensure `stdout` reports the version of the crate being tested, as in `tool 1.2.3`.
     ///
-    /// See [`predicates`] for more predicates.
+    /// The expected version is read from `CARGO_PKG_VERSION`, which cargo sets when running
+    /// tests.  Build metadata, like `+git.abc`, is ignored on both sides.  This catches
+    /// forgotten version bumps in release pipelines.
     ///
     /// # Examples
     ///
-    /// Accepting a bytes predicate:
     /// ```rust,no_run
-    /// use assert_cmd::prelude::*;
-    ///
-    /// use std::process::Command;
-    /// use predicates::prelude::*;
+    /// use assert_cmd::Command;
     ///
-    /// Command::cargo_bin("bin_fixture")
+    /// Command::cargo_bin(env!("CARGO_PKG_NAME"))
     ///     .unwrap()
-    ///     .env("stdout", "hello")
-    ///     .env("stderr", "world")
+    ///     .arg("--version")
     ///     .assert()
-    ///     .stderr(predicate::eq(b"world\n" as &[u8]));
+    ///     .stdout_reports_crate_version();
     /// ```
+    pub fn stdout_reports_crate_version(self) -> Self {
+        let expected = match env::var("CARGO_PKG_VERSION") {
+            Ok(expected) => expected,
+            Err(_) => self.fail(
+                "CARGO_PKG_VERSION is unset; use `stdout_reports_version(env!(\"CARGO_PKG_VERSION\"))`",
+            ),
+        };
+        self.stdout_reports_version(&expected)
+    }
+
+    /// Ensure `stdout` reports `expected` as its version.
     ///
-    /// Accepting a `str` predicate:
-    /// ```rust,no_run
-    /// use assert_cmd::prelude::*;
+    /// See [`Assert::stdout_reports_crate_version`].
+    pub fn stdout_reports_version(self, expected: &str) -> Self {
+        let text = String::from_utf8_lossy(&self.output.stdout);
+        let reported = match crate::numeric::find_version(&text) {
+            Some(reported) => reported.to_owned(),
+            None => self.fail("No version found in stdout"),
+        };
+        if !crate::numeric::same_version(&reported, expected) {
+            self.fail(format_args!(
+                "Reported version `{}` doesn't match expected `{}`",
+                reported, expected
+            ));
+        }
+        self
+    }
+
+    /// Extract the first capture group of `pattern` from `stdout`.
     ///
-    /// use std::process::Command;
-    /// use predicates::prelude::*;
+    /// This is useful for feeding generated IDs or paths from one command into the next.  If
+    /// `pattern` has no capture groups, the whole match is returned.
     ///
-    /// Command::cargo_bin("bin_fixture")
-    ///     .unwrap()
-    ///     .env("stdout", "hello")
-    ///     .env("stderr", "world")
-    ///     .assert()
-    ///     .stderr(predicate::str::diff("world\n"));
-    /// ```
+    /// # Panics
+    ///
+    /// If `pattern` is not a valid regular expression or doesn't match `stdout`.
+    ///
+    /// # Examples
     ///
-    /// Accepting bytes:
     /// ```rust,no_run
     /// use assert_cmd::prelude::*;
     ///
     /// use std::process::Command;
     ///
-    /// Command::cargo_bin("bin_fixture")
+    /// let id = Command::cargo_bin("bin_fixture")
     ///     .unwrap()
-    ///     .env("stdout", "hello")
-    ///     .env("stderr", "world")
+    ///     .env("stdout", "created id: 42")
     ///     .assert()
-    ///     .stderr(b"world\n" as &[u8]);
+    ///     .success()
+    ///     .stdout_extract(r"id: (\d+)");
+    /// assert_eq!(id, "42");
     /// ```
+    pub fn stdout_extract(&self, pattern: &str) -> String {
+        self.extract("stdout", &self.output.stdout, pattern)
+            .swap_remove(0)
+    }
+
+    /// Extract every capture group of `pattern`'s first match in `stdout`.
+    ///
+    /// Groups that didn't participate in the match are empty.
+    ///
+    /// # Panics
+    ///
+    /// If `pattern` is not a valid regular expression or doesn't match `stdout`.
+    ///
+    /// # Examples
     ///
-    /// Accepting a `str`:
     /// ```rust,no_run
     /// use assert_cmd::prelude::*;
     ///
     /// use std::process::Command;
     ///
-    /// Command::cargo_bin("bin_fixture")
+    /// let groups = Command::cargo_bin("bin_fixture")
     ///     .unwrap()
-    ///     .env("stdout", "hello")
-    ///     .env("stderr", "world")
+    ///     .env("stdout", "wrote 3 files to out/")
     ///     .assert()
-    ///     .stderr("world\n");
+    ///     .stdout_extract_all(r"wrote (\d+) files to (\S+)");
+    /// assert_eq!(groups, ["3", "out/"]);
     /// ```
+    pub fn stdout_extract_all(&self, pattern: &str) -> Vec<String> {
+        self.extract("stdout", &self.output.stdout, pattern)
+    }
+
+    /// Extract the first capture group of `pattern` from `stderr`.
     ///
-    pub fn stderr<I, P>(self, pred: I) -> Self
-    where
-        I: IntoOutputPredicate<P>,
-        P: predicates_core::Predicate<[u8]>,
-    {
-        self.stderr_impl(&pred.into_output())
+    /// See [`Assert::stdout_extract`].
+    pub fn stderr_extract(&self, pattern: &str) -> String {
+        self.extract("stderr", self.stderr_bytes(), pattern)
+            .swap_remove(0)
     }
 
-    fn stderr_impl(self, pred: &dyn predicates_core::Predicate<[u8]>) -> Self {
+    /// Extract every capture group of `pattern`'s first match in `stderr`.
+    ///
+    /// See [`Assert::stdout_extract_all`].
+    pub fn stderr_extract_all(&self, pattern: &str) -> Vec<String> {
+        self.extract("stderr", self.stderr_bytes(), pattern)
+    }
+
+    fn extract(&self, stream: &str, data: &[u8], pattern: &str) -> Vec<String> {
+        let re = regex::bytes::Regex::new(pattern)
+            .unwrap_or_else(|err| panic!("Invalid extraction pattern: {}", err));
+        let captures = match re.captures(data) {
+            Some(captures) => captures,
+            None => self.fail(format_args!(
+                "Pattern `{}` not found in {}",
+                pattern, stream
+            )),
+        };
+        let groups: Vec<_> = if captures.len() == 1 {
+            vec![captures.get(0)]
+        } else {
+            captures.iter().skip(1).collect()
+        };
+        groups
+            .into_iter()
+            .map(|m| m.map(|m| String::from_utf8_lossy(m.as_bytes()).into_owned()))
+            .map(Option::unwrap_or_default)
+            .collect()
+    }
+}
+
+/// How many lines [`excerpt`] shows, when not centered on a line.
+const EXCERPT_LINES: usize = 10;
+
+/// How many lines [`excerpt`] shows on either side of the line it's centered on.
+const EXCERPT_CONTEXT: usize = 2;
+
+/// `lines`, numbered, for a failure: those around line `number`, marked, else the first few.
+fn excerpt(lines: &[&str], number: Option<usize>) -> String {
+    use std::fmt::Write;
+
+    if lines.is_empty() {
+        return "(no lines)".to_owned();
+    }
+    let (start, end) = match number {
+        Some(number) => (
+            number.saturating_sub(EXCERPT_CONTEXT + 1),
+            (number + EXCERPT_CONTEXT).min(lines.len()),
+        ),
+        None => (0, EXCERPT_LINES.min(lines.len())),
+    };
+    let width = end.to_string().len();
+    let mut excerpt = String::new();
+    if 0 < start {
+        let _ = writeln!(excerpt, "  {:>width$} | ...", "", width = width);
+    }
+    for (i, line) in lines.iter().enumerate().take(end).skip(start) {
+        let marker = if Some(i + 1) == number { '>' } else { ' ' };
+        let _ = writeln!(
+            excerpt,
+            "{} {:>width$} | {}",
+            marker,
+            i + 1,
+            line,
+            width = width
+        );
+    }
+    if end < lines.len() {
+        let _ = writeln!(
+            excerpt,
+            "  {:>width$} | ... {} more",
+            "",
+            lines.len() - end,
+            width = width
+        );
+    }
+    excerpt.pop();
+    excerpt
+}
+
+fn format_records(records: &[crate::logs::Record]) -> String {
+    if records.is_empty() {
+        return " none".to_owned();
+    }
+    records.iter().map(|r| format!("\n  {}", r)).collect()
+}
+
+/// Split `data` on NUL, with a trailing NUL terminating the last record.
+fn nul_records(data: &[u8]) -> Vec<&[u8]> {
+    if data.is_empty() {
+        return Vec::new();
+    }
+    let data = data.strip_suffix(b"\0").unwrap_or(data);
+    data.split(|b| *b == 0).collect()
+}
+
+fn format_nul_records(records: &[&[u8]]) -> String {
+    let records: Vec<_> = records
+        .iter()
+        .map(|r| format!("{:?}", r.as_bstr()))
+        .collect();
+    format!("[{}]", records.join(", "))
+}
+
+fn max_width(stream: &str, data: &[u8], width: usize) -> Result<(), String> {
+    const MAX_REPORTED: usize = 5;
+
+    let data = crate::ansi::strip(data);
+    let text = String::from_utf8_lossy(&data);
+    let overflows: Vec<_> = text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.chars().count(), line))
+        .filter(|(_, line_width, _)| width < *line_width)
+        .collect();
+    if overflows.is_empty() {
+        return Ok(());
+    }
+
+    let mut err = format!(
+        "{} {} in {} exceeded {} columns:",
+        overflows.len(),
+        if overflows.len() == 1 {
+            "line"
+        } else {
+            "lines"
+        },
+        stream,
+        width
+    );
+    for (line_num, line_width, line) in overflows.iter().take(MAX_REPORTED) {
+        err.push_str(&format!(
+            "\n  line {} ({} columns): {:?}",
+            line_num, line_width, line
+        ));
+    }
+    if MAX_REPORTED < overflows.len() {
+        err.push_str(&format!("\n  ...{} more", overflows.len() - MAX_REPORTED));
+    }
+    Err(err)
+}
+
+fn has_ansi(stream: &str, data: &[u8]) -> Result<(), String> {
+    if crate::ansi::sequences(data).next().is_none() {
+        Err(format!(
+            "Expected ANSI escape sequences in {}, found none",
+            stream
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn no_ansi(stream: &str, data: &[u8]) -> Result<(), String> {
+    match crate::ansi::sequences(data).next() {
+        Some(seq) => Err(format!(
+            "Unexpected ANSI escape sequence in {} at byte {}: {}",
+            stream,
+            seq.range.start,
+            DebugBytes::new(&data[seq.range.clone()])
+        )),
+        None => Ok(()),
+    }
+}
+
+fn has_sgr(stream: &str, data: &[u8], params: &str) -> Result<(), String> {
+    let found: Vec<_> = crate::ansi::sequences(data)
+        .filter_map(|seq| seq.sgr_params())
+        .collect();
+    if found.contains(&params.as_bytes()) {
+        return Ok(());
+    }
+    let found = if found.is_empty() {
+        "none".to_owned()
+    } else {
+        found
+            .iter()
+            .map(|p| format!("`{}`", String::from_utf8_lossy(p)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    Err(format!(
+        "Expected SGR sequence `ESC[{}m` in {}, found {}",
+        params, stream, found
+    ))
+}
+
+impl Assert {
+    /// Treat `stderr` as checked, for an `Assert` the crate discards itself, so
+    /// [`Assert::strict_stderr`] doesn't fail it when dropped.
+    pub(crate) fn discard(&self) {
+        self.stderr_asserted.set(true);
+    }
+
+    fn stderr_bytes(&self) -> &[u8] {
+        self.stderr_asserted.set(true);
+        &self.output.stderr
+    }
+
+    fn stream(&self, stream: &str) -> &[u8] {
+        match stream {
+            "stdout" => &self.output.stdout,
+            _ => self.stderr_bytes(),
+        }
+    }
+
+    pub(crate) fn fail(&self, headline: impl fmt::Display) -> ! {
+        if let Some((dir, true)) = self.temp_cwd.as_ref() {
+            dir.keep();
+        }
+        let headline = headline.to_string();
+        if let Some(entry) = self.report.take() {
+            crate::report::record(entry, &self.output, Some(&headline));
+        }
+        if !crate::aggregate::is_collecting() {
+            crate::failure::handle(&self.to_failure(&headline), self.on_failure.as_deref());
+        }
+        let signature = match self.message {
+            Some(ref message) => format!("{}\n{}", message, headline),
+            None => headline,
+        };
+        crate::aggregate::fail(signature, self.to_string())
+    }
+
+    fn to_failure(&self, headline: &str) -> crate::failure::Failure {
+        let context = self
+            .context
+            .iter()
+            .map(|(name, context)| ((*name).to_owned(), context.to_string()))
+            .collect();
+        let (env, stdin) = match self.recording {
+            Some(ref recording) => (recording.env.clone(), recording.stdin.clone()),
+            None => (Vec::new(), None),
+        };
+        crate::failure::Failure::new(
+            self.message.clone(),
+            headline.to_owned(),
+            context,
+            self.cwd.clone(),
+            env,
+            stdin,
+            self.output.clone(),
+        )
+    }
+}
+
+/// The exit code a shell reports for `status`.
+#[cfg(unix)]
+fn shell_code(status: &process::ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+
+    status
+        .code()
+        .or_else(|| status.signal().map(|signal| 128 + signal))
+}
+
+/// The exit code a shell reports for `status`.
+#[cfg(not(unix))]
+fn shell_code(status: &process::ExitStatus) -> Option<i32> {
+    status.code()
+}
+
+#[cfg(unix)]
+fn raw_status(status: &process::ExitStatus) -> i32 {
+    std::os::unix::process::ExitStatusExt::into_raw(*status)
+}
+
+#[cfg(windows)]
+fn raw_status(status: &process::ExitStatus) -> i32 {
+    // Windows always has a code; `code()` is a bit-for-bit copy of the `u32` exit code.
+    status
+        .code()
+        .expect("Windows processes always have an exit code")
+}
+
+const STRICT_STDERR_ENV: &str = "ASSERT_CMD_STRICT_STDERR";
+
+impl Drop for Assert {
+    fn drop(&mut self) {
+        if self.strict_stderr
+            && !self.stderr_asserted.get()
+            && !self.output.stderr.is_empty()
+            && !std::thread::panicking()
         {
-            let actual = &self.output.stderr;
-            if let Some(case) = pred.find_case(false, &actual) {
-                panic!("Unexpected stderr, failed {}\n\n{}", case.tree(), self);
-            }
+            self.fail("Unexpected stderr (strict mode), assert on it or silence the command");
+        }
+        if let Some(entry) = self.report.take() {
+            crate::report::record(entry, &self.output, None);
         }
-        self
     }
 }
 
 impl fmt::Display for Assert {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for &(ref name, ref context) in &self.context {
+        for (name, context) in &self.context {
             writeln!(f, "{}=`{}`", name, context)?;
         }
-        output_fmt(&self.output, f)
+        match self.output.status.code() {
+            Some(code) => writeln!(f, "code={}", code)?,
+            None => writeln!(f, "code=<interrupted>")?,
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            if let Some(signal) = self.output.status.signal() {
+                writeln!(f, "signal={}", crate::signals::describe(signal))?;
+            }
+        }
+        let limit = self.settings.limit();
+        if let Some(ref combined) = self.combined {
+            return writeln!(
+                f,
+                "combined=```{}```",
+                DebugBytes::with_limit(&self.settings.display(combined), limit),
+            );
+        }
+        write!(
+            f,
+            "stdout=```{}```\nstderr=```{}```\n",
+            DebugBytes::with_limit(&self.settings.display(&self.output.stdout), limit),
+            DebugBytes::with_limit(&self.settings.display(&self.output.stderr), limit),
+        )
     }
 }
 
@@ -446,6 +4395,76 @@ impl fmt::Debug for Assert {
     }
 }
 
+/// Assertions that are all checked, with every failure reported together, see [`Assert::soft`].
+///
+/// Each [`SoftAssert::check`] runs on its own copy of the [`Assert`], so one failing doesn't
+/// stop the rest.  Finish with [`SoftAssert::verify`]; dropping unverified checks reports their
+/// failures too.
+#[derive(Debug)]
+pub struct SoftAssert {
+    assert: Assert,
+    failures: crate::aggregate::Failures,
+}
+
+impl SoftAssert {
+    /// Run `check`, recording its failure instead of panicking.
+    pub fn check<F>(mut self, check: F) -> Self
+    where
+        F: FnOnce(Assert) -> Assert,
+    {
+        let copy = self.assert.duplicate();
+        if let Some(checked) = self.failures.check(|| check(copy)) {
+            if checked.stderr_asserted.get() {
+                self.assert.stderr_asserted.set(true);
+            }
+        }
+        self
+    }
+
+    /// Panic with every failure if any check failed, else return the [`Assert`].
+    pub fn verify(self) -> Assert {
+        self.failures.finish();
+        self.assert
+    }
+}
+
+/// An [`Assert`] failure, returned by [`Assert::try_assert`] and the other `try_` assertions.
+///
+/// This carries the same information as the panic message of the failed assertion.
+#[derive(Debug)]
+pub struct AssertError {
+    headline: String,
+    context: String,
+    output: process::Output,
+}
+
+impl AssertError {
+    /// What failed, like `Unexpected failure.`, preceded by any [`Assert::with_message`].
+    pub fn headline(&self) -> &str {
+        &self.headline
+    }
+
+    /// The command, its context, and its output, as reported on failure.
+    pub fn context(&self) -> &str {
+        &self.context
+    }
+
+    /// Access the contained [`Output`].
+    ///
+    /// [`Output`]: std::process::Output
+    pub fn get_output(&self) -> &process::Output {
+        &self.output
+    }
+}
+
+impl std::error::Error for AssertError {}
+
+impl fmt::Display for AssertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}\n{}", self.headline, self.context)
+    }
+}
+
 /// Used by [`Assert::code`] to convert `Self` into the needed
 /// [`predicates_core::Predicate<i32>`].
 ///
@@ -509,11 +4528,11 @@ where
 ///     .code(42);
 /// ```
 #[derive(Debug)]
-pub struct EqCodePredicate(predicates::ord::EqPredicate<i32>);
+pub struct EqCodePredicate(preds::ord::EqPredicate<i32>);
 
 impl EqCodePredicate {
     pub(crate) fn new(value: i32) -> Self {
-        let pred = predicates::ord::eq(value);
+        let pred = preds::ord::eq(value);
         EqCodePredicate(pred)
     }
 }
@@ -578,11 +4597,11 @@ impl IntoCodePredicate<EqCodePredicate> for i32 {
 ///     .code(&[2, 42] as &[i32]);
 /// ```
 #[derive(Debug)]
-pub struct InCodePredicate(predicates::iter::InPredicate<i32>);
+pub struct InCodePredicate(preds::iter::InPredicate<i32>);
 
 impl InCodePredicate {
     pub(crate) fn new<I: IntoIterator<Item = i32>>(value: I) -> Self {
-        let pred = predicates::iter::in_iter(value);
+        let pred = preds::iter::in_iter(value);
         InCodePredicate(pred)
     }
 }
@@ -638,6 +4657,88 @@ impl IntoCodePredicate<InCodePredicate> for &'static [i32] {
     }
 }
 
+impl<const N: usize> IntoCodePredicate<InCodePredicate> for [i32; N] {
+    type Predicate = InCodePredicate;
+
+    fn into_code(self) -> Self::Predicate {
+        Self::Predicate::new(self)
+    }
+}
+
+/// [predicates_core::Predicate] used by [`IntoCodePredicate`] for ranges of codes.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use assert_cmd::prelude::*;
+///
+/// use std::process::Command;
+///
+/// Command::cargo_bin("bin_fixture")
+///     .unwrap()
+///     .env("exit", "2")
+///     .assert()
+///     .code(2..10);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeCodePredicate {
+    start: i32,
+    end: i32,
+    inclusive: bool,
+}
+
+impl predicates_core::reflection::PredicateReflection for RangeCodePredicate {}
+
+impl predicates_core::Predicate<i32> for RangeCodePredicate {
+    fn eval(&self, item: &i32) -> bool {
+        self.start <= *item && (*item < self.end || (self.inclusive && *item == self.end))
+    }
+
+    fn find_case<'a>(
+        &'a self,
+        expected: bool,
+        variable: &i32,
+    ) -> Option<predicates_core::reflection::Case<'a>> {
+        let result = self.eval(variable);
+        if result != expected {
+            return None;
+        }
+        let product = predicates_core::reflection::Product::new("var", *variable);
+        Some(predicates_core::reflection::Case::new(Some(self), result).add_product(product))
+    }
+}
+
+impl fmt::Display for RangeCodePredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = if self.inclusive { "..=" } else { ".." };
+        write!(f, "var in {}{}{}", self.start, op, self.end)
+    }
+}
+
+impl IntoCodePredicate<RangeCodePredicate> for std::ops::Range<i32> {
+    type Predicate = RangeCodePredicate;
+
+    fn into_code(self) -> Self::Predicate {
+        RangeCodePredicate {
+            start: self.start,
+            end: self.end,
+            inclusive: false,
+        }
+    }
+}
+
+impl IntoCodePredicate<RangeCodePredicate> for std::ops::RangeInclusive<i32> {
+    type Predicate = RangeCodePredicate;
+
+    fn into_code(self) -> Self::Predicate {
+        RangeCodePredicate {
+            start: *self.start(),
+            end: *self.end(),
+            inclusive: true,
+        }
+    }
+}
+
 /// Used by [`Assert::stdout`] and [`Assert::stderr`] to convert Self
 /// into the needed [`predicates_core::Predicate<[u8]>`].
 ///
@@ -727,19 +4828,47 @@ impl predicates_core::Predicate<[u8]> for BytesContentOutputPredicate {
         &self,
         expected: bool,
         variable: &[u8],
-    ) -> Option<predicates_core::reflection::Case> {
+    ) -> Option<predicates_core::reflection::Case<'_>> {
         let actual = self.eval(variable);
         if expected == actual {
-            Some(predicates_core::reflection::Case::new(Some(self), actual))
+            let case = predicates_core::reflection::Case::new(Some(self), actual);
+            Some(if actual {
+                case
+            } else {
+                let expected = String::from_utf8_lossy(&self.0);
+                let variable = String::from_utf8_lossy(variable);
+                let case = case.add_product(diff_product(&expected, &variable));
+                match mismatch_product(&expected, &variable) {
+                    Some(product) => case.add_product(product),
+                    None => case,
+                }
+            })
         } else {
             None
         }
     }
 }
 
+/// Where the outputs first differ, for equality predicates.
+pub(crate) fn mismatch_product(
+    expected: &str,
+    actual: &str,
+) -> Option<predicates_core::reflection::Product> {
+    crate::diff::first_mismatch(expected, actual)
+        .map(|at| predicates_core::reflection::Product::new("first mismatch", at))
+}
+
+/// Only the differing lines, rather than both outputs in full.
+pub(crate) fn diff_product(expected: &str, actual: &str) -> predicates_core::reflection::Product {
+    predicates_core::reflection::Product::new(
+        "diff",
+        crate::diff::unified(expected, actual, crate::diff::color()),
+    )
+}
+
 impl fmt::Display for BytesContentOutputPredicate {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        predicates::ord::eq(self.0.as_ref()).fmt(f)
+        preds::ord::eq(self.0.as_ref()).fmt(f)
     }
 }
 
@@ -779,19 +4908,23 @@ impl IntoOutputPredicate<BytesContentOutputPredicate> for &'static [u8] {
 ///
 /// [`str`]: https://doc.rust-lang.org/std/primitive.str.html
 #[derive(Debug, Clone)]
-pub struct StrContentOutputPredicate(
-    predicates::str::Utf8Predicate<predicates::str::DifferencePredicate>,
-);
+pub struct StrContentOutputPredicate {
+    expected: Cow<'static, str>,
+    pred: preds::str::Utf8Predicate<preds::str::DifferencePredicate>,
+}
 
 impl StrContentOutputPredicate {
     pub(crate) fn from_str(value: &'static str) -> Self {
-        let pred = predicates::str::diff(value).from_utf8();
-        StrContentOutputPredicate(pred)
+        Self::new(Cow::Borrowed(value))
     }
 
     pub(crate) fn from_string(value: String) -> Self {
-        let pred = predicates::str::diff(value).from_utf8();
-        StrContentOutputPredicate(pred)
+        Self::new(Cow::Owned(value))
+    }
+
+    fn new(expected: Cow<'static, str>) -> Self {
+        let pred = preds::str::diff(expected.clone()).from_utf8();
+        StrContentOutputPredicate { expected, pred }
     }
 }
 
@@ -799,20 +4932,20 @@ impl predicates_core::reflection::PredicateReflection for StrContentOutputPredic
     fn parameters<'a>(
         &'a self,
     ) -> Box<dyn Iterator<Item = predicates_core::reflection::Parameter<'a>> + 'a> {
-        self.0.parameters()
+        self.pred.parameters()
     }
 
     /// Nested `Predicate`s of the current `Predicate`.
     fn children<'a>(
         &'a self,
     ) -> Box<dyn Iterator<Item = predicates_core::reflection::Child<'a>> + 'a> {
-        self.0.children()
+        self.pred.children()
     }
 }
 
 impl predicates_core::Predicate<[u8]> for StrContentOutputPredicate {
     fn eval(&self, item: &[u8]) -> bool {
-        self.0.eval(item)
+        self.pred.eval(item)
     }
 
     fn find_case<'a>(
@@ -820,13 +4953,27 @@ impl predicates_core::Predicate<[u8]> for StrContentOutputPredicate {
         expected: bool,
         variable: &[u8],
     ) -> Option<predicates_core::reflection::Case<'a>> {
-        self.0.find_case(expected, variable)
+        let actual = self.eval(variable);
+        if expected != actual {
+            return None;
+        }
+        let case = predicates_core::reflection::Case::new(Some(self), actual);
+        Some(if actual {
+            case
+        } else {
+            let variable = String::from_utf8_lossy(variable);
+            let case = case.add_product(diff_product(&self.expected, &variable));
+            match mismatch_product(&self.expected, &variable) {
+                Some(product) => case.add_product(product),
+                None => case,
+            }
+        })
     }
 }
 
 impl fmt::Display for StrContentOutputPredicate {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.0.fmt(f)
+        self.pred.fmt(f)
     }
 }
 
@@ -866,9 +5013,7 @@ impl IntoOutputPredicate<StrContentOutputPredicate> for &'static str {
 ///     .stderr(predicate::str::diff("world\n"));
 /// ```
 #[derive(Debug, Clone)]
-pub struct StrOutputPredicate<P: predicates_core::Predicate<str>>(
-    predicates::str::Utf8Predicate<P>,
-);
+pub struct StrOutputPredicate<P: predicates_core::Predicate<str>>(preds::str::Utf8Predicate<P>);
 
 impl<P> StrOutputPredicate<P>
 where
@@ -973,6 +5118,20 @@ mod test {
     fn into_code_from_array() {
         let pred = convert_code(&[3, 10] as &[i32]);
         assert!(pred.eval(&10));
+        let pred = convert_code([3, 10]);
+        assert!(pred.eval(&10));
+    }
+
+    #[test]
+    fn into_code_from_range() {
+        let pred = convert_code(2..10);
+        assert!(pred.eval(&2));
+        assert!(!pred.eval(&10));
+        assert_eq!(pred.to_string(), "var in 2..10");
+        let pred = convert_code(2..=9);
+        assert!(pred.eval(&9));
+        assert!(!pred.eval(&1));
+        assert_eq!(pred.to_string(), "var in 2..=9");
     }
 
     // Since IntoOutputPredicate exists solely for conversion, test it under that scenario to ensure
@@ -5,6 +5,10 @@ use std::io::Write;
 use std::process;
 
 fn run() -> Result<(), Box<dyn Error>> {
+    assert_cmd::env_dump::dump()?;
+    #[cfg(unix)]
+    assert_cmd::signals::dump()?;
+
     if let Ok(text) = env::var("stdout") {
         println!("{}", text);
     }
@@ -16,6 +20,10 @@ fn run() -> Result<(), Box<dyn Error>> {
         std::thread::sleep(std::time::Duration::from_secs(timeout));
     }
 
+    if env::var_os("abort").is_some() {
+        process::abort();
+    }
+
     let code = env::var("exit")
         .ok()
         .map(|v| v.parse::<i32>())
@@ -0,0 +1,199 @@
+//! Process-wide limits on the children spawned through this crate.
+//!
+//! A safety net for accidentally quadratic test matrices: once a limit is exceeded, every
+//! further [`Command`] run panics with a summary of what was spent.
+//!
+//! Limits cover runs through [`Command`], and through [`OutputAssertExt`] and [`OutputOkExt`]
+//! for [`std::process::Command`].
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use assert_cmd::Command;
+//!
+//! assert_cmd::budget::max_subprocess_count(100);
+//! assert_cmd::budget::max_total_subprocess_time(std::time::Duration::from_secs(60));
+//!
+//! Command::cargo_bin("bin_fixture").unwrap().assert().success();
+//! ```
+//!
+//! [`Command`]: crate::cmd::Command
+//! [`OutputAssertExt`]: crate::assert::OutputAssertExt
+//! [`OutputOkExt`]: crate::output::OutputOkExt
+
+use std::fmt;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+static BUDGET: Budget = Budget::new();
+
+/// Fail once more than `count` children have been spawned by this process.
+pub fn max_subprocess_count(count: usize) {
+    BUDGET.max_count.store(count, Ordering::SeqCst);
+}
+
+/// Fail once children have run for more than `time` in total, summed across all of them.
+///
+/// The limit is checked as each child finishes and before the next one is spawned.
+pub fn max_total_subprocess_time(time: Duration) {
+    BUDGET.max_nanos.store(nanos(time), Ordering::SeqCst);
+}
+
+/// What has been spent so far.
+pub fn usage() -> Usage {
+    BUDGET.usage()
+}
+
+/// The children spawned by this process and how long they ran.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Usage {
+    /// Children spawned.
+    pub count: usize,
+    /// Time spent running children, summed across all of them.
+    pub time: Duration,
+}
+
+/// Record a run of `run`, panicking if the budget is, or becomes, exhausted.
+///
+/// `label` is passed on to [`timing`][crate::timing].
+pub(crate) fn track<T>(label: Option<String>, run: impl FnOnce() -> T) -> T {
+    BUDGET.spawning();
+    let start = Instant::now();
+    let result = run();
+    let elapsed = start.elapsed();
+    crate::timing::record(label, elapsed);
+    BUDGET.finished(elapsed);
+    result
+}
+
+struct Budget {
+    count: AtomicUsize,
+    nanos: AtomicU64,
+    max_count: AtomicUsize,
+    max_nanos: AtomicU64,
+}
+
+impl Budget {
+    const fn new() -> Self {
+        Self {
+            count: AtomicUsize::new(0),
+            nanos: AtomicU64::new(0),
+            max_count: AtomicUsize::new(usize::MAX),
+            max_nanos: AtomicU64::new(u64::MAX),
+        }
+    }
+
+    fn usage(&self) -> Usage {
+        Usage {
+            count: self.count.load(Ordering::SeqCst),
+            time: Duration::from_nanos(self.nanos.load(Ordering::SeqCst)),
+        }
+    }
+
+    fn spawning(&self) {
+        let count = self.count.fetch_add(1, Ordering::SeqCst) + 1;
+        if self.max_count.load(Ordering::SeqCst) < count
+            || self.max_nanos.load(Ordering::SeqCst) < self.nanos.load(Ordering::SeqCst)
+        {
+            panic!("{}", self.exceeded());
+        }
+    }
+
+    fn finished(&self, elapsed: Duration) {
+        let total = self
+            .nanos
+            .fetch_add(nanos(elapsed), Ordering::SeqCst)
+            .saturating_add(nanos(elapsed));
+        if self.max_nanos.load(Ordering::SeqCst) < total {
+            panic!("{}", self.exceeded());
+        }
+    }
+
+    fn exceeded(&self) -> Exceeded {
+        Exceeded {
+            usage: self.usage(),
+            max_count: self.max_count.load(Ordering::SeqCst),
+            max_nanos: self.max_nanos.load(Ordering::SeqCst),
+        }
+    }
+}
+
+struct Exceeded {
+    usage: Usage,
+    max_count: usize,
+    max_nanos: u64,
+}
+
+impl fmt::Display for Exceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Subprocess budget exceeded")?;
+        write!(f, "spawned={}", self.usage.count)?;
+        if self.max_count != usize::MAX {
+            write!(f, " (max {})", self.max_count)?;
+        }
+        write!(f, "\ntime={:?}", self.usage.time)?;
+        if self.max_nanos != u64::MAX {
+            write!(f, " (max {:?})", Duration::from_nanos(self.max_nanos))?;
+        }
+        Ok(())
+    }
+}
+
+fn nanos(time: Duration) -> u64 {
+    let nanos = time.as_nanos();
+    if nanos < u128::from(u64::MAX) {
+        nanos as u64
+    } else {
+        u64::MAX
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::panic;
+
+    #[test]
+    fn within_budget() {
+        let budget = Budget::new();
+        budget.max_count.store(2, Ordering::SeqCst);
+        budget.spawning();
+        budget.finished(Duration::from_millis(5));
+        budget.spawning();
+        assert_eq!(
+            budget.usage(),
+            Usage {
+                count: 2,
+                time: Duration::from_millis(5)
+            }
+        );
+    }
+
+    #[test]
+    fn too_many() {
+        let budget = Budget::new();
+        budget.max_count.store(1, Ordering::SeqCst);
+        budget.spawning();
+        let err = panic::catch_unwind(|| budget.spawning()).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<String>().unwrap(),
+            "Subprocess budget exceeded\nspawned=2 (max 1)\ntime=0ns"
+        );
+    }
+
+    #[test]
+    fn too_long() {
+        let budget = Budget::new();
+        budget
+            .max_nanos
+            .store(nanos(Duration::from_secs(1)), Ordering::SeqCst);
+        budget.spawning();
+        let err = panic::catch_unwind(|| budget.finished(Duration::from_secs(2))).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<String>().unwrap(),
+            "Subprocess budget exceeded\nspawned=1\ntime=2s (max 1s)"
+        );
+        assert!(panic::catch_unwind(|| budget.spawning()).is_err());
+    }
+}
@@ -0,0 +1,162 @@
+//! Reuse the results of identical, read-only invocations within a test process.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path;
+use std::process;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::cmd::Run;
+
+/// Everything that can change what an invocation observes.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct Key {
+    /// Also covers what the getters don't expose, like `env_clear`.
+    command: String,
+    program: OsString,
+    args: Vec<OsString>,
+    envs: Vec<(OsString, Option<OsString>)>,
+    /// A hash of the binary's contents, so a rebuilt binary is run again.
+    binary: Option<u64>,
+    /// A hash of the working directory's listing.
+    cwd: Option<u64>,
+    stdin: Option<Vec<u8>>,
+    /// How the run is collected, like the timeout.
+    config: String,
+}
+
+impl Key {
+    pub(crate) fn new(
+        cmd: &process::Command,
+        stdin: Option<&[u8]>,
+        config: impl std::fmt::Debug,
+    ) -> Self {
+        let mut envs: Vec<_> = cmd
+            .get_envs()
+            .map(|(key, value)| (key.to_owned(), value.map(|v| v.to_owned())))
+            .collect();
+        envs.sort();
+        let cwd = cmd
+            .get_current_dir()
+            .map(path::Path::to_owned)
+            .or_else(|| env::current_dir().ok());
+        Self {
+            command: format!("{:?}", cmd),
+            program: cmd.get_program().to_owned(),
+            args: cmd.get_args().map(|arg| arg.to_owned()).collect(),
+            envs,
+            binary: resolve(cmd)
+                .and_then(|path| fs::read(path).ok())
+                .map(|b| hash(&b)),
+            cwd: cwd.and_then(|cwd| fingerprint_dir(&cwd)),
+            stdin: stdin.map(|s| s.to_owned()),
+            config: format!("{:?}", config),
+        }
+    }
+}
+
+/// The slot for `key`, locked so identical invocations run once even when tests run in parallel.
+///
+/// The slot is empty until a run's result is stored in it.
+pub(crate) fn lookup(key: Key) -> Slot {
+    // `HashMap::new` isn't `const` on our MSRV.
+    static CACHE: Mutex<Option<Slots>> = Mutex::new(None);
+    let slot = {
+        let mut cache = lock(&CACHE);
+        cache
+            .get_or_insert_with(HashMap::new)
+            .entry(key)
+            .or_default()
+            .clone()
+    };
+    Slot { slot }
+}
+
+type Slots = HashMap<Key, Arc<Mutex<Option<Run>>>>;
+
+pub(crate) struct Slot {
+    slot: Arc<Mutex<Option<Run>>>,
+}
+
+impl Slot {
+    pub(crate) fn lock(&self) -> MutexGuard<'_, Option<Run>> {
+        lock(&self.slot)
+    }
+}
+
+/// A panicking run leaves the slot empty, so carry on.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Where `cmd`'s program is, following `PATH` for bare names.
+pub(crate) fn resolve(cmd: &process::Command) -> Option<path::PathBuf> {
+    let program = path::Path::new(cmd.get_program());
+    if program.components().count() != 1 {
+        return Some(program.to_owned());
+    }
+    let paths = cmd
+        .get_envs()
+        .find(|(key, _)| *key == "PATH")
+        .map(|(_, value)| value.map(|v| v.to_owned()))
+        .unwrap_or_else(|| env::var_os("PATH"))?;
+    env::split_paths(&paths)
+        .flat_map(|dir| {
+            let candidate = dir.join(program);
+            let mut candidates = vec![candidate.clone()];
+            if !env::consts::EXE_EXTENSION.is_empty() {
+                candidates.push(candidate.with_extension(env::consts::EXE_EXTENSION));
+            }
+            candidates
+        })
+        .find(|candidate| candidate.is_file())
+}
+
+/// Hash the names, sizes, and modification times of `dir`'s entries.
+fn fingerprint_dir(dir: &path::Path) -> Option<u64> {
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| {
+            let metadata = entry.metadata().ok();
+            (
+                entry.file_name(),
+                metadata.as_ref().map(fs::Metadata::len),
+                metadata.and_then(|m| m.modified().ok()),
+            )
+        })
+        .collect();
+    entries.sort();
+    let mut hasher = DefaultHasher::new();
+    dir.hash(&mut hasher);
+    entries.hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+fn hash(data: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    data.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn key_covers_inputs() {
+        let mut cmd = process::Command::new("cat");
+        let key = Key::new(&cmd, None, ());
+        assert_eq!(key, Key::new(&cmd, None, ()));
+        assert_ne!(key, Key::new(&cmd, Some(b"hello"), ()));
+        assert_ne!(key, Key::new(&cmd, None, 1));
+        cmd.env("LANG", "C");
+        assert_ne!(key, Key::new(&cmd, None, ()));
+    }
+}
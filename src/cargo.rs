@@ -59,6 +59,7 @@ use std::error::Error;
 use std::fmt;
 use std::path;
 use std::process;
+use std::sync::Mutex;
 
 /// Create a [`Command`] for a `bin` in the Cargo project.
 ///
@@ -130,14 +131,166 @@ impl CommandCargoExt for process::Command {
 }
 
 pub(crate) fn cargo_bin_cmd<S: AsRef<str>>(name: S) -> Result<process::Command, CargoError> {
+    existing_cmd(name.as_ref(), cargo_bin(name.as_ref()))
+}
+
+pub(crate) fn workspace_bin_cmd(name: &str) -> Result<process::Command, CargoError> {
+    existing_cmd(name, workspace_bin(name)?)
+}
+
+pub(crate) fn cargo_example_cmd<S: AsRef<str>>(name: S) -> Result<process::Command, CargoError> {
+    existing_cmd(name.as_ref(), cargo_example(name.as_ref()))
+}
+
+pub(crate) fn cargo_bin_with_profile_cmd(
+    name: &str,
+    profile: &str,
+) -> Result<process::Command, CargoError> {
+    let path = cargo_bin_with_profile(name, profile);
+    if !path.is_file() {
+        return Err(CargoError::with_cause(NotFoundError {
+            path,
+            suggestions: Vec::new(),
+            available: Vec::new(),
+            build: Some(build_command(name, profile)),
+        }));
+    }
+    existing_cmd(name, path)
+}
+
+/// The cargo invocation that builds `name` with `profile`.
+fn build_command(name: &str, profile: &str) -> String {
+    match profile {
+        "dev" | "debug" => format!("cargo build --bin {}", name),
+        "release" => format!("cargo build --release --bin {}", name),
+        profile => format!("cargo build --profile {} --bin {}", profile, name),
+    }
+}
+
+/// How [`Command::cargo_bin_as`] puts a binary under another name.
+///
+/// [`Command::cargo_bin_as`]: crate::cmd::Command::cargo_bin_as()
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Stage {
+    /// A symlink on Unix, and a hard link on Windows, where symlinks need extra privileges,
+    /// falling back to a copy if linking fails.
+    Link,
+    /// A copy, for binaries that find files relative to [`std::env::current_exe`], which
+    /// resolves symlinks.
+    Copy,
+}
+
+/// Stage the binary `name` in a fresh directory as `alias`, with the command to run it.
+pub(crate) fn cargo_bin_as_cmd(
+    name: &str,
+    alias: &str,
+    stage: Stage,
+) -> Result<(process::Command, crate::temp::TempDir), CargoError> {
     let path = cargo_bin(name);
+    // Report a missing binary like `cargo_bin` does.
+    existing_cmd(name, path.clone())?;
+    let dir = crate::temp::TempDir::new("assert_cmd-staged");
+    let staged = dir
+        .path()
+        .join(format!("{}{}", alias, env::consts::EXE_SUFFIX));
+    let linked = stage == Stage::Link && link(&path, &staged).is_ok();
+    if !linked {
+        std::fs::copy(&path, &staged).map_err(CargoError::with_cause)?;
+    }
+    Ok((existing_cmd(name, staged)?, dir))
+}
+
+#[cfg(unix)]
+fn link(original: &path::Path, link: &path::Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(original, link)
+}
+
+#[cfg(not(unix))]
+fn link(original: &path::Path, link: &path::Path) -> std::io::Result<()> {
+    std::fs::hard_link(original, link)
+}
+
+fn existing_cmd(name: &str, path: path::PathBuf) -> Result<process::Command, CargoError> {
     if path.is_file() {
-        Ok(process::Command::new(path))
+        let mut cmd = process::Command::new(path);
+        if let Some(runner) = runner() {
+            cmd = with_runner(&cmd, &runner);
+        }
+        Ok(cmd)
     } else {
-        Err(CargoError::with_cause(NotFoundError { path }))
+        let available = available_bins();
+        let suggestions = did_you_mean(name, available.clone());
+        Err(CargoError::with_cause(NotFoundError {
+            path,
+            suggestions,
+            available,
+            build: None,
+        }))
     }
 }
 
+/// The runner cargo is configured to wrap this target's binaries with, if any.
+///
+/// This is `CARGO_TARGET_<TRIPLE>_RUNNER`, split on whitespace like cargo does, so binaries
+/// cross-compiled for a target the host can't execute run under, for example, `qemu-aarch64`.
+/// [`Command::cargo_bin`] applies it automatically; pass it to [`Command::runner`] for commands
+/// created otherwise.
+///
+/// Runners configured only in `.cargo/config.toml` aren't visible to the test process.
+///
+/// [`Command::cargo_bin`]: crate::cmd::Command::cargo_bin()
+/// [`Command::runner`]: crate::cmd::Command::runner()
+pub fn runner() -> Option<Vec<String>> {
+    let runner = env::var(runner_var(env!("ASSERT_CMD_TARGET"))).ok()?;
+    let runner: Vec<_> = runner.split_whitespace().map(|s| s.to_owned()).collect();
+    Some(runner).filter(|r| !r.is_empty())
+}
+
+fn runner_var(target: &str) -> String {
+    format!(
+        "CARGO_TARGET_{}_RUNNER",
+        target.to_uppercase().replace(['-', '.'], "_")
+    )
+}
+
+/// Rebuild `cmd` to run its program through `runner`.
+///
+/// Only what [`process::Command`] exposes carries over: the program, arguments, environment, and
+/// working directory.
+pub(crate) fn with_runner<S: AsRef<std::ffi::OsStr>>(
+    cmd: &process::Command,
+    runner: &[S],
+) -> process::Command {
+    let (program, runner_args) = match runner.split_first() {
+        Some(split) => split,
+        None => return rebuild(cmd, cmd.get_program(), std::iter::empty()),
+    };
+    let args = runner_args
+        .iter()
+        .map(|arg| arg.as_ref())
+        .chain(std::iter::once(cmd.get_program()));
+    rebuild(cmd, program.as_ref(), args)
+}
+
+fn rebuild<'a>(
+    cmd: &'a process::Command,
+    program: &std::ffi::OsStr,
+    args: impl Iterator<Item = &'a std::ffi::OsStr>,
+) -> process::Command {
+    let mut wrapped = process::Command::new(program);
+    wrapped.args(args).args(cmd.get_args());
+    for (key, value) in cmd.get_envs() {
+        match value {
+            Some(value) => wrapped.env(key, value),
+            None => wrapped.env_remove(key),
+        };
+    }
+    if let Some(dir) = cmd.get_current_dir() {
+        wrapped.current_dir(dir);
+    }
+    wrapped
+}
+
 /// Error when finding crate binary.
 #[derive(Debug)]
 pub struct CargoError {
@@ -170,14 +323,130 @@ impl fmt::Display for CargoError {
 #[derive(Debug)]
 struct NotFoundError {
     path: path::PathBuf,
+    suggestions: Vec<String>,
+    available: Vec<String>,
+    /// How to build the binary, when it isn't one `cargo test` builds.
+    build: Option<String>,
 }
 
 impl Error for NotFoundError {}
 
 impl fmt::Display for NotFoundError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "Cargo command not found: {}", self.path.display())
+        writeln!(f, "Cargo command not found: {}", self.path.display())?;
+        if let Some(ref build) = self.build {
+            writeln!(f, "Build it with `{}`", build)?;
+        }
+        if !self.suggestions.is_empty() {
+            let suggestions: Vec<_> = self
+                .suggestions
+                .iter()
+                .map(|s| format!("`{}`", s))
+                .collect();
+            writeln!(f, "Did you mean {}?", suggestions.join(", "))?;
+        } else if !self.available.is_empty() {
+            writeln!(f, "Available binaries: {}", self.available.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Names of the binaries (and examples) cargo has built for this test run.
+fn available_bins() -> Vec<String> {
+    let mut names: Vec<String> = env::vars_os()
+        .filter_map(|(key, _)| {
+            key.to_str()
+                .and_then(|k| k.strip_prefix("CARGO_BIN_EXE_"))
+                .map(|k| k.to_owned())
+        })
+        .collect();
+    let target_dir = target_dir();
+    for dir in &[target_dir.clone(), target_dir.join("examples")] {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if !is_executable(&path) {
+                continue;
+            }
+            let name = path.file_name().and_then(|n| n.to_str()).and_then(|n| {
+                if env::consts::EXE_SUFFIX.is_empty() {
+                    // Skip build artifacts like `foo.d`
+                    Some(n).filter(|n| !n.contains('.'))
+                } else {
+                    n.strip_suffix(env::consts::EXE_SUFFIX)
+                }
+            });
+            if let Some(name) = name {
+                names.push(name.to_owned());
+            }
+        }
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+#[cfg(unix)]
+fn is_executable(path: &path::Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &path::Path) -> bool {
+    path.is_file()
+}
+
+fn did_you_mean(name: &str, candidates: Vec<String>) -> Vec<String> {
+    let threshold = std::cmp::max(1, name.chars().count() / 3);
+    let mut close: Vec<_> = candidates
+        .into_iter()
+        .map(|c| (edit_distance(name, &c), c))
+        .filter(|(distance, _)| *distance <= threshold)
+        .collect();
+    close.sort();
+    close.into_iter().take(3).map(|(_, c)| c).collect()
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_c) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_c) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(a_c != *b_c);
+            current.push(substitution.min(prev[j + 1] + 1).min(current[j] + 1));
+        }
+        prev = current;
     }
+    prev[b.len()]
+}
+
+/// The path to a binary of the current crate, as built by cargo for the integration tests.
+///
+/// This reads `CARGO_BIN_EXE_<name>` at compile time, so it only works in integration tests
+/// (and benchmarks), but there it is reliable for custom target dirs, cross-compilation, and
+/// workspaces.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use assert_cmd::Command;
+///
+/// Command::new(assert_cmd::cargo_bin!("bin_fixture"))
+///     .assert()
+///     .success();
+/// ```
+#[macro_export]
+macro_rules! cargo_bin {
+    ($bin_target_name:expr) => {
+        ::std::path::Path::new(env!(concat!("CARGO_BIN_EXE_", $bin_target_name)))
+    };
 }
 
 // Adapted from
@@ -196,13 +465,183 @@ fn target_dir() -> path::PathBuf {
 }
 
 /// Look up the path to a cargo-built binary within an integration test.
+///
+/// `CARGO_BIN_EXE_<name>` is used when set, falling back to guessing from the test's own
+/// location.  Prefer [`cargo_bin!`][crate::cargo_bin], which reads the variable cargo sets
+/// while compiling the test, and so works with any target dir layout or cross-compilation.
 pub fn cargo_bin<S: AsRef<str>>(name: S) -> path::PathBuf {
     cargo_bin_str(name.as_ref())
 }
 
+/// Look up the path to a binary built with a cargo `profile`, like the `release` build for a
+/// benchmark.
+///
+/// Unlike [`cargo_bin()`], the binary isn't built by `cargo test`; build it first, like with
+/// `cargo build --release`.  `dev` and `test` binaries are under `target/debug` and `bench` ones
+/// under `target/release`, like cargo; custom profiles are under `target/<profile>`.
+pub fn cargo_bin_with_profile<S: AsRef<str>, P: AsRef<str>>(name: S, profile: P) -> path::PathBuf {
+    let dir = match profile.as_ref() {
+        "dev" | "test" => "debug",
+        "bench" => "release",
+        profile => profile,
+    };
+    let mut target_dir = target_dir();
+    target_dir.pop();
+    target_dir
+        .join(dir)
+        .join(format!("{}{}", name.as_ref(), env::consts::EXE_SUFFIX))
+}
+
+/// Build a binary of any member of the current workspace, returning its path.
+///
+/// [`cargo_bin`][cargo_bin()] only finds binaries `cargo test` built, which are the current
+/// package's.  This runs `cargo build --workspace --bin <name>`, with the test's profile and
+/// target dir, so a sibling member's binary is built with that member's default features.  Each
+/// binary is built once per test process.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// let server = assert_cmd::cargo::workspace_bin("other-member-bin").unwrap();
+/// ```
+pub fn workspace_bin<S: AsRef<str>>(name: S) -> Result<path::PathBuf, CargoError> {
+    static BUILT: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    let name = name.as_ref();
+    let target_dir = target_dir();
+    let path = target_dir.join(format!("{}{}", name, env::consts::EXE_SUFFIX));
+    // Held while building, so concurrent tests wait for the build rather than racing it.
+    let mut built = BUILT.lock().unwrap_or_else(|err| err.into_inner());
+    if built.iter().any(|b| b == name) {
+        return Ok(path);
+    }
+
+    let cargo = env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let mut cmd = process::Command::new(cargo);
+    cmd.args(["build", "--workspace", "--bin", name]);
+    match target_dir.file_name().and_then(|p| p.to_str()) {
+        Some("debug") | None => {}
+        Some("release") => {
+            cmd.arg("--release");
+        }
+        Some(profile) => {
+            cmd.args(["--profile", profile]);
+        }
+    }
+    let mut root = target_dir.clone();
+    root.pop();
+    if root.ends_with(env!("ASSERT_CMD_TARGET")) {
+        cmd.args(["--target", env!("ASSERT_CMD_TARGET")]);
+        root.pop();
+    }
+    cmd.arg("--target-dir").arg(&root);
+    if let Some(dir) = env::var_os("CARGO_MANIFEST_DIR") {
+        cmd.current_dir(dir);
+    }
+    let output = cmd.output().map_err(|err| {
+        CargoError::with_cause(BuildError {
+            command: format!("{:?}", cmd),
+            stderr: err.to_string(),
+        })
+    })?;
+    if !output.status.success() {
+        return Err(CargoError::with_cause(BuildError {
+            command: format!("{:?}", cmd),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        }));
+    }
+    built.push(name.to_owned());
+    Ok(path)
+}
+
+/// Error when building a workspace binary, see [`workspace_bin`].
+#[derive(Debug)]
+struct BuildError {
+    command: String,
+    stderr: String,
+}
+
+impl Error for BuildError {}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Unable to build with {}:", self.command)?;
+        write!(f, "{}", self.stderr)
+    }
+}
+
+/// Look up the path to a cargo-built example within an integration test.
+///
+/// `cargo test` builds the crate's examples along with its tests.
+pub fn cargo_example<S: AsRef<str>>(name: S) -> path::PathBuf {
+    target_dir()
+        .join("examples")
+        .join(format!("{}{}", name.as_ref(), env::consts::EXE_SUFFIX))
+}
+
 fn cargo_bin_str(name: &str) -> path::PathBuf {
     let env_var = format!("CARGO_BIN_EXE_{}", name);
     std::env::var_os(&env_var)
         .map(|p| p.into())
         .unwrap_or_else(|| target_dir().join(format!("{}{}", name, env::consts::EXE_SUFFIX)))
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn edit_distance_counts_changes() {
+        assert_eq!(edit_distance("bin_fixture", "bin_fixture"), 0);
+        assert_eq!(edit_distance("bin_fixtur", "bin_fixture"), 1);
+        assert_eq!(edit_distance("bin-fixture", "bin_fixture"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn build_commands() {
+        assert_eq!(build_command("tool", "dev"), "cargo build --bin tool");
+        assert_eq!(
+            build_command("tool", "release"),
+            "cargo build --release --bin tool"
+        );
+        assert_eq!(
+            build_command("tool", "profiling"),
+            "cargo build --profile profiling --bin tool"
+        );
+    }
+
+    #[test]
+    fn runner_var_names() {
+        assert_eq!(
+            runner_var("aarch64-unknown-linux-gnu"),
+            "CARGO_TARGET_AARCH64_UNKNOWN_LINUX_GNU_RUNNER"
+        );
+        assert_eq!(
+            runner_var("thumbv7em-none-eabihf.json"),
+            "CARGO_TARGET_THUMBV7EM_NONE_EABIHF_JSON_RUNNER"
+        );
+    }
+
+    #[test]
+    fn with_runner_keeps_config() {
+        let mut cmd = process::Command::new("tool");
+        cmd.arg("--flag").env("A", "1").current_dir("dir");
+        let wrapped = with_runner(&cmd, &["qemu-aarch64", "-L", "/sysroot"]);
+        assert_eq!(wrapped.get_program(), "qemu-aarch64");
+        let args: Vec<_> = wrapped.get_args().collect();
+        assert_eq!(args, ["-L", "/sysroot", "tool", "--flag"]);
+        assert_eq!(wrapped.get_envs().count(), 1);
+        assert_eq!(wrapped.get_current_dir(), Some(path::Path::new("dir")));
+    }
+
+    #[test]
+    fn did_you_mean_finds_close_names() {
+        let candidates = vec!["bin_fixture".to_owned(), "example_fixture".to_owned()];
+        assert_eq!(
+            did_you_mean("bin-fixture", candidates.clone()),
+            vec!["bin_fixture".to_owned()]
+        );
+        assert!(did_you_mean("completely-different", candidates).is_empty());
+    }
+}
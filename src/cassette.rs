@@ -0,0 +1,383 @@
+//! Record the results of slow or networked commands once, then replay them.
+//!
+//! [`Command::cassette`] keeps each run's exit status, `stdout`, and `stderr` in a file, keyed
+//! by the program's name, its arguments, and its `stdin`.  Later runs replay the recording
+//! instead of running the program:
+//!
+//! ```rust,no_run
+//! use assert_cmd::Command;
+//!
+//! Command::cargo_bin("bin_fixture")
+//!     .unwrap()
+//!     .args(["fetch", "https://example.com/index.json"])
+//!     .cassette("tests/cassettes/fetch.cassette")
+//!     .assert()
+//!     .success();
+//! ```
+//!
+//! [`MODE_ENV`] controls when programs are run:
+//! - unset or `auto`: replay a recorded run, else run the program and record it
+//! - `record`: always run the program, recording over any earlier run
+//! - `replay`: only replay, failing when nothing was recorded, like in CI
+//! - `off`: always run the program, leaving cassettes alone
+//!
+//! Only the status and output are replayed: a replayed run has no timing, and
+//! [`Command::env_dump`] and the like see nothing.
+//!
+//! [`Command::cassette`]: crate::cmd::Command::cassette()
+//! [`Command::env_dump`]: crate::cmd::Command::env_dump()
+
+use std::env;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::path;
+use std::process;
+use std::sync::Mutex;
+
+/// Set to choose when [`Command::cassette`] runs programs, see the
+/// [module documentation][crate::cassette].
+///
+/// This is read once per process.
+///
+/// [`Command::cassette`]: crate::cmd::Command::cassette()
+pub const MODE_ENV: &str = "ASSERT_CMD_CASSETTE";
+
+const HEADER: &str = "# Recorded by assert_cmd; rerun with ASSERT_CMD_CASSETTE=record to update\n";
+
+/// Serializes rewrites of cassette files, which tests may share.
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// When to run programs, see [`MODE_ENV`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Mode {
+    Auto,
+    Record,
+    Replay,
+    Off,
+}
+
+impl Mode {
+    /// The mode from [`MODE_ENV`].
+    ///
+    /// # Panics
+    ///
+    /// If the variable isn't a mode.
+    pub(crate) fn from_env() -> Self {
+        static MODE: std::sync::Mutex<Option<Mode>> = std::sync::Mutex::new(None);
+        let mut cached = MODE.lock().unwrap_or_else(|err| err.into_inner());
+        *cached.get_or_insert_with(|| {
+            let mode = env::var_os(MODE_ENV).unwrap_or_default();
+            match mode.to_string_lossy().trim() {
+                "" | "auto" => Self::Auto,
+                "record" => Self::Record,
+                "replay" => Self::Replay,
+                "off" => Self::Off,
+                mode => panic!(
+                    "Invalid {}=`{}`, expected `auto`, `record`, `replay`, or `off`",
+                    MODE_ENV, mode
+                ),
+            }
+        })
+    }
+}
+
+/// What identifies a run within a cassette.
+///
+/// The program is identified by its name alone, so recordings carry across machines and target
+/// directories.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Key {
+    program: String,
+    args: Vec<String>,
+    stdin: Option<Vec<u8>>,
+}
+
+impl Key {
+    pub(crate) fn new(cmd: &process::Command, stdin: Option<&[u8]>) -> Self {
+        let program = path::Path::new(cmd.get_program());
+        let program = program
+            .file_stem()
+            .unwrap_or(program.as_os_str())
+            .to_string_lossy();
+        Self {
+            program: format!("{:?}", program),
+            args: cmd
+                .get_args()
+                .map(|arg| format!("{:?}", arg.to_string_lossy()))
+                .collect(),
+            stdin: stdin.map(|stdin| stdin.to_owned()),
+        }
+    }
+}
+
+/// Where the cassette `path` is: relative paths are within the crate.
+pub(crate) fn resolve(path: &path::Path) -> path::PathBuf {
+    let root = env::var_os("CARGO_MANIFEST_DIR")
+        .map(path::PathBuf::from)
+        .unwrap_or_default();
+    root.join(path)
+}
+
+/// The run recorded for `key` in the cassette at `path`, if any.
+pub(crate) fn lookup(path: &path::Path, key: &Key) -> io::Result<Option<process::Output>> {
+    let _lock = LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    let runs = load(path)?;
+    Ok(runs
+        .into_iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, output)| output))
+}
+
+/// Record `output` for `key` in the cassette at `path`, replacing any earlier recording.
+pub(crate) fn record(path: &path::Path, key: Key, output: &process::Output) -> io::Result<()> {
+    let _lock = LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    let mut runs = load(path)?;
+    match runs.iter_mut().find(|(k, _)| *k == key) {
+        Some(run) => run.1 = output.clone(),
+        None => runs.push((key, output.clone())),
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, render(&runs))
+}
+
+/// The error for a run missing from a cassette in [`Mode::Replay`].
+pub(crate) fn missing(path: &path::Path, key: &Key) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Other,
+        format!(
+            "No run of {} {} recorded in {}; record it with {}=record",
+            key.program,
+            key.args.join(" "),
+            path.display(),
+            MODE_ENV
+        ),
+    )
+}
+
+fn load(path: &path::Path) -> io::Result<Vec<(Key, process::Output)>> {
+    match fs::read(path) {
+        Ok(data) => parse(&data).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid cassette {}: {}", path.display(), err),
+            )
+        }),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err),
+    }
+}
+
+/// Write each run as lines of `name value`, with data as `name <length>`, the bytes, and a
+/// newline, and a blank line after each run.
+fn render(runs: &[(Key, process::Output)]) -> Vec<u8> {
+    fn data(out: &mut Vec<u8>, name: &str, data: &[u8]) {
+        let _ = writeln!(out, "{} {}", name, data.len());
+        out.extend_from_slice(data);
+        out.push(b'\n');
+    }
+
+    let mut out = HEADER.as_bytes().to_owned();
+    for (key, output) in runs {
+        let _ = writeln!(out, "program {}", key.program);
+        for arg in &key.args {
+            let _ = writeln!(out, "arg {}", arg);
+        }
+        if let Some(stdin) = &key.stdin {
+            data(&mut out, "stdin", stdin);
+        }
+        let _ = writeln!(out, "status {}", status_text(output.status));
+        data(&mut out, "stdout", &output.stdout);
+        data(&mut out, "stderr", &output.stderr);
+        out.push(b'\n');
+    }
+    out
+}
+
+fn parse(data: &[u8]) -> Result<Vec<(Key, process::Output)>, String> {
+    let mut parser = Parser { data, line: 1 };
+    let mut runs = Vec::new();
+    while let Some(line) = parser.peek_line() {
+        if line.is_empty() || line.starts_with('#') {
+            parser.next_line();
+            continue;
+        }
+        let program = parser.field("program")?;
+        let mut args = Vec::new();
+        while parser
+            .peek_line()
+            .map_or(false, |line| line.starts_with("arg "))
+        {
+            args.push(parser.field("arg")?);
+        }
+        let stdin = if parser
+            .peek_line()
+            .map_or(false, |line| line.starts_with("stdin "))
+        {
+            Some(parser.data("stdin")?)
+        } else {
+            None
+        };
+        let status = parser.field("status")?;
+        let status = parse_status(&status)
+            .ok_or_else(|| format!("line {}: invalid status `{}`", parser.line - 1, status))?;
+        let stdout = parser.data("stdout")?;
+        let stderr = parser.data("stderr")?;
+        runs.push((
+            Key {
+                program,
+                args,
+                stdin,
+            },
+            process::Output {
+                status,
+                stdout,
+                stderr,
+            },
+        ));
+    }
+    Ok(runs)
+}
+
+struct Parser<'a> {
+    data: &'a [u8],
+    line: usize,
+}
+
+impl Parser<'_> {
+    fn peek_line(&self) -> Option<&str> {
+        if self.data.is_empty() {
+            return None;
+        }
+        let end = self
+            .data
+            .iter()
+            .position(|b| *b == b'\n')
+            .unwrap_or(self.data.len());
+        std::str::from_utf8(&self.data[..end]).ok()
+    }
+
+    fn next_line(&mut self) -> Option<String> {
+        let line = self.peek_line()?.to_owned();
+        self.data = &self.data[(line.len() + 1).min(self.data.len())..];
+        self.line += 1;
+        Some(line)
+    }
+
+    /// The value of the line `name value`.
+    fn field(&mut self, name: &str) -> Result<String, String> {
+        let line = self.line;
+        match self.next_line() {
+            Some(text) => match text.split_once(' ') {
+                Some((n, value)) if n == name => Ok(value.to_owned()),
+                _ => Err(format!("line {}: expected `{}`", line, name)),
+            },
+            None => Err(format!("line {}: expected `{}`", line, name)),
+        }
+    }
+
+    /// The bytes after the line `name <length>`.
+    fn data(&mut self, name: &str) -> Result<Vec<u8>, String> {
+        let line = self.line;
+        let len: usize = self
+            .field(name)?
+            .parse()
+            .map_err(|_| format!("line {}: invalid length of `{}`", line, name))?;
+        if self.data.len() < len + 1 || self.data[len] != b'\n' {
+            return Err(format!("line {}: `{}` is truncated", line, name));
+        }
+        let data = self.data[..len].to_owned();
+        self.line += data.iter().filter(|b| **b == b'\n').count() + 1;
+        self.data = &self.data[len + 1..];
+        Ok(data)
+    }
+}
+
+fn status_text(status: process::ExitStatus) -> String {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return format!("signal {}", signal);
+        }
+    }
+    match status.code() {
+        Some(code) => code.to_string(),
+        None => "unknown".to_owned(),
+    }
+}
+
+fn parse_status(text: &str) -> Option<process::ExitStatus> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        match text.strip_prefix("signal ") {
+            Some(signal) => signal.parse().ok().map(process::ExitStatus::from_raw),
+            None => text
+                .parse::<i32>()
+                .ok()
+                .map(|code| process::ExitStatus::from_raw((code & 0xff) << 8)),
+        }
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::ExitStatusExt;
+        text.parse::<i32>()
+            .ok()
+            .map(|code| process::ExitStatus::from_raw(code as u32))
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = text;
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn round_trip() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let mut cmd = process::Command::new("/usr/bin/fetch");
+        cmd.args(["-v", "a b"]);
+        let runs = vec![
+            (
+                Key::new(&cmd, Some(b"line\n")),
+                process::Output {
+                    status: process::ExitStatus::from_raw(2 << 8),
+                    stdout: b"one\ntwo".to_vec(),
+                    stderr: vec![0xff, b'\n'],
+                },
+            ),
+            (
+                Key::new(&cmd, None),
+                process::Output {
+                    status: process::ExitStatus::from_raw(9),
+                    stdout: Vec::new(),
+                    stderr: Vec::new(),
+                },
+            ),
+        ];
+        let rendered = render(&runs);
+        assert!(String::from_utf8_lossy(&rendered)
+            .contains("program \"fetch\"\narg \"-v\"\narg \"a b\"\nstdin 5\nline\n\nstatus 2\n"));
+        let parsed = parse(&rendered).unwrap();
+        assert_eq!(parsed.len(), 2);
+        for ((key, output), (parsed_key, parsed_output)) in runs.iter().zip(&parsed) {
+            assert_eq!(key, parsed_key);
+            assert_eq!(output, parsed_output);
+        }
+    }
+
+    #[test]
+    fn truncated() {
+        let err = parse(b"program \"fetch\"\nstatus 0\nstdout 10\nshort\n").unwrap_err();
+        assert_eq!(err, "line 3: `stdout` is truncated");
+    }
+}
@@ -0,0 +1,615 @@
+//! Ensure every spawned child is reaped exactly once.
+//!
+//! On Unix, children are reaped with `wait4`, rather than through `std`, to also collect their
+//! [`ResourceUsage`].
+
+use std::fs;
+use std::io;
+use std::process;
+#[cfg(unix)]
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use crate::resources::ResourceUsage;
+
+/// A child's status and resource usage, once reaped.
+#[cfg(unix)]
+type Reaped = (process::ExitStatus, ResourceUsage);
+
+/// How long a child is given to exit after a [`ChildGuard::with_terminate`] signal, without a
+/// timeout.
+pub(crate) const TERMINATE_GRACE: Duration = Duration::from_secs(10);
+
+/// The signal [`ChildGuard::wait_until`] sent, see [`ChildGuard::with_terminate`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Terminated {
+    pub(crate) signal: i32,
+    pub(crate) after: Duration,
+    /// Whether the child was then killed for not exiting in time.
+    pub(crate) killed: bool,
+}
+
+impl std::fmt::Display for Terminated {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        #[cfg(unix)]
+        let signal = crate::signals::describe(self.signal);
+        #[cfg(not(unix))]
+        let signal = format!("signal {}", self.signal);
+        write!(f, "{} after {:?}", signal, self.after)?;
+        if self.killed {
+            write!(f, ", then killed")?;
+        }
+        Ok(())
+    }
+}
+
+/// Owns a spawned child until it has been waited on.
+///
+/// If dropped before being waited on, for example while unwinding from a panic, the child is
+/// killed and reaped so it doesn't linger as a zombie or orphan.  Debug builds also catch waiting
+/// twice or forgetting to wait outside of a panic.
+#[derive(Debug)]
+pub(crate) struct ChildGuard {
+    child: process::Child,
+    status: Option<process::ExitStatus>,
+    abandoned: bool,
+    /// Whether the child leads a process group whose other members are waited on and killed
+    /// along with it.
+    group: bool,
+    /// Whether the child leads a process group whose other members are killed along with it.
+    kill_group: bool,
+    /// How long spawning the child took.
+    spawn: Duration,
+    /// Our end of the terminal the child's `stdout` is attached to.
+    #[cfg(all(unix, feature = "pty"))]
+    terminal: Option<crate::pty::Master>,
+    /// Our end of the pipe the child's `stdout` and `stderr` share.
+    combined: Option<fs::File>,
+    /// The status the child was reaped with, which `std` doesn't know about.
+    #[cfg(unix)]
+    exited: Option<process::ExitStatus>,
+    /// A thread blocked until the child exits, from a [`ChildGuard::wait_timeout`] that timed
+    /// out.  It leaves the child for this thread to reap, so the process ID stays the child's
+    /// until `exited` is set.
+    #[cfg(unix)]
+    reaper: Option<mpsc::Receiver<io::Result<()>>>,
+    usage: Option<ResourceUsage>,
+    /// The signal to send, and after how long, while waiting.
+    #[cfg(unix)]
+    terminate: Option<(Duration, i32)>,
+    terminated: Option<Terminated>,
+}
+
+impl ChildGuard {
+    pub(crate) fn new(child: process::Child) -> Self {
+        Self {
+            child,
+            status: None,
+            abandoned: false,
+            group: false,
+            kill_group: false,
+            spawn: Duration::default(),
+            #[cfg(all(unix, feature = "pty"))]
+            terminal: None,
+            combined: None,
+            #[cfg(unix)]
+            exited: None,
+            #[cfg(unix)]
+            reaper: None,
+            usage: None,
+            #[cfg(unix)]
+            terminate: None,
+            terminated: None,
+        }
+    }
+
+    /// Record how long spawning the child took.
+    pub(crate) fn spawned_in(mut self, spawn: Duration) -> Self {
+        self.spawn = spawn;
+        self
+    }
+
+    pub(crate) fn spawn_duration(&self) -> Duration {
+        self.spawn
+    }
+
+    /// Treat the child's process group as part of the child, either waiting on and killing it
+    /// with the child, or only killing it.
+    ///
+    /// The child must have been spawned as the leader of a new process group.
+    #[cfg(unix)]
+    pub(crate) fn with_group(mut self, follow: bool, kill: bool) -> Self {
+        self.group = follow;
+        self.kill_group = follow || kill;
+        self
+    }
+
+    /// Send `signal` to the child if it's still running `after` it's waited on with
+    /// [`ChildGuard::wait_until`].
+    #[cfg(unix)]
+    pub(crate) fn with_terminate(mut self, terminate: Option<(Duration, i32)>) -> Self {
+        self.terminate = terminate;
+        self
+    }
+
+    /// Read `stdout` from `terminal`, the other end of the child's.
+    #[cfg(all(unix, feature = "pty"))]
+    pub(crate) fn with_terminal(mut self, terminal: Option<crate::pty::Master>) -> Self {
+        self.terminal = terminal;
+        self
+    }
+
+    /// Read `stdout` from `combined`, the other end of the child's `stdout` and `stderr`.
+    pub(crate) fn with_combined(mut self, combined: Option<fs::File>) -> Self {
+        self.combined = combined;
+        self
+    }
+
+    pub(crate) fn id(&self) -> u32 {
+        self.child.id()
+    }
+
+    pub(crate) fn take_stdin(&mut self) -> Option<process::ChildStdin> {
+        self.child.stdin.take()
+    }
+
+    pub(crate) fn take_stdout(&mut self) -> Option<Box<dyn io::Read + Send>> {
+        #[cfg(all(unix, feature = "pty"))]
+        {
+            if let Some(terminal) = self.terminal.take() {
+                return Some(Box::new(terminal));
+            }
+        }
+        if let Some(combined) = self.combined.take() {
+            return Some(Box::new(combined));
+        }
+        self.child
+            .stdout
+            .take()
+            .map(|stdout| Box::new(stdout) as Box<dyn io::Read + Send>)
+    }
+
+    /// The child's `stdout` pipe, to become another child's `stdin`.
+    pub(crate) fn take_stdout_pipe(&mut self) -> Option<process::ChildStdout> {
+        self.child.stdout.take()
+    }
+
+    pub(crate) fn take_stderr(&mut self) -> Option<process::ChildStderr> {
+        self.child.stderr.take()
+    }
+
+    /// The child's total resource usage, once it has been waited on.
+    pub(crate) fn resource_usage(&self) -> Option<ResourceUsage> {
+        self.usage
+    }
+
+    /// Wait for the child to exit.
+    pub(crate) fn wait(&mut self) -> io::Result<process::ExitStatus> {
+        debug_assert!(self.status.is_none(), "child was already waited on");
+        if let Some(status) = self.status {
+            return Ok(status);
+        }
+        #[cfg(unix)]
+        let status = self
+            .reap(None)
+            .map_err(|err| self.abandon(err))?
+            .expect("waited without a timeout");
+        #[cfg(not(unix))]
+        let status = self.child.wait().map_err(|err| self.abandon(err))?;
+        self.status = Some(status);
+        Ok(status)
+    }
+
+    /// Wait for the child to exit, giving up after `timeout`, if any.
+    ///
+    /// Any [`ChildGuard::with_terminate`] signal is sent first, if the child is still running.
+    /// It's then given the rest of `timeout` to exit, or [`TERMINATE_GRACE`].
+    pub(crate) fn wait_until(
+        &mut self,
+        timeout: Option<Duration>,
+    ) -> io::Result<Option<process::ExitStatus>> {
+        #[cfg(unix)]
+        {
+            let terminate = self
+                .terminate
+                .filter(|(after, _)| timeout.map_or(true, |timeout| *after < timeout));
+            if let Some((after, signal)) = terminate {
+                if let Some(status) = self.wait_timeout(after)? {
+                    return Ok(Some(status));
+                }
+                // The child may exit before the signal, which is then moot.
+                let _ = self.signal(signal);
+                let grace = timeout.map_or(TERMINATE_GRACE, |timeout| timeout - after);
+                let status = self.wait_timeout(grace)?;
+                self.terminated = Some(Terminated {
+                    signal,
+                    after,
+                    killed: status.is_none(),
+                });
+                return Ok(status);
+            }
+        }
+        match timeout {
+            Some(timeout) => self.wait_timeout(timeout),
+            None => self.wait().map(Some),
+        }
+    }
+
+    /// The signal [`ChildGuard::wait_until`] sent, if any.
+    pub(crate) fn terminated(&self) -> Option<Terminated> {
+        self.terminated
+    }
+
+    /// Wait for the child to exit, giving up after `timeout`.
+    pub(crate) fn wait_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> io::Result<Option<process::ExitStatus>> {
+        debug_assert!(self.status.is_none(), "child was already waited on");
+        if let Some(status) = self.status {
+            return Ok(Some(status));
+        }
+        #[cfg(unix)]
+        let status = self.reap(Some(timeout)).map_err(|err| self.abandon(err))?;
+        #[cfg(not(unix))]
+        let status = wait_timeout::ChildExt::wait_timeout(&mut self.child, timeout)
+            .map_err(|err| self.abandon(err))?;
+        self.status = status;
+        Ok(status)
+    }
+
+    /// Reap the child, giving up after `timeout`, unless it was already reaped.
+    ///
+    /// A blocking wait can't time out, so that waits for the exit on a thread, which a later call
+    /// picks up from.
+    #[cfg(unix)]
+    fn reap(&mut self, timeout: Option<Duration>) -> io::Result<Option<process::ExitStatus>> {
+        if let Some(status) = self.exited {
+            return Ok(Some(status));
+        }
+        // Like `std`, close `stdin` so the child doesn't wait for more.
+        drop(self.child.stdin.take());
+        let pid = self.child.id();
+        match (self.reaper.take(), timeout) {
+            (Some(reaper), None) => reaper.recv().map_err(|_| reaper_gone())??,
+            (None, None) => {}
+            (reaper, Some(timeout)) => {
+                let reaper = reaper.unwrap_or_else(|| {
+                    let (send, reaper) = mpsc::channel();
+                    std::thread::spawn(move || {
+                        let _ = send.send(wait_exited(pid));
+                    });
+                    reaper
+                });
+                match reaper.recv_timeout(timeout) {
+                    Ok(exited) => exited?,
+                    Err(mpsc::RecvTimeoutError::Timeout) => {
+                        self.reaper = Some(reaper);
+                        return Ok(None);
+                    }
+                    Err(mpsc::RecvTimeoutError::Disconnected) => return Err(reaper_gone()),
+                }
+            }
+        }
+        let reaped = wait4(pid, false)?.expect("blocking `wait4` reaps");
+        Ok(Some(self.reaped(reaped)))
+    }
+
+    #[cfg(unix)]
+    fn reaped(&mut self, (status, usage): Reaped) -> process::ExitStatus {
+        self.exited = Some(status);
+        self.usage = Some(usage);
+        status
+    }
+
+    /// Wait for the rest of the child's process group, if it has one, to exit after the child.
+    ///
+    /// Returns `false` if `deadline` passed first, after killing the group.
+    pub(crate) fn wait_group(&mut self, deadline: Option<Instant>) -> bool {
+        while self.group_alive() {
+            if matches!(deadline, Some(deadline) if deadline <= Instant::now()) {
+                self.kill_group();
+                return false;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        true
+    }
+
+    /// Whether the child has exited, without waiting.
+    #[cfg(unix)]
+    pub(crate) fn has_exited(&mut self) -> bool {
+        if self.exited.is_some() {
+            return true;
+        }
+        match self.reaper.as_ref().map(mpsc::Receiver::try_recv) {
+            Some(Err(mpsc::TryRecvError::Empty)) => return false,
+            Some(_) => self.reaper = None,
+            None => {}
+        }
+        match wait4(self.child.id(), true).ok().flatten() {
+            Some(reaped) => {
+                self.reaped(reaped);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether the child has exited, without waiting.
+    #[cfg(not(unix))]
+    pub(crate) fn has_exited(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(Some(_)))
+    }
+
+    /// Send `signal` to the child, unless it has already exited.
+    #[cfg(unix)]
+    pub(crate) fn signal(&mut self, signal: i32) -> io::Result<()> {
+        // Once reaped, the process ID may belong to another process.
+        if self.has_exited() {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "the child already exited",
+            ));
+        }
+        // SAFETY: `kill` has no preconditions
+        if unsafe { libc::kill(self.child.id() as libc::pid_t, signal) } == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    /// Kill the child and wait for it to exit.
+    pub(crate) fn kill(&mut self) -> io::Result<process::ExitStatus> {
+        // The child may have exited on its own in the meantime.
+        self.kill_child();
+        self.kill_group();
+        self.wait()
+    }
+
+    /// Kill and reap the child after a failure to wait on it.
+    fn abandon(&mut self, err: io::Error) -> io::Error {
+        self.abandoned = true;
+        self.kill_child();
+        self.kill_group();
+        self.reap_quietly();
+        err
+    }
+
+    fn kill_child(&mut self) {
+        // Once reaped, the process ID may belong to another process.  Only this thread reaps, so
+        // until `exited` is set the ID is still the child's, if only as a zombie.
+        #[cfg(unix)]
+        {
+            if self.exited.is_some() {
+                return;
+            }
+        }
+        let _ = self.child.kill();
+    }
+
+    fn reap_quietly(&mut self) {
+        #[cfg(unix)]
+        let _ = self.reap(None);
+        #[cfg(not(unix))]
+        let _ = self.child.wait();
+    }
+
+    #[cfg(unix)]
+    fn group_alive(&self) -> bool {
+        // SAFETY: `kill` has no preconditions; signal 0 only checks for the group
+        self.group && unsafe { libc::kill(-(self.child.id() as libc::pid_t), 0) } == 0
+    }
+
+    #[cfg(not(unix))]
+    fn group_alive(&self) -> bool {
+        false
+    }
+
+    fn kill_group(&self) {
+        #[cfg(unix)]
+        {
+            if self.kill_group {
+                // SAFETY: `kill` has no preconditions
+                unsafe { libc::kill(-(self.child.id() as libc::pid_t), libc::SIGKILL) };
+            }
+        }
+    }
+}
+
+/// An anonymous pipe, as its read and write ends, neither inherited by children.
+#[cfg(unix)]
+pub(crate) fn pipe() -> io::Result<(fs::File, fs::File)> {
+    use std::os::unix::io::FromRawFd;
+
+    let mut fds = [0; 2];
+    // SAFETY: `fds` has room for both descriptors
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: `pipe` just opened both descriptors, which nothing else owns
+    let (reader, writer) =
+        unsafe { (fs::File::from_raw_fd(fds[0]), fs::File::from_raw_fd(fds[1])) };
+    for fd in fds.iter() {
+        // SAFETY: `fcntl` has no preconditions
+        if unsafe { libc::fcntl(*fd, libc::F_SETFD, libc::FD_CLOEXEC) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok((reader, writer))
+}
+
+/// An anonymous pipe, as its read and write ends, neither inherited by children.
+#[cfg(windows)]
+pub(crate) fn pipe() -> io::Result<(fs::File, fs::File)> {
+    use std::os::windows::io::{FromRawHandle, RawHandle};
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn CreatePipe(
+            read: *mut RawHandle,
+            write: *mut RawHandle,
+            attributes: *mut std::ffi::c_void,
+            size: u32,
+        ) -> i32;
+    }
+
+    let mut read = std::ptr::null_mut();
+    let mut write = std::ptr::null_mut();
+    // SAFETY: `read` and `write` outlive the call; null attributes make both handles
+    // non-inheritable, and `std` duplicates them for the child
+    if unsafe { CreatePipe(&mut read, &mut write, std::ptr::null_mut(), 0) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: `CreatePipe` just opened both handles, which nothing else owns
+    Ok(unsafe {
+        (
+            fs::File::from_raw_handle(read),
+            fs::File::from_raw_handle(write),
+        )
+    })
+}
+
+#[cfg(not(any(unix, windows)))]
+pub(crate) fn pipe() -> io::Result<(fs::File, fs::File)> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "pipes are unsupported on this platform",
+    ))
+}
+
+/// Reap `pid`, unless `nohang` and it hasn't exited yet.
+#[cfg(unix)]
+fn wait4(pid: u32, nohang: bool) -> io::Result<Option<Reaped>> {
+    use std::os::unix::process::ExitStatusExt;
+
+    let options = if nohang { libc::WNOHANG } else { 0 };
+    loop {
+        let mut status = 0;
+        // SAFETY: `rusage` is plain data, for which zero is valid
+        let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+        // SAFETY: `status` and `usage` outlive the call
+        let ret = unsafe { libc::wait4(pid as libc::pid_t, &mut status, options, &mut usage) };
+        match ret {
+            0 => return Ok(None),
+            -1 => {
+                let err = io::Error::last_os_error();
+                if err.kind() != io::ErrorKind::Interrupted {
+                    return Err(err);
+                }
+            }
+            _ => {
+                return Ok(Some((
+                    process::ExitStatus::from_raw(status),
+                    ResourceUsage::from_rusage(&usage),
+                )))
+            }
+        }
+    }
+}
+
+/// Block until `pid` exits, leaving it to be reaped so its process ID isn't reused meanwhile.
+#[cfg(unix)]
+fn wait_exited(pid: u32) -> io::Result<()> {
+    loop {
+        // SAFETY: `siginfo_t` is plain data, for which zero is valid
+        let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+        let options = libc::WEXITED | libc::WNOWAIT;
+        // SAFETY: `info` outlives the call
+        if unsafe { libc::waitid(libc::P_PID, pid as libc::id_t, &mut info, options) } == 0 {
+            return Ok(());
+        }
+        let err = io::Error::last_os_error();
+        if err.kind() != io::ErrorKind::Interrupted {
+            return Err(err);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn reaper_gone() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Other,
+        "the thread waiting on the child exited",
+    )
+}
+
+impl Drop for ChildGuard {
+    fn drop(&mut self) {
+        if self.status.is_some() || self.abandoned {
+            return;
+        }
+        self.kill_child();
+        self.kill_group();
+        self.reap_quietly();
+        debug_assert!(
+            std::thread::panicking(),
+            "child {} was dropped without being waited on",
+            self.child.id()
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sleeper() -> ChildGuard {
+        let child = if cfg!(windows) {
+            process::Command::new("ping")
+                .args(["-n", "100", "127.0.0.1"])
+                .stdout(process::Stdio::null())
+                .spawn()
+        } else {
+            process::Command::new("sleep").arg("100").spawn()
+        };
+        ChildGuard::new(child.unwrap())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn timed_out_wait_leaves_reaping() {
+        let child = process::Command::new("sleep").arg("0.1").spawn().unwrap();
+        let mut child = ChildGuard::new(child);
+        assert_eq!(child.wait_timeout(Duration::from_millis(1)).unwrap(), None);
+        std::thread::sleep(Duration::from_millis(500));
+        // Still a zombie, so signaling it can't reach a process that reused its ID.
+        // SAFETY: `kill` has no preconditions; signal 0 only checks for the process
+        assert_eq!(unsafe { libc::kill(child.id() as libc::pid_t, 0) }, 0);
+        assert!(child.has_exited());
+        assert!(child.wait().unwrap().success());
+    }
+
+    #[test]
+    fn kill_reaps() {
+        let mut child = sleeper();
+        assert_eq!(child.wait_timeout(Duration::from_millis(10)).unwrap(), None);
+        assert!(!child.kill().unwrap().success());
+    }
+
+    #[test]
+    fn reaps_on_panic() {
+        let result = std::panic::catch_unwind(|| {
+            let _child = sleeper();
+            panic!("test failed");
+        });
+        assert!(result.is_err());
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "was dropped without being waited on")]
+    fn forgotten_wait() {
+        let _child = sleeper();
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "child was already waited on")]
+    fn double_wait() {
+        let mut child = sleeper();
+        child.kill().unwrap();
+        let _ = child.wait();
+    }
+}
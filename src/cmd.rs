@@ -3,6 +3,7 @@
 //! [Command]: std::process::Command
 
 use std::ffi;
+use std::fmt;
 use std::io;
 use std::io::{Read, Write};
 use std::path;
@@ -10,11 +11,65 @@ use std::process;
 
 use crate::assert::Assert;
 use crate::assert::OutputAssertExt;
+use crate::child::ChildGuard;
 use crate::output::DebugBuffer;
 use crate::output::DebugBytes;
 use crate::output::OutputError;
 use crate::output::OutputOkExt;
 use crate::output::OutputResult;
+use crate::resources::Sample;
+use crate::resources::Sampler;
+use crate::transcript::Chunk;
+use crate::transcript::Recording;
+use crate::transcript::Stream;
+
+/// How much of a streamed output [`Command::stream_stdout`] and [`Command::stream_stderr`] keep
+/// for assertions and failure messages.
+pub const STREAM_TAIL: usize = 64 * 1024;
+
+/// Set to something other than `0` to give every [`Command`] the [`Command::coverage`]
+/// environment, like when collecting coverage in CI.
+pub const COVERAGE_ENV: &str = "ASSERT_CMD_COVERAGE";
+
+/// Set to mirror every [`Command`]'s output as if by [`Command::tee`], without code changes.
+pub const TEE_ENV: &str = "ASSERT_CMD_TEE";
+
+/// Set to something other than `0` to print every [`Command`] as if by [`Command::echo`],
+/// without code changes.
+pub const ECHO_ENV: &str = "ASSERT_CMD_ECHO";
+
+/// Set to a number of seconds to give every [`Command`] that long as its default
+/// [`Command::timeout`], like to bound hung tests in CI.
+///
+/// This is read once per process.
+pub const TIMEOUT_ENV: &str = "ASSERT_CMD_TIMEOUT";
+
+/// How long [`Command::assert_requires_tty`] waits, absent a [`Command::timeout`].
+const REQUIRES_TTY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// What [`Command::env_deterministic`] sets.
+const DETERMINISTIC_ENV: [(&str, &str); 4] = [
+    ("LC_ALL", "C"),
+    ("TZ", "UTC"),
+    ("NO_COLOR", "1"),
+    ("COLUMNS", "80"),
+];
+
+/// What [`Command::env_deterministic`] keeps, to find and load programs.
+#[cfg(windows)]
+const PRESERVED_ENV: &[&str] = &[
+    "PATH",
+    "PATHEXT",
+    "SYSTEMROOT",
+    "WINDIR",
+    "COMSPEC",
+    "TEMP",
+    "TMP",
+];
+#[cfg(target_os = "macos")]
+const PRESERVED_ENV: &[&str] = &["PATH", "DYLD_FALLBACK_LIBRARY_PATH", "TMPDIR"];
+#[cfg(not(any(windows, target_os = "macos")))]
+const PRESERVED_ENV: &[&str] = &["PATH", "LD_LIBRARY_PATH", "TMPDIR"];
 
 /// [`std::process::Command`][Command] customized for testing.
 ///
@@ -23,7 +78,192 @@ use crate::output::OutputResult;
 pub struct Command {
     cmd: process::Command,
     stdin: Option<Vec<u8>>,
+    stdin_pacing: StdinPacing,
+    /// The previous [`Pipeline`][crate::pipeline::Pipeline] stage's `stdout`, for the next spawn.
+    stdin_from: Option<process::Stdio>,
     timeout: Option<std::time::Duration>,
+    capture: Streams,
+    capture_to_files: bool,
+    /// The files for the next spawn, see [`Command::capture_to_files`].
+    spool: Option<std::sync::Arc<crate::spool::Spool>>,
+    env_dump: bool,
+    /// Where the next spawn's child writes its dumps, set on that child only.
+    dump_env: Vec<(&'static str, path::PathBuf)>,
+    line_ending: LineEnding,
+    sample_interval: Option<std::time::Duration>,
+    #[cfg(unix)]
+    signal_dump: bool,
+    #[cfg(unix)]
+    inherit_handles: bool,
+    #[cfg(unix)]
+    process_group: bool,
+    #[cfg(unix)]
+    kill_process_group: bool,
+    /// The signal to send, and after how long, see [`Command::terminate_after`].
+    terminate: Option<(std::time::Duration, i32)>,
+    /// Whether the `pre_exec` hook starting a new process group is installed.
+    #[cfg(unix)]
+    process_group_hook: bool,
+    /// Shared with the `pre_exec` hook, once installed: where `fds::close_on_exec` stops, or `0`
+    /// to inherit everything.
+    #[cfg(unix)]
+    handle_bound: Option<std::sync::Arc<std::sync::atomic::AtomicI32>>,
+    #[cfg(all(unix, feature = "pty"))]
+    pty: bool,
+    isolated_temp_dir: bool,
+    temp_cwd: Option<std::sync::Arc<crate::temp::TempDir>>,
+    keep_temp_cwd: bool,
+    home: Option<Home>,
+    cached: bool,
+    context: Vec<(&'static str, String)>,
+    guard_worktree: bool,
+    strip_ansi: bool,
+    redactions: crate::settings::AssertSettings,
+    sinks: Sinks,
+    retries: usize,
+    retry_if: Option<RetryIf>,
+    coverage: bool,
+    echo: bool,
+    cassette: Option<path::PathBuf>,
+    hooks: Hooks,
+    /// Where [`Command::cargo_bin_as`] staged the program, kept for as long as the `Command`.
+    staged: Option<crate::temp::TempDir>,
+    /// Whether the environment was cleared, which `std` doesn't let us inspect.
+    env_cleared: bool,
+}
+
+/// Which of the child's output streams to capture.
+///
+/// Uncaptured streams are inherited from the test process, so the child writes straight to the
+/// console, bypassing the test harness's output capturing.
+///
+/// See [`Command::capture`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Streams {
+    /// Capture both `stdout` and `stderr` (the default).
+    #[default]
+    Both,
+    /// Capture `stdout`, letting `stderr` inherit the console.
+    StdoutOnly,
+    /// Capture `stderr`, letting `stdout` inherit the console.
+    StderrOnly,
+    /// Capture `stdout` and `stderr` through one pipe, interleaved in the order the child wrote
+    /// them, for [`Assert::combined_output`].
+    ///
+    /// [`Assert::combined_output`]: crate::assert::Assert::combined_output()
+    Combined,
+}
+
+impl Streams {
+    fn stdout(self) -> bool {
+        matches!(self, Streams::Both | Streams::StdoutOnly)
+    }
+
+    fn stderr(self) -> bool {
+        matches!(self, Streams::Both | Streams::StderrOnly)
+    }
+}
+
+impl fmt::Display for Streams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Streams::Both => "stdout, stderr".fmt(f),
+            Streams::StdoutOnly => "stdout".fmt(f),
+            Streams::StderrOnly => "stderr".fmt(f),
+            Streams::Combined => "stdout and stderr, combined".fmt(f),
+        }
+    }
+}
+
+/// Line terminator used by [`Command::write_stdin_line`] and [`Command::write_stdin_lines`].
+///
+/// See [`Command::stdin_line_ending`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `\n` (the default).
+    #[default]
+    Lf,
+    /// `\r\n`.
+    CrLf,
+    /// `\r\n` on Windows, `\n` elsewhere.
+    Native,
+}
+
+impl LineEnding {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+            LineEnding::Native if cfg!(windows) => "\r\n",
+            LineEnding::Native => "\n",
+        }
+    }
+}
+
+/// The child's home directory, for testing how a CLI copes without its config.
+///
+/// See [`Command::home`] and [`Command::assert_each_home`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Home {
+    /// `HOME` (and `USERPROFILE` on Windows) is removed from the environment.
+    Unset,
+    /// `HOME` is a fresh, empty directory.
+    Empty,
+    /// `HOME` is a fresh, empty directory without write permission.
+    ///
+    /// Permissions aren't enforced for privileged users, like root.
+    ReadOnly,
+}
+
+impl Home {
+    /// Every preset, in the order [`Command::assert_each_home`] runs them.
+    pub const ALL: [Home; 3] = [Home::Unset, Home::Empty, Home::ReadOnly];
+
+    fn env_vars() -> &'static [&'static str] {
+        if cfg!(windows) {
+            &["HOME", "USERPROFILE"]
+        } else {
+            &["HOME"]
+        }
+    }
+}
+
+impl fmt::Display for Home {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Home::Unset => "unset".fmt(f),
+            Home::Empty => "empty dir".fmt(f),
+            Home::ReadOnly => "read-only dir".fmt(f),
+        }
+    }
+}
+
+/// How concurrent instances of a command are expected to behave, see
+/// [`Command::assert_concurrent`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Contention {
+    /// Every instance succeeds, like when access to shared state is serialized.
+    AllSucceed,
+    /// Exactly one instance succeeds, like when the others fail to take a lock.
+    OneSucceeds,
+}
+
+impl Contention {
+    fn allows(self, succeeded: usize, instances: usize) -> bool {
+        match self {
+            Contention::AllSucceed => succeeded == instances,
+            Contention::OneSucceeds => succeeded == 1,
+        }
+    }
+}
+
+impl fmt::Display for Contention {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Contention::AllSucceed => "all succeed".fmt(f),
+            Contention::OneSucceeds => "exactly one succeeds".fmt(f),
+        }
+    }
 }
 
 impl Command {
@@ -32,7 +272,52 @@ impl Command {
         Self {
             cmd,
             stdin: None,
-            timeout: None,
+            stdin_pacing: StdinPacing::default(),
+            stdin_from: None,
+            timeout: default_timeout(),
+            capture: Streams::default(),
+            capture_to_files: false,
+            spool: None,
+            env_dump: false,
+            dump_env: Vec::new(),
+            line_ending: LineEnding::default(),
+            sample_interval: None,
+            #[cfg(unix)]
+            signal_dump: false,
+            #[cfg(unix)]
+            inherit_handles: true,
+            #[cfg(unix)]
+            process_group: false,
+            #[cfg(unix)]
+            kill_process_group: false,
+            terminate: None,
+            #[cfg(unix)]
+            process_group_hook: false,
+            #[cfg(unix)]
+            handle_bound: None,
+            #[cfg(all(unix, feature = "pty"))]
+            pty: false,
+            isolated_temp_dir: false,
+            temp_cwd: None,
+            keep_temp_cwd: false,
+            home: None,
+            cached: false,
+            context: Vec::new(),
+            guard_worktree: false,
+            strip_ansi: false,
+            redactions: crate::settings::AssertSettings::new(),
+            sinks: Sinks {
+                tee: matches!(std::env::var_os(TEE_ENV), Some(v) if !v.is_empty() && v != "0"),
+                ..Default::default()
+            },
+            retries: 0,
+            retry_if: None,
+            coverage: matches!(std::env::var_os(COVERAGE_ENV), Some(v) if !v.is_empty() && v != "0"),
+            echo: matches!(std::env::var_os(ECHO_ENV), Some(v) if !v.is_empty() && v != "0"),
+            cassette: None,
+            hooks: Hooks::default(),
+            staged: None,
+            env_cleared: false,
         }
     }
 
@@ -65,234 +350,1885 @@ impl Command {
         Ok(Self::from_std(cmd))
     }
 
-    /// Write `buffer` to `stdin` when the `Command` is run.
+    /// Create a `Command` to run a binary of the current crate under the name `alias`, like
+    /// through a symlink.
+    ///
+    /// This tests multi-call binaries, which branch on the name they were invoked as.  The
+    /// binary is staged as `alias` in a fresh directory, removed along with the `Command`, so
+    /// both `argv[0]` and the executable's path use the name.  See [`Command::invoked_as`] to
+    /// change only `argv[0]`, on Unix.
     ///
     /// # Examples
     ///
-    /// ```rust
+    /// ```rust,no_run
+    /// use assert_cmd::cargo::Stage;
     /// use assert_cmd::Command;
     ///
-    /// let mut cmd = Command::new("cat")
-    ///     .arg("-et")
-    ///     .write_stdin("42")
+    /// Command::cargo_bin_as("bin_fixture", "fixture-lite", Stage::Link)
+    ///     .unwrap()
     ///     .assert()
-    ///     .stdout("42");
+    ///     .success();
     /// ```
-    pub fn write_stdin<S>(&mut self, buffer: S) -> &mut Self
-    where
-        S: Into<Vec<u8>>,
-    {
-        self.stdin = Some(buffer.into());
-        self
+    pub fn cargo_bin_as<S: AsRef<str>, A: AsRef<str>>(
+        name: S,
+        alias: A,
+        stage: crate::cargo::Stage,
+    ) -> Result<Self, crate::cargo::CargoError> {
+        let (cmd, dir) = crate::cargo::cargo_bin_as_cmd(name.as_ref(), alias.as_ref(), stage)?;
+        let mut cmd = Self::from_std(cmd);
+        cmd.staged = Some(dir);
+        Ok(cmd)
     }
 
-    /// Error out if a timeout is reached
+    /// Create a `Command` to run `script` with the platform's shell: `sh -c` on Unix and
+    /// `cmd /S /C` on Windows.
+    ///
+    /// This is for tests that need the shell itself, like for globs, pipes, or redirection.
+    /// Quote words embedded in `script` with [`shell_quote`].
+    ///
+    /// # Examples
     ///
     /// ```rust,no_run
     /// use assert_cmd::Command;
     ///
-    /// let assert = Command::cargo_bin("bin_fixture")
-    ///     .unwrap()
-    ///     .timeout(std::time::Duration::from_secs(1))
-    ///     .env("sleep", "100")
-    ///     .assert();
-    /// assert.failure();
+    /// Command::shell("echo hello| sort")
+    ///     .assert()
+    ///     .success()
+    ///     .stdout(predicates::str::starts_with("hello"));
     /// ```
-    pub fn timeout(&mut self, timeout: std::time::Duration) -> &mut Self {
-        self.timeout = Some(timeout);
-        self
-    }
-
-    /// Write `path`s content to `stdin` when the `Command` is run.
-    ///
-    /// Paths are relative to the [`env::current_dir`][env_current_dir] and not
-    /// [`Command::current_dir`][Command_current_dir].
     ///
-    /// [env_current_dir]: std::env::current_dir()
-    /// [Command_current_dir]: std::process::Command::current_dir()
-    pub fn pipe_stdin<P>(&mut self, file: P) -> io::Result<&mut Self>
-    where
-        P: AsRef<path::Path>,
-    {
-        let buffer = std::fs::read(file)?;
-        Ok(self.write_stdin(buffer))
+    /// [`shell_quote`]: crate::platform::shell_quote()
+    pub fn shell<S: AsRef<str>>(script: S) -> Self {
+        #[cfg(windows)]
+        let cmd = {
+            use std::os::windows::process::CommandExt;
+
+            // Passed as-is, since `cmd` doesn't parse its command line like other programs.
+            let mut cmd = process::Command::new("cmd");
+            cmd.args(["/S", "/C"])
+                .raw_arg(format!("\"{}\"", script.as_ref()));
+            cmd
+        };
+        #[cfg(not(windows))]
+        let cmd = {
+            let mut cmd = process::Command::new("sh");
+            cmd.arg("-c").arg(script.as_ref());
+            cmd
+        };
+        Self::from_std(cmd)
     }
 
-    /// Run a `Command`, returning an [`OutputResult`][OutputResult].
+    /// Create a `Command` to run a binary of the current crate built with a cargo `profile`,
+    /// like `release`.
+    ///
+    /// The binary must already be built, see [`cargo_bin_with_profile`].
     ///
     /// # Examples
     ///
-    /// ```rust
+    /// ```rust,no_run
     /// use assert_cmd::Command;
     ///
-    /// let result = Command::new("echo")
-    ///     .args(&["42"])
-    ///     .ok();
-    /// assert!(result.is_ok());
+    /// let mut cmd = Command::cargo_bin_with_profile("bin_fixture", "release")
+    ///     .unwrap();
+    /// let output = cmd.unwrap();
+    /// println!("{:?}", output);
     /// ```
     ///
-    pub fn ok(&mut self) -> OutputResult {
-        OutputOkExt::ok(self)
+    /// [`cargo_bin_with_profile`]: crate::cargo::cargo_bin_with_profile()
+    pub fn cargo_bin_with_profile<S: AsRef<str>, P: AsRef<str>>(
+        name: S,
+        profile: P,
+    ) -> Result<Self, crate::cargo::CargoError> {
+        let cmd = crate::cargo::cargo_bin_with_profile_cmd(name.as_ref(), profile.as_ref())?;
+        Ok(Self::from_std(cmd))
     }
 
-    /// Run a `Command`, unwrapping the [`OutputResult`][OutputResult].
+    /// Create a `Command` to run a binary of any member of the current workspace, building it
+    /// if needed.
+    ///
+    /// See [`workspace_bin`].
     ///
     /// # Examples
     ///
-    /// ```rust
+    /// ```rust,no_run
     /// use assert_cmd::Command;
     ///
-    /// let output = Command::new("echo")
-    ///     .args(&["42"])
+    /// let mut cmd = Command::workspace_bin("other-member-bin")
     ///     .unwrap();
+    /// let output = cmd.unwrap();
+    /// println!("{:?}", output);
     /// ```
     ///
-    pub fn unwrap(&mut self) -> process::Output {
-        OutputOkExt::unwrap(self)
+    /// [`workspace_bin`]: crate::cargo::workspace_bin()
+    pub fn workspace_bin<S: AsRef<str>>(name: S) -> Result<Self, crate::cargo::CargoError> {
+        let cmd = crate::cargo::workspace_bin_cmd(name.as_ref())?;
+        Ok(Self::from_std(cmd))
     }
 
-    /// Run a `Command`, unwrapping the error in the [`OutputResult`][OutputResult].
+    /// Create a `Command` to run a specific example of the current crate.
+    ///
+    /// See the [`cargo` module documentation][crate::cargo] for caveats and workarounds.
     ///
     /// # Examples
     ///
     /// ```rust,no_run
     /// use assert_cmd::Command;
     ///
-    /// let err = Command::new("a-command")
-    ///     .args(&["--will-fail"])
-    ///     .unwrap_err();
+    /// let mut cmd = Command::cargo_example("example_fixture")
+    ///     .unwrap();
+    /// let output = cmd.unwrap();
+    /// println!("{:?}", output);
     /// ```
-    ///
-    /// [Output]: std::process::Output
-    pub fn unwrap_err(&mut self) -> OutputError {
-        OutputOkExt::unwrap_err(self)
+    pub fn cargo_example<S: AsRef<str>>(name: S) -> Result<Self, crate::cargo::CargoError> {
+        let cmd = crate::cargo::cargo_example_cmd(name)?;
+        Ok(Self::from_std(cmd))
     }
 
-    /// Run a `Command` and make assertions on the [`Output`].
+    /// Write `buffer` to `stdin` when the `Command` is run.
+    ///
+    /// `stdin` is closed once `buffer` is written, unless [`Command::hold_stdin_open`].
     ///
     /// # Examples
     ///
-    /// ```rust,no_run
+    /// ```rust
     /// use assert_cmd::Command;
     ///
-    /// let mut cmd = Command::cargo_bin("bin_fixture")
-    ///     .unwrap()
+    /// let mut cmd = Command::new("cat")
+    ///     .arg("-et")
+    ///     .write_stdin("42")
     ///     .assert()
-    ///     .success();
+    ///     .stdout("42");
     /// ```
-    ///
-    /// [`Output`]: std::process::Output
-    pub fn assert(&mut self) -> Assert {
-        OutputAssertExt::assert(self)
+    pub fn write_stdin<S>(&mut self, buffer: S) -> &mut Self
+    where
+        S: Into<Vec<u8>>,
+    {
+        self.stdin = Some(buffer.into());
+        self.stdin_pacing.breaks.clear();
+        self
     }
-}
 
-/// Mirror [`std::process::Command`][Command]'s API
-///
-/// [Command]: std::process::Command
-impl Command {
-    /// Constructs a new `Command` for launching the program at
-    /// path `program`, with the following default configuration:
-    ///
-    /// * No arguments to the program
-    /// * Inherit the current process's environment
-    /// * Inherit the current process's working directory
-    /// * Inherit stdin/stdout/stderr for `spawn` or `status`, but create pipes for `output`
-    ///
-    /// Builder methods are provided to change these defaults and
-    /// otherwise configure the process.
+    /// Write each of `chunks` to `stdin` when the `Command` is run, waiting `delay` before
+    /// each after the first, like to exercise incremental reads.
     ///
-    /// If `program` is not an absolute path, the `PATH` will be searched in
-    /// an OS-defined way.
-    ///
-    /// The search path to be used may be controlled by setting the
-    /// `PATH` environment variable on the Command,
-    /// but this has some implementation limitations on Windows
-    /// (see issue #37519).
+    /// The delays count toward any [`Command::timeout`], and the remaining chunks are dropped
+    /// once the child exits.  This replaces any earlier [`Command::write_stdin`] input.  Paced
+    /// runs aren't [`Command::cached`].
     ///
     /// # Examples
     ///
-    /// Basic usage:
-    ///
-    /// ```no_run
+    /// ```rust
     /// use assert_cmd::Command;
     ///
-    /// Command::new("sh").unwrap();
+    /// use std::time::Duration;
+    ///
+    /// Command::new("cat")
+    ///     .write_stdin_chunks(["partial ", "line\n"], Duration::from_millis(100))
+    ///     .assert()
+    ///     .stdout("partial line\n");
     /// ```
-    pub fn new<S: AsRef<ffi::OsStr>>(program: S) -> Self {
-        let cmd = process::Command::new(program);
-        Self::from_std(cmd)
+    pub fn write_stdin_chunks<I, S>(&mut self, chunks: I, delay: std::time::Duration) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<Vec<u8>>,
+    {
+        let mut stdin = Vec::new();
+        let mut breaks = Vec::new();
+        for chunk in chunks {
+            if !stdin.is_empty() {
+                breaks.push(stdin.len());
+            }
+            stdin.extend(chunk.into());
+        }
+        self.stdin = Some(stdin);
+        self.stdin_pacing.breaks = breaks;
+        self.stdin_pacing.delay = delay;
+        self
     }
 
-    /// Adds an argument to pass to the program.
+    /// Whether to keep `stdin` open until the child exits, rather than closing it once any
+    /// input is written.
     ///
-    /// Only one argument can be passed per use. So instead of:
+    /// A child reading to the end of its input then waits until it's killed for exceeding the
+    /// [`Command::timeout`], like a program left attached to a terminal.  Defaults to `false`.
     ///
-    /// ```no_run
-    /// # assert_cmd::Command::new("sh")
-    /// .arg("-C /path/to/repo")
-    /// # ;
-    /// ```
+    /// # Examples
     ///
-    /// usage would be:
+    /// ```rust
+    /// use assert_cmd::Command;
     ///
-    /// ```no_run
-    /// # assert_cmd::Command::new("sh")
-    /// .arg("-C")
-    /// .arg("/path/to/repo")
-    /// # ;
-    /// ```
+    /// use std::time::Duration;
     ///
-    /// To pass multiple arguments see [`args`].
+    /// Command::new("cat")
+    ///     .write_stdin("waiting")
+    ///     .hold_stdin_open(true)
+    ///     .timeout(Duration::from_millis(500))
+    ///     .assert()
+    ///     .interrupted()
+    ///     .stdout("waiting");
+    /// ```
+    pub fn hold_stdin_open(&mut self, yes: bool) -> &mut Self {
+        self.stdin_pacing.hold_open = yes;
+        self
+    }
+
+    /// Append `line` to `stdin`, terminated by exactly one line ending.
     ///
-    /// [`args`]: Command::args()
+    /// A trailing `\n` or `\r\n` on `line` is replaced by the [`Command::stdin_line_ending`],
+    /// so interactive programs reading a line at a time don't hang waiting for the end of it.
     ///
     /// # Examples
     ///
-    /// Basic usage:
-    ///
-    /// ```no_run
+    /// ```rust
     /// use assert_cmd::Command;
     ///
-    /// Command::new("ls")
-    ///         .arg("-l")
-    ///         .arg("-a")
-    ///         .unwrap();
+    /// Command::new("cat")
+    ///     .write_stdin_line("yes")
+    ///     .write_stdin_line("no\n")
+    ///     .assert()
+    ///     .stdout("yes\nno\n");
     /// ```
-    pub fn arg<S: AsRef<ffi::OsStr>>(&mut self, arg: S) -> &mut Self {
-        self.cmd.arg(arg);
+    pub fn write_stdin_line<S>(&mut self, line: S) -> &mut Self
+    where
+        S: AsRef<str>,
+    {
+        let line = line.as_ref();
+        let line = line
+            .strip_suffix('\n')
+            .map(|l| l.strip_suffix('\r').unwrap_or(l))
+            .unwrap_or(line);
+        let stdin = self.stdin.get_or_insert_with(Vec::new);
+        stdin.extend_from_slice(line.as_bytes());
+        stdin.extend_from_slice(self.line_ending.as_str().as_bytes());
         self
     }
 
-    /// Adds multiple arguments to pass to the program.
-    ///
-    /// To pass a single argument see [`arg`].
-    ///
-    /// [`arg`]: Command::arg()
+    /// Append each of `lines` to `stdin`, see [`Command::write_stdin_line`].
     ///
     /// # Examples
     ///
-    /// Basic usage:
-    ///
-    /// ```no_run
+    /// ```rust
     /// use assert_cmd::Command;
     ///
-    /// Command::new("ls")
-    ///         .args(&["-l", "-a"])
-    ///         .unwrap();
+    /// Command::new("cat")
+    ///     .write_stdin_lines(["name", "42"])
+    ///     .assert()
+    ///     .stdout("name\n42\n");
     /// ```
-    pub fn args<I, S>(&mut self, args: I) -> &mut Self
+    pub fn write_stdin_lines<I, S>(&mut self, lines: I) -> &mut Self
     where
         I: IntoIterator<Item = S>,
-        S: AsRef<ffi::OsStr>,
+        S: AsRef<str>,
     {
-        self.cmd.args(args);
+        for line in lines {
+            self.write_stdin_line(line);
+        }
         self
     }
 
-    /// Inserts or updates an environment variable mapping.
+    /// Select the line ending for later calls to [`Command::write_stdin_line`].
     ///
-    /// Note that environment variable names are case-insensitive (but case-preserving) on Windows,
+    /// # Examples
+    ///
+    /// ```rust
+    /// use assert_cmd::cmd::LineEnding;
+    /// use assert_cmd::Command;
+    ///
+    /// Command::new("cat")
+    ///     .stdin_line_ending(LineEnding::CrLf)
+    ///     .write_stdin_line("yes")
+    ///     .assert()
+    ///     .stdout("yes\r\n");
+    /// ```
+    pub fn stdin_line_ending(&mut self, line_ending: LineEnding) -> &mut Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Error out if a timeout is reached
+    ///
+    /// The child is killed once `timeout` elapses from its spawn, even while `stdin` is still
+    /// being written.  The output collected until then is still
+    /// available, see [`Assert::on_timeout`], and [`Assert::success`] fails with
+    /// "Command timed out" along with that partial output.
+    ///
+    /// This overrides any default from [`TIMEOUT_ENV`].
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// let assert = Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .timeout(std::time::Duration::from_secs(1))
+    ///     .env("sleep", "100")
+    ///     .assert();
+    /// assert.failure();
+    /// ```
+    pub fn timeout(&mut self, timeout: std::time::Duration) -> &mut Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Select which output streams are captured for assertions.
+    ///
+    /// Uncaptured streams inherit the test's console, which is useful for interactive debugging
+    /// or for enormous logs, and are empty in the resulting [`Output`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    /// use assert_cmd::cmd::Streams;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello")
+    ///     .env("stderr", "world")
+    ///     .capture(Streams::StderrOnly)
+    ///     .assert()
+    ///     .stderr("world\n");
+    /// ```
+    ///
+    /// [`Output`]: std::process::Output
+    pub fn capture(&mut self, streams: Streams) -> &mut Self {
+        self.capture = streams;
+        self
+    }
+
+    /// Write the captured output to temporary files rather than memory, for output too large to
+    /// hold.
+    ///
+    /// Assert on the files with [`Assert::stdout_file_size`], [`Assert::stdout_file_sha256`], and
+    /// [`Assert::stdout_file_check`], and their `stderr` equivalents.  `stdout` and `stderr` themselves
+    /// are empty, and failure messages show only each end of the files.  With
+    /// [`Streams::Combined`], both streams are written to the `stdout` file.  The files are
+    /// removed when the [`Assert`] is dropped, and runs captured to files aren't
+    /// [`Command::cached`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    /// use predicates::prelude::*;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .arg("--generate")
+    ///     .capture_to_files(true)
+    ///     .assert()
+    ///     .success()
+    ///     .stdout_file_size(predicate::gt(4_000_000_000));
+    /// ```
+    ///
+    /// [`Assert`]: crate::assert::Assert
+    /// [`Assert::stdout_file_size`]: crate::assert::Assert::stdout_file_size()
+    /// [`Assert::stdout_file_sha256`]: crate::assert::Assert::stdout_file_sha256()
+    /// [`Assert::stdout_file_check`]: crate::assert::Assert::stdout_file_check()
+    pub fn capture_to_files(&mut self, yes: bool) -> &mut Self {
+        self.capture_to_files = yes;
+        self
+    }
+
+    /// Feed `stdout` to `sink` as it is read, keeping only the last [`STREAM_TAIL`] bytes.
+    ///
+    /// For output too large to buffer, check it incrementally in `sink` instead of with
+    /// [`Assert::stdout`], which only sees the tail.  Failure messages note how much was
+    /// dropped.  Streamed commands aren't [`Command::cached`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::sync::Arc;
+    ///
+    /// let lines = Arc::new(AtomicUsize::new(0));
+    /// let counted = lines.clone();
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "one\ntwo")
+    ///     .stream_stdout(move |chunk| {
+    ///         let newlines = chunk.iter().filter(|b| **b == b'\n').count();
+    ///         counted.fetch_add(newlines, Ordering::SeqCst);
+    ///     })
+    ///     .assert()
+    ///     .success();
+    /// assert_eq!(lines.load(Ordering::SeqCst), 2);
+    /// ```
+    pub fn stream_stdout<F>(&mut self, sink: F) -> &mut Self
+    where
+        F: FnMut(&[u8]) + Send + 'static,
+    {
+        self.sinks.stdout = Some(std::sync::Arc::new(std::sync::Mutex::new(sink)));
+        self
+    }
+
+    /// Feed `stderr` to `sink` as it is read, keeping only the last [`STREAM_TAIL`] bytes.
+    ///
+    /// See [`Command::stream_stdout`].
+    pub fn stream_stderr<F>(&mut self, sink: F) -> &mut Self
+    where
+        F: FnMut(&[u8]) + Send + 'static,
+    {
+        self.sinks.stderr = Some(std::sync::Arc::new(std::sync::Mutex::new(sink)));
+        self
+    }
+
+    /// Mirror the captured output to the test's own `stdout` and `stderr` as it is read.
+    ///
+    /// This is for watching a failing test's child live, like with `cargo test -- --nocapture`;
+    /// otherwise the test harness shows the mirrored output along with the failure.  Assertions
+    /// still see the captured copy.  Output is mirrored a line at a time.
+    ///
+    /// Defaults to whether [`TEE_ENV`] is set to something other than `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "progress")
+    ///     .tee(true)
+    ///     .assert()
+    ///     .stdout("progress\n");
+    /// ```
+    pub fn tee(&mut self, yes: bool) -> &mut Self {
+        self.sinks.tee = yes;
+        self
+    }
+
+    /// Print the fully resolved command to the test's output before each run.
+    ///
+    /// This shows the program, arguments, working directory, environment changes, and `stdin`,
+    /// after any [`Command::before_spawn`] hooks, for debugging what a fixture-driven test
+    /// actually runs.  Like other test output, it is only shown for failing tests or with
+    /// `cargo test -- --nocapture`.
+    ///
+    /// Defaults to whether [`ECHO_ENV`] is set to something other than `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .arg("--verbose")
+    ///     .env("stdout", "hello")
+    ///     .write_stdin("input")
+    ///     .echo(true)
+    ///     .assert()
+    ///     .success();
+    /// ```
+    ///
+    /// Prints:
+    ///
+    /// ```text
+    /// assert_cmd: running "/path/to/bin_fixture" "--verbose"
+    ///   cwd: /path/to/crate
+    ///   env: stdout=hello
+    ///   stdin: "input"
+    /// ```
+    pub fn echo(&mut self, yes: bool) -> &mut Self {
+        self.echo = yes;
+        self
+    }
+
+    /// What [`Command::echo`] prints.
+    fn echo_text(&self) -> String {
+        let invocation = crate::transcript::Invocation::new(&self.cmd, self.env_cleared);
+        let mut text = format!("assert_cmd: running {:?}", invocation.program());
+        for arg in invocation.args() {
+            text.push_str(&format!(" {:?}", arg));
+        }
+        text.push_str(&format!("\n  cwd: {}", invocation.cwd().display()));
+        if let Some(env) = invocation.env_summary() {
+            text.push_str(&format!("\n  env: {}", env));
+        }
+        if let Some(stdin) = &self.stdin {
+            text.push_str(&format!("\n  stdin: {}", DebugBytes::new(stdin)));
+        }
+        text
+    }
+
+    /// Ask the child to dump its environment for [`Assert::child_env`].
+    ///
+    /// See the [`env_dump` module][crate::env_dump] for what the child needs to do.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("LEVEL", "debug")
+    ///     .env_dump(true)
+    ///     .assert()
+    ///     .child_env("LEVEL", "debug");
+    /// ```
+    pub fn env_dump(&mut self, yes: bool) -> &mut Self {
+        self.env_dump = yes;
+        self
+    }
+
+    /// Give the child a fresh, empty temp dir, for [`Assert::leaves_no_temp_files`].
+    ///
+    /// `TMPDIR`, `TEMP`, and `TMP` point at the directory, which is removed after the child
+    /// exits.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .isolated_temp_dir(true)
+    ///     .assert()
+    ///     .leaves_no_temp_files();
+    /// ```
+    pub fn isolated_temp_dir(&mut self, yes: bool) -> &mut Self {
+        self.isolated_temp_dir = yes;
+        self
+    }
+
+    /// Run the child in a fresh, empty directory, as its [`Command::current_dir`].
+    ///
+    /// Set up fixtures in [`Command::temp_cwd`].  The directory is removed once the `Command`
+    /// and every [`Assert`] of it are dropped, unless kept by
+    /// [`Command::keep_temp_cwd_on_failure`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// let mut cmd = Command::cargo_bin("bin_fixture").unwrap();
+    /// cmd.in_temp_dir();
+    /// std::fs::write(cmd.temp_cwd().unwrap().join("input.txt"), "hello").unwrap();
+    /// cmd.assert().success();
+    /// ```
+    pub fn in_temp_dir(&mut self) -> &mut Self {
+        let dir = crate::temp::TempDir::new("assert_cmd-cwd");
+        self.cmd.current_dir(dir.path());
+        self.temp_cwd = Some(std::sync::Arc::new(dir));
+        self
+    }
+
+    /// The directory created by [`Command::in_temp_dir`].
+    pub fn temp_cwd(&self) -> Option<&path::Path> {
+        self.temp_cwd.as_deref().map(crate::temp::TempDir::path)
+    }
+
+    /// Leave the [`Command::in_temp_dir`] directory behind when an assertion fails, to debug
+    /// what the child did to it.
+    ///
+    /// The failure message names the directory.
+    pub fn keep_temp_cwd_on_failure(&mut self, yes: bool) -> &mut Self {
+        self.keep_temp_cwd = yes;
+        self
+    }
+
+    /// Fail if the child modifies tracked files of the git working tree it runs in.
+    ///
+    /// This protects a contributor's checkout from tests that run the CLI in it rather than in
+    /// a temp dir.  Files that were already modified are compared by contents.  Untracked files
+    /// are ignored, but changes made by anything else running at the same time are blamed on the
+    /// child.
+    ///
+    /// Nothing is checked outside of a working tree, or without `git`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .guard_worktree(true)
+    ///     .assert()
+    ///     .success();
+    /// ```
+    pub fn guard_worktree(&mut self, yes: bool) -> &mut Self {
+        self.guard_worktree = yes;
+        self
+    }
+
+    /// Run the child with its home directory replaced by `home`.
+    ///
+    /// Any directory created for `home` is removed after the child exits.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::cmd::Home;
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .home(Home::ReadOnly)
+    ///     .assert()
+    ///     .success();
+    /// ```
+    pub fn home(&mut self, home: Home) -> &mut Self {
+        self.home = Some(home);
+        self
+    }
+
+    /// Point the child's home, config, cache, and data directories into `sandbox`, away from
+    /// the developer's own.
+    ///
+    /// This sets `HOME`, and the XDG base directories on Unix or `USERPROFILE`, `APPDATA`, and
+    /// `LOCALAPPDATA` on Windows.  [`Command::home`] takes precedence for the home directory.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::sandbox::Sandbox;
+    /// use assert_cmd::Command;
+    ///
+    /// let sandbox = Sandbox::new();
+    /// std::fs::write(sandbox.home().join(".mytoolrc"), "verbose = true\n").unwrap();
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .sandbox_home(&sandbox)
+    ///     .assert()
+    ///     .success();
+    /// ```
+    pub fn sandbox_home(&mut self, sandbox: &crate::sandbox::Sandbox) -> &mut Self {
+        for (name, path) in sandbox.vars() {
+            self.cmd.env(name, path);
+        }
+        self
+    }
+
+    /// Reuse the result of an identical earlier run in this process, rather than running again.
+    ///
+    /// This is for expensive, read-only invocations repeated across many tests.  Runs are
+    /// identical when they have the same program, binary contents, arguments, environment
+    /// changes, `stdin`, and working directory listing, and are collected the same way.  Runs
+    /// with per-run state, like [`Command::isolated_temp_dir`], are never reused.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .arg("--dump-schema")
+    ///     .cached(true)
+    ///     .assert()
+    ///     .success();
+    /// ```
+    pub fn cached(&mut self, yes: bool) -> &mut Self {
+        self.cached = yes;
+        self
+    }
+
+    /// Replay this run's status and output from the cassette file at `path`, recording it there
+    /// first if needed, rather than running the program every time.
+    ///
+    /// This is for programs too slow or too dependent on external services to run in every test
+    /// run.  Relative paths are within the crate, and runs are keyed by the program's name, the
+    /// arguments, and `stdin`.  Set [`MODE_ENV`][crate::cassette::MODE_ENV] to re-record or to
+    /// only replay; see the [`cassette` module][crate::cassette].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .args(["fetch", "https://example.com/index.json"])
+    ///     .cassette("tests/cassettes/fetch.cassette")
+    ///     .assert()
+    ///     .success();
+    /// ```
+    pub fn cassette<P: AsRef<path::Path>>(&mut self, path: P) -> &mut Self {
+        self.cassette = Some(crate::cassette::resolve(path.as_ref()));
+        self
+    }
+
+    /// Clarify failures of every [`Assert`] from this `Command` with additional context.
+    ///
+    /// See [`Assert::append_context`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// for fixture in &["cases/empty.toml", "cases/full.toml"] {
+    ///     Command::cargo_bin("bin_fixture")
+    ///         .unwrap()
+    ///         .arg(fixture)
+    ///         .append_context("fixture", fixture)
+    ///         .assert()
+    ///         .success();
+    /// }
+    /// ```
+    pub fn append_context<D>(&mut self, name: &'static str, context: D) -> &mut Self
+    where
+        D: fmt::Display,
+    {
+        self.context.push((name, context.to_string()));
+        self
+    }
+
+    /// Run `hook` just before the program is spawned, after any [`hooks::before_spawn`] hooks.
+    ///
+    /// See the [`hooks` module documentation][crate::hooks] for when hooks run.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .before_spawn(|cmd| eprintln!("running {:?}", cmd))
+    ///     .assert()
+    ///     .success();
+    /// ```
+    ///
+    /// [`hooks::before_spawn`]: crate::hooks::before_spawn()
+    pub fn before_spawn<F>(&mut self, hook: F) -> &mut Self
+    where
+        F: Fn(&mut Command) + Send + Sync + 'static,
+    {
+        self.hooks.before_spawn.push(std::sync::Arc::new(hook));
+        self
+    }
+
+    /// Run `hook` on each run's [`Assert`], after any [`hooks::after_output`] hooks.
+    ///
+    /// See the [`hooks` module documentation][crate::hooks] for when hooks run.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .after_output(|assert| {
+    ///         std::fs::write("target/last-stdout", &assert.get_output().stdout).unwrap();
+    ///     })
+    ///     .assert()
+    ///     .success();
+    /// ```
+    ///
+    /// [`hooks::after_output`]: crate::hooks::after_output()
+    pub fn after_output<F>(&mut self, hook: F) -> &mut Self
+    where
+        F: Fn(&Assert) + Send + Sync + 'static,
+    {
+        self.hooks.after_output.push(std::sync::Arc::new(hook));
+        self
+    }
+
+    /// Remove ANSI escape sequences, like colors, from the output of every [`Assert`] from this
+    /// `Command`, before any assertion or failure message sees it.
+    ///
+    /// This is applied before redactions.  See [`AssertSettings::strip_ansi`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "\x1b[32mok\x1b[0m")
+    ///     .strip_ansi(true)
+    ///     .assert()
+    ///     .stdout("ok\n");
+    /// ```
+    ///
+    /// [`AssertSettings::strip_ansi`]: crate::settings::AssertSettings::strip_ansi()
+    pub fn strip_ansi(&mut self, yes: bool) -> &mut Self {
+        self.strip_ansi = yes;
+        self
+    }
+
+    /// Replace every occurrence of `value` in the output of every [`Assert`] from this
+    /// `Command` with `placeholder`, before any assertion or failure message sees it.
+    ///
+    /// See [`AssertSettings::redact`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// let temp = std::env::temp_dir();
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", format!("wrote {} in 15ms", temp.display()))
+    ///     .redact("[TEMPDIR]", &temp)
+    ///     .redact_regex("[DURATION]", r"\d+ms")
+    ///     .assert()
+    ///     .stdout("wrote [TEMPDIR] in [DURATION]\n");
+    /// ```
+    ///
+    /// [`AssertSettings::redact`]: crate::settings::AssertSettings::redact()
+    pub fn redact<V: AsRef<ffi::OsStr>>(&mut self, placeholder: &str, value: V) -> &mut Self {
+        self.redactions = std::mem::take(&mut self.redactions).redact(placeholder, value);
+        self
+    }
+
+    /// Replace every match of the regular expression `pattern` in the output of every
+    /// [`Assert`] from this `Command` with `placeholder`.
+    ///
+    /// See [`Command::redact`] and [`AssertSettings::redact_regex`].
+    ///
+    /// [`AssertSettings::redact_regex`]: crate::settings::AssertSettings::redact_regex()
+    pub fn redact_regex(&mut self, placeholder: &str, pattern: &str) -> &mut Self {
+        self.redactions = std::mem::take(&mut self.redactions).redact_regex(placeholder, pattern);
+        self
+    }
+
+    /// Put the [`Mocks`][crate::mock::Mocks] fake programs first on the child's `PATH`, so they
+    /// stand in for the real ones.
+    ///
+    /// Call this after setting the child's `PATH`, like with [`Command::env_deterministic`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::mock::{Mocks, Reply};
+    /// use assert_cmd::Command;
+    ///
+    /// let mut mocks = Mocks::new();
+    /// mocks.program("docker").reply(Reply::new().code(1));
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .mocks(&mocks)
+    ///     .assert()
+    ///     .failure();
+    /// ```
+    #[cfg(unix)]
+    pub fn mocks(&mut self, mocks: &crate::mock::Mocks) -> &mut Self {
+        let current = match self.cmd.get_envs().find(|(key, _)| *key == "PATH") {
+            Some((_, value)) => value.map(ffi::OsStr::to_owned),
+            None => std::env::var_os("PATH"),
+        };
+        let mut dirs = vec![mocks.dir()];
+        if let Some(current) = current {
+            dirs.extend(std::env::split_paths(&current));
+        }
+        if let Ok(path) = std::env::join_paths(dirs) {
+            self.cmd.env("PATH", path);
+        }
+        self
+    }
+
+    /// Run the program through `runner`, like an emulator for a binary cross-compiled for a
+    /// target the host can't execute.
+    ///
+    /// The first item is the runner program, the rest are its arguments, which come before the
+    /// program being run.  An empty `runner` changes nothing.
+    ///
+    /// [`Command::cargo_bin`] already applies the runner cargo is configured with, see
+    /// [`cargo::runner`].
+    ///
+    /// Call this right after creating the `Command`: the command is rebuilt from its program,
+    /// arguments, environment, and working directory, dropping configuration `std` doesn't let
+    /// us inspect, like [`Command::env_clear`] or `pre_exec` hooks.
+    ///
+    /// [`cargo::runner`]: crate::cargo::runner()
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::new(assert_cmd::cargo::cargo_bin("bin_fixture"))
+    ///     .runner(["qemu-aarch64", "-L", "/usr/aarch64-linux-gnu"])
+    ///     .assert()
+    ///     .success();
+    /// ```
+    pub fn runner<I, S>(&mut self, runner: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<ffi::OsStr>,
+    {
+        let runner: Vec<_> = runner.into_iter().collect();
+        if !runner.is_empty() {
+            self.cmd = crate::cargo::with_runner(&self.cmd, &runner);
+            self.env_cleared = false;
+            // Re-install our own `pre_exec` hooks, lost with the old command
+            #[cfg(unix)]
+            {
+                self.handle_bound = None;
+                self.process_group_hook = false;
+                if self.process_group || self.kill_process_group {
+                    self.new_process_group();
+                }
+            }
+        }
+        self
+    }
+
+    /// Re-run the `Command` up to `retries` more times while it fails, like from a transient
+    /// network error, asserting on the last run.
+    ///
+    /// If the last run fails the assertions too, every earlier run's output is reported.
+    /// Retried `Command`s aren't [`Command::cached`].  See [`Command::retry_if`] to decide
+    /// what's worth retrying, and [`Command::allow_flaky`] to retry failed assertions instead.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .retries(3)
+    ///     .assert()
+    ///     .success();
+    /// ```
+    pub fn retries(&mut self, retries: usize) -> &mut Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Retry a run when `retry` returns `true` for it, rather than when it fails.
+    ///
+    /// This only applies with [`Command::retries`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .retries(3)
+    ///     .retry_if(|run| run.get_output().stderr.starts_with(b"timed out"))
+    ///     .assert()
+    ///     .success();
+    /// ```
+    pub fn retry_if<F>(&mut self, retry: F) -> &mut Self
+    where
+        F: Fn(&Assert) -> bool + Send + Sync + 'static,
+    {
+        self.retry_if = Some(RetryIf(std::sync::Arc::new(retry)));
+        self
+    }
+
+    /// Run the `Command` and `check` it, re-running up to `reruns` more times if it fails.
+    ///
+    /// This quarantines known-flaky behavior: if any run passes, the pass/fail distribution and
+    /// the failures are printed to stderr instead of failing the test.  If every run fails, the
+    /// test fails with the grouped failures, like [`aggregate::Failures`].
+    ///
+    /// [`aggregate::Failures`]: crate::aggregate::Failures
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// let flakiness = Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello")
+    ///     .allow_flaky(3, |assert| assert.success().stdout("hello\n"));
+    /// assert!(flakiness.is_stable());
+    /// ```
+    pub fn allow_flaky<F>(&mut self, reruns: usize, check: F) -> crate::aggregate::Flakiness
+    where
+        F: Fn(Assert) -> Assert,
+    {
+        let mut failures = crate::aggregate::Failures::new();
+        let mut flakiness = crate::aggregate::Flakiness { runs: 0, passed: 0 };
+        for run in 0..=reruns {
+            flakiness.runs += 1;
+            if failures.check(|| check(self.assert())).is_some() {
+                flakiness.passed += 1;
+                if run == 0 {
+                    return flakiness;
+                }
+            }
+        }
+        if flakiness.passed == 0 {
+            failures.finish();
+        } else {
+            let _ = writeln!(
+                io::stderr(),
+                "Flaky: {}\n{}",
+                flakiness,
+                failures.into_summary()
+            );
+        }
+        flakiness
+    }
+
+    /// Run the `Command` once for each [`Home`] preset, checking each run with `check`.
+    ///
+    /// Failures report which preset was in effect.  The `Command` is left configured with the
+    /// last preset.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "no config found")
+    ///     .assert_each_home(|assert| assert.success().stdout("no config found\n"));
+    /// ```
+    pub fn assert_each_home<F>(&mut self, mut check: F)
+    where
+        F: FnMut(Assert) -> Assert,
+    {
+        for home in Home::ALL.iter() {
+            check(self.home(*home).assert());
+        }
+    }
+
+    /// Run the `Command` once for every combination of the environment variables' values.
+    ///
+    /// Each run is returned with a label, like `RUST_LOG=debug COLOR=never`, which its failures
+    /// report as context.  The `Command` is left configured with the last combination.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// let runs = Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .assert_env_matrix(&[
+    ///         ("RUST_LOG", &["warn", "debug"]),
+    ///         ("stdout", &["plain", "fancy"]),
+    ///     ]);
+    /// assert_eq!(runs.len(), 4);
+    /// for (label, assert) in runs {
+    ///     println!("{}", label);
+    ///     assert.success();
+    /// }
+    /// ```
+    pub fn assert_env_matrix(&mut self, vars: &[(&str, &[&str])]) -> Vec<(String, Assert)> {
+        let mut combinations: Vec<Vec<(&str, &str)>> = vec![Vec::new()];
+        for (key, values) in vars {
+            combinations = combinations
+                .into_iter()
+                .flat_map(|combination| {
+                    values.iter().map(move |value| {
+                        let mut combination = combination.clone();
+                        combination.push((*key, *value));
+                        combination
+                    })
+                })
+                .collect();
+        }
+        combinations
+            .into_iter()
+            .map(|combination| {
+                let label = combination
+                    .iter()
+                    .map(|(key, value)| format!("{}={}", key, value))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let assert = self
+                    .envs(combination)
+                    .assert()
+                    .append_context("env", label.clone());
+                (label, assert)
+            })
+            .collect()
+    }
+
+    /// Run the `Command` without a terminal, ensuring it fails fast and says why, as an
+    /// interactive-only CLI should rather than hanging on a prompt.
+    ///
+    /// The child's stdin is closed without input, and none of its streams is a terminal.  It
+    /// must exit within the [`Command::timeout`], or 5 seconds if none is set, with a failure
+    /// code and a message on stderr.  The run is returned, like to check the message.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stderr", "error: a terminal is required")
+    ///     .env("exit", "2")
+    ///     .assert_requires_tty()
+    ///     .stderr("error: a terminal is required\n");
+    /// ```
+    pub fn assert_requires_tty(&mut self) -> Assert {
+        let timeout = self.timeout;
+        let stdin = self.stdin.take();
+        self.timeout = Some(timeout.unwrap_or(REQUIRES_TTY_TIMEOUT));
+        let assert = self.assert().append_context("terminal", "none");
+        self.timeout = timeout;
+        self.stdin = stdin;
+        assert.refuses_without_tty()
+    }
+
+    /// Start the `Command` for an interactive session, like with a REPL.
+    ///
+    /// Any [`Command::write_stdin`] input is written first.  See [`AssertChild`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    /// use predicates::prelude::*;
+    ///
+    /// use std::time::Duration;
+    ///
+    /// let mut cmd = Command::new("cat");
+    /// let mut session = cmd.spawn().unwrap();
+    /// session
+    ///     .send_line("ping")
+    ///     .expect_stdout(predicate::str::contains("ping"), Duration::from_secs(5))
+    ///     .close_stdin();
+    /// session.assert().success();
+    /// ```
+    ///
+    /// [`AssertChild`]: crate::interactive::AssertChild
+    pub fn spawn(&mut self) -> io::Result<crate::interactive::AssertChild<'_>> {
+        let dumps = self.request_dumps();
+        let child = self.spawn_guarded()?;
+        let input = self.stdin.clone();
+        let line_ending = self.line_ending;
+        let timeout = self.timeout;
+        Ok(crate::interactive::AssertChild::new(
+            self,
+            child,
+            dumps,
+            input,
+            line_ending,
+            timeout,
+        ))
+    }
+
+    /// Start the `Command` in the background, like a server for the test to talk to.
+    ///
+    /// Any [`Command::write_stdin`] input is written first.  See [`Server`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// use std::time::Duration;
+    ///
+    /// let mut cmd = Command::cargo_bin("bin_fixture").unwrap();
+    /// let mut server = cmd.spawn_server().unwrap();
+    /// server.ready_when_listening("127.0.0.1:8080", Duration::from_secs(5));
+    /// // ... talk to the server ...
+    /// server.shutdown();
+    /// ```
+    ///
+    /// [`Server`]: crate::server::Server
+    pub fn spawn_server(&mut self) -> io::Result<crate::server::Server<'_>> {
+        self.spawn().map(crate::server::Server::new)
+    }
+
+    /// Pipe the `Command`'s `stdout` into `next`, like `producer | consumer` in a shell.
+    ///
+    /// See [`Pipeline`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::new("printf")
+    ///     .arg("b\\na\\n")
+    ///     .pipe(&mut Command::new("sort"))
+    ///     .assert()
+    ///     .success()
+    ///     .stdout("a\nb\n");
+    /// ```
+    ///
+    /// [`Pipeline`]: crate::pipeline::Pipeline
+    pub fn pipe<'a>(&'a mut self, next: &'a mut Command) -> crate::pipeline::Pipeline<'a> {
+        crate::pipeline::Pipeline::new(self).pipe(next)
+    }
+
+    /// Spawn the `Command` as a [`Pipeline`][crate::pipeline::Pipeline] stage that feeds the
+    /// next, reading `stdin` if it isn't the first.
+    pub(crate) fn spawn_stage(&mut self, stdin: Option<process::ChildStdout>) -> ChildGuard {
+        self.stdin_from = stdin.map(process::Stdio::from);
+        let mut child = match self.spawn_guarded() {
+            Ok(child) => child,
+            Err(err) => panic!(
+                "Failed to spawn {:?}: {}",
+                self,
+                crate::exec::SpawnError::new(&self.cmd, err)
+            ),
+        };
+        // Only the first stage has its own stdin, closed after any input is written.
+        if let Some(mut stdin) = child.take_stdin() {
+            if let Some(input) = self.stdin.clone() {
+                // A write error means the stage stopped reading, which its status reports.
+                std::thread::spawn(move || stdin.write_all(&input));
+            }
+        }
+        child
+    }
+
+    /// Run the `Command` as the last [`Pipeline`][crate::pipeline::Pipeline] stage, reading
+    /// `stdin`.
+    pub(crate) fn assert_stage(&mut self, stdin: Option<process::ChildStdout>) -> Assert {
+        self.stdin_from = stdin.map(process::Stdio::from);
+        self.assert()
+    }
+
+    /// Wait for an upstream [`Pipeline`][crate::pipeline::Pipeline] stage, killing it after its
+    /// timeout.
+    pub(crate) fn finish_stage(&self, mut child: ChildGuard) -> process::ExitStatus {
+        let result = match self.timeout {
+            Some(timeout) => match child.wait_timeout(timeout) {
+                Ok(Some(status)) => Ok(status),
+                Ok(None) => child.kill(),
+                Err(err) => Err(err),
+            },
+            None => child.wait(),
+        };
+        match result {
+            Ok(status) => status,
+            Err(err) => panic!("Failed to wait on {:?}: {}", self, err),
+        }
+    }
+
+    /// The program and arguments, to name the `Command` in messages.
+    pub(crate) fn label(&self) -> String {
+        format!("{:?}", self.cmd)
+    }
+
+    /// Build the [`Assert`] for an [`AssertChild`][crate::interactive::AssertChild].
+    pub(crate) fn finish_interactive(
+        &self,
+        output: process::Output,
+        chunks: Vec<Chunk>,
+        child: &ChildGuard,
+        duration: std::time::Duration,
+        dumps: Dumps,
+        timed_out: Option<std::time::Duration>,
+    ) -> Assert {
+        let run = Run {
+            output,
+            timed_out: timed_out.is_some(),
+            chunks,
+            spawn: child.spawn_duration(),
+            duration,
+            samples: None,
+            usage: child.resource_usage(),
+            stdin_unread: None,
+            replayed: false,
+            terminated: None,
+        };
+        self.build_assert(run, dumps, timed_out)
+    }
+
+    /// Run the `Command`, ensuring it is still running after `duration`.
+    ///
+    /// This is meant for "starts and doesn't immediately crash" smoke tests of daemons.  If the
+    /// process exits within the window, this panics with its output and exit code.  Otherwise the
+    /// process is killed and the output collected so far is available for further assertions.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "listening")
+    ///     .env("sleep", "100")
+    ///     .stays_alive_for(std::time::Duration::from_secs(1))
+    ///     .stdout("listening\n");
+    /// ```
+    pub fn stays_alive_for(&mut self, duration: std::time::Duration) -> Assert {
+        let dumps = self.request_dumps();
+        let run = match self.run(Some(duration), None) {
+            Ok(run) => run,
+            Err(err) => {
+                panic!(
+                    "Failed to spawn {:?}: {}",
+                    self,
+                    crate::exec::SpawnError::new(&self.cmd, err)
+                );
+            }
+        };
+        let timed_out = run.timed_out;
+        let assert = self.build_assert(run, dumps, None);
+        if !timed_out {
+            assert.fail(format_args!("Process exited within {:?}", duration));
+        }
+        assert
+    }
+
+    /// Sample the child's memory and CPU usage every `interval` while it runs.
+    ///
+    /// The samples are available for assertions like [`Assert::peak_rss`].  Sampling is only
+    /// supported on Linux.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    /// use predicates::prelude::*;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("sleep", "1")
+    ///     .sample_resources(std::time::Duration::from_millis(100))
+    ///     .assert()
+    ///     .peak_rss(predicate::lt(200 * 1024 * 1024))
+    ///     .no_monotonic_rss_growth();
+    /// ```
+    pub fn sample_resources(&mut self, interval: std::time::Duration) -> &mut Self {
+        self.sample_interval = Some(interval);
+        self
+    }
+
+    /// Run the `Command`, closing its `stdout` after reading `bytes` of it.
+    ///
+    /// This simulates piping into `head`, so a CLI's reaction to a broken pipe can be tested.
+    /// Conventionally, a CLI either dies from `SIGPIPE` (see [`Assert::killed_by_sigpipe`]) or
+    /// exits cleanly.  `stdout` holds just the bytes that were read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::new("yes")
+    ///     .stdout_closed_after(10)
+    ///     .killed_by_sigpipe()
+    ///     .stdout("y\ny\ny\ny\ny\n");
+    /// ```
+    pub fn stdout_closed_after(&mut self, bytes: usize) -> Assert {
+        let dumps = self.request_dumps();
+        let run = match self.run(self.timeout, Some(bytes)) {
+            Ok(run) => run,
+            Err(err) => {
+                panic!(
+                    "Failed to spawn {:?}: {}",
+                    self,
+                    crate::exec::SpawnError::new(&self.cmd, err)
+                );
+            }
+        };
+        let timed_out = if run.timed_out { self.timeout } else { None };
+        self.build_assert(run, dumps, timed_out)
+            .append_context("stdout closed after", format!("{} bytes", bytes))
+    }
+
+    /// Run the `Command` as configured and then from each of `dirs`, ensuring every run has the
+    /// same status, `stdout`, and `stderr`.
+    ///
+    /// This catches CLIs that accidentally depend on being run from a particular directory.  The
+    /// returned [`Assert`] is for the first run.  The `Command` is left running from the last of
+    /// `dirs`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello")
+    ///     .assert_from_dirs(&["/", "src"])
+    ///     .stdout("hello\n");
+    /// ```
+    pub fn assert_from_dirs<I, P>(&mut self, dirs: I) -> Assert
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<path::Path>,
+    {
+        let baseline = self.assert();
+        for dir in dirs {
+            let dir = dir.as_ref();
+            // Compare filtered output on both sides, so redactions don't look like differences.
+            let other = self.current_dir(dir).assert();
+            let (expected, actual) = (baseline.get_output(), other.get_output());
+            let mut differences = Vec::new();
+            if actual.status != expected.status {
+                differences.push(format!("status={}", actual.status));
+            }
+            if actual.stdout != expected.stdout {
+                differences.push(format!("stdout=```{}```", DebugBytes::new(&actual.stdout)));
+            }
+            if actual.stderr != expected.stderr {
+                differences.push(format!("stderr=```{}```", DebugBytes::new(&actual.stderr)));
+            }
+            if !differences.is_empty() {
+                baseline.fail(format_args!(
+                    "Output differs when run from `{}`\n{}",
+                    dir.display(),
+                    differences.join("\n")
+                ));
+            }
+            other.discard();
+        }
+        baseline
+    }
+
+    /// Run the `Command` as configured, from the crate root, from an empty temp dir, and from a
+    /// deep subdirectory of it, ensuring equivalent results.
+    ///
+    /// See [`Command::assert_from_dirs`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello")
+    ///     .assert_cwd_independent()
+    ///     .stdout("hello\n");
+    /// ```
+    pub fn assert_cwd_independent(&mut self) -> Assert {
+        let temp = crate::temp::create_dir("assert_cmd-cwd");
+        let deep = temp.join("a").join("b").join("c");
+        let _ = std::fs::create_dir_all(&deep);
+        let mut dirs = Vec::new();
+        if let Some(root) = std::env::var_os("CARGO_MANIFEST_DIR") {
+            dirs.push(path::PathBuf::from(root));
+        }
+        dirs.push(temp.clone());
+        dirs.push(deep);
+        let assert = self.assert_from_dirs(&dirs);
+        crate::temp::remove_dir(&temp);
+        assert
+    }
+
+    /// Run the `Command` `runs` times, ensuring every run succeeds with the same `stdout` and
+    /// `stderr`.
+    ///
+    /// This is for CLIs that should be stable when repeated, like a formatter whose second run
+    /// changes nothing.  The first run that differs is reported with a diff against the first
+    /// run, which is returned.
+    ///
+    /// # Panics
+    ///
+    /// If `runs` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .args(["fmt", "--check"])
+    ///     .assert_idempotent(3)
+    ///     .stdout("");
+    /// ```
+    pub fn assert_idempotent(&mut self, runs: usize) -> Assert {
+        assert!(runs != 0, "`assert_idempotent` needs at least one run");
+        let first = self
+            .assert()
+            .append_context("run", format!("1 of {}", runs))
+            .success();
+        for n in 2..=runs {
+            let other = self
+                .assert()
+                .append_context("run", format!("{} of {}", n, runs))
+                .success();
+            let (expected, actual) = (first.get_output(), other.get_output());
+            for (stream, expected, actual) in [
+                ("stdout", &expected.stdout, &actual.stdout),
+                ("stderr", &expected.stderr, &actual.stderr),
+            ] {
+                if expected != actual {
+                    other.fail(format_args!(
+                        "Unexpected {} on run {} of {}, differs from run 1\n{}",
+                        stream,
+                        n,
+                        runs,
+                        crate::diff::unified(
+                            &String::from_utf8_lossy(expected),
+                            &String::from_utf8_lossy(actual),
+                            crate::diff::color()
+                        )
+                    ));
+                }
+            }
+            other.discard();
+        }
+        first
+    }
+
+    /// Run the `Command` twice in a fresh, shared environment, checking the first (cold) run with
+    /// `cold` and the second (warm) run with `warm`.
+    ///
+    /// This tests caching, like "the second run prints `cache hit`".  The home directory, the
+    /// XDG base directories on Unix, `APPDATA` and `LOCALAPPDATA` on Windows, and the temp dir
+    /// all point into a directory that is removed afterwards.  This takes precedence over
+    /// [`Command::home`] and [`Command::isolated_temp_dir`] for the two runs.  The `Command` is
+    /// left with those variables set.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .assert_cold_warm(|cold| cold.success(), |warm| warm.success());
+    /// ```
+    pub fn assert_cold_warm<C, W>(&mut self, cold: C, warm: W)
+    where
+        C: FnOnce(Assert) -> Assert,
+        W: FnOnce(Assert) -> Assert,
+    {
+        let root = crate::temp::create_dir("assert_cmd-cold-warm");
+        let temp = root.join("tmp");
+        let _ = std::fs::create_dir_all(&temp);
+        let mut vars = crate::sandbox::vars(&root);
+        for name in &["TMPDIR", "TEMP", "TMP"] {
+            vars.push((name, temp.clone()));
+        }
+        for (name, path) in &vars {
+            self.cmd.env(name, path);
+        }
+
+        let home = self.home.take();
+        let isolated_temp_dir = std::mem::replace(&mut self.isolated_temp_dir, false);
+        cold(self.assert().append_context("run", "cold"));
+        warm(self.assert().append_context("run", "warm"));
+        self.home = home;
+        self.isolated_temp_dir = isolated_temp_dir;
+        crate::temp::remove_dir(&root);
+    }
+
+    /// Run `instances` copies of the `Command` at the same time, ensuring they succeed according
+    /// to `policy`.
+    ///
+    /// The instances share the `Command`'s configuration, like its working directory, so this
+    /// tests how a CLI copes with contention for shared state, like a lock file.  If `policy` is
+    /// violated, every instance's output is reported.  Otherwise, the instances are returned, in
+    /// the order they were spawned, for further assertions.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use assert_cmd::cmd::Contention;
+    /// use assert_cmd::Command;
+    ///
+    /// let dir = std::env::temp_dir().join("assert_cmd-doc-lock");
+    /// let _ = std::fs::remove_dir(&dir);
+    /// Command::new("mkdir")
+    ///     .arg(&dir)
+    ///     .assert_concurrent(2, Contention::OneSucceeds);
+    /// std::fs::remove_dir(&dir).unwrap();
+    /// ```
+    pub fn assert_concurrent(&mut self, instances: usize, policy: Contention) -> Vec<Assert> {
+        let mut spawned = Vec::with_capacity(instances);
+        for _ in 0..instances {
+            let dumps = self.request_dumps();
+            let child = match self.spawn_guarded() {
+                Ok(child) => child,
+                Err(err) => {
+                    panic!(
+                        "Failed to spawn {:?}: {}",
+                        self,
+                        crate::exec::SpawnError::new(&self.cmd, err)
+                    );
+                }
+            };
+            spawned.push((dumps, child));
+        }
+        let waiting: Vec<_> = spawned
+            .into_iter()
+            .map(|(dumps, child)| {
+                let label = crate::timing::label(&self.cmd);
+                let stdin = self.stdin.clone();
+                let pacing = self.stdin_pacing.clone();
+                let timeout = self.timeout;
+                let sample_interval = self.sample_interval;
+                let sinks = self.sinks.clone();
+                let thread = std::thread::spawn(move || {
+                    crate::budget::track(label, || {
+                        Self::wait_with_input_output(
+                            child,
+                            stdin,
+                            pacing,
+                            timeout,
+                            sample_interval,
+                            None,
+                            sinks,
+                        )
+                    })
+                });
+                (dumps, thread)
+            })
+            .collect();
+        let asserts: Vec<_> = waiting
+            .into_iter()
+            .enumerate()
+            .map(|(i, (dumps, thread))| {
+                let run = match thread.join().unwrap() {
+                    Ok(run) => run,
+                    Err(err) => {
+                        panic!("Failed to wait on {:?}: {}", self, err);
+                    }
+                };
+                let timed_out = if run.timed_out { self.timeout } else { None };
+                self.build_assert(run, dumps, timed_out)
+                    .append_context("instance", format!("{} of {}", i + 1, instances))
+            })
+            .collect();
+
+        let succeeded = asserts
+            .iter()
+            .filter(|assert| assert.get_output().status.success())
+            .count();
+        if !policy.allows(succeeded, instances) {
+            let dumps: Vec<_> = asserts.iter().map(|assert| assert.to_string()).collect();
+            crate::aggregate::fail(
+                format!(
+                    "Concurrent instances didn't match `{}`, {} of {} succeeded",
+                    policy, succeeded, instances
+                ),
+                dumps.join("\n"),
+            );
+        }
+        asserts
+    }
+
+    /// Start the child's clock at `time`, an RFC 3339 timestamp like `2020-01-01T00:00:00Z`.
+    ///
+    /// This preloads [libfaketime] into the child, so date-sensitive output can be asserted
+    /// exactly.  A trailing `Z` also sets `TZ=UTC`.  Requires the `faketime` feature and
+    /// libfaketime to be installed; see [`faketime`][crate::faketime] for how it is found.
+    ///
+    /// Fails if libfaketime can't be found or `time` is malformed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::new("date")
+    ///     .arg("+%Y")
+    ///     .fake_time("2020-01-01T00:00:00Z")
+    ///     .unwrap()
+    ///     .assert()
+    ///     .stdout("2020\n");
+    /// ```
+    ///
+    /// [libfaketime]: https://github.com/wolfcw/libfaketime
+    #[cfg(feature = "faketime")]
+    pub fn fake_time(&mut self, time: &str) -> io::Result<&mut Self> {
+        for (key, value) in crate::faketime::env(time)? {
+            self.cmd.env(key, value);
+        }
+        Ok(self)
+    }
+
+    /// Write `path`s content to `stdin` when the `Command` is run.
+    ///
+    /// Paths are relative to the [`env::current_dir`][env_current_dir] and not
+    /// [`Command::current_dir`][Command_current_dir].
+    ///
+    /// [env_current_dir]: std::env::current_dir()
+    /// [Command_current_dir]: std::process::Command::current_dir()
+    pub fn pipe_stdin<P>(&mut self, file: P) -> io::Result<&mut Self>
+    where
+        P: AsRef<path::Path>,
+    {
+        let buffer = std::fs::read(file)?;
+        Ok(self.write_stdin(buffer))
+    }
+
+    /// Run a `Command`, returning an [`OutputResult`][OutputResult].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use assert_cmd::Command;
+    ///
+    /// let result = Command::new("echo")
+    ///     .args(&["42"])
+    ///     .ok();
+    /// assert!(result.is_ok());
+    /// ```
+    ///
+    pub fn ok(&mut self) -> OutputResult {
+        OutputOkExt::ok(self)
+    }
+
+    /// Run a `Command`, unwrapping the [`OutputResult`][OutputResult].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use assert_cmd::Command;
+    ///
+    /// let output = Command::new("echo")
+    ///     .args(&["42"])
+    ///     .unwrap();
+    /// ```
+    ///
+    pub fn unwrap(&mut self) -> process::Output {
+        OutputOkExt::unwrap(self)
+    }
+
+    /// Run a `Command`, unwrapping the error in the [`OutputResult`][OutputResult].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// let err = Command::new("a-command")
+    ///     .args(&["--will-fail"])
+    ///     .unwrap_err();
+    /// ```
+    ///
+    /// [Output]: std::process::Output
+    pub fn unwrap_err(&mut self) -> OutputError {
+        OutputOkExt::unwrap_err(self)
+    }
+
+    /// Run a `Command` and make assertions on the [`Output`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// let mut cmd = Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .assert()
+    ///     .success();
+    /// ```
+    ///
+    /// [`Output`]: std::process::Output
+    pub fn assert(&mut self) -> Assert {
+        OutputAssertExt::assert(self)
+    }
+}
+
+/// Mirror [`std::process::Command`][Command]'s API
+///
+/// [Command]: std::process::Command
+impl Command {
+    /// Constructs a new `Command` for launching the program at
+    /// path `program`, with the following default configuration:
+    ///
+    /// * No arguments to the program
+    /// * Inherit the current process's environment
+    /// * Inherit the current process's working directory
+    /// * Inherit stdin/stdout/stderr for `spawn` or `status`, but create pipes for `output`
+    ///
+    /// Builder methods are provided to change these defaults and
+    /// otherwise configure the process.
+    ///
+    /// If `program` is not an absolute path, the `PATH` will be searched in
+    /// an OS-defined way.
+    ///
+    /// The search path to be used may be controlled by setting the
+    /// `PATH` environment variable on the Command,
+    /// but this has some implementation limitations on Windows
+    /// (see issue #37519).
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::new("sh").unwrap();
+    /// ```
+    pub fn new<S: AsRef<ffi::OsStr>>(program: S) -> Self {
+        let cmd = process::Command::new(program);
+        Self::from_std(cmd)
+    }
+
+    /// Adds an argument to pass to the program.
+    ///
+    /// Only one argument can be passed per use. So instead of:
+    ///
+    /// ```no_run
+    /// # assert_cmd::Command::new("sh")
+    /// .arg("-C /path/to/repo")
+    /// # ;
+    /// ```
+    ///
+    /// usage would be:
+    ///
+    /// ```no_run
+    /// # assert_cmd::Command::new("sh")
+    /// .arg("-C")
+    /// .arg("/path/to/repo")
+    /// # ;
+    /// ```
+    ///
+    /// To pass multiple arguments see [`args`].
+    ///
+    /// [`args`]: Command::args()
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::new("ls")
+    ///         .arg("-l")
+    ///         .arg("-a")
+    ///         .unwrap();
+    /// ```
+    pub fn arg<S: AsRef<ffi::OsStr>>(&mut self, arg: S) -> &mut Self {
+        self.cmd.arg(arg);
+        self
+    }
+
+    /// Adds multiple arguments to pass to the program.
+    ///
+    /// To pass a single argument see [`arg`].
+    ///
+    /// [`arg`]: Command::arg()
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::new("ls")
+    ///         .args(&["-l", "-a"])
+    ///         .unwrap();
+    /// ```
+    pub fn args<I, S>(&mut self, args: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<ffi::OsStr>,
+    {
+        self.cmd.args(args);
+        self
+    }
+
+    /// Inserts or updates an environment variable mapping.
+    ///
+    /// Note that environment variable names are case-insensitive (but case-preserving) on Windows,
     /// and case-sensitive on all other platforms.
     ///
     /// # Examples
@@ -303,204 +2239,913 @@ impl Command {
     /// use assert_cmd::Command;
     ///
     /// Command::new("ls")
-    ///         .env("PATH", "/bin")
+    ///         .env("PATH", "/bin")
+    ///         .unwrap_err();
+    /// ```
+    pub fn env<K, V>(&mut self, key: K, val: V) -> &mut Self
+    where
+        K: AsRef<ffi::OsStr>,
+        V: AsRef<ffi::OsStr>,
+    {
+        self.cmd.env(key, val);
+        self
+    }
+
+    /// Adds or updates multiple environment variable mappings.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use assert_cmd::Command;
+    /// use std::process::Stdio;
+    /// use std::env;
+    /// use std::collections::HashMap;
+    ///
+    /// let filtered_env : HashMap<String, String> =
+    ///     env::vars().filter(|&(ref k, _)|
+    ///         k == "TERM" || k == "TZ" || k == "LANG" || k == "PATH"
+    ///     ).collect();
+    ///
+    /// Command::new("printenv")
+    ///         .env_clear()
+    ///         .envs(&filtered_env)
+    ///         .unwrap();
+    /// ```
+    pub fn envs<I, K, V>(&mut self, vars: I) -> &mut Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<ffi::OsStr>,
+        V: AsRef<ffi::OsStr>,
+    {
+        self.cmd.envs(vars);
+        self
+    }
+
+    /// Removes an environment variable mapping.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::new("ls")
+    ///         .env_remove("PATH")
+    ///         .unwrap_err();
+    /// ```
+    pub fn env_remove<K: AsRef<ffi::OsStr>>(&mut self, key: K) -> &mut Self {
+        self.cmd.env_remove(key);
+        self
+    }
+
+    /// Clears the entire environment map for the child process.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::new("ls")
+    ///         .env_clear()
     ///         .unwrap_err();
     /// ```
-    pub fn env<K, V>(&mut self, key: K, val: V) -> &mut Self
+    pub fn env_clear(&mut self) -> &mut Self {
+        self.cmd.env_clear();
+        self.env_cleared = true;
+        self
+    }
+
+    /// Run the child in a deterministic environment, rather than the developer's.
+    ///
+    /// The environment is cleared except for what's needed to run programs, like `PATH`, and
+    /// then set to:
+    /// - `LC_ALL=C`
+    /// - `TZ=UTC`
+    /// - `NO_COLOR=1`
+    /// - `COLUMNS=80`
+    ///
+    /// Add back variables with [`Command::env_inherit`] or set them with [`Command::env`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env_deterministic()
+    ///     .env_inherit("HOME")
+    ///     .assert()
+    ///     .success();
+    /// ```
+    pub fn env_deterministic(&mut self) -> &mut Self {
+        self.cmd.env_clear();
+        self.env_cleared = true;
+        for key in PRESERVED_ENV {
+            self.env_inherit(key);
+        }
+        self.cmd.envs(DETERMINISTIC_ENV);
+        self
+    }
+
+    /// Give the child what it needs to contribute to the test's coverage and sanitizer reports.
+    ///
+    /// Binaries built with `-C instrument-coverage` write a profile when they exit, named by
+    /// `LLVM_PROFILE_FILE`.  Each child gets the test's `LLVM_PROFILE_FILE`, or the default
+    /// `default_%m_%p.profraw`, resolved against the test's working directory rather than the
+    /// child's and with `%p`, the child's process ID, added unless the name is already unique
+    /// per process.  So profiles survive [`Command::in_temp_dir`] and runs don't overwrite each
+    /// other.  The sanitizers' `ASAN_OPTIONS`, `LSAN_OPTIONS`, `MSAN_OPTIONS`, `TSAN_OPTIONS`
+    /// and `UBSAN_OPTIONS` are passed through.
+    ///
+    /// These are set when the child is spawned, so they survive [`Command::env_clear`] and
+    /// [`Command::env_deterministic`]; a variable set with [`Command::env`] is left alone.
+    ///
+    /// Defaults to whether [`COVERAGE_ENV`] is set to something other than `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env_deterministic()
+    ///     .coverage(true)
+    ///     .assert()
+    ///     .success();
+    /// ```
+    pub fn coverage(&mut self, yes: bool) -> &mut Self {
+        self.coverage = yes;
+        self
+    }
+
+    /// Pass the test process's value of the environment variable `key` to the child, if it's
+    /// set.
+    ///
+    /// See [`Command::env_deterministic`].
+    pub fn env_inherit<K: AsRef<ffi::OsStr>>(&mut self, key: K) -> &mut Self {
+        let key = key.as_ref();
+        match std::env::var_os(key) {
+            Some(val) => self.cmd.env(key, val),
+            None => self.cmd.env_remove(key),
+        };
+        self
+    }
+
+    /// Sets the working directory for the child process.
+    ///
+    /// # Platform-specific behavior
+    ///
+    /// If the program path is relative (e.g., `"./script.sh"`), it's ambiguous
+    /// whether it should be interpreted relative to the parent's working
+    /// directory or relative to `current_dir`. The behavior in this case is
+    /// platform specific and unstable, and it's recommended to use
+    /// [`canonicalize`] to get an absolute program path instead.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::new("ls")
+    ///         .current_dir("/bin")
+    ///         .unwrap();
+    /// ```
+    ///
+    /// [`canonicalize`]: std::fs::canonicalize()
+    pub fn current_dir<P: AsRef<path::Path>>(&mut self, dir: P) -> &mut Self {
+        self.cmd.current_dir(dir);
+        self
+    }
+
+    /// Executes the `Command` as a child process, waiting for it to finish and collecting all of its
+    /// output.
+    ///
+    /// By default, stdout and stderr are captured (and used to provide the resulting output).
+    /// Stdin is not inherited from the parent and any attempt by the child process to read from
+    /// the stdin stream will result in the stream immediately closing.
+    ///
+    /// # Examples
+    ///
+    /// ```should_panic
+    /// use assert_cmd::Command;
+    /// use std::io::{self, Write};
+    /// let output = Command::new("/bin/cat")
+    ///                      .arg("file.txt")
+    ///                      .output()
+    ///                      .expect("failed to execute process");
+    ///
+    /// println!("status: {}", output.status);
+    /// io::stdout().write_all(&output.stdout).unwrap();
+    /// io::stderr().write_all(&output.stderr).unwrap();
+    ///
+    /// assert!(output.status.success());
+    /// ```
+    pub fn output(&mut self) -> io::Result<process::Output> {
+        self.run(self.timeout, None).map(|run| run.output)
+    }
+
+    fn run(
+        &mut self,
+        timeout: Option<std::time::Duration>,
+        stdout_limit: Option<usize>,
+    ) -> io::Result<Run> {
+        if let Some(cassette) = self.cassette.clone() {
+            return self.run_cassette(&cassette, timeout, stdout_limit);
+        }
+        if !self.cached
+            || !self.dump_env.is_empty()
+            || self.capture_to_files
+            || self.sinks.is_streaming()
+            || self.stdin_from.is_some()
+            || self.retries != 0
+            || self.stdin_pacing.is_paced()
+        {
+            return self.run_uncached(timeout, stdout_limit);
+        }
+        let key = crate::cache::Key::new(
+            &self.cmd,
+            self.stdin.as_deref(),
+            (
+                timeout,
+                stdout_limit,
+                self.capture,
+                self.sample_interval,
+                self.terminate,
+            ),
+        );
+        let slot = crate::cache::lookup(key);
+        let mut cached = slot.lock();
+        if let Some(run) = cached.as_ref() {
+            return Ok(run.clone());
+        }
+        let run = self.run_uncached(timeout, stdout_limit)?;
+        *cached = Some(run.clone());
+        Ok(run)
+    }
+
+    fn run_cassette(
+        &mut self,
+        cassette: &path::Path,
+        timeout: Option<std::time::Duration>,
+        stdout_limit: Option<usize>,
+    ) -> io::Result<Run> {
+        use crate::cassette::Mode;
+
+        let mode = Mode::from_env();
+        let key = crate::cassette::Key::new(&self.cmd, self.stdin.as_deref());
+        if mode == Mode::Auto || mode == Mode::Replay {
+            match crate::cassette::lookup(cassette, &key)? {
+                Some(output) => {
+                    return Ok(Run {
+                        output,
+                        timed_out: false,
+                        chunks: Vec::new(),
+                        spawn: std::time::Duration::ZERO,
+                        duration: std::time::Duration::ZERO,
+                        samples: None,
+                        usage: None,
+                        stdin_unread: None,
+                        replayed: true,
+                        terminated: None,
+                    })
+                }
+                None if mode == Mode::Replay => {
+                    return Err(crate::cassette::missing(cassette, &key));
+                }
+                None => {}
+            }
+        }
+        let run = self.run_uncached(timeout, stdout_limit)?;
+        // A timed out run is incomplete, so is run again next time.
+        if mode != Mode::Off && !run.timed_out {
+            crate::cassette::record(cassette, key, &run.output)?;
+        }
+        Ok(run)
+    }
+
+    fn run_uncached(
+        &mut self,
+        timeout: Option<std::time::Duration>,
+        stdout_limit: Option<usize>,
+    ) -> io::Result<Run> {
+        crate::budget::track(crate::timing::label(&self.cmd), || {
+            let spawn = self.spawn_guarded()?;
+            Self::wait_with_input_output(
+                spawn,
+                self.stdin.clone(),
+                self.stdin_pacing.clone(),
+                timeout,
+                self.sample_interval,
+                stdout_limit,
+                self.sinks.clone(),
+            )
+        })
+    }
+
+    /// If `input`, write it to `child`'s stdin while also reading `child`'s
+    /// stdout and stderr, then wait on `child` and return its status and output.
+    ///
+    /// This was lifted from `std::process::Child::wait_with_output` and modified
+    /// to also write to stdin.
+    fn wait_with_input_output(
+        mut child: ChildGuard,
+        input: Option<Vec<u8>>,
+        pacing: StdinPacing,
+        timeout: Option<std::time::Duration>,
+        sample_interval: Option<std::time::Duration>,
+        stdout_limit: Option<usize>,
+        sinks: Sinks,
+    ) -> io::Result<Run> {
+        let stdin_len = input.as_ref().map(Vec::len);
+        let input = if pacing.hold_open {
+            Some(input.unwrap_or_default())
+        } else {
+            input
+        };
+        // Dropped once the child exits, to cut any remaining pacing short.
+        let (exited, pacer) = std::sync::mpsc::channel::<()>();
+        let stdin = input.and_then(|i| {
+            child.take_stdin().map(|mut stdin| {
+                std::thread::spawn(move || {
+                    // Track progress so a broken pipe can report how much was read.
+                    let mut written = 0;
+                    let ends = pacing.breaks.iter().copied().chain(Some(i.len()));
+                    'chunks: for end in ends {
+                        if written != 0 {
+                            let _ = stdin.flush();
+                            if pacer.recv_timeout(pacing.delay)
+                                != Err(std::sync::mpsc::RecvTimeoutError::Timeout)
+                            {
+                                break 'chunks;
+                            }
+                        }
+                        while written < end {
+                            match stdin.write(&i[written..end]) {
+                                Ok(0) => break 'chunks,
+                                Ok(len) => written += len,
+                                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                                Err(_) => break 'chunks,
+                            }
+                        }
+                    }
+                    (written, Some(stdin).filter(|_| pacing.hold_open))
+                })
+            })
+        });
+        let spawn = child.spawn_duration();
+        let start = std::time::Instant::now();
+        let sampler = sample_interval.map(|interval| Sampler::start(child.id(), start, interval));
+        let chunks = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        fn read<R>(
+            mut input: R,
+            stream: Stream,
+            start: std::time::Instant,
+            chunks: std::sync::Arc<std::sync::Mutex<Vec<Chunk>>>,
+            limit: Option<usize>,
+            sink: Option<Sink>,
+            tee: bool,
+        ) -> std::thread::JoinHandle<io::Result<Vec<u8>>>
+        where
+            R: Read + Send + 'static,
+        {
+            std::thread::spawn(move || {
+                let mut mirror = if tee { Some(Mirror::new(stream)) } else { None };
+                let mut ret = Vec::new();
+                let mut buf = [0; 8 * 1024];
+                let limit = limit.unwrap_or(usize::MAX);
+                let mut total = 0;
+                loop {
+                    // Returning drops `input`, closing our end of the pipe.
+                    let want = buf.len().min(limit - total);
+                    if want == 0 {
+                        return Ok(ret);
+                    }
+                    let len = match input.read(&mut buf[..want]) {
+                        Ok(0) => return Ok(ret),
+                        Ok(len) => len,
+                        Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                        Err(err) => return Err(err),
+                    };
+                    total += len;
+                    if let Some(mirror) = &mut mirror {
+                        mirror.write(&buf[..len]);
+                    }
+                    ret.extend_from_slice(&buf[..len]);
+                    if let Some(sink) = &sink {
+                        (sink.lock().unwrap())(&buf[..len]);
+                        if STREAM_TAIL < ret.len() {
+                            ret.drain(..ret.len() - STREAM_TAIL);
+                        }
+                    }
+                    chunks.lock().unwrap().push(Chunk {
+                        stream,
+                        elapsed: start.elapsed(),
+                        len,
+                    });
+                }
+            })
+        }
+        let stdout = child.take_stdout().map(|s| {
+            let sink = sinks.stdout.clone();
+            read(
+                s,
+                Stream::Stdout,
+                start,
+                chunks.clone(),
+                stdout_limit,
+                sink,
+                sinks.tee,
+            )
+        });
+        let stderr = child.take_stderr().map(|s| {
+            let sink = sinks.stderr;
+            read(
+                s,
+                Stream::Stderr,
+                start,
+                chunks.clone(),
+                None,
+                sink,
+                sinks.tee,
+            )
+        });
+
+        // Wait while stdin is still being written, so the timeout counts from the spawn, and
+        // killing a child that doesn't read its input unblocks the writer.
+        let mut timed_out = false;
+        let status = match child.wait_until(timeout)? {
+            Some(status) => status,
+            None => {
+                timed_out = true;
+                child.kill()?
+            }
+        };
+        drop(exited);
+        let (stdin_written, held_stdin) = match stdin.map(|t| t.join().unwrap()) {
+            Some((written, held)) => (Some(written), held),
+            None => (None, None),
+        };
+        let stdin_unread = match (stdin_written, stdin_len) {
+            (Some(written), Some(len)) if written < len => Some((written, len)),
+            _ => None,
+        };
+        drop(held_stdin);
+        if !timed_out && !child.wait_group(timeout.map(|timeout| start + timeout)) {
+            timed_out = true;
+        }
+        let duration = start.elapsed();
+        let samples = sampler.map(Sampler::finish);
+        let usage = child.resource_usage();
+
+        let stdout = stdout
+            .and_then(|t| t.join().unwrap().ok())
+            .unwrap_or_default();
+        let stderr = stderr
+            .and_then(|t| t.join().unwrap().ok())
+            .unwrap_or_default();
+        let chunks = std::mem::take(&mut *chunks.lock().unwrap());
+
+        Ok(Run {
+            output: process::Output {
+                status,
+                stdout,
+                stderr,
+            },
+            timed_out,
+            chunks,
+            spawn,
+            duration,
+            samples,
+            usage,
+            stdin_unread,
+            replayed: false,
+            terminated: child.terminated(),
+        })
+    }
+
+    fn spawn_guarded(&mut self) -> io::Result<ChildGuard> {
+        for hook in crate::hooks::before_spawn_hooks(&self.hooks.before_spawn) {
+            hook(self);
+        }
+        if self.echo {
+            eprintln!("{}", self.echo_text());
+        }
+        #[cfg(feature = "log")]
+        crate::logging::spawning(&self.cmd);
+        // stdout/stderr should only be piped for `output` according to `process::Command::new`.
+        let piped = self.stdin_from.take();
+        let piped_stdin = piped.is_some();
+        self.cmd.stdin(piped.unwrap_or_else(process::Stdio::piped));
+        self.cmd.stdout(if self.capture.stdout() {
+            process::Stdio::piped()
+        } else {
+            process::Stdio::inherit()
+        });
+        self.cmd.stderr(if self.capture.stderr() {
+            process::Stdio::piped()
+        } else {
+            process::Stdio::inherit()
+        });
+        let spool = self.spool.take();
+        let combined = if let Some(spool) = &spool {
+            match self.capture {
+                Streams::Both => {
+                    self.cmd.stdout(spool.create("stdout")?);
+                    self.cmd.stderr(spool.create("stderr")?);
+                }
+                Streams::StdoutOnly => {
+                    self.cmd.stdout(spool.create("stdout")?);
+                }
+                Streams::StderrOnly => {
+                    self.cmd.stderr(spool.create("stderr")?);
+                }
+                Streams::Combined => {
+                    let file = spool.create("stdout")?;
+                    self.cmd.stdout(file.try_clone()?);
+                    self.cmd.stderr(file);
+                }
+            }
+            None
+        } else if self.capture == Streams::Combined {
+            let (reader, writer) = crate::child::pipe()?;
+            self.cmd.stdout(writer.try_clone()?);
+            self.cmd.stderr(writer);
+            Some(reader)
+        } else {
+            None
+        };
+        #[cfg(unix)]
+        self.restrict_handles();
+        if self.coverage {
+            crate::coverage::apply(&mut self.cmd);
+        }
+
+        #[cfg(all(unix, feature = "pty"))]
+        let terminal = if self.pty && self.capture.stdout() {
+            let (master, slave) = crate::pty::open()?;
+            self.cmd.stdout(slave);
+            Some(master)
+        } else {
+            None
+        };
+
+        let dump_env = std::mem::take(&mut self.dump_env);
+        for (key, path) in &dump_env {
+            self.cmd.env(key, path);
+        }
+        let spawned = crate::env_guard::spawning(|| {
+            let start = std::time::Instant::now();
+            self.cmd.spawn().map(|child| (child, start.elapsed()))
+        });
+        // The paths are only for this child, and would make later runs report stale dumps.
+        for (key, _) in &dump_env {
+            self.cmd.env_remove(key);
+        }
+        let (child, spawn) = spawned?;
+        let child = ChildGuard::new(child).spawned_in(spawn);
+        if piped_stdin {
+            // Release our copy of the pipe, so the previous stage sees it close with the child.
+            self.cmd.stdin(process::Stdio::piped());
+        }
+        if spool.is_some() {
+            // Release our copies of the files.
+            self.cmd.stdout(process::Stdio::null());
+            self.cmd.stderr(process::Stdio::null());
+        }
+        let child = if combined.is_some() {
+            // Release our copies of the child's end, so reading ours ends when the child's close.
+            self.cmd.stdout(process::Stdio::null());
+            self.cmd.stderr(process::Stdio::null());
+            child.with_combined(combined)
+        } else {
+            child
+        };
+        #[cfg(all(unix, feature = "pty"))]
+        let child = {
+            // Release our copy of the child's end, so reading ours ends when the child's closes.
+            self.cmd.stdout(process::Stdio::null());
+            child.with_terminal(terminal)
+        };
+        #[cfg(unix)]
+        let child = child
+            .with_group(self.process_group, self.kill_process_group)
+            .with_terminate(self.terminate);
+        Ok(child)
+    }
+}
+
+/// Mirror [`std::os::unix::process::CommandExt`][CommandExt]'s API
+///
+/// [CommandExt]: std::os::unix::process::CommandExt
+#[cfg(unix)]
+impl Command {
+    /// Schedules a closure to be run just before the `exec` function is
+    /// invoked.
+    ///
+    /// The closure is allowed to return an I/O error whose OS error code will
+    /// be communicated back to the parent and returned as an error from when
+    /// the spawn was requested.
+    ///
+    /// Multiple closures can be registered and they will be called in order of
+    /// their registration. If a closure returns `Err` then no further closures
+    /// will be called and the spawn operation will immediately return with a
+    /// failure.
+    ///
+    /// # Safety
+    ///
+    /// This closure will be run in the context of the child process after a
+    /// `fork`. This primarily means that any modifications made to memory on
+    /// behalf of this closure will **not** be visible to the parent process.
+    /// This is often a very constrained environment where normal operations
+    /// like `malloc`, accessing environment variables through [`std::env`]
+    /// or acquiring a mutex are not guaranteed to work (due to other
+    /// threads perhaps still running when the `fork` was run).
+    ///
+    /// Test harnesses are multi-threaded, so limit the closure to
+    /// [async-signal-safe] operations, like `setsid`, `umask`, or `close`.
+    ///
+    /// [async-signal-safe]: https://man7.org/linux/man-pages/man7/signal-safety.7.html
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use assert_cmd::Command;
+    ///
+    /// let mut cmd = Command::new("echo");
+    /// unsafe {
+    ///     cmd.pre_exec(|| Ok(()));
+    /// }
+    /// cmd.arg("42").assert().stdout("42\n");
+    /// ```
+    pub unsafe fn pre_exec<F>(&mut self, f: F) -> &mut Self
     where
-        K: AsRef<ffi::OsStr>,
-        V: AsRef<ffi::OsStr>,
+        F: FnMut() -> io::Result<()> + Send + Sync + 'static,
     {
-        self.cmd.env(key, val);
+        std::os::unix::process::CommandExt::pre_exec(&mut self.cmd, f);
         self
     }
 
-    /// Adds or updates multiple environment variable mappings.
+    /// Sets the file mode creation mask for the child process.
+    ///
+    /// This makes it possible to verify the permissions of created files under restrictive and
+    /// permissive umasks, independent of the umask of the test runner.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use assert_cmd::Command;
+    ///
+    /// Command::new("sh")
+    ///     .args(&["-c", "umask"])
+    ///     .umask(0o077)
+    ///     .assert()
+    ///     .stdout("0077\n");
+    /// ```
+    pub fn umask(&mut self, mask: u32) -> &mut Self {
+        let mask = mask as libc::mode_t;
+        // SAFETY: `umask` is async-signal-safe and cannot fail
+        unsafe {
+            self.pre_exec(move || {
+                libc::umask(mask);
+                Ok(())
+            })
+        }
+    }
+
+    /// Reset the child's signal mask and signal dispositions to the defaults.
+    ///
+    /// Children otherwise inherit signals blocked or ignored by the test harness, like `SIGPIPE`
+    /// or `SIGCHLD`, which can change how the CLI behaves.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use assert_cmd::Command;
+    ///
+    /// Command::new("sh")
+    ///     .args(["-c", "trap - PIPE; exit 0"])
+    ///     .reset_signals()
+    ///     .assert()
+    ///     .success();
+    /// ```
+    pub fn reset_signals(&mut self) -> &mut Self {
+        // SAFETY: `signals::reset` is async-signal-safe
+        unsafe { self.pre_exec(crate::signals::reset) }
+    }
+
+    /// Ask the child to dump its signal mask and dispositions for verification.
+    ///
+    /// See the [`signals` module][crate::signals] for what the child needs to do.
+    pub fn signal_dump(&mut self, yes: bool) -> &mut Self {
+        self.signal_dump = yes;
+        self
+    }
+
+    /// Run the program with `name` as its `argv[0]`, like when invoked through a symlink.
+    ///
+    /// This tests multi-call binaries, which branch on the name they were invoked as, for each
+    /// persona.  Only `argv[0]` changes; the executable's path, like from
+    /// [`std::env::current_exe`], doesn't.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use assert_cmd::Command;
+    ///
+    /// Command::new("sh")
+    ///     .invoked_as("my-shell")
+    ///     .args(["-c", "echo $0"])
+    ///     .assert()
+    ///     .stdout("my-shell\n");
+    /// ```
+    pub fn invoked_as<S: AsRef<ffi::OsStr>>(&mut self, name: S) -> &mut Self {
+        std::os::unix::process::CommandExt::arg0(&mut self.cmd, name);
+        self
+    }
+
+    /// Whether the child inherits descriptors beyond stdio that the test process left open
+    /// without close-on-exec.  Defaults to `true`, like [`std::process::Command`].
+    ///
+    /// Disabling this verifies the CLI doesn't depend on accidentally inherited descriptors,
+    /// and keeps the test process's own from leaking into it.  Descriptors passed on purpose,
+    /// like from a [`pre_exec`][Command::pre_exec] hook, are affected too.
+    ///
+    /// This is only supported on Unix; Windows children always inherit inheritable handles.
     ///
     /// # Examples
     ///
-    /// Basic usage:
-    ///
-    /// ```no_run
+    /// ```rust
     /// use assert_cmd::Command;
-    /// use std::process::Stdio;
-    /// use std::env;
-    /// use std::collections::HashMap;
-    ///
-    /// let filtered_env : HashMap<String, String> =
-    ///     env::vars().filter(|&(ref k, _)|
-    ///         k == "TERM" || k == "TZ" || k == "LANG" || k == "PATH"
-    ///     ).collect();
     ///
-    /// Command::new("printenv")
-    ///         .env_clear()
-    ///         .envs(&filtered_env)
-    ///         .unwrap();
+    /// Command::new("sh")
+    ///     .args(["-c", "test ! -e /dev/fd/3"])
+    ///     .inherit_handles(false)
+    ///     .assert()
+    ///     .success();
     /// ```
-    pub fn envs<I, K, V>(&mut self, vars: I) -> &mut Self
-    where
-        I: IntoIterator<Item = (K, V)>,
-        K: AsRef<ffi::OsStr>,
-        V: AsRef<ffi::OsStr>,
-    {
-        self.cmd.envs(vars);
+    pub fn inherit_handles(&mut self, yes: bool) -> &mut Self {
+        self.inherit_handles = yes;
         self
     }
 
-    /// Removes an environment variable mapping.
+    /// Treat processes the child spawns, like when it daemonizes or re-executes itself, as part
+    /// of the child.
     ///
-    /// # Examples
+    /// The child is started in a new process group.  After the child exits, the rest of the
+    /// group is waited on too, and a [`Command::timeout`] kills the whole group.  Output from
+    /// the group is captured for as long as its members hold on to the child's `stdout` and
+    /// `stderr`.
     ///
-    /// Basic usage:
+    /// Processes that leave the group, like with `setsid`, aren't followed.  Without a timeout,
+    /// a group member that keeps running blocks the test.
     ///
-    /// ```no_run
+    /// # Examples
+    ///
+    /// ```rust
     /// use assert_cmd::Command;
     ///
-    /// Command::new("ls")
-    ///         .env_remove("PATH")
-    ///         .unwrap_err();
+    /// Command::new("sh")
+    ///     .args(["-c", "(sleep 1; echo ready) &"])
+    ///     .follow_process_group(true)
+    ///     .assert()
+    ///     .stdout("ready\n");
     /// ```
-    pub fn env_remove<K: AsRef<ffi::OsStr>>(&mut self, key: K) -> &mut Self {
-        self.cmd.env_remove(key);
+    pub fn follow_process_group(&mut self, yes: bool) -> &mut Self {
+        if yes {
+            self.new_process_group();
+        }
+        self.process_group = yes;
         self
     }
 
-    /// Clears the entire environment map for the child process.
+    /// Kill processes the child spawns, like workers, along with the child when a
+    /// [`Command::timeout`] fires.
     ///
-    /// # Examples
+    /// The child is started in a new process group, which is killed as a whole.  Unlike
+    /// [`Command::follow_process_group`], the rest of the group isn't waited on after the child
+    /// exits on its own.  Processes that leave the group, like with `setsid`, aren't killed.
     ///
-    /// Basic usage:
+    /// # Examples
     ///
-    /// ```no_run
+    /// ```rust
     /// use assert_cmd::Command;
     ///
-    /// Command::new("ls")
-    ///         .env_clear()
-    ///         .unwrap_err();
+    /// Command::new("sh")
+    ///     .args(["-c", "sleep 100 & sleep 100"])
+    ///     .timeout(std::time::Duration::from_millis(100))
+    ///     .kill_process_group(true)
+    ///     .assert()
+    ///     .interrupted();
     /// ```
-    pub fn env_clear(&mut self) -> &mut Self {
-        self.cmd.env_clear();
+    pub fn kill_process_group(&mut self, yes: bool) -> &mut Self {
+        if yes {
+            self.new_process_group();
+        }
+        self.kill_process_group = yes;
         self
     }
 
-    /// Sets the working directory for the child process.
+    /// Send `signal`, like `libc::SIGTERM`, to the child if it's still running `after` it
+    /// started, then capture its output until it exits, like to test graceful shutdown.
     ///
-    /// # Platform-specific behavior
+    /// `after` counts from the spawn, even while `stdin` is still being written.
     ///
-    /// If the program path is relative (e.g., `"./script.sh"`), it's ambiguous
-    /// whether it should be interpreted relative to the parent's working
-    /// directory or relative to `current_dir`. The behavior in this case is
-    /// platform specific and unstable, and it's recommended to use
-    /// [`canonicalize`] to get an absolute program path instead.
+    /// The child is killed if it's still running at the [`Command::timeout`], or 10s after the
+    /// signal without one.  The signal is reported with any failure.
     ///
     /// # Examples
     ///
-    /// Basic usage:
-    ///
-    /// ```no_run
+    /// ```rust
     /// use assert_cmd::Command;
     ///
-    /// Command::new("ls")
-    ///         .current_dir("/bin")
-    ///         .unwrap();
-    /// ```
+    /// const SIGTERM: i32 = 15;
     ///
-    /// [`canonicalize`]: std::fs::canonicalize()
-    pub fn current_dir<P: AsRef<path::Path>>(&mut self, dir: P) -> &mut Self {
-        self.cmd.current_dir(dir);
+    /// Command::new("sh")
+    ///     .args(["-c", "trap 'echo shutting down; exit 0' TERM; while :; do sleep 0.1; done"])
+    ///     .terminate_after(std::time::Duration::from_millis(200), SIGTERM)
+    ///     .assert()
+    ///     .success()
+    ///     .stdout("shutting down\n");
+    /// ```
+    pub fn terminate_after(&mut self, after: std::time::Duration, signal: i32) -> &mut Self {
+        self.terminate = Some((after, signal));
         self
     }
 
-    /// Executes the `Command` as a child process, waiting for it to finish and collecting all of its
-    /// output.
+    /// Start the child in a new process group.
+    fn new_process_group(&mut self) {
+        if self.process_group_hook {
+            return;
+        }
+        // SAFETY: `setpgid` is async-signal-safe
+        unsafe {
+            self.pre_exec(|| {
+                if libc::setpgid(0, 0) == 0 {
+                    Ok(())
+                } else {
+                    Err(io::Error::last_os_error())
+                }
+            });
+        }
+        self.process_group_hook = true;
+    }
+
+    /// Attach the child's `stdout` to a pseudo-terminal, so it takes the code path for
+    /// interactive use, like colors and progress bars.
     ///
-    /// By default, stdout and stderr are captured (and used to provide the resulting output).
-    /// Stdin is not inherited from the parent and any attempt by the child process to read from
-    /// the stdin stream will result in the stream immediately closing.
+    /// The terminal is 80 columns by 24 rows and passes output through unchanged, rather than
+    /// translating `\n` to `\r\n`.  `stdin` and `stderr` are still pipes, and the terminal
+    /// isn't made the child's controlling terminal.  So keystrokes can't be sent through the
+    /// terminal, like to test line editing, and it can't be resized, like to test `SIGWINCH`.
+    ///
+    /// Requires the `pty` feature.
     ///
     /// # Examples
     ///
-    /// ```should_panic
+    /// ```rust
     /// use assert_cmd::Command;
-    /// use std::io::{self, Write};
-    /// let output = Command::new("/bin/cat")
-    ///                      .arg("file.txt")
-    ///                      .output()
-    ///                      .expect("failed to execute process");
-    ///
-    /// println!("status: {}", output.status);
-    /// io::stdout().write_all(&output.stdout).unwrap();
-    /// io::stderr().write_all(&output.stderr).unwrap();
     ///
-    /// assert!(output.status.success());
+    /// Command::new("sh")
+    ///     .args(["-c", "test -t 1 && echo interactive"])
+    ///     .pty(true)
+    ///     .assert()
+    ///     .stdout("interactive\n");
     /// ```
-    pub fn output(&mut self) -> io::Result<process::Output> {
-        let spawn = self.spawn()?;
-        Self::wait_with_input_output(spawn, self.stdin.clone(), self.timeout)
+    #[cfg(feature = "pty")]
+    pub fn pty(&mut self, yes: bool) -> &mut Self {
+        self.pty = yes;
+        self
     }
 
-    /// If `input`, write it to `child`'s stdin while also reading `child`'s
-    /// stdout and stderr, then wait on `child` and return its status and output.
-    ///
-    /// This was lifted from `std::process::Child::wait_with_output` and modified
-    /// to also write to stdin.
-    fn wait_with_input_output(
-        mut child: process::Child,
-        input: Option<Vec<u8>>,
-        timeout: Option<std::time::Duration>,
-    ) -> io::Result<process::Output> {
-        let stdin = input.and_then(|i| {
-            child
-                .stdin
-                .take()
-                .map(|mut stdin| std::thread::spawn(move || stdin.write_all(&i)))
-        });
-        fn read<R>(mut input: R) -> std::thread::JoinHandle<io::Result<Vec<u8>>>
-        where
-            R: Read + Send + 'static,
-        {
-            std::thread::spawn(move || {
-                let mut ret = Vec::new();
-                input.read_to_end(&mut ret).map(|_| ret)
-            })
-        }
-        let stdout = child.stdout.take().map(read);
-        let stderr = child.stderr.take().map(read);
-
-        // Finish writing stdin before waiting, because waiting drops stdin.
-        stdin.and_then(|t| t.join().unwrap().ok());
-        let status = if let Some(timeout) = timeout {
-            wait_timeout::ChildExt::wait_timeout(&mut child, timeout)
-                .transpose()
-                .unwrap_or_else(|| {
-                    let _ = child.kill();
-                    child.wait()
-                })
+    fn restrict_handles(&mut self) {
+        let bound = if self.inherit_handles {
+            0
         } else {
-            child.wait()
-        }?;
-
-        let stdout = stdout
-            .and_then(|t| t.join().unwrap().ok())
-            .unwrap_or_default();
-        let stderr = stderr
-            .and_then(|t| t.join().unwrap().ok())
-            .unwrap_or_default();
-
-        Ok(process::Output {
-            status,
-            stdout,
-            stderr,
-        })
-    }
-
-    fn spawn(&mut self) -> io::Result<process::Child> {
-        // stdout/stderr should only be piped for `output` according to `process::Command::new`.
-        self.cmd.stdin(process::Stdio::piped());
-        self.cmd.stdout(process::Stdio::piped());
-        self.cmd.stderr(process::Stdio::piped());
-
-        self.cmd.spawn()
+            crate::fds::upper_bound()
+        };
+        if self.handle_bound.is_none() && bound != 0 {
+            let shared = std::sync::Arc::new(std::sync::atomic::AtomicI32::new(0));
+            let hook = shared.clone();
+            // SAFETY: `fds::close_on_exec` is async-signal-safe, as is reading an atomic
+            unsafe {
+                self.pre_exec(move || {
+                    crate::fds::close_on_exec(hook.load(std::sync::atomic::Ordering::SeqCst))
+                });
+            }
+            self.handle_bound = Some(shared);
+        }
+        if let Some(shared) = &self.handle_bound {
+            shared.store(bound, std::sync::atomic::Ordering::SeqCst);
+        }
     }
 }
 
@@ -510,9 +3155,11 @@ impl From<process::Command> for Command {
     }
 }
 
-impl<'c> OutputOkExt for &'c mut Command {
+impl OutputOkExt for &mut Command {
     fn ok(self) -> OutputResult {
-        let output = self.output().map_err(OutputError::with_cause)?;
+        let output = self
+            .output()
+            .map_err(|err| OutputError::with_cause(crate::exec::SpawnError::new(&self.cmd, err)))?;
         if output.status.success() {
             Ok(output)
         } else {
@@ -533,7 +3180,7 @@ impl<'c> OutputOkExt for &'c mut Command {
                     panic!(
                         "Completed successfully:\ncommand=`{:?}`\nstdin=```{}```\nstdout=```{}```",
                         self.cmd,
-                        DebugBytes::new(&stdin),
+                        DebugBytes::new(stdin),
                         DebugBytes::new(&output.stdout)
                     )
                 } else {
@@ -549,19 +3196,471 @@ impl<'c> OutputOkExt for &'c mut Command {
     }
 }
 
-impl<'c> OutputAssertExt for &'c mut Command {
+impl OutputAssertExt for &mut Command {
     fn assert(self) -> Assert {
-        let output = match self.output() {
-            Ok(output) => output,
+        let mut attempts = Vec::new();
+        let mut assert = self.assert_once();
+        while attempts.len() < self.retries {
+            let retry = match self.retry_if {
+                Some(ref retry_if) => (retry_if.0)(&assert),
+                None => !assert.get_output().status.success(),
+            };
+            if !retry {
+                break;
+            }
+            let output = assert.get_output();
+            attempts.push(format!(
+                "{}\nstdout=```{}```\nstderr=```{}```",
+                output.status,
+                DebugBytes::new(&output.stdout),
+                DebugBytes::new(&output.stderr)
+            ));
+            assert.discard();
+            assert = self.assert_once();
+        }
+        let total = attempts.len() + 1;
+        for (i, attempt) in attempts.into_iter().enumerate() {
+            assert =
+                assert.append_context("attempt", format!("{} of {}, {}", i + 1, total, attempt));
+        }
+        assert
+    }
+}
+
+impl Command {
+    fn assert_once(&mut self) -> Assert {
+        let dumps = self.request_dumps();
+        let run = match self.run(self.timeout, None) {
+            Ok(run) => run,
             Err(err) => {
-                panic!("Failed to spawn {:?}: {}", self, err);
+                panic!(
+                    "Failed to spawn {:?}: {}",
+                    self,
+                    crate::exec::SpawnError::new(&self.cmd, err)
+                );
             }
         };
-        let assert = Assert::new(output).append_context("command", format!("{:?}", self.cmd));
-        if let Some(stdin) = self.stdin.as_ref() {
-            assert.append_context("stdin", DebugBuffer::new(stdin.clone()))
+        let timed_out = if run.timed_out { self.timeout } else { None };
+        self.build_assert(run, dumps, timed_out)
+    }
+}
+
+impl Command {
+    fn request_dumps(&mut self) -> Dumps {
+        self.dump_env.clear();
+        let env = if self.env_dump {
+            let path = crate::temp::unique_path("assert_cmd-env");
+            self.dump_env.push((crate::env_dump::ENV_VAR, path.clone()));
+            Some(path)
+        } else {
+            None
+        };
+        #[cfg(unix)]
+        let signals = if self.signal_dump {
+            let path = crate::temp::unique_path("assert_cmd-signals");
+            self.dump_env.push((crate::signals::ENV_VAR, path.clone()));
+            Some(path)
+        } else {
+            None
+        };
+        let temp_dir = if self.isolated_temp_dir {
+            let path = crate::temp::create_dir("assert_cmd-tmp");
+            for var in &["TMPDIR", "TEMP", "TMP"] {
+                self.cmd.env(var, &path);
+            }
+            Some(path)
+        } else {
+            None
+        };
+        let home_dir = match self.home {
+            Some(Home::Unset) => {
+                for var in Home::env_vars() {
+                    self.cmd.env_remove(var);
+                }
+                None
+            }
+            Some(home) => {
+                let path = crate::temp::create_dir("assert_cmd-home");
+                if home == Home::ReadOnly {
+                    set_readonly(&path, true);
+                }
+                for var in Home::env_vars() {
+                    self.cmd.env(var, &path);
+                }
+                Some(path)
+            }
+            None => None,
+        };
+        let spool = if self.capture_to_files {
+            let spool = std::sync::Arc::new(crate::spool::Spool::new());
+            self.spool = Some(spool.clone());
+            Some(spool)
+        } else {
+            None
+        };
+        let worktree = if self.guard_worktree {
+            let dir = self
+                .cmd
+                .get_current_dir()
+                .map(path::Path::to_owned)
+                .or_else(|| std::env::current_dir().ok());
+            dir.and_then(|dir| crate::worktree::Snapshot::new(&dir))
+        } else {
+            None
+        };
+        Dumps {
+            env,
+            #[cfg(unix)]
+            signals,
+            temp_dir,
+            home_dir,
+            spool,
+            worktree,
+        }
+    }
+
+    /// The filters applied to each run's output.
+    fn output_settings(&self) -> crate::settings::AssertSettings {
+        if self.strip_ansi {
+            let redactions = self.redactions.clone();
+            crate::settings::AssertSettings::new()
+                .strip_ansi()
+                .normalize(move |data| redactions.apply(data.to_vec()))
+        } else {
+            self.redactions.clone()
+        }
+    }
+
+    fn build_assert(
+        &self,
+        run: Run,
+        dumps: Dumps,
+        timed_out: Option<std::time::Duration>,
+    ) -> Assert {
+        let streamed = [
+            (
+                Stream::Stdout,
+                self.sinks.stdout.is_some(),
+                run.output.stdout.len(),
+            ),
+            (
+                Stream::Stderr,
+                self.sinks.stderr.is_some(),
+                run.output.stderr.len(),
+            ),
+        ];
+        let streamed: Vec<_> = streamed
+            .iter()
+            .filter(|(_, streaming, _)| *streaming)
+            .map(|(stream, _, kept)| {
+                let total: usize = run
+                    .chunks
+                    .iter()
+                    .filter(|c| c.stream == *stream)
+                    .map(|c| c.len)
+                    .sum();
+                (*stream, *kept, total)
+            })
+            .filter(|(_, kept, total)| kept < total)
+            .collect();
+        let mut recording = Recording::new(&self.cmd);
+        recording.stdin = self.stdin.clone();
+        recording.chunks = run.chunks;
+        if !run.replayed {
+            recording.spawn = Some(run.spawn);
+            recording.duration = Some(run.duration);
+        }
+        let duration = if run.replayed {
+            None
+        } else {
+            Some(run.duration)
+        };
+        let replayed = run.replayed;
+        let terminated = run.terminated;
+        let mut output = run.output;
+        let combined = if self.capture == Streams::Combined && dumps.spool.is_none() {
+            Some(std::mem::take(&mut output.stdout))
         } else {
-            assert
+            None
+        };
+        let mut assert = Assert::new(output)
+            .set_combined(combined)
+            .set_recording(recording)
+            .set_samples(run.samples)
+            .set_resource_usage(run.usage)
+            .set_stdin_unread(run.stdin_unread)
+            .with_settings(&self.output_settings())
+            .set_report(crate::report::entry(&self.cmd, duration))
+            .append_context("command", format!("{:?}", self.cmd));
+        for (name, context) in &self.context {
+            assert = assert.append_context(name, context.clone());
+        }
+        if let Some(cassette) = self.cassette.as_ref().filter(|_| replayed) {
+            assert = assert.append_context("replayed from", cassette.display().to_string());
+        }
+        if let Some(terminated) = terminated {
+            assert = assert.append_context("terminated", terminated);
+        }
+        if let Some(timeout) = timed_out {
+            assert = assert
+                .set_timed_out(timeout)
+                .append_context("timed out after", format!("{:?}", timeout));
+        }
+        if self.capture != Streams::Both {
+            assert = assert.append_context("captured", self.capture);
+        }
+        for (stream, kept, total) in streamed {
+            assert = assert.append_context(
+                "streamed",
+                format!("{}, kept the last {} of {} bytes", stream, kept, total),
+            );
+        }
+        #[cfg(all(unix, feature = "pty"))]
+        {
+            if self.pty {
+                assert = assert.append_context("terminal", "stdout");
+            }
+        }
+        if let Some(spool) = dumps.spool {
+            for (stream, name) in [("stdout", "stdout file"), ("stderr", "stderr file")] {
+                if spool.path(stream).exists() {
+                    assert = assert.append_context(name, spool.excerpt(stream));
+                }
+            }
+            assert = assert.set_spool(spool);
+        }
+        if let Some(path) = dumps.env {
+            assert = assert.set_child_env(crate::env_dump::take(&path));
+        }
+        #[cfg(unix)]
+        {
+            if let Some(path) = dumps.signals {
+                assert = assert.set_child_signals(crate::signals::take(&path));
+            }
         }
+        if let Some(path) = dumps.temp_dir {
+            assert = assert.set_temp_leftovers(crate::temp::take_dir(&path));
+        }
+        if let Some(path) = dumps.home_dir {
+            set_readonly(&path, false);
+            crate::temp::take_dir(&path);
+        }
+        if let Some(home) = self.home {
+            assert = assert.append_context("home", home);
+        }
+        assert = assert
+            .set_cwd(self.cmd.get_current_dir().map(path::Path::to_owned))
+            .set_invocation(crate::transcript::Invocation::new(
+                &self.cmd,
+                self.env_cleared,
+            ));
+        if let Some(dir) = self.temp_cwd.as_ref() {
+            assert = assert.set_temp_cwd(dir.clone(), self.keep_temp_cwd);
+        }
+        if let Some((written, len)) = run.stdin_unread {
+            assert = assert.append_context(
+                "stdin unread",
+                format!(
+                    "child exited after {} of {} bytes were written, see `Assert::expect_early_exit` if that's expected",
+                    written, len
+                ),
+            );
+        }
+        if let Some(stdin) = self.stdin.as_ref() {
+            assert = assert.append_context("stdin", DebugBuffer::new(stdin.clone()));
+        }
+        #[cfg(feature = "log")]
+        crate::logging::exited(&self.cmd, assert.get_output(), duration);
+        for hook in crate::hooks::after_output_hooks(&self.hooks.after_output) {
+            hook(&assert);
+        }
+        if let Some(before) = dumps.worktree {
+            if let Some(after) = crate::worktree::Snapshot::new(before.root()) {
+                let changes = before.changes(&after);
+                if !changes.is_empty() {
+                    assert.fail(format_args!(
+                        "Command modified tracked files in `{}`:\n  {}",
+                        before.root().display(),
+                        changes.join("\n  ")
+                    ));
+                }
+            }
+        }
+        assert
+    }
+}
+
+type Sink = std::sync::Arc<std::sync::Mutex<dyn FnMut(&[u8]) + Send>>;
+
+/// Where to stream output as it is read, see [`Command::stream_stdout`].
+#[derive(Clone, Default)]
+struct Sinks {
+    stdout: Option<Sink>,
+    stderr: Option<Sink>,
+    /// See [`Command::tee`].
+    tee: bool,
+}
+
+impl Sinks {
+    fn is_streaming(&self) -> bool {
+        self.stdout.is_some() || self.stderr.is_some()
+    }
+}
+
+/// How `stdin` is written, see [`Command::write_stdin_chunks`] and [`Command::hold_stdin_open`].
+#[derive(Clone, Debug, Default)]
+struct StdinPacing {
+    /// Where each chunk after the first starts in the input.
+    breaks: Vec<usize>,
+    /// How long to wait before writing each chunk after the first.
+    delay: std::time::Duration,
+    hold_open: bool,
+}
+
+impl StdinPacing {
+    fn is_paced(&self) -> bool {
+        !self.breaks.is_empty() || self.hold_open
+    }
+}
+
+/// See [`Command::before_spawn`] and [`Command::after_output`].
+#[derive(Clone, Default)]
+struct Hooks {
+    before_spawn: Vec<crate::hooks::BeforeSpawn>,
+    after_output: Vec<crate::hooks::AfterOutput>,
+}
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hooks")
+            .field("before_spawn", &self.before_spawn.len())
+            .field("after_output", &self.after_output.len())
+            .finish()
+    }
+}
+
+/// When to retry, see [`Command::retry_if`].
+#[derive(Clone)]
+struct RetryIf(std::sync::Arc<dyn Fn(&Assert) -> bool + Send + Sync>);
+
+impl fmt::Debug for RetryIf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RetryIf").finish()
+    }
+}
+
+impl fmt::Debug for Sinks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sinks")
+            .field("stdout", &self.stdout.is_some())
+            .field("stderr", &self.stderr.is_some())
+            .field("tee", &self.tee)
+            .finish()
+    }
+}
+
+/// Print a stream to the test's own, through the test harness's capturing.
+struct Mirror {
+    stream: Stream,
+    /// The last, incomplete line.
+    pending: Vec<u8>,
+}
+
+impl Mirror {
+    fn new(stream: Stream) -> Self {
+        Self {
+            stream,
+            pending: Vec::new(),
+        }
+    }
+
+    fn write(&mut self, data: &[u8]) {
+        self.pending.extend_from_slice(data);
+        if let Some(end) = self.pending.iter().rposition(|b| *b == b'\n') {
+            let lines: Vec<_> = self.pending.drain(..=end).collect();
+            self.print(&lines);
+        }
+    }
+
+    fn print(&self, data: &[u8]) {
+        let text = String::from_utf8_lossy(data);
+        match self.stream {
+            Stream::Stdout => print!("{}", text),
+            Stream::Stderr => eprint!("{}", text),
+        }
+    }
+}
+
+impl Drop for Mirror {
+    fn drop(&mut self) {
+        if !self.pending.is_empty() {
+            self.print(&self.pending);
+        }
+    }
+}
+
+/// Files the child was asked to dump its state to.
+pub(crate) struct Dumps {
+    env: Option<path::PathBuf>,
+    #[cfg(unix)]
+    signals: Option<path::PathBuf>,
+    /// Not a dump, but similarly inspected after the child exits.
+    temp_dir: Option<path::PathBuf>,
+    /// Not a dump, but similarly cleaned up after the child exits.
+    home_dir: Option<path::PathBuf>,
+    /// Not a dump, but similarly inspected after the child exits.
+    spool: Option<std::sync::Arc<crate::spool::Spool>>,
+    /// Not a dump, but similarly compared after the child exits.
+    worktree: Option<crate::worktree::Snapshot>,
+}
+
+/// The timeout from [`TIMEOUT_ENV`], if set.
+///
+/// # Panics
+///
+/// If the variable isn't a non-negative number of seconds.
+fn default_timeout() -> Option<std::time::Duration> {
+    static TIMEOUT: std::sync::Mutex<Option<Option<std::time::Duration>>> =
+        std::sync::Mutex::new(None);
+    let mut cached = TIMEOUT.lock().unwrap_or_else(|err| err.into_inner());
+    *cached.get_or_insert_with(|| {
+        let secs = std::env::var_os(TIMEOUT_ENV)?;
+        let secs = secs.to_string_lossy();
+        match secs.trim().parse::<f64>() {
+            Ok(secs) if secs.is_finite() && 0.0 <= secs => {
+                Some(std::time::Duration::from_secs_f64(secs))
+            }
+            _ => panic!(
+                "Invalid {}=`{}`, expected a number of seconds",
+                TIMEOUT_ENV, secs
+            ),
+        }
+    })
+}
+
+fn set_readonly(path: &path::Path, readonly: bool) {
+    if let Ok(metadata) = std::fs::metadata(path) {
+        let mut permissions = metadata.permissions();
+        permissions.set_readonly(readonly);
+        let _ = std::fs::set_permissions(path, permissions);
     }
 }
+
+/// Everything observed while running the child.
+#[derive(Clone)]
+pub(crate) struct Run {
+    output: process::Output,
+    timed_out: bool,
+    chunks: Vec<Chunk>,
+    /// How long spawning the child took.
+    spawn: std::time::Duration,
+    duration: std::time::Duration,
+    samples: Option<Vec<Sample>>,
+    usage: Option<crate::resources::ResourceUsage>,
+    /// Bytes of stdin written, out of the total, if the child closed stdin early.
+    stdin_unread: Option<(usize, usize)>,
+    /// Whether this was replayed from a [`Command::cassette`], rather than run.
+    replayed: bool,
+    /// The [`Command::terminate_after`] signal, if it was sent.
+    terminated: Option<crate::child::Terminated>,
+}
@@ -0,0 +1,194 @@
+//! Assert how labeled runs relate to each other.
+//!
+//! Collect [`Assert`]s under labels, like before and after a migration, then check relations
+//! between them.  A failure reports both runs.
+//!
+//! ```rust,no_run
+//! use assert_cmd::compare::Comparison;
+//! use assert_cmd::Command;
+//!
+//! let mut runs = Comparison::new();
+//! runs.insert("before-migration", Command::cargo_bin("bin_fixture").unwrap().assert())
+//!     .insert("after-migration", Command::cargo_bin("bin_fixture").unwrap().assert());
+//! runs.same_stdout("before-migration", "after-migration")
+//!     .same_code("before-migration", "after-migration");
+//! ```
+//!
+//! [`Assert`]: crate::assert::Assert
+
+use std::fmt;
+
+use crate::assert::Assert;
+
+/// A set of labeled [`Assert`]s.
+///
+/// See the [`compare` module documentation][crate::compare].
+#[derive(Debug, Default)]
+pub struct Comparison {
+    runs: Vec<(String, Assert)>,
+}
+
+impl Comparison {
+    /// Create an empty `Comparison`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `assert` under `label`, replacing any run with the same label.
+    pub fn insert<L: Into<String>>(&mut self, label: L, assert: Assert) -> &mut Self {
+        let label = label.into();
+        self.runs.retain(|(existing, _)| *existing != label);
+        self.runs.push((label, assert));
+        self
+    }
+
+    /// The run with `label`, if any.
+    pub fn get(&self, label: &str) -> Option<&Assert> {
+        self.runs
+            .iter()
+            .find(|(existing, _)| existing == label)
+            .map(|(_, assert)| assert)
+    }
+
+    /// Ensure runs `a` and `b` wrote the same `stdout`.
+    ///
+    /// # Panics
+    ///
+    /// If either label wasn't inserted.
+    pub fn same_stdout(&self, a: &str, b: &str) -> &Self {
+        self.same_stream("stdout", a, b, |assert| &assert.get_output().stdout)
+    }
+
+    /// Ensure runs `a` and `b` wrote the same `stderr`.
+    ///
+    /// # Panics
+    ///
+    /// If either label wasn't inserted.
+    pub fn same_stderr(&self, a: &str, b: &str) -> &Self {
+        self.same_stream("stderr", a, b, |assert| &assert.get_output().stderr)
+    }
+
+    fn same_stream(&self, stream: &str, a: &str, b: &str, data: fn(&Assert) -> &Vec<u8>) -> &Self {
+        let (first, second) = (self.run(a), self.run(b));
+        if data(first) != data(second) {
+            self.fail(
+                a,
+                b,
+                format_args!(
+                    "Unexpected {}, `{}` and `{}` differ\n{}",
+                    stream,
+                    a,
+                    b,
+                    crate::diff::unified(
+                        &String::from_utf8_lossy(data(first)),
+                        &String::from_utf8_lossy(data(second)),
+                        crate::diff::color()
+                    )
+                ),
+            );
+        }
+        self
+    }
+
+    /// Ensure runs `a` and `b` exited with the same code.
+    ///
+    /// # Panics
+    ///
+    /// If either label wasn't inserted.
+    pub fn same_code(&self, a: &str, b: &str) -> &Self {
+        let (first, second) = (code(self.run(a)), code(self.run(b)));
+        if first != second {
+            self.fail(
+                a,
+                b,
+                format_args!(
+                    "Unexpected code, `{}` exited with {} but `{}` with {}",
+                    a, first, b, second
+                ),
+            );
+        }
+        self
+    }
+
+    /// Ensure runs `a` and `b` exited with different codes.
+    ///
+    /// # Panics
+    ///
+    /// If either label wasn't inserted.
+    pub fn codes_differ(&self, a: &str, b: &str) -> &Self {
+        let (first, second) = (code(self.run(a)), code(self.run(b)));
+        if first == second {
+            self.fail(
+                a,
+                b,
+                format_args!(
+                    "Unexpected code, `{}` and `{}` both exited with {}",
+                    a, b, first
+                ),
+            );
+        }
+        self
+    }
+
+    /// Ensure run `fast` took less time than run `slow`.
+    ///
+    /// Both runs must come from [`Command`], which records how long they took.
+    ///
+    /// # Panics
+    ///
+    /// If either label wasn't inserted.
+    ///
+    /// [`Command`]: crate::cmd::Command
+    pub fn faster(&self, fast: &str, slow: &str) -> &Self {
+        let duration = |label: &str| match self.run(label).transcript().duration() {
+            Some(duration) => duration,
+            None => self.fail(
+                fast,
+                slow,
+                format_args!(
+                    "No duration recorded for `{}`, run it with `Command`",
+                    label
+                ),
+            ),
+        };
+        let (fast_duration, slow_duration) = (duration(fast), duration(slow));
+        if slow_duration <= fast_duration {
+            self.fail(
+                fast,
+                slow,
+                format_args!(
+                    "`{}` wasn't faster than `{}`, took {:?} vs {:?}",
+                    fast, slow, fast_duration, slow_duration
+                ),
+            );
+        }
+        self
+    }
+
+    fn run(&self, label: &str) -> &Assert {
+        self.get(label).unwrap_or_else(|| {
+            let labels: Vec<_> = self.runs.iter().map(|(label, _)| label.as_str()).collect();
+            panic!(
+                "No run labeled `{}`, expected one of `{}`",
+                label,
+                labels.join("`, `")
+            )
+        })
+    }
+
+    fn fail(&self, a: &str, b: &str, headline: impl fmt::Display) -> ! {
+        let dump = [a, b]
+            .iter()
+            .map(|label| format!("run=`{}`\n{}", label, self.run(label)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        crate::aggregate::fail(headline.to_string(), dump)
+    }
+}
+
+fn code(assert: &Assert) -> String {
+    match assert.get_output().status.code() {
+        Some(code) => code.to_string(),
+        None => "<interrupted>".to_owned(),
+    }
+}
@@ -0,0 +1,254 @@
+//! Tests that double as readable transcripts.
+//!
+//! A transcript is a `console` code block of commands, each prefixed with `$ ` and followed by
+//! the `stdout` it is expected to print:
+//!
+//! ```console
+//! $ stdout=hello bin_fixture
+//! hello
+//! $ exit=3 bin_fixture --verbose
+//! ...
+//! ? 3
+//! ```
+//!
+//! - Commands are split on whitespace, respecting `"` and `'` quotes.  Leading `NAME=value`
+//!   words set environment variables, like in a shell.
+//! - Binaries of the current crate are found like [`Command::cargo_bin`]; other programs are
+//!   looked up on `PATH`.
+//! - Within an expected line, `[..]` matches any text.  A line of just `...` matches any number
+//!   of lines, like in a [`pattern`][crate::pattern::pattern()].
+//! - Commands must succeed, unless their output ends with `? <code>`, the expected exit code.
+//!
+//! Use [`console_test!`][crate::console_test] to turn a test's doc comment into a test, or
+//! [`assert_transcript`] to check a transcript from elsewhere.
+//!
+//! [`Command::cargo_bin`]: crate::cmd::Command::cargo_bin()
+
+use crate::cmd::Command;
+
+/// Run every command in `text`'s `console` code blocks, ensuring each prints what follows it.
+///
+/// # Panics
+///
+/// If `text` has no `console` blocks, a block is malformed, or a command doesn't behave as
+/// transcribed.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// let readme = std::fs::read_to_string("README.md").unwrap();
+/// assert_cmd::console::assert_transcript(&readme);
+/// ```
+pub fn assert_transcript(text: &str) {
+    let steps = parse(text).unwrap_or_else(|err| panic!("Malformed transcript: {}", err));
+    if steps.is_empty() {
+        panic!("Malformed transcript: no ```console blocks");
+    }
+    for step in &steps {
+        step.run();
+    }
+}
+
+/// Check the transcript in a [`console_test!`][crate::console_test]'s doc comment.
+#[doc(hidden)]
+pub fn assert_doc(lines: &[&str]) {
+    let lines: Vec<_> = lines
+        .iter()
+        .map(|line| line.strip_prefix(' ').unwrap_or(line))
+        .collect();
+    assert_transcript(&lines.join("\n"));
+}
+
+/// One transcribed command.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Step {
+    line: String,
+    env: Vec<(String, String)>,
+    program: String,
+    args: Vec<String>,
+    expected: Vec<String>,
+    code: i32,
+}
+
+impl Step {
+    fn run(&self) {
+        let mut cmd = command(&self.program);
+        cmd.args(&self.args);
+        for (name, value) in &self.env {
+            cmd.env(name, value);
+        }
+        let assert = cmd
+            .assert()
+            .append_context("transcript", format!("$ {}", self.line))
+            .code(self.code);
+        // Transcripts only show `stdout`.
+        assert.discard();
+        let stdout = String::from_utf8_lossy(&assert.get_output().stdout).replace("\r\n", "\n");
+        let stdout = stdout.strip_suffix('\n').unwrap_or(&stdout);
+        let actual: Vec<_> = if stdout.is_empty() {
+            Vec::new()
+        } else {
+            stdout.split('\n').collect()
+        };
+        let expected: Vec<_> = self.expected.iter().map(String::as_str).collect();
+        if !crate::pattern::lines_match(&expected, &actual) {
+            assert.fail(format_args!(
+                "Unexpected stdout, expected:\n{}",
+                self.expected.join("\n")
+            ));
+        }
+    }
+}
+
+/// A [`Command`] for `program`, a binary of the current crate or else a program on `PATH`.
+pub(crate) fn command(program: &str) -> Command {
+    let bin = crate::cargo::cargo_bin(program);
+    if bin.is_file() {
+        Command::new(bin)
+    } else {
+        Command::new(program)
+    }
+}
+
+fn parse(text: &str) -> Result<Vec<Step>, String> {
+    let mut steps = Vec::new();
+    let mut in_block = false;
+    for (i, line) in text.lines().enumerate() {
+        let fence = line.trim_start();
+        if !in_block {
+            in_block =
+                fence.starts_with("```") && fence.trim_start_matches('`').trim() == "console";
+            continue;
+        }
+        if fence.starts_with("```") {
+            in_block = false;
+        } else if let Some(command) = line.strip_prefix("$ ") {
+            steps.push(parse_command(command).map_err(|err| format!("line {}: {}", i + 1, err))?);
+        } else {
+            let step = steps
+                .last_mut()
+                .ok_or_else(|| format!("line {}: output before the first `$ ` command", i + 1))?;
+            step.expected.push(line.to_owned());
+        }
+    }
+    if in_block {
+        return Err("unterminated ```console block".to_owned());
+    }
+    for step in &mut steps {
+        if let Some(code) = step
+            .expected
+            .last()
+            .and_then(|line| line.strip_prefix("? "))
+        {
+            step.code = code
+                .trim()
+                .parse()
+                .map_err(|_| format!("invalid exit code `{}` for `$ {}`", code, step.line))?;
+            step.expected.pop();
+        }
+    }
+    Ok(steps)
+}
+
+fn parse_command(line: &str) -> Result<Step, String> {
+    let mut words = split_words(line)?.into_iter().peekable();
+    let mut env = Vec::new();
+    while let Some(word) = words.peek() {
+        match word.find('=') {
+            Some(i) if 0 < i => {
+                env.push((word[..i].to_owned(), word[i + 1..].to_owned()));
+                words.next();
+            }
+            _ => break,
+        }
+    }
+    let program = words.next().ok_or("no program")?;
+    Ok(Step {
+        line: line.to_owned(),
+        env,
+        program,
+        args: words.collect(),
+        expected: Vec::new(),
+        code: 0,
+    })
+}
+
+pub(crate) fn split_words(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word = None;
+    let mut quote = None;
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => word.get_or_insert_with(String::new).push(c),
+            (None, '"') | (None, '\'') => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => words.extend(word.take()),
+            (None, c) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if let Some(q) = quote {
+        return Err(format!("unterminated {} quote", q));
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// Turn each function's doc comment, a [transcript][crate::console], into a test.
+///
+/// # Examples
+///
+/// ````rust,no_run
+/// assert_cmd::console_test! {
+///     /// Greets, even when failing.
+///     ///
+///     /// ```console
+///     /// $ stdout=hello exit=1 bin_fixture
+///     /// hello
+///     /// ? 1
+///     /// ```
+///     fn greets() {}
+/// }
+/// ````
+#[macro_export]
+macro_rules! console_test {
+    ($($(#[doc = $doc:expr])+ fn $name:ident() {})*) => {
+        $(
+            $(#[doc = $doc])+
+            #[test]
+            fn $name() {
+                $crate::console::assert_doc(&[$($doc),+]);
+            }
+        )*
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_steps() {
+        let steps = parse(
+            "Intro\n```console\n$ A=1 tool \"two words\" 'x'\nout\n? 2\n$ tool\n```\n$ ignored\n",
+        )
+        .unwrap();
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].env, vec![("A".to_owned(), "1".to_owned())]);
+        assert_eq!(steps[0].program, "tool");
+        assert_eq!(steps[0].args, vec!["two words", "x"]);
+        assert_eq!(steps[0].expected, vec!["out"]);
+        assert_eq!(steps[0].code, 2);
+        assert!(steps[1].expected.is_empty());
+        assert_eq!(steps[1].code, 0);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(parse("```console\nout\n```").is_err());
+        assert!(parse("```console\n$ tool \"open\n```").is_err());
+        assert!(parse("```console\n$ tool\n").is_err());
+    }
+}
@@ -0,0 +1,126 @@
+//! The environment instrumented children need, for [`Command::coverage`].
+//!
+//! [`Command::coverage`]: crate::cmd::Command::coverage()
+
+use std::env;
+use std::ffi;
+use std::path;
+use std::process;
+
+/// Where `-C instrument-coverage` binaries write their profiles.
+const PROFILE_ENV: &str = "LLVM_PROFILE_FILE";
+
+/// The profile a binary writes without [`PROFILE_ENV`], in its own working directory.
+const DEFAULT_PROFILE: &str = "default_%m_%p.profraw";
+
+/// Sanitizer runtime options, passed through as-is.
+const SANITIZER_ENV: [&str; 5] = [
+    "ASAN_OPTIONS",
+    "LSAN_OPTIONS",
+    "MSAN_OPTIONS",
+    "TSAN_OPTIONS",
+    "UBSAN_OPTIONS",
+];
+
+/// Set the coverage and sanitizer environment from the test process on `cmd`, except for
+/// variables `cmd` sets itself.
+pub(crate) fn apply(cmd: &mut process::Command) {
+    let explicit: Vec<ffi::OsString> = cmd
+        .get_envs()
+        .filter(|(_, value)| value.is_some())
+        .map(|(key, _)| key.to_owned())
+        .collect();
+    let cwd = env::current_dir().unwrap_or_default();
+    for (key, value) in vars(&cwd, |key| env::var_os(key)) {
+        if !explicit.iter().any(|k| k == key) {
+            cmd.env(key, value);
+        }
+    }
+}
+
+/// The variables to set, given the test's working directory and environment.
+fn vars(
+    cwd: &path::Path,
+    var: impl Fn(&str) -> Option<ffi::OsString>,
+) -> Vec<(&'static str, ffi::OsString)> {
+    let profile = var(PROFILE_ENV).filter(|profile| !profile.is_empty());
+    let profile = profile
+        .as_deref()
+        .unwrap_or_else(|| ffi::OsStr::new(DEFAULT_PROFILE));
+    let mut vars = vec![(PROFILE_ENV, profile_file(cwd, profile))];
+    vars.extend(
+        SANITIZER_ENV
+            .iter()
+            .filter_map(|key| var(key).map(|value| (*key, value))),
+    );
+    vars
+}
+
+/// `profile`, anchored to the test's working directory rather than the child's, and unique to
+/// each child by its process ID, so concurrent and successive runs don't overwrite each other.
+fn profile_file(cwd: &path::Path, profile: &ffi::OsStr) -> ffi::OsString {
+    let mut profile = cwd.join(profile);
+    let unique = profile.file_name().map_or(false, |name| {
+        let name = name.to_string_lossy();
+        name.contains("%p") || name.contains("%m")
+    });
+    if !unique {
+        let stem = profile
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        let name = match profile.extension() {
+            Some(ext) => format!("{}-%p.{}", stem, ext.to_string_lossy()),
+            None => format!("{}-%p", stem),
+        };
+        profile.set_file_name(name);
+    }
+    profile.into_os_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn vars_with(env: &[(&str, &str)]) -> Vec<(&'static str, ffi::OsString)> {
+        let cwd = path::Path::new("/work");
+        vars(cwd, |key| {
+            env.iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| ffi::OsString::from(v))
+        })
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn profile_files() {
+        let cwd = path::Path::new("/work");
+        let cases = [
+            ("default_%m_%p.profraw", "/work/default_%m_%p.profraw"),
+            ("cov/test.profraw", "/work/cov/test-%p.profraw"),
+            ("/cov/test-%p-%8m.profraw", "/cov/test-%p-%8m.profraw"),
+            ("/cov/test", "/cov/test-%p"),
+        ];
+        for (profile, expected) in cases.iter() {
+            assert_eq!(
+                profile_file(cwd, ffi::OsStr::new(profile)),
+                ffi::OsString::from(expected),
+                "{}",
+                profile
+            );
+        }
+    }
+
+    #[test]
+    fn passes_through_sanitizers() {
+        let vars = vars_with(&[
+            ("ASAN_OPTIONS", "detect_leaks=1"),
+            ("LLVM_PROFILE_FILE", ""),
+            ("PAGER", "less"),
+        ]);
+        let keys: Vec<_> = vars.iter().map(|(key, _)| *key).collect();
+        assert_eq!(keys, ["LLVM_PROFILE_FILE", "ASAN_OPTIONS"]);
+        assert_eq!(vars[1].1, "detect_leaks=1");
+    }
+}
@@ -0,0 +1,291 @@
+//! Line-based diffs for failure messages.
+
+use std::fmt::Write;
+
+/// Unchanged lines shown around each change.
+const CONTEXT: usize = 3;
+
+/// The most cells the line-matching table may have; larger changes are shown as wholesale
+/// replacements rather than spending quadratic time and memory on them.
+const MAX_CELLS: usize = 4_000_000;
+
+/// Whether diffs are colored: with the `color` feature, unless `NO_COLOR` is set.
+pub(crate) fn color() -> bool {
+    cfg!(feature = "color") && !matches!(std::env::var_os("NO_COLOR"), Some(v) if !v.is_empty())
+}
+
+/// Render the changes from `expected` to `actual` as a unified diff.
+pub(crate) fn unified(expected: &str, actual: &str, color: bool) -> String {
+    let old: Vec<_> = expected.split_inclusive('\n').collect();
+    let new: Vec<_> = actual.split_inclusive('\n').collect();
+    let ops = diff(&old, &new);
+
+    let mut out = String::new();
+    let paint = |code: &'static str| if color { code } else { "" };
+    let reset = paint("\x1b[0m");
+    let _ = writeln!(out, "{}--- expected", paint("\x1b[31m"));
+    let _ = writeln!(out, "+++ actual{}", reset);
+    for hunk in hunks(&ops) {
+        let ops = &ops[hunk.clone()];
+        let (old_start, new_start) = match ops[0] {
+            Op::Equal(o, n) => (o, n),
+            Op::Delete(o) => (o, new_position(&ops[1..]).unwrap_or(new.len())),
+            Op::Insert(n) => (old_position(&ops[1..]).unwrap_or(old.len()), n),
+        };
+        let old_len = ops.iter().filter(|op| !matches!(op, Op::Insert(_))).count();
+        let new_len = ops.iter().filter(|op| !matches!(op, Op::Delete(_))).count();
+        let _ = writeln!(
+            out,
+            "{}@@ -{} +{} @@{}",
+            paint("\x1b[36m"),
+            range(old_start, old_len),
+            range(new_start, new_len),
+            reset
+        );
+        for op in ops {
+            let (sign, line, code) = match *op {
+                Op::Equal(o, _) => (' ', old[o], ""),
+                Op::Delete(o) => ('-', old[o], "\x1b[31m"),
+                Op::Insert(n) => ('+', new[n], "\x1b[32m"),
+            };
+            let text = line.strip_suffix('\n').unwrap_or(line);
+            let (start, end) = if code.is_empty() {
+                ("", "")
+            } else {
+                (paint(code), reset)
+            };
+            let _ = writeln!(out, "{}{}{}{}", start, sign, text, end);
+            if !line.ends_with('\n') {
+                let _ = writeln!(out, "\\ No newline at end of file");
+            }
+        }
+    }
+    out
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Op {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+fn diff(old: &[&str], new: &[&str]) -> Vec<Op> {
+    let prefix = old.iter().zip(new).take_while(|(o, n)| o == n).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(o, n)| o == n)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    let mut ops: Vec<_> = (0..prefix).map(|i| Op::Equal(i, i)).collect();
+    if old_middle.len().saturating_mul(new_middle.len()) <= MAX_CELLS {
+        ops.extend(
+            lcs(old_middle, new_middle)
+                .into_iter()
+                .map(|op| offset(op, prefix)),
+        );
+    } else {
+        ops.extend((prefix..old.len() - suffix).map(Op::Delete));
+        ops.extend((prefix..new.len() - suffix).map(Op::Insert));
+    }
+    ops.extend((0..suffix).map(|i| Op::Equal(old.len() - suffix + i, new.len() - suffix + i)));
+    ops
+}
+
+fn offset(op: Op, by: usize) -> Op {
+    match op {
+        Op::Equal(o, n) => Op::Equal(o + by, n + by),
+        Op::Delete(o) => Op::Delete(o + by),
+        Op::Insert(n) => Op::Insert(n + by),
+    }
+}
+
+/// Match up lines by their longest common subsequence.
+fn lcs(old: &[&str], new: &[&str]) -> Vec<Op> {
+    let width = new.len() + 1;
+    // `table[i * width + j]` is the LCS length of `old[i..]` and `new[j..]`
+    let mut table = vec![0_u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            table[i * width + j] = if old[i] == new[j] {
+                table[(i + 1) * width + j + 1] + 1
+            } else {
+                table[(i + 1) * width + j].max(table[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::with_capacity(old.len() + new.len());
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            ops.push(Op::Equal(i, j));
+            i += 1;
+            j += 1;
+        } else if table[(i + 1) * width + j] >= table[i * width + j + 1] {
+            ops.push(Op::Delete(i));
+            i += 1;
+        } else {
+            ops.push(Op::Insert(j));
+            j += 1;
+        }
+    }
+    ops.extend((i..old.len()).map(Op::Delete));
+    ops.extend((j..new.len()).map(Op::Insert));
+    ops
+}
+
+/// The ranges of `ops` to show: each change with its context, merging close changes.
+fn hunks(ops: &[Op]) -> Vec<std::ops::Range<usize>> {
+    let mut hunks: Vec<std::ops::Range<usize>> = Vec::new();
+    for (i, op) in ops.iter().enumerate() {
+        if matches!(op, Op::Equal(..)) {
+            continue;
+        }
+        let start = i.saturating_sub(CONTEXT);
+        let end = (i + 1 + CONTEXT).min(ops.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.end => last.end = end,
+            _ => hunks.push(start..end),
+        }
+    }
+    hunks
+}
+
+fn old_position(ops: &[Op]) -> Option<usize> {
+    ops.iter().find_map(|op| match *op {
+        Op::Equal(o, _) | Op::Delete(o) => Some(o),
+        Op::Insert(_) => None,
+    })
+}
+
+fn new_position(ops: &[Op]) -> Option<usize> {
+    ops.iter().find_map(|op| match *op {
+        Op::Equal(_, n) | Op::Insert(n) => Some(n),
+        Op::Delete(_) => None,
+    })
+}
+
+/// A hunk header range, 1-based, like `diff -u`.
+fn range(start: usize, len: usize) -> String {
+    match len {
+        0 => format!("{},0", start),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{}", start + 1, len),
+    }
+}
+
+/// Where `actual` first differs from `expected`, as a 1-based line and column, or `None` if
+/// they're equal.
+pub(crate) fn first_mismatch(expected: &str, actual: &str) -> Option<String> {
+    let (mut line, mut column) = (1, 1);
+    let mut expected_chars = expected.chars();
+    let mut actual_chars = actual.chars();
+    loop {
+        let (e, a) = (expected_chars.next(), actual_chars.next());
+        if e != a {
+            let describe = |c: Option<char>| match c {
+                Some(c) => format!("{:?}", c),
+                None => "end of output".to_owned(),
+            };
+            return Some(format!(
+                "line {}, column {}: expected {}, found {}",
+                line,
+                column,
+                describe(e),
+                describe(a)
+            ));
+        }
+        match e {
+            Some('\n') => {
+                line += 1;
+                column = 1;
+            }
+            Some(_) => column += 1,
+            None => return None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn first_mismatches() {
+        assert_eq!(first_mismatch("a\nb\n", "a\nb\n"), None);
+        assert_eq!(
+            first_mismatch("one\ntwo\n", "one\ntoo\n").unwrap(),
+            "line 2, column 2: expected 'w', found 'o'"
+        );
+        assert_eq!(
+            first_mismatch("hello\n", "hello").unwrap(),
+            "line 1, column 6: expected '\\n', found end of output"
+        );
+        assert_eq!(
+            first_mismatch("", "extra").unwrap(),
+            "line 1, column 1: expected end of output, found 'e'"
+        );
+    }
+
+    #[test]
+    fn only_changed_regions() {
+        let expected: String = (0..30).map(|i| format!("line {}\n", i)).collect();
+        let actual = expected.replace("line 15\n", "line fifteen\n");
+        assert_eq!(
+            unified(&expected, &actual, false),
+            "--- expected
++++ actual
+@@ -13,7 +13,7 @@
+ line 12
+ line 13
+ line 14
+-line 15
++line fifteen
+ line 16
+ line 17
+ line 18
+"
+        );
+    }
+
+    #[test]
+    fn separate_and_merged_hunks() {
+        let expected: String = (0..20).map(|i| format!("{}\n", i)).collect();
+        let actual: String = (0..20)
+            .map(|i| match i {
+                2 => "two\n".to_owned(),
+                5 => String::new(),
+                17 => "17\nextra\n".to_owned(),
+                _ => format!("{}\n", i),
+            })
+            .collect();
+        let diff = unified(&expected, &actual, false);
+        assert_eq!(diff.matches("@@ -").count(), 2, "{}", diff);
+        assert!(diff.contains("@@ -1,9 +1,8 @@\n"), "{}", diff);
+        assert!(diff.contains("@@ -16,5 +15,6 @@\n"), "{}", diff);
+    }
+
+    #[test]
+    fn missing_newline() {
+        assert_eq!(
+            unified("hello\n", "hello", false),
+            "--- expected\n+++ actual\n@@ -1 +1 @@\n-hello\n+hello\n\\ No newline at end of file\n"
+        );
+        assert_eq!(
+            unified("", "new\n", false),
+            "--- expected\n+++ actual\n@@ -0,0 +1 @@\n+new\n"
+        );
+    }
+
+    #[test]
+    fn colored() {
+        let diff = unified("a\n", "b\n", true);
+        assert!(diff.contains("\x1b[31m-a\x1b[0m\n"), "{:?}", diff);
+        assert!(diff.contains("\x1b[32m+b\x1b[0m\n"), "{:?}", diff);
+    }
+}
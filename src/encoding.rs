@@ -0,0 +1,150 @@
+//! Decode output that isn't UTF-8, like from Windows tools writing UTF-16.
+//!
+//! See [`Assert::decode_stdout`].
+//!
+//! [`Assert::decode_stdout`]: crate::assert::Assert::decode_stdout()
+
+use std::fmt;
+
+/// How output is encoded, see [`Assert::decode_stdout`].
+///
+/// [`Assert::decode_stdout`]: crate::assert::Assert::decode_stdout()
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Encoding {
+    /// Detect the encoding by its byte order mark, else by the `NUL`s of mostly-ASCII UTF-16,
+    /// else leave the output as-is.
+    Auto,
+    /// UTF-8, dropping any byte order mark.
+    Utf8,
+    /// Little-endian UTF-16, as most Windows tools write it.
+    Utf16Le,
+    /// Big-endian UTF-16.
+    Utf16Be,
+    /// ISO-8859-1, where each byte is the code point of the same value.
+    Latin1,
+}
+
+impl Encoding {
+    /// The encoding `data` appears to be in, for [`Encoding::Auto`].
+    fn detect(data: &[u8]) -> Option<Self> {
+        if data.starts_with(UTF8_BOM) {
+            return Some(Self::Utf8);
+        }
+        if data.starts_with(UTF16LE_BOM) {
+            return Some(Self::Utf16Le);
+        }
+        if data.starts_with(UTF16BE_BOM) {
+            return Some(Self::Utf16Be);
+        }
+        if data.len() < 2 || data.len() % 2 != 0 {
+            return None;
+        }
+        // ASCII text in UTF-16 has a `NUL` in every pair, on the side of the high byte.
+        let pairs = data.len() / 2;
+        let nuls = |side: usize| data.chunks(2).filter(|pair| pair[side] == 0).count();
+        let mostly = |side: usize| pairs * 3 <= nuls(side) * 4;
+        if mostly(1) && nuls(0) == 0 {
+            Some(Self::Utf16Le)
+        } else if mostly(0) && nuls(1) == 0 {
+            Some(Self::Utf16Be)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Auto => "auto-detected",
+            Self::Utf8 => "UTF-8",
+            Self::Utf16Le => "UTF-16LE",
+            Self::Utf16Be => "UTF-16BE",
+            Self::Latin1 => "ISO-8859-1",
+        };
+        f.write_str(name)
+    }
+}
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+const UTF16LE_BOM: &[u8] = b"\xFF\xFE";
+const UTF16BE_BOM: &[u8] = b"\xFE\xFF";
+
+/// `data`, in `encoding`, as UTF-8, with the encoding used.
+///
+/// Undecodable data is replaced with `U+FFFD`.  With [`Encoding::Auto`], undetected data is
+/// returned as-is, with `None`.
+pub(crate) fn decode(data: &[u8], encoding: Encoding) -> (Vec<u8>, Option<Encoding>) {
+    let encoding = match encoding {
+        Encoding::Auto => match Encoding::detect(data) {
+            Some(encoding) => encoding,
+            None => return (data.to_owned(), None),
+        },
+        encoding => encoding,
+    };
+    let decoded = match encoding {
+        Encoding::Auto => unreachable!("detected above"),
+        Encoding::Utf8 => {
+            let data = data.strip_prefix(UTF8_BOM).unwrap_or(data);
+            String::from_utf8_lossy(data).into_owned()
+        }
+        Encoding::Utf16Le => {
+            let data = data.strip_prefix(UTF16LE_BOM).unwrap_or(data);
+            utf16(data, u16::from_le_bytes)
+        }
+        Encoding::Utf16Be => {
+            let data = data.strip_prefix(UTF16BE_BOM).unwrap_or(data);
+            utf16(data, u16::from_be_bytes)
+        }
+        Encoding::Latin1 => data.iter().map(|b| char::from(*b)).collect(),
+    };
+    (decoded.into_bytes(), Some(encoding))
+}
+
+fn utf16(data: &[u8], unit: fn([u8; 2]) -> u16) -> String {
+    let units = data.chunks_exact(2).map(|pair| unit([pair[0], pair[1]]));
+    let mut decoded: String = char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect();
+    if data.len() % 2 != 0 {
+        decoded.push(char::REPLACEMENT_CHARACTER);
+    }
+    decoded
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn decodes() {
+        let cases: [(&[u8], Encoding, &str); 6] = [
+            (b"h\0i\0\n\0", Encoding::Utf16Le, "hi\n"),
+            (b"\xFF\xFEh\0\xE9\0", Encoding::Utf16Le, "h\u{e9}"),
+            (b"\0h\0i", Encoding::Utf16Be, "hi"),
+            (b"h\0i", Encoding::Utf16Le, "h\u{fffd}"),
+            (b"\xEF\xBB\xBFhi", Encoding::Utf8, "hi"),
+            (b"caf\xE9", Encoding::Latin1, "caf\u{e9}"),
+        ];
+        for (data, encoding, expected) in cases.iter() {
+            let (decoded, _) = decode(data, *encoding);
+            assert_eq!(String::from_utf8(decoded).unwrap(), *expected, "{:?}", data);
+        }
+    }
+
+    #[test]
+    fn detects() {
+        let cases: [(&[u8], Option<Encoding>); 5] = [
+            (b"\xFF\xFEh\0", Some(Encoding::Utf16Le)),
+            (b"\xFE\xFF\0h", Some(Encoding::Utf16Be)),
+            (b"h\0i\0\n\0", Some(Encoding::Utf16Le)),
+            (b"\0h\0i", Some(Encoding::Utf16Be)),
+            (b"hi\n\0", None),
+        ];
+        for (data, expected) in cases.iter() {
+            assert_eq!(decode(data, Encoding::Auto).1, *expected, "{:?}", data);
+        }
+        assert_eq!(decode(b"plain", Encoding::Auto).0, b"plain");
+    }
+}
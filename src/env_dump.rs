@@ -0,0 +1,117 @@
+//! Verify the environment that reached the child.
+//!
+//! When [`Command::env_dump`] is enabled, the child is given [`ENV_VAR`], pointing at a file it
+//! can dump its effective environment to.  The dump is then available for assertions via
+//! [`Assert::child_env`].
+//!
+//! The child can use [`dump`] or write the file itself: each variable is written as
+//! `NAME=VALUE`, terminated by a `\0`.
+//!
+//! # Examples
+//!
+//! In the program under test:
+//! ```rust
+//! // Early in `main`
+//! assert_cmd::env_dump::dump().unwrap();
+//! ```
+//!
+//! In the test:
+//! ```rust,no_run
+//! use assert_cmd::Command;
+//!
+//! Command::cargo_bin("bin_fixture")
+//!     .unwrap()
+//!     .env("LEVEL", "debug")
+//!     .env_dump(true)
+//!     .assert()
+//!     .child_env("LEVEL", "debug");
+//! ```
+//!
+//! [`Command::env_dump`]: crate::cmd::Command::env_dump()
+//! [`Assert::child_env`]: crate::assert::Assert::child_env()
+
+use std::env;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::path;
+
+/// Environment variable holding the path the child should dump its environment to.
+pub const ENV_VAR: &str = "ASSERT_CMD_ENV_DUMP";
+
+/// Dump the current process's environment, if requested by [`ENV_VAR`].
+///
+/// Returns whether a dump was written.
+pub fn dump() -> io::Result<bool> {
+    let path = match env::var_os(ENV_VAR) {
+        Some(path) => path,
+        None => return Ok(false),
+    };
+    let mut file = io::BufWriter::new(fs::File::create(path)?);
+    for (key, value) in env::vars_os() {
+        if key == ENV_VAR || is_signal_dump_var(&key) {
+            continue;
+        }
+        write!(
+            file,
+            "{}={}\0",
+            key.to_string_lossy(),
+            value.to_string_lossy()
+        )?;
+    }
+    file.flush()?;
+    Ok(true)
+}
+
+#[cfg(unix)]
+fn is_signal_dump_var(key: &std::ffi::OsStr) -> bool {
+    key == crate::signals::ENV_VAR
+}
+
+#[cfg(not(unix))]
+fn is_signal_dump_var(_key: &std::ffi::OsStr) -> bool {
+    false
+}
+
+/// Read and remove the dump written by [`dump`].
+pub(crate) fn take(path: &path::Path) -> Option<Vec<(String, String)>> {
+    let data = fs::read(path).ok()?;
+    let _ = fs::remove_file(path);
+    let data = String::from_utf8_lossy(&data);
+    let vars = data
+        .split_terminator('\0')
+        .map(|entry| match entry.split_once('=') {
+            Some((key, value)) => (key.to_owned(), value.to_owned()),
+            None => (entry.to_owned(), String::new()),
+        })
+        .collect();
+    Some(vars)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn take_parses_entries() {
+        let path = crate::temp::unique_path("assert_cmd-test-env");
+        fs::write(&path, "A=1\0B=multi\nline\0C=\0D=x=y\0").unwrap();
+        let vars = take(&path).unwrap();
+        assert_eq!(
+            vars,
+            vec![
+                ("A".to_owned(), "1".to_owned()),
+                ("B".to_owned(), "multi\nline".to_owned()),
+                ("C".to_owned(), "".to_owned()),
+                ("D".to_owned(), "x=y".to_owned()),
+            ]
+        );
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn take_missing() {
+        let path = crate::temp::unique_path("assert_cmd-test-env");
+        assert_eq!(take(&path), None);
+    }
+}
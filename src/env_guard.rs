@@ -0,0 +1,199 @@
+//! Temporarily change the test process's environment.
+//!
+//! Prefer [`Command::env`] and friends, which only affect the child.  For CLIs driven by an
+//! environment they inherit, like from a library spawning them, an [`EnvGuard`] sets and removes
+//! variables in the test process itself, restoring them when dropped.
+//!
+//! The process environment is shared by every test, so guards are exclusive: a guard waits for
+//! any other thread's guard to be dropped, and commands run through this crate from other threads
+//! wait to be spawned so they don't inherit another test's variables.  A guard's own thread is
+//! free to run commands, which inherit its variables, unless overridden with [`Command::env`] or
+//! cleared with [`Command::env_clear`].
+//!
+//! ```rust,no_run
+//! use assert_cmd::env_guard::EnvGuard;
+//! use assert_cmd::Command;
+//!
+//! let mut env = EnvGuard::new();
+//! env.set("RUST_LOG", "debug").remove("NO_COLOR");
+//! Command::cargo_bin("bin_fixture").unwrap().assert().success();
+//! drop(env);
+//! ```
+//!
+//! [`Command::env`]: crate::cmd::Command::env()
+//! [`Command::env_clear`]: crate::cmd::Command::env_clear()
+
+use std::cell::Cell;
+use std::env;
+use std::ffi;
+use std::fmt;
+use std::sync;
+use std::thread;
+
+static LOCK: sync::Mutex<()> = sync::Mutex::new(());
+
+thread_local! {
+    /// Guards alive on this thread.
+    static HELD: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Hold the process environment exclusively, for guards or spawning.
+///
+/// This is reentrant, so guards may nest and their thread may spawn.
+struct Lock {
+    _held: Option<sync::MutexGuard<'static, ()>>,
+}
+
+impl Lock {
+    fn acquire() -> Self {
+        if HELD.with(|held| held.get()) != 0 {
+            return Lock { _held: None };
+        }
+        // A test panicking with a guard poisons the lock, but the guard restored the
+        // environment while unwinding.
+        Lock {
+            _held: Some(LOCK.lock().unwrap_or_else(sync::PoisonError::into_inner)),
+        }
+    }
+}
+
+impl fmt::Debug for Lock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Lock").finish()
+    }
+}
+
+/// Run `spawn` without another thread's [`EnvGuard`] changing the environment it inherits.
+pub(crate) fn spawning<R>(spawn: impl FnOnce() -> R) -> R {
+    let _lock = Lock::acquire();
+    spawn()
+}
+
+/// Set and remove environment variables of the test process, until dropped.
+///
+/// See the [`env_guard` module documentation][crate::env_guard].
+///
+/// # Panics
+///
+/// When dropped, if a variable it changed was changed again by something else, like a test
+/// calling [`std::env::set_var`] directly.  Every variable is restored regardless.
+#[derive(Debug)]
+pub struct EnvGuard {
+    /// Each variable's original value, in the order first changed.
+    original: Vec<(ffi::OsString, Option<ffi::OsString>)>,
+    /// Each variable's value as last changed by the guard.
+    current: Vec<(ffi::OsString, Option<ffi::OsString>)>,
+    _lock: Lock,
+}
+
+impl EnvGuard {
+    /// Take exclusive control of the process environment, waiting for other threads' guards.
+    pub fn new() -> Self {
+        let lock = Lock::acquire();
+        HELD.with(|held| held.set(held.get() + 1));
+        Self {
+            original: Vec::new(),
+            current: Vec::new(),
+            _lock: lock,
+        }
+    }
+
+    /// Set the environment variable `key` to `value`.
+    pub fn set<K, V>(&mut self, key: K, value: V) -> &mut Self
+    where
+        K: AsRef<ffi::OsStr>,
+        V: AsRef<ffi::OsStr>,
+    {
+        self.change(key.as_ref(), Some(value.as_ref()));
+        self
+    }
+
+    /// Remove the environment variable `key`.
+    pub fn remove<K: AsRef<ffi::OsStr>>(&mut self, key: K) -> &mut Self {
+        self.change(key.as_ref(), None);
+        self
+    }
+
+    fn change(&mut self, key: &ffi::OsStr, value: Option<&ffi::OsStr>) {
+        if !self.original.iter().any(|(k, _)| k == key) {
+            self.original.push((key.to_owned(), env::var_os(key)));
+        }
+        set(key, value);
+        let value = value.map(ffi::OsStr::to_owned);
+        match self.current.iter_mut().find(|(k, _)| k == key) {
+            Some(entry) => entry.1 = value,
+            None => self.current.push((key.to_owned(), value)),
+        }
+    }
+}
+
+impl Default for EnvGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for EnvGuard {
+    fn drop(&mut self) {
+        let changed: Vec<_> = self
+            .current
+            .iter()
+            .filter(|(key, value)| env::var_os(key) != *value)
+            .map(|(key, _)| key.to_string_lossy().into_owned())
+            .collect();
+        for (key, value) in self.original.iter().rev() {
+            set(key, value.as_deref());
+        }
+        HELD.with(|held| held.set(held.get() - 1));
+        if !changed.is_empty() && !thread::panicking() {
+            panic!(
+                "Environment variables changed behind an `EnvGuard`'s back: `{}`",
+                changed.join("`, `")
+            );
+        }
+    }
+}
+
+fn set(key: &ffi::OsStr, value: Option<&ffi::OsStr>) {
+    match value {
+        Some(value) => env::set_var(key, value),
+        None => env::remove_var(key),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn restores_on_drop() {
+        let key = "ASSERT_CMD_ENV_GUARD_RESTORES";
+        {
+            let mut outer = EnvGuard::new();
+            outer.set(key, "outer");
+            {
+                let mut inner = EnvGuard::new();
+                inner.set(key, "inner").set(key, "again");
+                assert_eq!(env::var(key).unwrap(), "again");
+            }
+            assert_eq!(env::var(key).unwrap(), "outer");
+            outer.remove(key);
+            assert_eq!(env::var_os(key), None);
+        }
+        assert_eq!(env::var_os(key), None);
+    }
+
+    #[test]
+    fn detects_changes_behind_its_back() {
+        let key = "ASSERT_CMD_ENV_GUARD_CHANGED";
+        let result = std::panic::catch_unwind(|| {
+            let mut guard = EnvGuard::new();
+            guard.set(key, "guarded");
+            env::set_var(key, "sneaky");
+        });
+        let err = result.unwrap_err();
+        let err = err.downcast_ref::<String>().unwrap();
+        assert!(err.contains(key), "{}", err);
+        assert_eq!(env::var_os(key), None);
+    }
+}
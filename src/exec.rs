@@ -0,0 +1,281 @@
+//! Why a program couldn't be run.
+//!
+//! When a [`Command`] fails to spawn, the failure is classified so it's clear whether the binary
+//! under test is broken (a build problem) or the test is pointing at the wrong thing (a test
+//! problem):
+//!
+//! ```rust,no_run
+//! use assert_cmd::exec::ExecFailure;
+//! use assert_cmd::Command;
+//!
+//! let err = Command::new("no-such-program").unwrap_err();
+//! let failure = err.spawn_error().unwrap().failure();
+//! assert_eq!(*failure, ExecFailure::NotFound);
+//! ```
+//!
+//! Programs that start but can't load, like from a missing shared library, are reported in
+//! [`Assert`]'s failure messages.
+//!
+//! [`Command`]: crate::cmd::Command
+//! [`Assert`]: crate::assert::Assert
+
+use std::error::Error;
+use std::ffi;
+use std::fmt;
+use std::io;
+use std::path;
+use std::process;
+
+use bstr::ByteSlice;
+
+/// The class of an exec failure.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ExecFailure {
+    /// No program at the path, or on `PATH`.
+    NotFound,
+    /// The program exists, but isn't executable by us.
+    PermissionDenied,
+    /// The program exists, but its interpreter, from a `#!` line or the dynamic loader, doesn't.
+    MissingInterpreter,
+    /// The program isn't in a format the host can execute, like when built for another target.
+    WrongArchitecture,
+    /// The program started, but a shared library it links against couldn't be found.
+    MissingLibrary(String),
+    /// Any other I/O error.
+    Other,
+}
+
+impl ExecFailure {
+    /// Whether this points at the binary under test, rather than the test.
+    pub fn is_build_problem(&self) -> bool {
+        match self {
+            ExecFailure::MissingInterpreter
+            | ExecFailure::WrongArchitecture
+            | ExecFailure::MissingLibrary(_) => true,
+            ExecFailure::NotFound | ExecFailure::PermissionDenied | ExecFailure::Other => false,
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            ExecFailure::NotFound => Some("check the path, and that the program is built"),
+            ExecFailure::PermissionDenied => Some("check the file is executable"),
+            ExecFailure::MissingInterpreter => {
+                Some("the binary may be built for another target, or its `#!` line is wrong")
+            }
+            ExecFailure::WrongArchitecture => {
+                Some("the binary may be built for another target, see `Command::runner`")
+            }
+            ExecFailure::MissingLibrary(_) => Some(
+                "the binary links against a library that isn't installed or on the search path",
+            ),
+            ExecFailure::Other => None,
+        }
+    }
+}
+
+impl fmt::Display for ExecFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecFailure::NotFound => write!(f, "program not found"),
+            ExecFailure::PermissionDenied => write!(f, "permission denied"),
+            ExecFailure::MissingInterpreter => write!(f, "program's interpreter not found"),
+            ExecFailure::WrongArchitecture => write!(f, "not executable on this host"),
+            ExecFailure::MissingLibrary(library) => write!(f, "missing shared library {}", library),
+            ExecFailure::Other => write!(f, "couldn't run program"),
+        }
+    }
+}
+
+/// Error when a program couldn't be spawned.
+///
+/// See the [`exec` module documentation][crate::exec].
+#[derive(Debug)]
+pub struct SpawnError {
+    program: ffi::OsString,
+    failure: ExecFailure,
+    source: io::Error,
+}
+
+impl SpawnError {
+    pub(crate) fn new(cmd: &process::Command, source: io::Error) -> Self {
+        let program = cmd.get_program().to_owned();
+        let failure = classify(&program, &source);
+        Self {
+            program,
+            failure,
+            source,
+        }
+    }
+
+    /// The program that couldn't be spawned.
+    pub fn program(&self) -> &ffi::OsStr {
+        &self.program
+    }
+
+    /// Why it couldn't be spawned.
+    pub fn failure(&self) -> &ExecFailure {
+        &self.failure
+    }
+}
+
+impl Error for SpawnError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+impl fmt::Display for SpawnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.failure, self.source)?;
+        if let Some(hint) = self.failure.hint() {
+            write!(f, "\nhint: {}", hint)?;
+        }
+        Ok(())
+    }
+}
+
+fn classify(program: &ffi::OsStr, err: &io::Error) -> ExecFailure {
+    let program = path::Path::new(program);
+    match err.kind() {
+        // A bare name was looked up on `PATH`, so only a path can point at an existing file
+        io::ErrorKind::NotFound if 1 < program.components().count() && program.is_file() => {
+            ExecFailure::MissingInterpreter
+        }
+        io::ErrorKind::NotFound => ExecFailure::NotFound,
+        io::ErrorKind::PermissionDenied => ExecFailure::PermissionDenied,
+        _ if is_bad_format(err) => ExecFailure::WrongArchitecture,
+        _ => ExecFailure::Other,
+    }
+}
+
+#[cfg(unix)]
+fn is_bad_format(err: &io::Error) -> bool {
+    err.raw_os_error() == Some(libc::ENOEXEC)
+}
+
+#[cfg(windows)]
+fn is_bad_format(err: &io::Error) -> bool {
+    const ERROR_BAD_EXE_FORMAT: i32 = 193;
+    const ERROR_EXE_MACHINE_TYPE_MISMATCH: i32 = 216;
+    matches!(
+        err.raw_os_error(),
+        Some(ERROR_BAD_EXE_FORMAT) | Some(ERROR_EXE_MACHINE_TYPE_MISMATCH)
+    )
+}
+
+#[cfg(not(any(unix, windows)))]
+fn is_bad_format(_err: &io::Error) -> bool {
+    false
+}
+
+/// Recognize a program that started but couldn't load, from what the loader reported.
+pub(crate) fn from_output(output: &process::Output) -> Option<ExecFailure> {
+    const STATUS_DLL_NOT_FOUND: i32 = 0xC000_0135_u32 as i32;
+    let code = output.status.code()?;
+    if cfg!(windows) && code == STATUS_DLL_NOT_FOUND {
+        return Some(ExecFailure::MissingLibrary("<unknown DLL>".to_owned()));
+    }
+    for line in output.stderr.lines() {
+        let line = line.to_str_lossy();
+        // glibc and musl: `prog: error while loading shared libraries: libfoo.so.1: cannot open
+        // shared object file: No such file or directory`
+        if let Some(i) = line.find("error while loading shared libraries: ") {
+            let rest = &line[i + "error while loading shared libraries: ".len()..];
+            let library = rest.split(':').next().unwrap_or(rest);
+            return Some(ExecFailure::MissingLibrary(library.trim().to_owned()));
+        }
+        // macOS: `dyld[123]: Library not loaded: @rpath/libfoo.dylib`
+        if let Some(i) = line.find("Library not loaded: ") {
+            let library = line[i + "Library not loaded: ".len()..].trim();
+            return Some(ExecFailure::MissingLibrary(library.to_owned()));
+        }
+        // A shell running a foreign binary as a fallback from `ENOEXEC`
+        if code == 126
+            && (line.contains("cannot execute binary file") || line.contains("Exec format error"))
+        {
+            return Some(ExecFailure::WrongArchitecture);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(unix)]
+    fn output(code: i32, stderr: &str) -> process::Output {
+        use std::os::unix::process::ExitStatusExt;
+        process::Output {
+            status: process::ExitStatus::from_raw(code << 8),
+            stdout: Vec::new(),
+            stderr: stderr.as_bytes().to_owned(),
+        }
+    }
+
+    #[test]
+    fn classify_errors() {
+        let not_found = io::Error::from(io::ErrorKind::NotFound);
+        assert_eq!(
+            classify("no-such-program".as_ref(), &not_found),
+            ExecFailure::NotFound
+        );
+        assert_eq!(
+            classify("./Cargo.toml".as_ref(), &not_found),
+            ExecFailure::MissingInterpreter
+        );
+        assert_eq!(
+            classify(
+                "tool".as_ref(),
+                &io::Error::from(io::ErrorKind::PermissionDenied)
+            ),
+            ExecFailure::PermissionDenied
+        );
+        #[cfg(unix)]
+        assert_eq!(
+            classify(
+                "tool".as_ref(),
+                &io::Error::from_raw_os_error(libc::ENOEXEC)
+            ),
+            ExecFailure::WrongArchitecture
+        );
+        assert_eq!(
+            classify(
+                "tool".as_ref(),
+                &io::Error::from(io::ErrorKind::Interrupted)
+            ),
+            ExecFailure::Other
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn sniff_loader_errors() {
+        assert_eq!(
+            from_output(&output(
+                127,
+                "tool: error while loading shared libraries: libfoo.so.1: cannot open shared object file: No such file or directory\n"
+            )),
+            Some(ExecFailure::MissingLibrary("libfoo.so.1".to_owned()))
+        );
+        assert_eq!(
+            from_output(&output(
+                134,
+                "dyld[42]: Library not loaded: @rpath/libfoo.dylib\n  Referenced from: tool\n"
+            )),
+            Some(ExecFailure::MissingLibrary(
+                "@rpath/libfoo.dylib".to_owned()
+            ))
+        );
+        assert_eq!(
+            from_output(&output(
+                126,
+                "sh: 1: ./tool: cannot execute binary file: Exec format error\n"
+            )),
+            Some(ExecFailure::WrongArchitecture)
+        );
+        assert_eq!(from_output(&output(1, "error: bad flag\n")), None);
+    }
+}
@@ -0,0 +1,146 @@
+//! Named exit codes, from BSD's `sysexits.h`, for [`Assert::code`].
+//!
+//! ```rust,no_run
+//! use assert_cmd::exit;
+//! use assert_cmd::Command;
+//!
+//! Command::cargo_bin("bin_fixture")
+//!     .unwrap()
+//!     .arg("--unknown")
+//!     .assert()
+//!     .code(exit::USAGE);
+//! ```
+//!
+//! Failures name the expected code, and the actual one when it is also one of these.
+//!
+//! [`Assert::code`]: crate::assert::Assert::code()
+
+use std::fmt;
+
+use predicates_core::reflection;
+
+/// A named exit code, see the [`exit` module documentation][crate::exit].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ExitCode {
+    name: &'static str,
+    code: i32,
+}
+
+impl ExitCode {
+    const fn new(name: &'static str, code: i32) -> Self {
+        Self { name, code }
+    }
+
+    /// The named code for `code`, if any.
+    pub fn from_code(code: i32) -> Option<Self> {
+        ALL.iter().copied().find(|exit| exit.code == code)
+    }
+
+    /// The name, like `EX_USAGE`.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The numeric code.
+    pub fn code(&self) -> i32 {
+        self.code
+    }
+}
+
+/// Successful termination.
+pub const OK: ExitCode = ExitCode::new("EX_OK", 0);
+/// The command was used incorrectly, like with the wrong arguments.
+pub const USAGE: ExitCode = ExitCode::new("EX_USAGE", 64);
+/// The input data was incorrect.
+pub const DATAERR: ExitCode = ExitCode::new("EX_DATAERR", 65);
+/// An input file didn't exist or wasn't readable.
+pub const NOINPUT: ExitCode = ExitCode::new("EX_NOINPUT", 66);
+/// The user specified didn't exist.
+pub const NOUSER: ExitCode = ExitCode::new("EX_NOUSER", 67);
+/// The host specified didn't exist.
+pub const NOHOST: ExitCode = ExitCode::new("EX_NOHOST", 68);
+/// A service is unavailable, like a support program or file.
+pub const UNAVAILABLE: ExitCode = ExitCode::new("EX_UNAVAILABLE", 69);
+/// An internal software error.
+pub const SOFTWARE: ExitCode = ExitCode::new("EX_SOFTWARE", 70);
+/// An operating system error, like being unable to fork.
+pub const OSERR: ExitCode = ExitCode::new("EX_OSERR", 71);
+/// A system file didn't exist, wasn't readable, or had a syntax error.
+pub const OSFILE: ExitCode = ExitCode::new("EX_OSFILE", 72);
+/// An output file couldn't be created.
+pub const CANTCREAT: ExitCode = ExitCode::new("EX_CANTCREAT", 73);
+/// An error doing I/O on some file.
+pub const IOERR: ExitCode = ExitCode::new("EX_IOERR", 74);
+/// A temporary failure, where retrying may succeed.
+pub const TEMPFAIL: ExitCode = ExitCode::new("EX_TEMPFAIL", 75);
+/// The remote system returned something invalid during a protocol exchange.
+pub const PROTOCOL: ExitCode = ExitCode::new("EX_PROTOCOL", 76);
+/// The user lacked permission for the operation.
+pub const NOPERM: ExitCode = ExitCode::new("EX_NOPERM", 77);
+/// Something was found in an unconfigured or misconfigured state.
+pub const CONFIG: ExitCode = ExitCode::new("EX_CONFIG", 78);
+
+const ALL: [ExitCode; 16] = [
+    OK,
+    USAGE,
+    DATAERR,
+    NOINPUT,
+    NOUSER,
+    NOHOST,
+    UNAVAILABLE,
+    SOFTWARE,
+    OSERR,
+    OSFILE,
+    CANTCREAT,
+    IOERR,
+    TEMPFAIL,
+    PROTOCOL,
+    NOPERM,
+    CONFIG,
+];
+
+impl fmt::Display for ExitCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name, self.code)
+    }
+}
+
+impl reflection::PredicateReflection for ExitCode {}
+
+impl predicates_core::Predicate<i32> for ExitCode {
+    fn eval(&self, variable: &i32) -> bool {
+        *variable == self.code
+    }
+
+    fn find_case<'a>(&'a self, expected: bool, variable: &i32) -> Option<reflection::Case<'a>> {
+        let result = self.eval(variable);
+        if result != expected {
+            return None;
+        }
+        let actual = match Self::from_code(*variable) {
+            Some(exit) => exit.to_string(),
+            None => variable.to_string(),
+        };
+        Some(
+            reflection::Case::new(Some(self), result)
+                .add_product(reflection::Product::new("var", actual)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use predicates_core::Predicate;
+
+    #[test]
+    fn names() {
+        assert_eq!(ExitCode::from_code(78), Some(CONFIG));
+        assert_eq!(ExitCode::from_code(1), None);
+        assert_eq!(USAGE.to_string(), "EX_USAGE (64)");
+        assert!(USAGE.eval(&64));
+        let case = USAGE.find_case(false, &65).unwrap();
+        let products: Vec<_> = case.products().map(|p| p.to_string()).collect();
+        assert_eq!(products, ["var: EX_DATAERR (65)"]);
+    }
+}
@@ -0,0 +1,353 @@
+//! Handle [`Assert`] failures as data, rather than as panic messages.
+//!
+//! A handler sees each failure before the panic reporting it, like to log it or to convert it to
+//! a framework's own error:
+//!
+//! ```rust,no_run
+//! use assert_cmd::failure::Failure;
+//! use assert_cmd::Command;
+//!
+//! assert_cmd::failure::set_handler(|failure: &Failure| {
+//!     eprintln!("::error title={:?}::{}", failure.kind(), failure.headline());
+//! });
+//!
+//! Command::cargo_bin("bin_fixture")
+//!     .unwrap()
+//!     .assert()
+//!     .success();
+//! ```
+//!
+//! A handler may panic itself, with its own payload, to replace the usual panic.  Failures
+//! returned by the `try_` assertions or collected by
+//! [`Failures::check`][crate::aggregate::Failures::check] aren't handled.
+//!
+//! With the `serde` feature, a [`Failure`] is `Serialize`, like to write a JSON artifact for CI
+//! to triage: output and input are strings, with invalid UTF-8 replaced.
+//!
+//! [`Assert`]: crate::assert::Assert
+
+use std::path;
+use std::process;
+use std::sync::{Arc, RwLock};
+
+type Handler = Arc<dyn Fn(&Failure) + Send + Sync>;
+
+/// An [`Assert::on_failure`] handler.
+///
+/// [`Assert::on_failure`]: crate::assert::Assert::on_failure()
+pub(crate) type LocalHandler = std::rc::Rc<dyn Fn(&Failure)>;
+
+static HANDLER: RwLock<Option<Handler>> = RwLock::new(None);
+
+/// Handle every [`Assert`] failure, on any thread, that has no [`Assert::on_failure`] handler.
+///
+/// This replaces any earlier handler.
+///
+/// [`Assert`]: crate::assert::Assert
+/// [`Assert::on_failure`]: crate::assert::Assert::on_failure()
+pub fn set_handler<F>(handler: F)
+where
+    F: Fn(&Failure) + Send + Sync + 'static,
+{
+    *HANDLER.write().unwrap_or_else(|err| err.into_inner()) = Some(Arc::new(handler));
+}
+
+/// Stop handling failures, see [`set_handler`].
+pub fn reset_handler() {
+    *HANDLER.write().unwrap_or_else(|err| err.into_inner()) = None;
+}
+
+/// Run the [`Assert::on_failure`] handler, else any [`set_handler`] handler, on `failure`.
+///
+/// [`Assert::on_failure`]: crate::assert::Assert::on_failure()
+pub(crate) fn handle(failure: &Failure, local: Option<&dyn Fn(&Failure)>) {
+    if let Some(local) = local {
+        return local(failure);
+    }
+    // Cloned so the handler can itself set a handler, or panic, without the lock.
+    let global = HANDLER
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone();
+    if let Some(global) = global {
+        global(failure);
+    }
+}
+
+/// What an [`Assert`] failure was about.
+///
+/// [`Assert`]: crate::assert::Assert
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[non_exhaustive]
+pub enum FailureKind {
+    /// The command succeeded or failed unexpectedly, or was interrupted.
+    Status,
+    /// The command returned an unexpected exit code.
+    Code,
+    /// The command didn't finish within its [`Command::timeout`].
+    ///
+    /// [`Command::timeout`]: crate::cmd::Command::timeout()
+    Timeout,
+    /// The command's `stdout` was unexpected.
+    Stdout,
+    /// The command's `stderr` was unexpected.
+    Stderr,
+    /// Any other assertion.
+    Other,
+}
+
+impl FailureKind {
+    /// Classify a failure by its headline.
+    fn of(headline: &str) -> Self {
+        let first = headline.lines().next().unwrap_or_default();
+        if first.starts_with("Command timed out") {
+            Self::Timeout
+        } else if first.starts_with("Unexpected failure")
+            || first.starts_with("Unexpected success")
+            || first.starts_with("Command interrupted")
+        {
+            Self::Status
+        } else if first.starts_with("Unexpected return code") {
+            Self::Code
+        } else if first.contains("stdout") {
+            Self::Stdout
+        } else if first.contains("stderr") {
+            Self::Stderr
+        } else {
+            Self::Other
+        }
+    }
+}
+
+/// A failed [`Assert`]ion, see the [`failure` module documentation][crate::failure].
+///
+/// [`Assert`]: crate::assert::Assert
+#[derive(Clone, Debug)]
+pub struct Failure {
+    kind: FailureKind,
+    message: Option<String>,
+    headline: String,
+    context: Vec<(String, String)>,
+    cwd: Option<path::PathBuf>,
+    env: Vec<(String, Option<String>)>,
+    stdin: Option<Vec<u8>>,
+    output: process::Output,
+}
+
+impl Failure {
+    pub(crate) fn new(
+        message: Option<String>,
+        headline: String,
+        context: Vec<(String, String)>,
+        cwd: Option<path::PathBuf>,
+        env: Vec<(String, Option<String>)>,
+        stdin: Option<Vec<u8>>,
+        output: process::Output,
+    ) -> Self {
+        Self {
+            kind: FailureKind::of(&headline),
+            message,
+            headline,
+            context,
+            cwd,
+            env,
+            stdin,
+            output,
+        }
+    }
+
+    /// What the failure was about.
+    pub fn kind(&self) -> FailureKind {
+        self.kind
+    }
+
+    /// Any [`Assert::with_message`].
+    ///
+    /// [`Assert::with_message`]: crate::assert::Assert::with_message()
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    /// What failed, like `Unexpected failure.`
+    pub fn headline(&self) -> &str {
+        &self.headline
+    }
+
+    /// The failed predicate, with what it expected and what it found, for assertions against a
+    /// predicate.
+    pub fn expected(&self) -> Option<&str> {
+        self.headline
+            .split_once(", failed ")
+            .map(|(_, predicate)| predicate)
+    }
+
+    /// The output the failure was about, for [`FailureKind::Stdout`] and [`FailureKind::Stderr`].
+    pub fn actual(&self) -> Option<&[u8]> {
+        match self.kind {
+            FailureKind::Stdout => Some(&self.output.stdout),
+            FailureKind::Stderr => Some(&self.output.stderr),
+            _ => None,
+        }
+    }
+
+    /// The command line, when run from a [`Command`].
+    ///
+    /// [`Command`]: crate::cmd::Command
+    pub fn command(&self) -> Option<&str> {
+        self.context("command")
+    }
+
+    /// The first context named `name`, like `"command"`, as reported in the failure's message.
+    pub fn context(&self, name: &str) -> Option<&str> {
+        self.context
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// All of the context, in order, as reported in the failure's message.
+    pub fn contexts(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.context
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// The command's working directory, when not the test's.
+    pub fn cwd(&self) -> Option<&path::Path> {
+        self.cwd.as_deref()
+    }
+
+    /// How the command's environment differed from the test's: each variable set, or removed
+    /// as `None`.
+    pub fn env(&self) -> &[(String, Option<String>)] {
+        &self.env
+    }
+
+    /// The command's [`Command::write_stdin`] input.
+    ///
+    /// [`Command::write_stdin`]: crate::cmd::Command::write_stdin()
+    pub fn stdin(&self) -> Option<&[u8]> {
+        self.stdin.as_deref()
+    }
+
+    /// The command's output.
+    pub fn get_output(&self) -> &process::Output {
+        &self.output
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Failure {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use std::collections::BTreeMap;
+
+        #[derive(serde::Serialize)]
+        struct Status {
+            success: bool,
+            code: Option<i32>,
+            signal: Option<i32>,
+        }
+
+        #[derive(serde::Serialize)]
+        struct Serialized<'a> {
+            kind: FailureKind,
+            message: Option<&'a str>,
+            headline: &'a str,
+            expected: Option<&'a str>,
+            command: Option<&'a str>,
+            cwd: Option<std::borrow::Cow<'a, str>>,
+            env: BTreeMap<&'a str, Option<&'a str>>,
+            stdin: Option<std::borrow::Cow<'a, str>>,
+            status: Status,
+            stdout: std::borrow::Cow<'a, str>,
+            stderr: std::borrow::Cow<'a, str>,
+            context: Vec<(&'a str, &'a str)>,
+        }
+
+        #[cfg(unix)]
+        let signal = std::os::unix::process::ExitStatusExt::signal(&self.output.status);
+        #[cfg(not(unix))]
+        let signal = None;
+        Serialized {
+            kind: self.kind,
+            message: self.message(),
+            headline: &self.headline,
+            expected: self.expected(),
+            command: self.command(),
+            cwd: self.cwd.as_ref().map(|cwd| cwd.to_string_lossy()),
+            env: self
+                .env
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.as_deref()))
+                .collect(),
+            stdin: self
+                .stdin
+                .as_ref()
+                .map(|stdin| String::from_utf8_lossy(stdin)),
+            status: Status {
+                success: self.output.status.success(),
+                code: self.output.status.code(),
+                signal,
+            },
+            stdout: String::from_utf8_lossy(&self.output.stdout),
+            stderr: String::from_utf8_lossy(&self.output.stderr),
+            context: self.contexts().collect(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn kinds() {
+        let cases = [
+            ("Command timed out after 1s", FailureKind::Timeout),
+            (
+                "Unexpected failure.\ncode=1\nstderr=``````",
+                FailureKind::Status,
+            ),
+            ("Unexpected success", FailureKind::Status),
+            ("Unexpected return code, failed var == 2", FailureKind::Code),
+            ("Unexpected stdout, failed var == \"\"", FailureKind::Stdout),
+            ("Invalid JSON in stdout", FailureKind::Stdout),
+            ("Unexpected stderr, failed var == \"\"", FailureKind::Stderr),
+            ("Hermeticity violations", FailureKind::Other),
+        ];
+        for (headline, kind) in cases.iter() {
+            assert_eq!(FailureKind::of(headline), *kind, "{}", headline);
+        }
+    }
+
+    #[cfg(all(feature = "serde", feature = "json"))]
+    #[test]
+    fn serialize() {
+        let output = process::Command::new(env!("CARGO"))
+            .arg("--version")
+            .output()
+            .unwrap();
+        let failure = Failure::new(
+            None,
+            "Unexpected stdout, failed var == \"\"".to_owned(),
+            vec![("command".to_owned(), "\"cargo\" \"--version\"".to_owned())],
+            Some("work".into()),
+            vec![("PAGER".to_owned(), None)],
+            Some(vec![0xff]),
+            output,
+        );
+        let json = serde_json::to_value(&failure).unwrap();
+        assert_eq!(json["kind"], "stdout");
+        assert_eq!(json["expected"], "var == \"\"");
+        assert_eq!(json["command"], "\"cargo\" \"--version\"");
+        assert_eq!(json["cwd"], "work");
+        assert_eq!(json["env"]["PAGER"], serde_json::Value::Null);
+        assert_eq!(json["stdin"], "\u{fffd}");
+        assert_eq!(json["status"]["success"], true);
+        assert_eq!(json["status"]["code"], 0);
+        assert!(json["stdout"].as_str().unwrap().starts_with("cargo "));
+    }
+}
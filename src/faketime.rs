@@ -0,0 +1,118 @@
+//! Control the child's clock with [libfaketime].
+//!
+//! Requires the `faketime` feature and libfaketime to be installed.  See
+//! [`Command::fake_time`].
+//!
+//! [libfaketime]: https://github.com/wolfcw/libfaketime
+//! [`Command::fake_time`]: crate::cmd::Command::fake_time()
+
+use std::env;
+use std::io;
+use std::path;
+
+/// Environment variable to point at the libfaketime library, overriding the search.
+pub const LIB_ENV: &str = "ASSERT_CMD_FAKETIME_LIB";
+
+#[cfg(target_os = "macos")]
+const CANDIDATES: &[&str] = &[
+    "/opt/homebrew/lib/faketime/libfaketime.1.dylib",
+    "/usr/local/lib/faketime/libfaketime.1.dylib",
+];
+
+#[cfg(not(target_os = "macos"))]
+const CANDIDATES: &[&str] = &[
+    "/usr/lib/x86_64-linux-gnu/faketime/libfaketime.so.1",
+    "/usr/lib/aarch64-linux-gnu/faketime/libfaketime.so.1",
+    "/usr/lib/faketime/libfaketime.so.1",
+    "/usr/lib64/faketime/libfaketime.so.1",
+    "/usr/local/lib/faketime/libfaketime.so.1",
+];
+
+/// Locate the libfaketime library.
+pub(crate) fn library() -> io::Result<path::PathBuf> {
+    if let Some(lib) = env::var_os(LIB_ENV) {
+        return Ok(lib.into());
+    }
+    CANDIDATES
+        .iter()
+        .map(path::Path::new)
+        .find(|p| p.exists())
+        .map(|p| p.to_owned())
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("libfaketime not found; install it or set `{}`", LIB_ENV),
+            )
+        })
+}
+
+/// The environment that loads libfaketime, starting the clock at `time`.
+pub(crate) fn env(time: &str) -> io::Result<Vec<(&'static str, String)>> {
+    if cfg!(windows) {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "faking time is not supported on Windows",
+        ));
+    }
+    let (faketime, utc) = parse_time(time)?;
+    let lib = library()?.display().to_string();
+    let mut vars = vec![("FAKETIME", faketime)];
+    if utc {
+        vars.push(("TZ", "UTC".to_owned()));
+    }
+    if cfg!(target_os = "macos") {
+        vars.push(("DYLD_INSERT_LIBRARIES", lib));
+        vars.push(("DYLD_FORCE_FLAT_NAMESPACE", "1".to_owned()));
+    } else {
+        vars.push(("LD_PRELOAD", lib));
+    }
+    Ok(vars)
+}
+
+/// Convert an RFC 3339 timestamp to libfaketime's "start at" format, and whether it is UTC.
+fn parse_time(time: &str) -> io::Result<(String, bool)> {
+    let invalid = || {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "expected a time like `2020-01-01T00:00:00Z`, got `{}`",
+                time
+            ),
+        )
+    };
+    let (time, utc) = match time.strip_suffix('Z') {
+        Some(time) => (time, true),
+        None => (time, false),
+    };
+    let (date, clock) = time.split_once(['T', ' ']).ok_or_else(invalid)?;
+    let shape_ok = |s: &str, sep: char, parts: usize| {
+        let fields: Vec<_> = s.split(sep).collect();
+        fields.len() == parts
+            && fields
+                .iter()
+                .all(|f| !f.is_empty() && f.chars().all(|c| c.is_ascii_digit()))
+    };
+    if !shape_ok(date, '-', 3) || !shape_ok(clock, ':', 3) {
+        return Err(invalid());
+    }
+    Ok((format!("@{} {}", date, clock), utc))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_rfc3339() {
+        assert_eq!(
+            parse_time("2020-01-01T00:00:00Z").unwrap(),
+            ("@2020-01-01 00:00:00".to_owned(), true)
+        );
+        assert_eq!(
+            parse_time("2020-01-01 12:30:00").unwrap(),
+            ("@2020-01-01 12:30:00".to_owned(), false)
+        );
+        assert!(parse_time("yesterday").is_err());
+        assert!(parse_time("2020-01-01T00:00Z").is_err());
+    }
+}
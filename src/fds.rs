@@ -0,0 +1,68 @@
+//! Keep the test process's descriptors from leaking into the child.
+
+use std::fs;
+use std::io;
+
+/// The first descriptor after stdio.
+const FIRST: libc::c_int = 3;
+
+/// One past the highest descriptor that may currently be open.
+pub(crate) fn upper_bound() -> libc::c_int {
+    let listed = fs::read_dir("/proc/self/fd")
+        .or_else(|_| fs::read_dir("/dev/fd"))
+        .ok()
+        .and_then(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter_map(|entry| entry.file_name().to_str()?.parse::<libc::c_int>().ok())
+                .max()
+        });
+    match listed {
+        Some(highest) => highest.saturating_add(1),
+        None => {
+            // SAFETY: `sysconf` has no preconditions
+            let max = unsafe { libc::sysconf(libc::_SC_OPEN_MAX) };
+            if 0 < max && max <= libc::c_long::from(libc::c_int::MAX) {
+                max as libc::c_int
+            } else {
+                libc::c_int::from(u16::MAX)
+            }
+        }
+    }
+}
+
+/// Mark every descriptor after stdio, up to `bound`, close-on-exec.
+///
+/// This is async-signal-safe, for use between `fork` and `exec`.  Descriptors are marked rather
+/// than closed so `std`'s own close-on-exec pipe for reporting `exec` failures keeps working.
+pub(crate) fn close_on_exec(bound: libc::c_int) -> io::Result<()> {
+    for fd in FIRST..bound {
+        // SAFETY: `fcntl` is async-signal-safe.  Descriptors that aren't open just fail.
+        unsafe {
+            let flags = libc::fcntl(fd, libc::F_GETFD);
+            if 0 <= flags && flags & libc::FD_CLOEXEC == 0 {
+                libc::fcntl(fd, libc::F_SETFD, flags | libc::FD_CLOEXEC);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cmd::Command;
+
+    #[test]
+    fn leaked_descriptor() {
+        // SAFETY: `dup` has no preconditions; the copy is leaked on purpose, without close-on-exec.
+        let fd = unsafe { libc::dup(2) };
+        assert!(0 <= fd);
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "test -e /dev/fd/$0"]).arg(fd.to_string());
+        cmd.assert().success();
+        cmd.inherit_handles(false).assert().failure();
+        cmd.inherit_handles(true).assert().success();
+        // SAFETY: `fd` is ours
+        unsafe { libc::close(fd) };
+    }
+}
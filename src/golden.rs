@@ -0,0 +1,512 @@
+//! Compare output against files checked into the repo.
+//!
+//! [`Assert::matches_expected`] compares against files in `tests/expected`, and
+//! [`Assert::stdout_matches_path`] against any file.  Rather than
+//! overwriting them all with `ASSERT_CMD_OVERWRITE=1`, set `ASSERT_CMD_PENDING=1` to save each
+//! mismatching output next to its expected file, as `<file>.pending`, then review the changes,
+//! like from a `cargo xtask`:
+//!
+//! ```rust,no_run
+//! let reviewed = assert_cmd::golden::review("tests/expected").unwrap();
+//! println!("{}", reviewed);
+//! ```
+//!
+//! Or go through [`pending`] to apply your own policy.
+//!
+//! [`Assert::matches_snapshot`] instead keeps a command's exit code, `stdout`, and `stderr`
+//! together in one named file under `tests/snapshots`, updated with `UPDATE_SNAPSHOTS=1`, and
+//! [`Assert::matches_path`] keeps them as a TOML document at any path.
+//!
+//! [`Assert::matches_expected`]: crate::assert::Assert::matches_expected()
+//! [`Assert::matches_path`]: crate::assert::Assert::matches_path()
+//! [`Assert::matches_snapshot`]: crate::assert::Assert::matches_snapshot()
+//! [`Assert::stdout_matches_path`]: crate::assert::Assert::stdout_matches_path()
+
+use std::env;
+use std::ffi;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::io::{BufRead, Write};
+use std::path;
+
+/// Set to rewrite expected-output files with the actual output.
+pub(crate) const OVERWRITE_ENV: &str = "ASSERT_CMD_OVERWRITE";
+
+/// Set to save mismatching output as `<file>.pending`, for [`review`].
+pub(crate) const PENDING_ENV: &str = "ASSERT_CMD_PENDING";
+
+/// Set to rewrite snapshot files with the actual output.
+pub(crate) const UPDATE_SNAPSHOTS_ENV: &str = "UPDATE_SNAPSHOTS";
+
+const PENDING_EXTENSION: &str = "pending";
+
+const SNAPSHOT_EXTENSION: &str = "snap";
+
+fn is_set(var: &str) -> bool {
+    matches!(env::var_os(var), Some(v) if !v.is_empty() && v != "0")
+}
+
+fn pending_path(path: &path::Path) -> path::PathBuf {
+    let mut pending = path.as_os_str().to_owned();
+    pending.push(".");
+    pending.push(PENDING_EXTENSION);
+    pending.into()
+}
+
+/// Where [`Assert::matches_expected`] looks for the current test's files.
+///
+/// [`Assert::matches_expected`]: crate::assert::Assert::matches_expected()
+pub(crate) fn expected_stem() -> Result<path::PathBuf, String> {
+    let thread = std::thread::current();
+    let test = match thread.name() {
+        Some(name) if name != "main" => name.rsplit("::").next().unwrap_or(name),
+        _ => {
+            return Err(
+                "Unable to determine the test name; run with the default test harness and more than one test thread".to_owned(),
+            )
+        }
+    };
+    let root = env::var_os("CARGO_MANIFEST_DIR")
+        .map(path::PathBuf::from)
+        .unwrap_or_default();
+    Ok(root.join("tests").join("expected").join(test))
+}
+
+/// Where [`Assert::matches_snapshot`] keeps the snapshot `name` for the current test's module.
+///
+/// [`Assert::matches_snapshot`]: crate::assert::Assert::matches_snapshot()
+pub(crate) fn snapshot_path(name: &str) -> path::PathBuf {
+    let mut dir = env::var_os("CARGO_MANIFEST_DIR")
+        .map(path::PathBuf::from)
+        .unwrap_or_default()
+        .join("tests")
+        .join("snapshots");
+    let thread = std::thread::current();
+    if let Some((module, _test)) = thread.name().and_then(|name| name.rsplit_once("::")) {
+        dir.extend(module.split("::"));
+    }
+    let mut file = ffi::OsString::from(name);
+    file.push(".");
+    file.push(SNAPSHOT_EXTENSION);
+    dir.join(file)
+}
+
+/// Render what [`Assert::matches_snapshot`] compares.
+///
+/// [`Assert::matches_snapshot`]: crate::assert::Assert::matches_snapshot()
+pub(crate) fn snapshot(code: Option<i32>, streams: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut snapshot = match code {
+        Some(code) => format!("code: {}\n", code),
+        None => "code: interrupted\n".to_owned(),
+    }
+    .into_bytes();
+    for (name, data) in streams {
+        snapshot.extend_from_slice(format!("--- {}\n", name).as_bytes());
+        snapshot.extend_from_slice(data);
+        if !data.is_empty() && !data.ends_with(b"\n") {
+            snapshot.extend_from_slice(b"\n\\ No newline at end\n");
+        }
+    }
+    snapshot
+}
+
+/// Render what [`Assert::matches_path`] compares: a TOML document with the status and each
+/// stream.
+///
+/// Multi-line text is kept as-is in a literal string where possible, to read well in diffs.
+///
+/// [`Assert::matches_path`]: crate::assert::Assert::matches_path()
+pub(crate) fn toml_result(status: &str, streams: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut document = format!("{}\n", status);
+    for (name, data) in streams {
+        let text = String::from_utf8_lossy(data);
+        document.push_str(&format!("{} = {}\n", name, toml_string(&text)));
+    }
+    document.into_bytes()
+}
+
+fn toml_string(text: &str) -> String {
+    let literal = text.contains('\n')
+        && !text.contains("'''")
+        && !text.ends_with('\'')
+        && !text
+            .chars()
+            .any(|c| c.is_control() && c != '\n' && c != '\t');
+    if literal {
+        // A newline right after the opening delimiter is trimmed.
+        return format!("'''\n{}'''", text);
+    }
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04X}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Compare `actual` against the contents of `path`, rewriting it in overwrite mode.
+pub(crate) fn compare(path: &path::Path, actual: &[u8]) -> Result<(), String> {
+    compare_with(path, actual, OVERWRITE_ENV)
+}
+
+/// Compare `actual` against the contents of `path`, rewriting it when `overwrite_env` is set.
+pub(crate) fn compare_with(
+    path: &path::Path,
+    actual: &[u8],
+    overwrite_env: &str,
+) -> Result<(), String> {
+    compare_impl(path, actual, overwrite_env, None)
+}
+
+/// [`compare`], overlooking the differences `tolerance` allows.
+///
+/// Overwriting and pending files still save `actual` as-is.
+pub(crate) fn compare_tolerant(
+    path: &path::Path,
+    actual: &[u8],
+    tolerance: &crate::settings::Tolerance,
+) -> Result<(), String> {
+    compare_impl(path, actual, OVERWRITE_ENV, Some(tolerance))
+}
+
+fn compare_impl(
+    path: &path::Path,
+    actual: &[u8],
+    overwrite_env: &str,
+    tolerance: Option<&crate::settings::Tolerance>,
+) -> Result<(), String> {
+    if is_set(overwrite_env) {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|err| format!("Failed to create `{}`: {}", parent.display(), err))?;
+        }
+        return fs::write(path, actual)
+            .map_err(|err| format!("Failed to write `{}`: {}", path.display(), err));
+    }
+
+    let pending = pending_path(path);
+    let expected = match fs::read(path) {
+        Ok(expected) => expected,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            save_pending(&pending, actual)?;
+            return Err(format!(
+                "Expected file `{}` is missing; rerun with `{}=1` to create it",
+                path.display(),
+                overwrite_env
+            ));
+        }
+        Err(err) => return Err(format!("Failed to read `{}`: {}", path.display(), err)),
+    };
+    let (expected, compared) = match tolerance {
+        Some(tolerance) => (tolerance.apply(&expected), tolerance.apply(actual)),
+        None => (expected, actual.to_vec()),
+    };
+    if expected == compared {
+        // Whatever was pending is outdated
+        let _ = fs::remove_file(&pending);
+        return Ok(());
+    }
+
+    save_pending(&pending, actual)?;
+    let tolerating = match tolerance {
+        Some(tolerance) => format!(" (tolerating {})", tolerance),
+        None => String::new(),
+    };
+    Err(format!(
+        "Output doesn't match `{}`{}, rerun with `{}=1` to update it\n{}",
+        path.display(),
+        tolerating,
+        overwrite_env,
+        diff(&expected, &compared)
+    ))
+}
+
+fn save_pending(pending: &path::Path, actual: &[u8]) -> Result<(), String> {
+    if !is_set(PENDING_ENV) {
+        return Ok(());
+    }
+    if let Some(parent) = pending.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|err| format!("Failed to create `{}`: {}", parent.display(), err))?;
+    }
+    fs::write(pending, actual)
+        .map_err(|err| format!("Failed to write `{}`: {}", pending.display(), err))
+}
+
+fn diff(expected: &[u8], actual: &[u8]) -> String {
+    crate::diff::unified(
+        &String::from_utf8_lossy(expected),
+        &String::from_utf8_lossy(actual),
+        crate::diff::color(),
+    )
+}
+
+/// An output saved by `ASSERT_CMD_PENDING=1`, waiting to be accepted or rejected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pending {
+    expected: path::PathBuf,
+    pending: path::PathBuf,
+}
+
+impl Pending {
+    /// The expected-output file this would replace.
+    pub fn expected_path(&self) -> &path::Path {
+        &self.expected
+    }
+
+    /// Where the new output is saved.
+    pub fn pending_path(&self) -> &path::Path {
+        &self.pending
+    }
+
+    /// Whether there is no expected-output file yet.
+    pub fn is_new(&self) -> bool {
+        !self.expected.exists()
+    }
+
+    /// The differences between the expected and the pending output.
+    pub fn diff(&self) -> io::Result<String> {
+        let expected = match fs::read(&self.expected) {
+            Ok(expected) => expected,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err),
+        };
+        let actual = fs::read(&self.pending)?;
+        Ok(diff(&expected, &actual))
+    }
+
+    /// Replace the expected output with the pending one.
+    pub fn accept(self) -> io::Result<()> {
+        fs::rename(&self.pending, &self.expected)
+    }
+
+    /// Discard the pending output.
+    pub fn reject(self) -> io::Result<()> {
+        fs::remove_file(&self.pending)
+    }
+}
+
+/// Find the [`Pending`] outputs under `dir`, like `tests/expected`, sorted by path.
+pub fn pending<P: AsRef<path::Path>>(dir: P) -> io::Result<Vec<Pending>> {
+    let mut found = Vec::new();
+    let mut dirs = vec![dir.as_ref().to_owned()];
+    while let Some(dir) = dirs.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        };
+        for entry in entries {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if matches!(path.extension(), Some(ext) if ext == PENDING_EXTENSION) {
+                found.push(Pending {
+                    expected: path.with_extension(""),
+                    pending: path,
+                });
+            }
+        }
+    }
+    found.sort_by(|a, b| a.pending.cmp(&b.pending));
+    Ok(found)
+}
+
+/// Interactively accept or reject each [`Pending`] output under `dir`, on stdin and stdout.
+pub fn review<P: AsRef<path::Path>>(dir: P) -> io::Result<Reviewed> {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    review_with(pending(dir)?, &mut stdin.lock(), &mut stdout.lock())
+}
+
+fn review_with(
+    pending: Vec<Pending>,
+    input: &mut dyn BufRead,
+    output: &mut dyn Write,
+) -> io::Result<Reviewed> {
+    let mut reviewed = Reviewed::default();
+    let total = pending.len();
+    for (i, change) in pending.into_iter().enumerate() {
+        let status = if change.is_new() { "new" } else { "changed" };
+        writeln!(
+            output,
+            "[{}/{}] {} ({})",
+            i + 1,
+            total,
+            change.expected_path().display(),
+            status
+        )?;
+        writeln!(output, "{}", change.diff()?)?;
+        loop {
+            write!(output, "[a]ccept, [r]eject, [s]kip? ")?;
+            output.flush()?;
+            let mut answer = String::new();
+            if input.read_line(&mut answer)? == 0 {
+                // Out of input: leave the rest pending
+                reviewed.skipped += total - i;
+                return Ok(reviewed);
+            }
+            match answer.trim() {
+                "a" => {
+                    change.accept()?;
+                    reviewed.accepted += 1;
+                }
+                "r" => {
+                    change.reject()?;
+                    reviewed.rejected += 1;
+                }
+                "s" => reviewed.skipped += 1,
+                _ => continue,
+            }
+            break;
+        }
+    }
+    Ok(reviewed)
+}
+
+/// The outcome of a [`review`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Reviewed {
+    /// How many pending outputs replaced their expected files.
+    pub accepted: usize,
+    /// How many pending outputs were discarded.
+    pub rejected: usize,
+    /// How many pending outputs are still pending.
+    pub skipped: usize,
+}
+
+impl fmt::Display for Reviewed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} accepted, {} rejected, {} skipped",
+            self.accepted, self.rejected, self.skipped
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn missing_file_hints_overwrite() {
+        let path = crate::temp::unique_path("assert_cmd-golden");
+        let err = compare(&path, b"actual").unwrap_err();
+        assert!(err.contains("ASSERT_CMD_OVERWRITE=1"), "{}", err);
+    }
+
+    #[test]
+    fn compares_contents() {
+        let path = crate::temp::unique_path("assert_cmd-golden");
+        fs::write(&path, "expected\n").unwrap();
+        assert_eq!(compare(&path, b"expected\n"), Ok(()));
+        assert!(compare(&path, b"actual\n").is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn snapshot_layout() {
+        let path = std::thread::Builder::new()
+            .name("cli::help::short".to_owned())
+            .spawn(|| snapshot_path("usage"))
+            .unwrap()
+            .join()
+            .unwrap();
+        assert!(
+            path.ends_with("tests/snapshots/cli/help/usage.snap"),
+            "{}",
+            path.display()
+        );
+
+        let rendered = snapshot(Some(2), &[("stdout", b""), ("stderr", b"usage: bin")]);
+        assert_eq!(
+            String::from_utf8(rendered).unwrap(),
+            "code: 2\n--- stdout\n--- stderr\nusage: bin\n\\ No newline at end\n"
+        );
+    }
+
+    #[test]
+    fn toml_layout() {
+        let rendered = toml_result(
+            "status.code = 2",
+            &[
+                ("stdout", b"line 1\nline 2\n"),
+                ("stderr", b"say \"hi\"\x1b"),
+            ],
+        );
+        assert_eq!(
+            String::from_utf8(rendered).unwrap(),
+            "status.code = 2\nstdout = '''\nline 1\nline 2\n'''\nstderr = \"say \\\"hi\\\"\\u001B\"\n"
+        );
+        assert_eq!(toml_string("it's\n"), "'''\nit's\n'''");
+        assert_eq!(toml_string("ends in '\n'"), "\"ends in '\\n'\"");
+    }
+
+    #[test]
+    fn review_pending() {
+        let dir = crate::temp::unique_path("assert_cmd-golden");
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        fs::write(dir.join("changed.stdout"), "old\n").unwrap();
+        fs::write(dir.join("changed.stdout.pending"), "new\n").unwrap();
+        fs::write(dir.join("nested/new.stderr.pending"), "new\n").unwrap();
+        fs::write(dir.join("skipped.code.pending"), "1\n").unwrap();
+
+        let found = pending(&dir).unwrap();
+        let expected: Vec<_> = found.iter().map(Pending::expected_path).collect();
+        assert_eq!(
+            expected,
+            [
+                dir.join("changed.stdout"),
+                dir.join("nested/new.stderr"),
+                dir.join("skipped.code")
+            ]
+        );
+        assert!(!found[0].is_new());
+        assert!(found[1].is_new());
+
+        let mut output = Vec::new();
+        let reviewed = review_with(found, &mut &b"x\na\nr\n"[..], &mut output).unwrap();
+        assert_eq!(
+            reviewed,
+            Reviewed {
+                accepted: 1,
+                rejected: 1,
+                skipped: 1
+            }
+        );
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("changed.stdout (changed)"), "{}", output);
+        assert!(output.contains("new.stderr (new)"), "{}", output);
+        assert_eq!(
+            fs::read_to_string(dir.join("changed.stdout")).unwrap(),
+            "new\n"
+        );
+        assert!(!dir.join("nested/new.stderr").exists());
+        assert!(!dir.join("nested/new.stderr.pending").exists());
+        assert!(dir.join("skipped.code.pending").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn stem_from_test_name() {
+        std::thread::Builder::new()
+            .name("cli::help_output".to_owned())
+            .spawn(|| {
+                let stem = expected_stem().unwrap();
+                assert!(stem.ends_with("tests/expected/help_output"));
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+}
@@ -0,0 +1,668 @@
+//! Run a directory of test cases, each declared in a file.
+//!
+//! Rather than a test function per case, declare each case in a file and run them all from one
+//! test:
+//!
+//! ```rust,no_run
+//! #[test]
+//! fn cli() {
+//!     assert_cmd::harness::TestCases::new()
+//!         .case("tests/cmd/*.toml")
+//!         .case("tests/cmd/*.trycmd");
+//! }
+//! ```
+//!
+//! A `.toml` case runs one command:
+//!
+//! ```toml
+//! bin.name = "bin_fixture"
+//! args = ["--verbose", "input.txt"]
+//! stdin = "hello"
+//! stdout = """
+//! Processed [..] bytes
+//! """
+//! status.code = 0
+//!
+//! [env]
+//! RUST_LOG = "debug"
+//! ```
+//!
+//! - `bin.name` (or `bin`) is found like [`Command::cargo_bin`], else looked up on `PATH`.
+//! - `args` is an array, or a string split on whitespace, respecting `"` and `'` quotes.
+//! - `stdout` and `stderr` are [`pattern`][crate::pattern::pattern()]s, read from the case's
+//!   sibling `.stdout` and `.stderr` files when not given inline.  Unspecified output isn't
+//!   checked.
+//! - `status` is `"success"` (the default) or `"failed"`, or `status.code` an exit code.
+//!
+//! A `.trycmd` or `.md` case is a [`console` transcript][crate::console].
+//!
+//! The cases are named in the test's output as they run.  Every case is run, and then any
+//! failures are reported together.
+//!
+//! [`Command::cargo_bin`]: crate::cmd::Command::cargo_bin()
+
+use std::convert::TryFrom;
+use std::ffi;
+use std::fmt::Write;
+use std::fs;
+use std::path;
+use std::thread;
+
+/// Test cases declared in files, see the [`harness` module documentation][crate::harness].
+///
+/// The cases run with [`TestCases::run`], or when dropped.
+#[derive(Debug, Default)]
+pub struct TestCases {
+    patterns: Vec<path::PathBuf>,
+    ran: bool,
+}
+
+impl TestCases {
+    /// Start with no cases.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the case files matching `pattern`, where `*` and `?` in the file name match any text
+    /// and any one character.
+    ///
+    /// Relative paths are relative to the test's working directory, the crate root under
+    /// `cargo test`.
+    pub fn case<P: AsRef<path::Path>>(&mut self, pattern: P) -> &mut Self {
+        self.patterns.push(pattern.as_ref().to_owned());
+        self
+    }
+
+    /// Run every case, panicking with each failure if any failed.
+    pub fn run(&mut self) {
+        self.ran = true;
+        let mut failures = Vec::new();
+        let mut cases = Vec::new();
+        for pattern in &self.patterns {
+            match expand(pattern) {
+                Ok(found) if found.is_empty() => failures.push((
+                    pattern.clone(),
+                    "No case files match".to_owned(),
+                    String::new(),
+                )),
+                Ok(found) => cases.extend(found),
+                Err(err) => failures.push((pattern.clone(), err, String::new())),
+            }
+        }
+        cases.sort();
+        cases.dedup();
+
+        let total = cases.len();
+        for case in cases {
+            let result = crate::aggregate::catch_all(|| run_case(&case));
+            let status = if result.is_ok() { "ok" } else { "FAILED" };
+            println!("case {} ... {}", case.display(), status);
+            if let Err((signature, dump)) = result {
+                failures.push((case, signature, dump));
+            }
+        }
+
+        if !failures.is_empty() {
+            let mut report = format!("{} of {} cases failed", failures.len(), total);
+            for (case, signature, dump) in &failures {
+                let _ = write!(report, "\n--- {} ---\n{}", case.display(), signature);
+                if !dump.is_empty() {
+                    let _ = write!(report, "\n{}", dump);
+                }
+            }
+            panic!("{}", report);
+        }
+    }
+}
+
+impl Drop for TestCases {
+    fn drop(&mut self) {
+        if !self.ran && !thread::panicking() {
+            self.run();
+        }
+    }
+}
+
+/// The files matching `pattern`, sorted.
+fn expand(pattern: &path::Path) -> Result<Vec<path::PathBuf>, String> {
+    let name = match pattern.file_name().and_then(ffi::OsStr::to_str) {
+        Some(name) if name.contains(['*', '?']) => name,
+        _ => return Ok(vec![pattern.to_owned()]),
+    };
+    let dir = match pattern.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => path::Path::new("."),
+    };
+    let entries =
+        fs::read_dir(dir).map_err(|err| format!("Unable to read `{}`: {}", dir.display(), err))?;
+    let mut found: Vec<_> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            matches!(path.file_name().and_then(ffi::OsStr::to_str), Some(file) if wildcard(name, file))
+        })
+        .collect();
+    found.sort();
+    Ok(found)
+}
+
+fn wildcard(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<_> = pattern.chars().collect();
+    let name: Vec<_> = name.chars().collect();
+    fn matches(pattern: &[char], name: &[char]) -> bool {
+        match pattern.split_first() {
+            None => name.is_empty(),
+            Some(('*', rest)) => (0..=name.len()).any(|i| matches(rest, &name[i..])),
+            Some((&c, rest)) => match name.split_first() {
+                Some((&n, others)) => (c == '?' || c == n) && matches(rest, others),
+                None => false,
+            },
+        }
+    }
+    matches(&pattern, &name)
+}
+
+fn run_case(path: &path::Path) {
+    let text = fs::read_to_string(path)
+        .unwrap_or_else(|err| panic!("Unable to read `{}`: {}", path.display(), err));
+    match path.extension().and_then(ffi::OsStr::to_str) {
+        Some("toml") => Case::parse(&text)
+            .unwrap_or_else(|err| panic!("Malformed case `{}`: {}", path.display(), err))
+            .run(path),
+        Some("trycmd") | Some("md") => crate::console::assert_transcript(&text),
+        _ => panic!(
+            "Unsupported case `{}`, expected a `.toml`, `.trycmd`, or `.md` file",
+            path.display()
+        ),
+    }
+}
+
+/// A `.toml` case.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Case {
+    bin: String,
+    args: Vec<String>,
+    env: Vec<(String, String)>,
+    stdin: Option<String>,
+    stdout: Option<String>,
+    stderr: Option<String>,
+    status: Status,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+enum Status {
+    #[default]
+    Success,
+    Failed,
+    Code(i32),
+}
+
+impl Case {
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut case = Case::default();
+        for (key, value) in parse_toml(text)? {
+            match (key.as_str(), value) {
+                ("bin", Value::Str(bin)) | ("bin.name", Value::Str(bin)) => case.bin = bin,
+                ("args", Value::Str(args)) => case.args = crate::console::split_words(&args)?,
+                ("args", Value::Array(args)) => {
+                    case.args = args
+                        .into_iter()
+                        .map(|arg| match arg {
+                            Value::Str(arg) => Ok(arg),
+                            _ => Err("`args` must be strings".to_owned()),
+                        })
+                        .collect::<Result<_, _>>()?
+                }
+                ("stdin", Value::Str(stdin)) => case.stdin = Some(stdin),
+                ("stdout", Value::Str(stdout)) => case.stdout = Some(stdout),
+                ("stderr", Value::Str(stderr)) => case.stderr = Some(stderr),
+                ("status", Value::Str(status)) => {
+                    case.status = match status.as_str() {
+                        "success" => Status::Success,
+                        "failed" => Status::Failed,
+                        _ => return Err(format!("unknown `status` `{}`", status)),
+                    }
+                }
+                ("status.code", Value::Int(code)) => {
+                    let code = i32::try_from(code)
+                        .map_err(|_| format!("`status.code` {} is out of range", code))?;
+                    case.status = Status::Code(code);
+                }
+                (key, Value::Str(value)) if key.starts_with("env.") => {
+                    let name = key.trim_start_matches("env.");
+                    let name = name.strip_prefix("add.").unwrap_or(name);
+                    case.env.push((name.to_owned(), value));
+                }
+                (key, _) => return Err(format!("unsupported key `{}`", key)),
+            }
+        }
+        if case.bin.is_empty() {
+            return Err("missing `bin.name`".to_owned());
+        }
+        Ok(case)
+    }
+
+    fn run(&self, path: &path::Path) {
+        let mut cmd = crate::console::command(&self.bin);
+        cmd.args(&self.args);
+        for (name, value) in &self.env {
+            cmd.env(name, value);
+        }
+        if let Some(stdin) = &self.stdin {
+            cmd.write_stdin(stdin.as_bytes());
+        }
+        let mut assert = cmd
+            .assert()
+            .append_context("case", path.display().to_string());
+        assert = match self.status {
+            Status::Success => assert.success(),
+            Status::Failed => assert.failure(),
+            Status::Code(code) => assert.code(code),
+        };
+        let sibling = |ext| fs::read_to_string(path.with_extension(ext)).ok();
+        if let Some(stdout) = self.stdout.clone().or_else(|| sibling("stdout")) {
+            assert = assert.stdout(crate::pattern::pattern(stdout));
+        }
+        match self.stderr.clone().or_else(|| sibling("stderr")) {
+            Some(stderr) => {
+                assert.stderr(crate::pattern::pattern(stderr));
+            }
+            // A case without `stderr` doesn't check it.
+            None => assert.discard(),
+        }
+    }
+}
+
+/// A value in the subset of TOML cases use.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Value {
+    Str(String),
+    Int(i64),
+    Bool(bool),
+    Array(Vec<Value>),
+}
+
+/// Parse `text` into its keys, dotted with their tables, and values.
+///
+/// This covers strings, integers, booleans, arrays, and tables, but not inline tables or dates.
+fn parse_toml(text: &str) -> Result<Vec<(String, Value)>, String> {
+    let mut parser = Parser {
+        chars: text.chars().collect(),
+        pos: 0,
+    };
+    parser.document().map_err(|err| {
+        let line = parser.chars[..parser.pos]
+            .iter()
+            .filter(|&&c| c == '\n')
+            .count();
+        format!("line {}: {}", line + 1, err)
+    })
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn document(&mut self) -> Result<Vec<(String, Value)>, String> {
+        let mut entries = Vec::new();
+        let mut table = String::new();
+        loop {
+            self.skip_blank();
+            match self.peek() {
+                None => return Ok(entries),
+                Some('[') => {
+                    self.pos += 1;
+                    table = self.key()?;
+                    self.expect(']')?;
+                }
+                Some(_) => {
+                    let key = self.key()?;
+                    self.expect('=')?;
+                    let value = self.value()?;
+                    let key = if table.is_empty() {
+                        key
+                    } else {
+                        format!("{}.{}", table, key)
+                    };
+                    entries.push((key, value));
+                }
+            }
+            self.end_of_line()?;
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        s.chars()
+            .enumerate()
+            .all(|(i, c)| self.chars.get(self.pos + i) == Some(&c))
+    }
+
+    fn skip_spaces(&mut self) {
+        while matches!(self.peek(), Some(' ') | Some('\t')) {
+            self.pos += 1;
+        }
+    }
+
+    /// Skip whitespace, newlines, and comments.
+    fn skip_blank(&mut self) {
+        loop {
+            match self.peek() {
+                Some(' ') | Some('\t') | Some('\r') | Some('\n') => self.pos += 1,
+                Some('#') => self.skip_comment(),
+                _ => return,
+            }
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        while !matches!(self.peek(), None | Some('\n')) {
+            self.pos += 1;
+        }
+    }
+
+    fn end_of_line(&mut self) -> Result<(), String> {
+        self.skip_spaces();
+        if self.peek() == Some('#') {
+            self.skip_comment();
+        }
+        match self.peek() {
+            None | Some('\n') | Some('\r') => Ok(()),
+            Some(c) => Err(format!("unexpected `{}`", c)),
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_spaces();
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(format!("expected `{}`", expected))
+        }
+    }
+
+    fn key(&mut self) -> Result<String, String> {
+        let mut parts = Vec::new();
+        loop {
+            self.skip_spaces();
+            let part = match self.peek() {
+                Some('"') => self.basic_string()?,
+                Some('\'') => self.literal_string()?,
+                _ => {
+                    let start = self.pos;
+                    while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || c == '_' || c == '-')
+                    {
+                        self.pos += 1;
+                    }
+                    if start == self.pos {
+                        return Err("expected a key".to_owned());
+                    }
+                    self.chars[start..self.pos].iter().collect()
+                }
+            };
+            parts.push(part);
+            self.skip_spaces();
+            if self.peek() != Some('.') {
+                return Ok(parts.join("."));
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_spaces();
+        if self.starts_with("\"\"\"") {
+            return self.multiline_string("\"\"\"", true).map(Value::Str);
+        }
+        if self.starts_with("'''") {
+            return self.multiline_string("'''", false).map(Value::Str);
+        }
+        if self.starts_with("true") {
+            self.pos += 4;
+            return Ok(Value::Bool(true));
+        }
+        if self.starts_with("false") {
+            self.pos += 5;
+            return Ok(Value::Bool(false));
+        }
+        match self.peek() {
+            Some('"') => self.basic_string().map(Value::Str),
+            Some('\'') => self.literal_string().map(Value::Str),
+            Some('[') => self.array(),
+            Some(c) if c.is_ascii_digit() || c == '-' || c == '+' => self.integer(),
+            _ => Err("expected a value".to_owned()),
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        loop {
+            self.skip_blank();
+            if self.peek() == Some(']') {
+                self.pos += 1;
+                return Ok(Value::Array(items));
+            }
+            items.push(self.value()?);
+            self.skip_blank();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(']') => {}
+                _ => return Err("expected `,` or `]`".to_owned()),
+            }
+        }
+    }
+
+    fn integer(&mut self) -> Result<Value, String> {
+        let start = self.pos;
+        self.pos += 1;
+        while matches!(self.peek(), Some(c) if c.is_ascii_digit() || c == '_') {
+            self.pos += 1;
+        }
+        let digits: String = self.chars[start..self.pos]
+            .iter()
+            .filter(|&&c| c != '_')
+            .collect();
+        digits
+            .parse()
+            .map(Value::Int)
+            .map_err(|_| format!("invalid integer `{}`", digits))
+    }
+
+    fn basic_string(&mut self) -> Result<String, String> {
+        self.pos += 1;
+        let mut value = String::new();
+        loop {
+            match self.peek() {
+                None | Some('\n') => return Err("unterminated string".to_owned()),
+                Some('"') => {
+                    self.pos += 1;
+                    return Ok(value);
+                }
+                Some('\\') => value.push(self.escape()?),
+                Some(c) => {
+                    self.pos += 1;
+                    value.push(c);
+                }
+            }
+        }
+    }
+
+    fn literal_string(&mut self) -> Result<String, String> {
+        self.pos += 1;
+        let start = self.pos;
+        loop {
+            match self.peek() {
+                None | Some('\n') => return Err("unterminated string".to_owned()),
+                Some('\'') => {
+                    let value = self.chars[start..self.pos].iter().collect();
+                    self.pos += 1;
+                    return Ok(value);
+                }
+                Some(_) => self.pos += 1,
+            }
+        }
+    }
+
+    fn multiline_string(&mut self, delimiter: &str, escapes: bool) -> Result<String, String> {
+        self.pos += delimiter.len();
+        // A newline right after the opening delimiter is trimmed.
+        if self.starts_with("\r\n") {
+            self.pos += 2;
+        } else if self.starts_with("\n") {
+            self.pos += 1;
+        }
+        let mut value = String::new();
+        loop {
+            if self.starts_with(delimiter) {
+                self.pos += delimiter.len();
+                return Ok(value);
+            }
+            match self.peek() {
+                None => return Err("unterminated string".to_owned()),
+                Some('\\') if escapes => {
+                    if matches!(self.chars.get(self.pos + 1), Some(c) if c.is_whitespace()) {
+                        // A line-ending backslash trims the whitespace that follows.
+                        self.pos += 1;
+                        while matches!(self.peek(), Some(c) if c.is_whitespace()) {
+                            self.pos += 1;
+                        }
+                    } else {
+                        value.push(self.escape()?);
+                    }
+                }
+                Some(c) => {
+                    self.pos += 1;
+                    value.push(c);
+                }
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, String> {
+        self.pos += 1;
+        let c = self.peek().ok_or("unterminated string")?;
+        self.pos += 1;
+        let unicode = |parser: &mut Self, len: usize| {
+            let hex: String = parser.chars.iter().skip(parser.pos).take(len).collect();
+            parser.pos += len;
+            u32::from_str_radix(&hex, 16)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or_else(|| format!("invalid escape `\\{}{}`", c, hex))
+        };
+        match c {
+            'b' => Ok('\u{8}'),
+            't' => Ok('\t'),
+            'n' => Ok('\n'),
+            'f' => Ok('\u{c}'),
+            'r' => Ok('\r'),
+            '"' => Ok('"'),
+            '\\' => Ok('\\'),
+            'u' => unicode(self, 4),
+            'U' => unicode(self, 8),
+            _ => Err(format!("invalid escape `\\{}`", c)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_toml_subset() {
+        let entries = parse_toml(
+            r#"
+# A comment
+bin.name = "tool"  # trailing
+args = [
+    "a b",
+    'c\d',
+]
+stdout = """
+line\tone
+line two \
+    continued
+"""
+raw = '''
+C:\path
+'''
+status.code = -2
+quiet = true
+
+[env]
+"RUST_LOG" = "debug"
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                ("bin.name".to_owned(), Value::Str("tool".to_owned())),
+                (
+                    "args".to_owned(),
+                    Value::Array(vec![
+                        Value::Str("a b".to_owned()),
+                        Value::Str("c\\d".to_owned())
+                    ])
+                ),
+                (
+                    "stdout".to_owned(),
+                    Value::Str("line\tone\nline two continued\n".to_owned())
+                ),
+                ("raw".to_owned(), Value::Str("C:\\path\n".to_owned())),
+                ("status.code".to_owned(), Value::Int(-2)),
+                ("quiet".to_owned(), Value::Bool(true)),
+                ("env.RUST_LOG".to_owned(), Value::Str("debug".to_owned())),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_toml_errors() {
+        let err = parse_toml("bin = \"tool\"\nargs = [\"open\"\n").unwrap_err();
+        assert!(err.starts_with("line 3: "), "{}", err);
+        assert!(parse_toml("bin = tool").is_err());
+        assert!(parse_toml("bin = \"a\" \"b\"").is_err());
+    }
+
+    #[test]
+    fn parse_case() {
+        let case = Case::parse(
+            "bin = \"tool\"\nargs = \"--flag 'two words'\"\nstatus = \"failed\"\n[env.add]\nA = \"1\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            case,
+            Case {
+                bin: "tool".to_owned(),
+                args: vec!["--flag".to_owned(), "two words".to_owned()],
+                env: vec![("A".to_owned(), "1".to_owned())],
+                status: Status::Failed,
+                ..Case::default()
+            }
+        );
+
+        assert_eq!(Case::parse("args = []").unwrap_err(), "missing `bin.name`");
+        assert_eq!(
+            Case::parse("bin = \"tool\"\nstdot = \"\"").unwrap_err(),
+            "unsupported key `stdot`"
+        );
+    }
+
+    #[test]
+    fn wildcards() {
+        assert!(wildcard("*.toml", "help.toml"));
+        assert!(wildcard("case-?.toml", "case-1.toml"));
+        assert!(!wildcard("*.toml", "help.stdout"));
+        assert!(!wildcard("case-?.toml", "case-10.toml"));
+    }
+}
@@ -0,0 +1,205 @@
+//! Audit a command for leaks out of its sandbox.
+//!
+//! See [`Assert::audit`].
+//!
+//! Network access is not audited on any platform, because a short-lived child's sockets can't be
+//! observed reliably from outside it.  To keep a command off the network, run the tests without
+//! one, like under `unshare --map-root-user --net cargo test` on Linux.
+//!
+//! [`Assert::audit`]: crate::assert::Assert::audit()
+
+use std::fmt;
+use std::path;
+
+/// Hermeticity rules a command is expected to follow.
+///
+/// The rules cover the child's environment and the paths in its output, not its network access,
+/// see the [module documentation][crate::hermetic].
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use assert_cmd::hermetic::Rules;
+/// use assert_cmd::Command;
+///
+/// let sandbox = std::env::temp_dir();
+/// let rules = Rules::new().allow_env("PATH").allow_env("LC_*").allow_path(&sandbox);
+///
+/// Command::cargo_bin("bin_fixture")
+///     .unwrap()
+///     .env_clear()
+///     .env("PATH", "/usr/bin")
+///     .env_dump(true)
+///     .assert()
+///     .audit(&rules);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Rules {
+    env: Option<Vec<String>>,
+    paths: Vec<path::PathBuf>,
+}
+
+impl Rules {
+    /// Rules that allow no absolute paths in the output and don't audit the environment.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow the child to receive the environment variable `name`.
+    ///
+    /// A trailing `*` allows every variable with that prefix.  Allowing any variable enables
+    /// auditing of the child's environment, which requires [`Command::env_dump`].
+    ///
+    /// [`Command::env_dump`]: crate::cmd::Command::env_dump()
+    pub fn allow_env(mut self, name: impl Into<String>) -> Self {
+        self.env.get_or_insert_with(Vec::new).push(name.into());
+        self
+    }
+
+    /// Allow absolute paths under `root` to appear in the output.
+    pub fn allow_path(mut self, root: impl Into<path::PathBuf>) -> Self {
+        self.paths.push(root.into());
+        self
+    }
+
+    pub(crate) fn audits_env(&self) -> bool {
+        self.env.is_some()
+    }
+
+    pub(crate) fn check_env<'a>(
+        &self,
+        child_env: impl IntoIterator<Item = &'a str>,
+    ) -> Vec<Violation> {
+        let allowed = match self.env {
+            Some(ref allowed) => allowed,
+            None => return Vec::new(),
+        };
+        child_env
+            .into_iter()
+            .filter(|name| !allowed.iter().any(|pattern| env_matches(pattern, name)))
+            .map(|name| Violation::Env(name.to_owned()))
+            .collect()
+    }
+
+    pub(crate) fn check_output(&self, stream: &'static str, data: &[u8]) -> Vec<Violation> {
+        let text = String::from_utf8_lossy(data);
+        let mut violations: Vec<Violation> = Vec::new();
+        for found in absolute_paths(&text) {
+            let allowed = self
+                .paths
+                .iter()
+                .any(|root| path::Path::new(found).starts_with(root));
+            let violation = Violation::Path {
+                stream,
+                path: found.to_owned(),
+            };
+            if !allowed && !violations.contains(&violation) {
+                violations.push(violation);
+            }
+        }
+        violations
+    }
+}
+
+/// A broken hermeticity rule.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Violation {
+    Env(String),
+    Path { stream: &'static str, path: String },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::Env(name) => write!(f, "child was given unlisted env var `{}`", name),
+            Violation::Path { stream, path } => {
+                write!(f, "{} mentions `{}` outside the sandbox", stream, path)
+            }
+        }
+    }
+}
+
+fn env_matches(pattern: &str, name: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => name.starts_with(prefix),
+        None => pattern == name,
+    }
+}
+
+/// Find tokens in `text` that look like absolute paths.
+fn absolute_paths(text: &str) -> impl Iterator<Item = &str> {
+    const DELIMITERS: &[char] = &['"', '\'', '`', '(', ')', '[', ']', '<', '>', ',', ';', '='];
+
+    text.split(|c: char| c.is_whitespace() || DELIMITERS.contains(&c))
+        .flat_map(split_path_list)
+        .map(|token| token.trim_end_matches(['.', ':']))
+        .filter(|token| is_absolute(token))
+}
+
+#[cfg(windows)]
+fn split_path_list(token: &str) -> std::vec::IntoIter<&str> {
+    vec![token].into_iter()
+}
+
+#[cfg(not(windows))]
+fn split_path_list(token: &str) -> std::vec::IntoIter<&str> {
+    token.split(':').collect::<Vec<_>>().into_iter()
+}
+
+#[cfg(windows)]
+fn is_absolute(token: &str) -> bool {
+    let bytes = token.as_bytes();
+    token.starts_with("\\\\")
+        || (bytes.len() >= 3
+            && bytes[0].is_ascii_alphabetic()
+            && bytes[1] == b':'
+            && (bytes[2] == b'\\' || bytes[2] == b'/'))
+}
+
+#[cfg(not(windows))]
+fn is_absolute(token: &str) -> bool {
+    token.len() > 1 && token.starts_with('/') && !token.starts_with("//")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn env_patterns() {
+        let rules = Rules::new().allow_env("PATH").allow_env("LC_*");
+        assert_eq!(
+            rules.check_env(vec!["PATH", "LC_ALL", "HOME"]),
+            vec![Violation::Env("HOME".to_owned())]
+        );
+        assert_eq!(Rules::new().check_env(vec!["HOME"]), vec![]);
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn finds_paths() {
+        let found: Vec<_> =
+            absolute_paths("wrote '/tmp/out.txt'. PATH=/usr/bin:/bin, see https://x.io/a")
+                .collect();
+        assert_eq!(found, vec!["/tmp/out.txt", "/usr/bin", "/bin"]);
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn allowed_roots() {
+        let rules = Rules::new().allow_path("/tmp");
+        assert_eq!(
+            rules.check_output("stdout", b"/tmp/a /home/me/a /home/me/a /tmpfile"),
+            vec![
+                Violation::Path {
+                    stream: "stdout",
+                    path: "/home/me/a".to_owned()
+                },
+                Violation::Path {
+                    stream: "stdout",
+                    path: "/tmpfile".to_owned()
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,102 @@
+//! Run code around every command, like to log, collect metrics or artifacts, or clean up.
+//!
+//! Hooks registered here apply to every [`Command`], on any thread, before those registered
+//! with [`Command::before_spawn`] and [`Command::after_output`]:
+//!
+//! ```rust,no_run
+//! use assert_cmd::Command;
+//!
+//! assert_cmd::hooks::before_spawn(|cmd: &mut Command| {
+//!     cmd.env("RUST_LOG", "debug");
+//! });
+//! assert_cmd::hooks::after_output(|assert| {
+//!     let output = assert.get_output();
+//!     eprintln!("exited with {}, {} bytes of stdout", output.status, output.stdout.len());
+//! });
+//!
+//! Command::cargo_bin("bin_fixture")
+//!     .unwrap()
+//!     .assert()
+//!     .success();
+//! ```
+//!
+//! A spawn hook runs each time the program is spawned, including for each retry, but not for
+//! [`Command::cached`] or replayed runs.  An output hook runs once the [`Assert`] is built,
+//! before any assertions on it.
+//!
+//! [`Assert`]: crate::assert::Assert
+//! [`Command`]: crate::cmd::Command
+//! [`Command::after_output`]: crate::cmd::Command::after_output()
+//! [`Command::before_spawn`]: crate::cmd::Command::before_spawn()
+//! [`Command::cached`]: crate::cmd::Command::cached()
+
+use std::sync::{Arc, RwLock};
+
+use crate::assert::Assert;
+use crate::cmd::Command;
+
+pub(crate) type BeforeSpawn = Arc<dyn Fn(&mut Command) + Send + Sync>;
+pub(crate) type AfterOutput = Arc<dyn Fn(&Assert) + Send + Sync>;
+
+static BEFORE_SPAWN: RwLock<Vec<BeforeSpawn>> = RwLock::new(Vec::new());
+static AFTER_OUTPUT: RwLock<Vec<AfterOutput>> = RwLock::new(Vec::new());
+
+/// Run `hook` on every [`Command`] just before its program is spawned.
+///
+/// [`Command`]: crate::cmd::Command
+pub fn before_spawn<F>(hook: F)
+where
+    F: Fn(&mut Command) + Send + Sync + 'static,
+{
+    BEFORE_SPAWN
+        .write()
+        .unwrap_or_else(|err| err.into_inner())
+        .push(Arc::new(hook));
+}
+
+/// Run `hook` on every [`Command`]'s [`Assert`], once its program has exited.
+///
+/// [`Assert`]: crate::assert::Assert
+/// [`Command`]: crate::cmd::Command
+pub fn after_output<F>(hook: F)
+where
+    F: Fn(&Assert) + Send + Sync + 'static,
+{
+    AFTER_OUTPUT
+        .write()
+        .unwrap_or_else(|err| err.into_inner())
+        .push(Arc::new(hook));
+}
+
+/// Remove every hook registered with [`before_spawn`] and [`after_output`].
+pub fn reset() {
+    BEFORE_SPAWN
+        .write()
+        .unwrap_or_else(|err| err.into_inner())
+        .clear();
+    AFTER_OUTPUT
+        .write()
+        .unwrap_or_else(|err| err.into_inner())
+        .clear();
+}
+
+/// The global spawn hooks, followed by `local`.
+pub(crate) fn before_spawn_hooks(local: &[BeforeSpawn]) -> Vec<BeforeSpawn> {
+    // Cloned so a hook can itself register hooks, or run a command, without the lock.
+    let mut hooks = BEFORE_SPAWN
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone();
+    hooks.extend(local.iter().cloned());
+    hooks
+}
+
+/// The global output hooks, followed by `local`.
+pub(crate) fn after_output_hooks(local: &[AfterOutput]) -> Vec<AfterOutput> {
+    let mut hooks = AFTER_OUTPUT
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone();
+    hooks.extend(local.iter().cloned());
+    hooks
+}
@@ -0,0 +1,526 @@
+//! Drive interactive programs, like REPLs, one exchange at a time.
+//!
+//! [`Command::spawn`] starts a session: send input, wait for the output it should produce, then
+//! [`AssertChild::assert`] on everything the program did.
+//!
+//! ```rust,no_run
+//! use assert_cmd::Command;
+//!
+//! use std::time::Duration;
+//!
+//! let timeout = Duration::from_secs(5);
+//! let mut cmd = Command::new("bc");
+//! let mut session = cmd.spawn().unwrap();
+//! session
+//!     .send_line("1 + 1")
+//!     .expect_stdout("2\n", timeout)
+//!     .send_line("2 * 3")
+//!     .expect_stdout("6\n", timeout)
+//!     .send_line("quit");
+//! session.assert().success();
+//! ```
+//!
+//! On Unix, [`AssertChild::send_signal`] interrupts the session, like to test graceful
+//! shutdown:
+//!
+//! ```rust,no_run
+//! # #[cfg(unix)] {
+//! use assert_cmd::Command;
+//! use predicates::prelude::*;
+//!
+//! use std::time::Duration;
+//!
+//! const SIGTERM: i32 = 15;
+//!
+//! let mut cmd = Command::cargo_bin("server").unwrap();
+//! let mut session = cmd.spawn().unwrap();
+//! println!("serving as {}", session.pid());
+//! session
+//!     .expect_stdout(predicate::str::contains("listening"), Duration::from_secs(5))
+//!     .send_signal(SIGTERM);
+//! session
+//!     .assert()
+//!     .success()
+//!     .stdout(predicate::str::contains("shutting down"));
+//! # }
+//! ```
+//!
+//! [`Command::spawn`]: crate::cmd::Command::spawn()
+
+use std::fmt;
+use std::io::{Read, Write};
+use std::process;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use predicates_tree::CaseTreeExt;
+
+use crate::assert::Assert;
+use crate::assert::IntoOutputPredicate;
+use crate::child::ChildGuard;
+use crate::cmd::Command;
+use crate::cmd::Dumps;
+use crate::cmd::LineEnding;
+use crate::output::DebugBytes;
+use crate::transcript::Chunk;
+use crate::transcript::Stream;
+
+/// A running child, driven through its stdin and checked as its output arrives.
+///
+/// Each `expect_` waits for the output received since the last expectation to match.  A failed
+/// expectation kills the child and reports like any [`Assert`] failure.
+///
+/// Finish with [`AssertChild::assert`]; dropping the session kills the child.
+///
+/// See [`Command::spawn`].
+///
+/// [`Command::spawn`]: crate::cmd::Command::spawn()
+pub struct AssertChild<'a> {
+    cmd: &'a Command,
+    child: ChildGuard,
+    dumps: Option<Dumps>,
+    stdin: Option<process::ChildStdin>,
+    line_ending: LineEnding,
+    timeout: Option<Duration>,
+    start: Instant,
+    stdout: Arc<Pipe>,
+    stderr: Arc<Pipe>,
+    chunks: Arc<Mutex<Vec<Chunk>>>,
+    readers: Vec<thread::JoinHandle<()>>,
+    /// How much of stdout and stderr earlier expectations matched.
+    stdout_matched: usize,
+    stderr_matched: usize,
+    /// The signals sent, and when.
+    signals: Vec<(i32, Duration)>,
+}
+
+/// Output received so far, shared with the thread reading it.
+#[derive(Default)]
+struct Pipe {
+    state: Mutex<PipeState>,
+    changed: Condvar,
+}
+
+#[derive(Default)]
+struct PipeState {
+    data: Vec<u8>,
+    closed: bool,
+}
+
+impl<'a> AssertChild<'a> {
+    pub(crate) fn new(
+        cmd: &'a Command,
+        mut child: ChildGuard,
+        dumps: Dumps,
+        input: Option<Vec<u8>>,
+        line_ending: LineEnding,
+        timeout: Option<Duration>,
+    ) -> Self {
+        let start = Instant::now();
+        let chunks = Arc::new(Mutex::new(Vec::new()));
+        let stdout = Arc::new(Pipe::default());
+        let stderr = Arc::new(Pipe::default());
+        let mut readers = Vec::new();
+        match child.take_stdout() {
+            Some(out) => readers.push(read(out, Stream::Stdout, start, &stdout, &chunks)),
+            None => stdout.close(),
+        }
+        match child.take_stderr() {
+            Some(err) => readers.push(read(err, Stream::Stderr, start, &stderr, &chunks)),
+            None => stderr.close(),
+        }
+        let stdin = child.take_stdin();
+        let mut session = Self {
+            cmd,
+            child,
+            dumps: Some(dumps),
+            stdin,
+            line_ending,
+            timeout,
+            start,
+            stdout,
+            stderr,
+            chunks,
+            readers,
+            stdout_matched: 0,
+            stderr_matched: 0,
+            signals: Vec::new(),
+        };
+        if let Some(input) = input {
+            session.send(input);
+        }
+        session
+    }
+
+    /// Write `data` to the child's stdin.
+    pub fn send<D: AsRef<[u8]>>(&mut self, data: D) -> &mut Self {
+        let result = match self.stdin.as_mut() {
+            Some(stdin) => stdin.write_all(data.as_ref()).and_then(|()| stdin.flush()),
+            None => self.fail("Unable to write to stdin, it was closed"),
+        };
+        if let Err(err) = result {
+            self.fail(format_args!("Unable to write to stdin: {}", err));
+        }
+        self
+    }
+
+    /// The child's process ID.
+    pub fn pid(&self) -> u32 {
+        self.child.id()
+    }
+
+    /// Send `signal`, like `libc::SIGTERM`, to the child.
+    ///
+    /// Signals sent are reported with any failure.  Sending a signal after the child exited
+    /// fails, rather than risk signaling another process that reused its ID.
+    #[cfg(unix)]
+    pub fn send_signal(&mut self, signal: i32) -> &mut Self {
+        if let Err(err) = self.child.signal(signal) {
+            self.fail(format_args!(
+                "Unable to send {}: {}",
+                crate::signals::describe(signal),
+                err
+            ));
+        }
+        self.signals.push((signal, self.start.elapsed()));
+        self
+    }
+
+    /// Write `line` and a line terminator to the child's stdin.
+    ///
+    /// See [`Command::stdin_line_ending`].
+    ///
+    /// [`Command::stdin_line_ending`]: crate::cmd::Command::stdin_line_ending()
+    pub fn send_line<L: AsRef<str>>(&mut self, line: L) -> &mut Self {
+        let line = format!("{}{}", line.as_ref(), self.line_ending.as_str());
+        self.send(line)
+    }
+
+    /// Close the child's stdin, signaling the end of its input.
+    pub fn close_stdin(&mut self) -> &mut Self {
+        self.stdin = None;
+        self
+    }
+
+    /// Wait up to `timeout` for the `stdout` received since the last expectation to match
+    /// `pred`.
+    ///
+    /// This uses [`IntoOutputPredicate`] to provide short-hands for common cases.
+    pub fn expect_stdout<I, P>(&mut self, pred: I, timeout: Duration) -> &mut Self
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        let stdout = self.stdout.clone();
+        self.stdout_matched = self.expect(
+            "stdout",
+            &stdout,
+            self.stdout_matched,
+            &pred.into_output(),
+            timeout,
+        );
+        self
+    }
+
+    /// Wait up to `timeout` for the `stderr` received since the last expectation to match
+    /// `pred`.
+    ///
+    /// See [`AssertChild::expect_stdout`].
+    pub fn expect_stderr<I, P>(&mut self, pred: I, timeout: Duration) -> &mut Self
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        let stderr = self.stderr.clone();
+        self.stderr_matched = self.expect(
+            "stderr",
+            &stderr,
+            self.stderr_matched,
+            &pred.into_output(),
+            timeout,
+        );
+        self
+    }
+
+    /// Wait up to `timeout` for the `stdout` received since the last expectation to match
+    /// `pred`, returning whether it did.
+    ///
+    /// Unlike [`AssertChild::expect_stdout`], the test continues either way, like to only
+    /// give a slow program a chance to get ready.  A match counts as an expectation.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    /// use predicates::prelude::*;
+    ///
+    /// use std::time::Duration;
+    ///
+    /// let mut cmd = Command::cargo_bin("bin_fixture").unwrap();
+    /// let mut session = cmd.spawn().unwrap();
+    /// if !session.wait_for_stdout(predicate::str::contains("ready"), Duration::from_secs(5)) {
+    ///     eprintln!("not ready yet, continuing anyway");
+    /// }
+    /// session.assert().success();
+    /// ```
+    pub fn wait_for_stdout<I, P>(&mut self, pred: I, timeout: Duration) -> bool
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        match wait_match(
+            &self.stdout,
+            self.stdout_matched,
+            &pred.into_output(),
+            timeout,
+        ) {
+            Ok(matched) => {
+                self.stdout_matched = matched;
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Wait up to `timeout` for the `stderr` received since the last expectation to match
+    /// `pred`, returning whether it did.
+    ///
+    /// See [`AssertChild::wait_for_stdout`].
+    pub fn wait_for_stderr<I, P>(&mut self, pred: I, timeout: Duration) -> bool
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        match wait_match(
+            &self.stderr,
+            self.stderr_matched,
+            &pred.into_output(),
+            timeout,
+        ) {
+            Ok(matched) => {
+                self.stderr_matched = matched;
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Wait for `pipe`, past `matched`, to satisfy `pred`, returning how much has been matched.
+    fn expect(
+        &mut self,
+        stream: &str,
+        pipe: &Pipe,
+        matched: usize,
+        pred: &dyn predicates_core::Predicate<[u8]>,
+        timeout: Duration,
+    ) -> usize {
+        let unmatched = match wait_match(pipe, matched, pred, timeout) {
+            Ok(matched) => return matched,
+            Err(unmatched) => unmatched,
+        };
+        let why = if unmatched.closed {
+            format!("{} closed before matching", stream)
+        } else {
+            format!("Timed out after {:?} waiting for {}", timeout, stream)
+        };
+        let case = pred
+            .find_case(false, &unmatched.data)
+            .map(|case| case.tree().to_string())
+            .unwrap_or_else(|| pred.to_string());
+        self.fail(format_args!(
+            "{}, failed {}\nunmatched=```{}```",
+            why,
+            case,
+            DebugBytes::new(&unmatched.data)
+        ));
+    }
+
+    /// Close stdin, wait for the child to exit, and [`Assert`] on the whole session.
+    ///
+    /// The child is killed if it doesn't exit within the [`Command::timeout`].
+    ///
+    /// [`Command::timeout`]: crate::cmd::Command::timeout()
+    pub fn assert(mut self) -> Assert {
+        self.finish(false)
+    }
+
+    /// Kill the child and [`Assert`] on the whole session.
+    pub(crate) fn kill(mut self) -> Assert {
+        self.finish(true)
+    }
+
+    /// Kill the child and report `headline` like an [`Assert`] failure.
+    pub(crate) fn fail(&mut self, headline: impl fmt::Display) -> ! {
+        self.finish(true).fail(headline)
+    }
+
+    /// Whether the child has exited, without waiting.
+    pub(crate) fn has_exited(&mut self) -> bool {
+        self.child.has_exited()
+    }
+
+    /// Whether the session was asserted on, like by a failed expectation.
+    pub(crate) fn is_finished(&self) -> bool {
+        self.dumps.is_none()
+    }
+
+    fn finish(&mut self, kill: bool) -> Assert {
+        self.stdin = None;
+        let (status, mut timed_out) = match self.wait(kill) {
+            Ok(result) => result,
+            Err(err) => panic!("Failed to wait on {:?}: {}", self.cmd, err),
+        };
+        if timed_out.is_none() && !self.child.wait_group(self.timeout.map(|t| self.start + t)) {
+            timed_out = self.timeout;
+        }
+        let duration = self.start.elapsed();
+        for reader in self.readers.drain(..) {
+            let _ = reader.join();
+        }
+        let take = |pipe: &Pipe| std::mem::take(&mut pipe.state.lock().unwrap().data);
+        let output = process::Output {
+            status,
+            stdout: take(&self.stdout),
+            stderr: take(&self.stderr),
+        };
+        let chunks = std::mem::take(&mut *self.chunks.lock().unwrap());
+        let dumps = self.dumps.take().expect("session finished once");
+        let assert =
+            self.cmd
+                .finish_interactive(output, chunks, &self.child, duration, dumps, timed_out);
+        if self.signals.is_empty() {
+            return assert;
+        }
+        let signals: Vec<_> = self
+            .signals
+            .iter()
+            .map(|(signal, at)| format!("{} at {:?}", describe_signal(*signal), at))
+            .collect();
+        assert.append_context("signals sent", signals.join(", "))
+    }
+}
+
+impl AssertChild<'_> {
+    /// Wait for the child to exit, returning the timeout if it was killed for exceeding it.
+    fn wait(&mut self, kill: bool) -> std::io::Result<(process::ExitStatus, Option<Duration>)> {
+        if kill {
+            return Ok((self.child.kill()?, None));
+        }
+        match self.timeout {
+            Some(timeout) => match self.child.wait_timeout(timeout)? {
+                Some(status) => Ok((status, None)),
+                None => Ok((self.child.kill()?, Some(timeout))),
+            },
+            None => Ok((self.child.wait()?, None)),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn describe_signal(signal: i32) -> String {
+    crate::signals::describe(signal)
+}
+
+#[cfg(not(unix))]
+fn describe_signal(signal: i32) -> String {
+    signal.to_string()
+}
+
+impl Drop for AssertChild<'_> {
+    fn drop(&mut self) {
+        if self.is_finished() {
+            return;
+        }
+        // Abandoned without asserting: kill and reap the child, but don't wait for readers,
+        // which a grandchild could keep blocked.
+        self.stdin = None;
+        let _ = self.child.kill();
+    }
+}
+
+impl fmt::Debug for AssertChild<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AssertChild")
+            .field("cmd", &self.cmd)
+            .field("child", &self.child)
+            .finish()
+    }
+}
+
+/// What a [`wait_match`] that gave up hadn't matched.
+struct Unmatched {
+    /// Whether the pipe closed, rather than the wait timing out.
+    closed: bool,
+    data: Vec<u8>,
+}
+
+/// Wait up to `timeout` for `pipe`, past `matched`, to satisfy `pred`, returning how much has
+/// been matched.
+fn wait_match(
+    pipe: &Pipe,
+    matched: usize,
+    pred: &dyn predicates_core::Predicate<[u8]>,
+    timeout: Duration,
+) -> Result<usize, Unmatched> {
+    let deadline = Instant::now() + timeout;
+    let mut state = pipe.state.lock().unwrap();
+    loop {
+        let unmatched = &state.data[matched..];
+        if pred.eval(unmatched) {
+            return Ok(state.data.len());
+        }
+        let now = Instant::now();
+        if state.closed || deadline <= now {
+            return Err(Unmatched {
+                closed: state.closed,
+                data: unmatched.to_owned(),
+            });
+        }
+        state = pipe.changed.wait_timeout(state, deadline - now).unwrap().0;
+    }
+}
+
+impl Pipe {
+    fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.changed.notify_all();
+    }
+}
+
+fn read<R>(
+    mut input: R,
+    stream: Stream,
+    start: Instant,
+    pipe: &Arc<Pipe>,
+    chunks: &Arc<Mutex<Vec<Chunk>>>,
+) -> thread::JoinHandle<()>
+where
+    R: Read + Send + 'static,
+{
+    let pipe = pipe.clone();
+    let chunks = chunks.clone();
+    thread::spawn(move || {
+        let mut buf = [0; 8 * 1024];
+        loop {
+            let len = match input.read(&mut buf) {
+                Ok(0) => break,
+                Ok(len) => len,
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            };
+            chunks.lock().unwrap().push(Chunk {
+                stream,
+                elapsed: start.elapsed(),
+                len,
+            });
+            pipe.state
+                .lock()
+                .unwrap()
+                .data
+                .extend_from_slice(&buf[..len]);
+            pipe.changed.notify_all();
+        }
+        pipe.close();
+    })
+}
@@ -0,0 +1,238 @@
+//! Assertions on JSON output.
+//!
+//! Requires the `json` feature.
+
+use std::fmt;
+
+pub use serde_json::Value;
+
+/// A stream of newline-delimited JSON (NDJSON) records, like structured logs.
+///
+/// See [`Assert::stderr_jsonl`].
+///
+/// Fields are looked up by name for top-level keys or by [JSON Pointer] when starting with `/`.
+///
+/// [`Assert::stderr_jsonl`]: crate::assert::Assert::stderr_jsonl()
+/// [JSON Pointer]: https://tools.ietf.org/html/rfc6901
+#[derive(Clone, Debug, PartialEq)]
+pub struct JsonLines {
+    records: Vec<Value>,
+}
+
+impl JsonLines {
+    /// Parse `data`, one JSON value per line, skipping blank lines.
+    pub(crate) fn parse(data: &[u8]) -> Result<Self, ParseError> {
+        let text = String::from_utf8_lossy(data);
+        let mut records = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = serde_json::from_str(line).map_err(|err| ParseError {
+                line_num: i + 1,
+                line: line.to_owned(),
+                message: err.to_string(),
+            })?;
+            records.push(record);
+        }
+        Ok(Self { records })
+    }
+
+    /// All parsed records, in order.
+    pub fn records(&self) -> &[Value] {
+        &self.records
+    }
+
+    /// Whether any record has `field` equal to `value`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stderr", r#"{"level":"info","event":"started"}"#)
+    ///     .assert()
+    ///     .stderr_jsonl(|logs| logs.contains("event", "started"));
+    /// ```
+    pub fn contains<V>(&self, field: &str, value: V) -> bool
+    where
+        V: Into<Value>,
+    {
+        let value = value.into();
+        self.records
+            .iter()
+            .any(|r| lookup(r, field) == Some(&value))
+    }
+
+    /// Whether every record has `field` equal to `value`.
+    pub fn all<V>(&self, field: &str, value: V) -> bool
+    where
+        V: Into<Value>,
+    {
+        let value = value.into();
+        self.records
+            .iter()
+            .all(|r| lookup(r, field) == Some(&value))
+    }
+
+    /// Whether records with `field` equal to each of `values` appear in that order.
+    ///
+    /// Other records may appear in between.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stderr", "{\"event\":\"started\"}\n{\"event\":\"stopped\"}")
+    ///     .assert()
+    ///     .stderr_jsonl(|logs| logs.in_order("event", &["started", "stopped"]));
+    /// ```
+    pub fn in_order<V>(&self, field: &str, values: &[V]) -> bool
+    where
+        V: Clone + Into<Value>,
+    {
+        let mut records = self.records.iter();
+        values.iter().all(|value| {
+            let value = value.clone().into();
+            records.any(|r| lookup(r, field) == Some(&value))
+        })
+    }
+
+    /// Iterate over the values of `field`, skipping records without it.
+    pub fn field<'a>(&'a self, field: &'a str) -> impl Iterator<Item = &'a Value> + 'a {
+        self.records.iter().filter_map(move |r| lookup(r, field))
+    }
+}
+
+impl fmt::Display for JsonLines {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for record in &self.records {
+            writeln!(f, "{}", record)?;
+        }
+        Ok(())
+    }
+}
+
+/// Rewrite JSON output with sorted keys and consistent formatting.
+///
+/// If all of `data` is one JSON document, it is pretty-printed.  Otherwise each line that is a
+/// JSON value, as in NDJSON, is re-serialized compactly and other lines are kept as-is.  Numbers
+/// are written in their shortest form, so `1.50` and `15e-1` both become `1.5`.
+///
+/// Use this with [`AssertSettings::canonical_json`] so golden files don't churn when the CLI's
+/// serializer changes key order or float formatting.
+///
+/// [`AssertSettings::canonical_json`]: crate::settings::AssertSettings::canonical_json()
+///
+/// # Examples
+///
+/// ```rust
+/// use assert_cmd::json::canonicalize;
+///
+/// assert_eq!(
+///     canonicalize(b"{\"b\": 1.50, \"a\": [1]}"),
+///     b"{\n  \"a\": [\n    1\n  ],\n  \"b\": 1.5\n}\n".to_vec()
+/// );
+/// assert_eq!(
+///     canonicalize(b"{\"b\":1,\"a\":2}\nplain\n"),
+///     b"{\"a\":2,\"b\":1}\nplain\n".to_vec()
+/// );
+/// ```
+pub fn canonicalize(data: &[u8]) -> Vec<u8> {
+    if let Ok(value) = serde_json::from_slice::<Value>(data) {
+        let mut canonical = serde_json::to_vec_pretty(&value).unwrap();
+        canonical.push(b'\n');
+        return canonical;
+    }
+
+    let text = String::from_utf8_lossy(data);
+    let mut canonical = String::with_capacity(text.len());
+    let mut lines = text.split('\n').peekable();
+    while let Some(line) = lines.next() {
+        match serde_json::from_str::<Value>(line) {
+            Ok(value) => canonical.push_str(&value.to_string()),
+            Err(_) => canonical.push_str(line),
+        }
+        if lines.peek().is_some() {
+            canonical.push('\n');
+        }
+    }
+    canonical.into_bytes()
+}
+
+fn lookup<'v>(record: &'v Value, field: &str) -> Option<&'v Value> {
+    if field.starts_with('/') {
+        record.pointer(field)
+    } else {
+        record.get(field)
+    }
+}
+
+/// A line of output that wasn't valid JSON.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ParseError {
+    line_num: usize,
+    line: String,
+    message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {} is not valid JSON ({}): {:?}",
+            self.line_num, self.message, self.line
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_skips_blank_lines() {
+        let lines = JsonLines::parse(b"{\"a\":1}\n\n{\"a\":2}\n").unwrap();
+        assert_eq!(lines.records().len(), 2);
+    }
+
+    #[test]
+    fn parse_error_reports_line() {
+        let err = JsonLines::parse(b"{\"a\":1}\nnot json\n").unwrap_err();
+        assert_eq!(err.line_num, 2);
+        assert_eq!(err.line, "not json");
+    }
+
+    #[test]
+    fn lookup_fields() {
+        let lines = JsonLines::parse(
+            b"{\"event\":\"a\",\"span\":{\"id\":1}}\n{\"event\":\"b\"}\n{\"event\":\"c\"}",
+        )
+        .unwrap();
+        assert!(lines.contains("event", "b"));
+        assert!(lines.contains("/span/id", 1));
+        assert!(!lines.contains("event", "z"));
+        assert!(!lines.all("event", "a"));
+        assert!(lines.in_order("event", &["a", "c"]));
+        assert!(!lines.in_order("event", &["c", "a"]));
+        assert_eq!(lines.field("/span/id").count(), 1);
+    }
+
+    #[test]
+    fn canonicalize_numbers_and_keys() {
+        assert_eq!(
+            canonicalize(b"{\"z\":15e-1,\"a\":{\"y\":1,\"x\":null}}\n\n"),
+            b"{\n  \"a\": {\n    \"x\": null,\n    \"y\": 1\n  },\n  \"z\": 1.5\n}\n".to_vec()
+        );
+        assert_eq!(canonicalize(b"plain\n"), b"plain\n".to_vec());
+    }
+}
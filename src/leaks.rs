@@ -0,0 +1,79 @@
+//! Recognize debugging output that shouldn't reach users of a release build.
+
+use bstr::ByteSlice;
+use regex::Regex;
+
+/// A line that looks like debugging output.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Leak {
+    /// 1-based.
+    pub(crate) line: usize,
+    pub(crate) text: String,
+    pub(crate) kind: &'static str,
+}
+
+/// The first line of `data` that looks like debugging output.
+///
+/// `internal_paths` are paths of the build machine, like the crate's source directory.
+pub(crate) fn find(data: &[u8], internal_paths: &[&str]) -> Option<Leak> {
+    let markers = [
+        (r"^\[\S+\.rs:\d+(:\d+)?\] ", "`dbg!` output"),
+        (r"panicked at ", "a panic"),
+        (
+            r"assertion failed: |assertion `left == right` failed",
+            "a failed assertion",
+        ),
+        (r"/rustc/[0-9a-f]{40}/", "a path into the Rust toolchain"),
+        (
+            r"[/\\]\.cargo[/\\]registry[/\\]src[/\\]",
+            "a path into the cargo registry",
+        ),
+    ];
+    let markers: Vec<_> = markers
+        .iter()
+        .map(|(pattern, kind)| (Regex::new(pattern).unwrap(), *kind))
+        .collect();
+    for (i, line) in data.lines().enumerate() {
+        let line = line.to_str_lossy();
+        let kind = markers
+            .iter()
+            .find(|(regex, _)| regex.is_match(&line))
+            .map(|(_, kind)| *kind)
+            .or_else(|| {
+                internal_paths
+                    .iter()
+                    .find(|path| !path.is_empty() && line.contains(*path))
+                    .map(|_| "a path on the build machine")
+            });
+        if let Some(kind) = kind {
+            return Some(Leak {
+                line: i + 1,
+                text: line.into_owned(),
+                kind,
+            });
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn markers() {
+        let kind = |data: &str| find(data.as_bytes(), &["/home/ci/tool"]).map(|leak| leak.kind);
+        assert_eq!(kind("[src/main.rs:10:5] x = 1"), Some("`dbg!` output"));
+        assert_eq!(
+            kind("thread 'main' panicked at src/main.rs:3:5:"),
+            Some("a panic")
+        );
+        assert_eq!(kind("assertion failed: x < 3"), Some("a failed assertion"));
+        assert_eq!(
+            kind("loading /home/ci/tool/assets/logo.png"),
+            Some("a path on the build machine")
+        );
+        assert_eq!(kind("all good\n[1] item"), None);
+        assert_eq!(find(b"ok\n[src/a.rs:1] y = 2\n", &[]).unwrap().line, 2);
+    }
+}
@@ -10,24 +10,99 @@
 //! - `Command::new(path)`
 //! - `Command::from_std(...)`
 //! - `Command::cargo_bin(name)`
+//! - `Command::cargo_bin_with_profile(name, profile)`, like for the `release` binary
+//! - `Command::cargo_bin_as(name, alias, stage)` for a multi-call binary invoked as `alias`
+//! - `Command::workspace_bin(name)` for a binary of another workspace member
+//! - `Command::cargo_example(name)`
+//! - `Command::new(cargo_bin!(name))`, see [`cargo_bin!`]
+//! - `Command::shell(script)` for globs, pipes, or redirection, with [`platform::shell_quote`]
 //!
 //! Configure a [`Command`]:
 //! - `arg` / `args`
 //! - `current_dir`
-//! - `env` / `envs` / `env_remove` / `env_clear`
-//! - `write_stdin` / `pipe_stdin`
-//! - `timeout`
+//! - `env` / `envs` / `env_remove` / `env_clear` / `env_deterministic` / `env_inherit`, or [`env_guard::EnvGuard`] for the environment children inherit
+//! - `write_stdin` / `write_stdin_line` / `write_stdin_lines` / `write_stdin_chunks` / `pipe_stdin`, and `hold_stdin_open`
+//! - `timeout`, or `ASSERT_CMD_TIMEOUT` for a default
+//! - `capture`
+//! - `stream_stdout` / `stream_stderr` for output too large to buffer
+//! - `capture_to_files` to write output to files, checked with `stdout_file_size`, `stdout_file_sha256`, or `stdout_file_check`
+//! - `tee` to watch the output live while still capturing it
+//! - `echo`, or `ASSERT_CMD_ECHO`, to print each resolved command line, working directory, environment changes, and `stdin` before running it
+//! - `coverage`, or `ASSERT_CMD_COVERAGE`, so children contribute to coverage and sanitizer reports
+//! - `env_dump`
+//! - `sample_resources`
+//! - `isolated_temp_dir`
+//! - `in_temp_dir` / `keep_temp_cwd_on_failure` to run in a scratch directory
+//! - `guard_worktree`
+//! - `strip_ansi` to remove colors from the output
+//! - `redact` / `redact_regex` to replace dynamic values in the output with placeholders
+//! - `home`, or `sandbox_home` for a pre-seeded home with its own config and cache dirs, see [`sandbox::Sandbox`]
+//! - `cached`
+//! - `cassette` to record slow commands' output once and replay it, see [`cassette`]
+//! - `append_context`, to describe failures
+//! - `runner`, for cross-compiled binaries
+//! - `mocks` to stand in for the programs a CLI runs, see [`mock`] (Unix)
+//! - `pre_exec` / `umask` / `reset_signals` / `signal_dump` / `inherit_handles` / `follow_process_group` / `kill_process_group` / `invoked_as` / `terminate_after` (Unix)
+//! - `pty` to attach `stdout` to a pseudo-terminal (Unix, `pty` feature)
+//! - with the `log` feature, each run is logged with its exit status, duration, and output
 //!
 //! Validate a [`Command`]:
-//! - `ok` / `unwrap` / `unwrap_err`
+//! - `ok` / `unwrap` / `unwrap_err`, with [`exec::SpawnError`] when the program couldn't run
 //! - `assert`
 //!   - `success`, see [`Assert`]
 //!   - `failure`, see [`Assert`]
-//!   - `interrupted`, see [`Assert`]
-//!   - `code`, see [`Assert`]
+//!   - `success_with_empty_stderr` / `failure_with_code` for the common combinations, see [`Assert`]
+//!   - `interrupted` / `interrupted_by` / `signal` (Unix), see [`Assert`]
+//!   - `crashed` / `exit_nt_status` for crashes on Unix and Windows alike, see [`platform`]
+//!   - `code`, with [`exit`] naming `sysexits.h` codes, see [`Assert`]
 //!   - `stdout`, see [`Assert`]
 //!   - `stderr`, see [`Assert`]
-//!   - `get_output` for everything else, see [`Assert`]
+//!   - `combined_output` for `stdout` and `stderr` as interleaved, with [`cmd::Streams::Combined`], see [`Assert`]
+//!   - `stdout_str` / `stderr_str` for text, see [`Assert`]
+//!   - `stdout_has_line` / `stdout_line_count` / `stdout_line` for individual lines, see [`Assert`]
+//!   - `stdout_does_not_contain` / `stdout_does_not_match` to forbid text, showing where it occurred, see [`Assert`]
+//!   - `stdout_empty` / `stderr_empty` to expect no output, showing what was written, see [`Assert`]
+//!   - [`pattern::pattern`] with `stdout` / `stderr` to elide dynamic text with `[..]` and `...`
+//!   - [`pattern::in_order`] with `stdout` / `stderr` to check that snippets appear in order
+//!   - [`matches::matches()`] with `stdout` / `stderr` for regular expressions
+//!   - `stdout_normalized` / `stderr_normalized` to ignore `\r\n` vs `\n`, see [`Assert`]
+//!   - `stdout_tolerant` / `stderr_tolerant` to overlook trailing whitespace, final newlines, or
+//!     repeated blank lines, see [`settings::Tolerance`]
+//!   - `decode_stdout` / `decode_stderr` for UTF-16 or Latin-1 output, see [`encoding::Encoding`]
+//!   - `stdout_matches_path` / `stderr_matches_path` or `matches_expected` for golden files, see [`Assert`]
+//!   - `matches_snapshot` for a named snapshot of the whole run, see [`Assert`]
+//!   - `matches_path` for the whole run as a TOML file, see [`Assert`]
+//!   - `stdout_nul_records` / `stdout_nul_records_unordered` for `find -print0`-style output, see [`Assert`]
+//!   - `stdout_has_ansi` / `stdout_no_ansi`, see [`Assert`]
+//!   - `stdout_per_os` / `stderr_per_os`, or [`platform::PerOs`] for any expectation, see [`Assert`]
+//!   - `try_assert` / `try_success` / ... to return failures instead of panicking, see [`Assert`]
+//!   - `soft` to check every assertion and report all of their failures together, see [`assert::SoftAssert`]
+//!   - `on_failure`, or [`failure::set_handler`] for every `Assert`, to handle failures as data, serializable with the `serde` feature, see [`Assert`]
+//!   - `stdin_consumed` / `expect_early_exit`, see [`Assert`]
+//!   - `file` / `dir_exists` for what the command left on disk, see [`Assert`]
+//!   - `duration` / `completes_within` for performance smoke tests, see [`Assert`]
+//!   - `get_resource_usage` / `max_rss_below` for peak memory and CPU time (Unix), see [`resources::ResourceUsage`]
+//!   - `first_stdout_within` / `first_stderr_within` / `exits_within` / ... for startup latency, see [`Assert`]
+//!   - `invocation` for the resolved program, arguments, working directory, and environment changes, see [`transcript::Invocation`]
+//!   - `get_output` / `into_output` / `stdout_lossy` / `stderr_lossy` for everything else, see [`Assert`]
+//! - `assert_from_dirs` / `assert_cwd_independent` / `assert_each_home`
+//! - `assert_idempotent` to check repeated runs succeed with the same output
+//! - `assert_concurrent` / `assert_cold_warm`
+//! - `assert_env_matrix`
+//! - `spawn` for an interactive session, with `expect_stdout` / `wait_for_stdout` for output as it arrives, its `pid`, and `send_signal` (Unix), see [`interactive::AssertChild`]
+//! - `spawn_server` for a program running in the background, see [`server::Server`]
+//! - `pipe` to feed one command's `stdout` into another, see [`pipeline::Pipeline`]
+//! - `assert_requires_tty` for interactive-only CLIs
+//! - [`compare::Comparison`] to assert how labeled runs relate, like before and after a migration
+//! - [`console_test!`] to turn transcripts in doc comments into tests
+//! - [`harness::TestCases`] to run a directory of cases declared in `.toml` or transcript files
+//! - [`matrix::CommandMatrix`] to run every combination of arguments and environment variables
+//! - [`parallel::Parallel`] to run several commands concurrently and check them all
+//! - [`spec::CommandSpec`] to share a base invocation, stamping out a fresh `Command` per test
+//! - `allow_flaky`
+//! - `retries` and `retry_if` to re-run commands that fail transiently, like on network errors
+//! - `before_spawn` and `after_output`, or [`hooks`] for every `Command`, to run code around each command
+//! - [`report`], or `ASSERT_CMD_REPORT`, to record every asserted command as JUnit XML or JSON lines, like for CI dashboards
 //!
 //! Note: [`Command`] is provided as a convenience. Extension traits for [`std::process::Command`]
 //! and `Output` are provided for interoperability:
@@ -35,6 +110,8 @@
 //! - [`OutputOkExt`]
 //! - [`OutputAssertExt`]
 //!
+//! Other ways of running a program can reuse [`Assert`] by implementing [`OutputLike`].
+//!
 //! ## Examples
 //!
 //! Here's a trivial example:
@@ -99,6 +176,7 @@
 //! [`CommandCargoExt`]: cargo::CommandCargoExt
 //! [`OutputOkExt`]: output::OutputOkExt
 //! [`OutputAssertExt`]: assert::OutputAssertExt
+//! [`OutputLike`]: assert::OutputLike
 
 #![warn(missing_docs)]
 
@@ -130,10 +208,68 @@ macro_rules! crate_name {
     };
 }
 
+pub mod aggregate;
+mod ansi;
 pub mod assert;
+pub mod budget;
+mod cache;
 pub mod cargo;
+pub mod cassette;
+mod child;
 pub mod cmd;
+pub mod compare;
+pub mod console;
+mod coverage;
+mod diff;
+pub mod encoding;
+pub mod env_dump;
+pub mod env_guard;
+pub mod exec;
+pub mod exit;
+pub mod failure;
+#[cfg(feature = "faketime")]
+pub mod faketime;
+#[cfg(unix)]
+mod fds;
+pub mod golden;
+pub mod harness;
+pub mod hermetic;
+pub mod hooks;
+pub mod interactive;
+#[cfg(feature = "json")]
+pub mod json;
+mod leaks;
+#[cfg(not(feature = "predicates"))]
+mod lite;
+#[cfg(feature = "log")]
+mod logging;
+pub mod logs;
+pub mod matches;
+pub mod matrix;
+#[cfg(unix)]
+pub mod mock;
+pub mod numeric;
 pub mod output;
+pub mod parallel;
+pub mod pattern;
+pub mod pipeline;
+pub mod platform;
+#[cfg(all(unix, feature = "pty"))]
+mod pty;
+pub mod registry;
+pub mod report;
+pub mod resources;
+pub mod sandbox;
+pub mod server;
+pub mod settings;
+#[cfg(unix)]
+pub mod signals;
+pub mod spec;
+mod spool;
+mod temp;
+pub mod timing;
+pub mod transcript;
+mod worktree;
 
 /// Extension traits that are useful to have available.
 pub mod prelude {
@@ -0,0 +1,228 @@
+//! Minimal stand-ins for the `predicates` this crate uses internally.
+//!
+//! These are used when the `predicates` feature is disabled so that the built-in equality and
+//! code assertions keep working.  They mirror the subset of `predicates`' API that [`assert`]
+//! relies on, without its diffing or its palette.
+//!
+//! [`assert`]: crate::assert
+
+use std::fmt;
+
+use predicates_core::reflection;
+use predicates_core::Predicate;
+
+fn default_find_case<'a, P, T>(
+    pred: &'a P,
+    expected: bool,
+    variable: &T,
+) -> Option<reflection::Case<'a>>
+where
+    P: Predicate<T>,
+    T: ?Sized,
+{
+    let actual = pred.eval(variable);
+    if expected == actual {
+        Some(reflection::Case::new(Some(pred), actual))
+    } else {
+        None
+    }
+}
+
+pub(crate) mod ord {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub(crate) struct EqPredicate<T> {
+        constant: T,
+    }
+
+    pub(crate) fn eq<T>(constant: T) -> EqPredicate<T> {
+        EqPredicate { constant }
+    }
+
+    impl<T: PartialEq + fmt::Debug> Predicate<T> for EqPredicate<T> {
+        fn eval(&self, variable: &T) -> bool {
+            *variable == self.constant
+        }
+
+        fn find_case<'a>(&'a self, expected: bool, variable: &T) -> Option<reflection::Case<'a>> {
+            default_find_case(self, expected, variable).map(|case| {
+                case.add_product(reflection::Product::new("var", format!("{:?}", variable)))
+            })
+        }
+    }
+
+    impl<T: fmt::Debug> reflection::PredicateReflection for EqPredicate<T> {}
+
+    impl<T: fmt::Debug> fmt::Display for EqPredicate<T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "var == {:?}", self.constant)
+        }
+    }
+}
+
+pub(crate) mod iter {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub(crate) struct InPredicate<T> {
+        inner: Vec<T>,
+    }
+
+    pub(crate) fn in_iter<I, T>(iter: I) -> InPredicate<T>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        InPredicate {
+            inner: iter.into_iter().collect(),
+        }
+    }
+
+    impl<T: PartialEq + fmt::Debug> Predicate<T> for InPredicate<T> {
+        fn eval(&self, variable: &T) -> bool {
+            self.inner.contains(variable)
+        }
+
+        fn find_case<'a>(&'a self, expected: bool, variable: &T) -> Option<reflection::Case<'a>> {
+            default_find_case(self, expected, variable).map(|case| {
+                case.add_product(reflection::Product::new("var", format!("{:?}", variable)))
+            })
+        }
+    }
+
+    impl<T: fmt::Debug> reflection::PredicateReflection for InPredicate<T> {}
+
+    impl<T: fmt::Debug> fmt::Display for InPredicate<T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "var in {:?}", self.inner)
+        }
+    }
+}
+
+pub(crate) mod str {
+    use super::*;
+
+    use std::borrow::Cow;
+    use std::str;
+
+    /// Exact string comparison; unlike `predicates::str::diff`, no diff is rendered.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub(crate) struct DifferencePredicate {
+        orig: Cow<'static, str>,
+    }
+
+    pub(crate) fn diff<S>(orig: S) -> DifferencePredicate
+    where
+        S: Into<Cow<'static, str>>,
+    {
+        DifferencePredicate { orig: orig.into() }
+    }
+
+    impl Predicate<str> for DifferencePredicate {
+        fn eval(&self, edit: &str) -> bool {
+            edit == self.orig
+        }
+
+        fn find_case<'a>(&'a self, expected: bool, variable: &str) -> Option<reflection::Case<'a>> {
+            default_find_case(self, expected, variable).map(|case| {
+                case.add_product(reflection::Product::new("expected", self.orig.to_string()))
+            })
+        }
+    }
+
+    impl reflection::PredicateReflection for DifferencePredicate {}
+
+    impl fmt::Display for DifferencePredicate {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "var == {:?}", self.orig)
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub(crate) struct Utf8Predicate<P> {
+        p: P,
+    }
+
+    impl<P> Predicate<[u8]> for Utf8Predicate<P>
+    where
+        P: Predicate<str>,
+    {
+        fn eval(&self, variable: &[u8]) -> bool {
+            str::from_utf8(variable)
+                .map(|s| self.p.eval(s))
+                .unwrap_or(false)
+        }
+
+        fn find_case<'a>(
+            &'a self,
+            expected: bool,
+            variable: &[u8],
+        ) -> Option<reflection::Case<'a>> {
+            match (expected, str::from_utf8(variable)) {
+                (_, Ok(var_str)) => self.p.find_case(expected, var_str),
+                (true, Err(_)) => None,
+                (false, Err(err)) => Some(
+                    reflection::Case::new(Some(self), false)
+                        .add_product(reflection::Product::new("error", err)),
+                ),
+            }
+        }
+    }
+
+    impl<P> reflection::PredicateReflection for Utf8Predicate<P>
+    where
+        P: Predicate<str>,
+    {
+        fn children<'a>(&'a self) -> Box<dyn Iterator<Item = reflection::Child<'a>> + 'a> {
+            let params = vec![reflection::Child::new("predicate", &self.p)];
+            Box::new(params.into_iter())
+        }
+    }
+
+    impl<P> fmt::Display for Utf8Predicate<P>
+    where
+        P: Predicate<str>,
+    {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            self.p.fmt(f)
+        }
+    }
+
+    /// Mirrors `predicates::str::PredicateStrExt`.
+    pub(crate) trait PredicateStrExt
+    where
+        Self: Predicate<str>,
+        Self: Sized,
+    {
+        // Named after the `predicates` method it stands in for.
+        #[allow(clippy::wrong_self_convention)]
+        fn from_utf8(self) -> Utf8Predicate<Self> {
+            Utf8Predicate { p: self }
+        }
+    }
+
+    impl<P> PredicateStrExt for P where P: Predicate<str> {}
+}
+
+#[cfg(test)]
+mod test {
+    use super::str::PredicateStrExt;
+    use super::*;
+
+    #[test]
+    fn eq_and_in() {
+        assert!(ord::eq(10).eval(&10));
+        assert!(!ord::eq(10).eval(&3));
+        assert!(iter::in_iter(vec![3, 10]).eval(&10));
+        assert_eq!(iter::in_iter(vec![3, 10]).to_string(), "var in [3, 10]");
+    }
+
+    #[test]
+    fn str_diff_from_utf8() {
+        let pred = str::diff("hello\n").from_utf8();
+        assert!(pred.eval(b"hello\n" as &[u8]));
+        assert!(!pred.eval(b"world\n" as &[u8]));
+        assert!(!pred.eval(b"\xff" as &[u8]));
+        assert!(pred.find_case(false, b"\xff" as &[u8]).is_some());
+    }
+}
@@ -0,0 +1,85 @@
+//! Records of each command, for the `log` feature.
+//!
+//! Records use the `assert_cmd` target: a `debug` record when a program is spawned and when it
+//! exits, and a `trace` record of each stream's output, truncated like in failure messages.
+
+use std::process;
+use std::time::Duration;
+
+use crate::output::DebugBytes;
+
+const TARGET: &str = "assert_cmd";
+
+pub(crate) fn spawning(cmd: &process::Command) {
+    log::debug!(target: TARGET, "spawning {:?}", cmd);
+}
+
+/// How the run of `cmd` ended, taking `duration`, if it was run rather than replayed.
+pub(crate) fn exited(cmd: &process::Command, output: &process::Output, duration: Option<Duration>) {
+    let program = cmd.get_program().to_string_lossy();
+    match duration {
+        Some(duration) => log::debug!(
+            target: TARGET,
+            "{} exited with {} after {:?}, args={:?}",
+            program,
+            output.status,
+            duration,
+            cmd.get_args().collect::<Vec<_>>()
+        ),
+        None => log::debug!(
+            target: TARGET,
+            "{} replayed with {}, args={:?}",
+            program,
+            output.status,
+            cmd.get_args().collect::<Vec<_>>()
+        ),
+    }
+    if log::log_enabled!(target: TARGET, log::Level::Trace) {
+        log::trace!(target: TARGET, "{} stdout={}", program, DebugBytes::new(&output.stdout));
+        log::trace!(target: TARGET, "{} stderr={}", program, DebugBytes::new(&output.stderr));
+    }
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use std::sync::Mutex;
+
+    static RECORDS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    struct Capture;
+
+    impl log::Log for Capture {
+        fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+            metadata.target() == super::TARGET
+        }
+
+        fn log(&self, record: &log::Record<'_>) {
+            if self.enabled(record.metadata()) {
+                let line = format!("{} {}", record.level(), record.args());
+                RECORDS.lock().unwrap().push(line);
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn records_runs() {
+        log::set_logger(&Capture).unwrap();
+        log::set_max_level(log::LevelFilter::Trace);
+        crate::cmd::Command::new("echo")
+            .arg("logged")
+            .assert()
+            .success();
+        let records = RECORDS.lock().unwrap();
+        let records: Vec<_> = records.iter().filter(|r| r.contains("echo")).collect();
+        assert!(records[0].starts_with("DEBUG spawning"), "{:?}", records);
+        assert!(
+            records[1].starts_with("DEBUG echo exited with exit status: 0 after"),
+            "{:?}",
+            records
+        );
+        assert!(records[1].ends_with("args=[\"logged\"]"), "{:?}", records);
+        assert_eq!(records[2], "TRACE echo stdout=\"logged\\n\"");
+    }
+}
@@ -0,0 +1,235 @@
+//! Parse log records out of program output.
+//!
+//! This understands the default layouts of [`env_logger`] and [`tracing-subscriber`]:
+//! ```text
+//! [2021-07-04T12:00:00Z WARN  my_app::config] falling back to defaults
+//! 2021-07-04T12:00:00.000000Z  WARN my_app::config: falling back to defaults
+//! ```
+//! Timestamps, module paths, and ANSI colors are tolerated.  Lines without a level are treated
+//! as a continuation of the previous record.
+//!
+//! See [`Assert::stderr_no_logs`] and [`Assert::stderr_log_count`].
+//!
+//! [`env_logger`]: https://crates.io/crates/env_logger
+//! [`tracing-subscriber`]: https://crates.io/crates/tracing-subscriber
+//! [`Assert::stderr_no_logs`]: crate::assert::Assert::stderr_no_logs()
+//! [`Assert::stderr_log_count`]: crate::assert::Assert::stderr_log_count()
+
+use std::fmt;
+use std::str;
+
+/// Severity of a log [`Record`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    /// `ERROR`
+    Error,
+    /// `WARN`
+    Warn,
+    /// `INFO`
+    Info,
+    /// `DEBUG`
+    Debug,
+    /// `TRACE`
+    Trace,
+}
+
+impl Level {
+    fn as_str(self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        }
+    }
+}
+
+impl str::FromStr for Level {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ERROR" => Ok(Level::Error),
+            "WARN" => Ok(Level::Warn),
+            "INFO" => Ok(Level::Info),
+            "DEBUG" => Ok(Level::Debug),
+            "TRACE" => Ok(Level::Trace),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+/// A log record parsed from program output.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Record {
+    /// Severity of the record.
+    pub level: Level,
+    /// Module path or target the record was logged from, if present.
+    pub target: Option<String>,
+    /// The logged message, including any continuation lines.
+    pub message: String,
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.target {
+            Some(ref target) => write!(f, "{} {}: {}", self.level, target, self.message),
+            None => write!(f, "{} {}", self.level, self.message),
+        }
+    }
+}
+
+/// Parse all log records out of `output`.
+///
+/// # Examples
+///
+/// ```rust
+/// use assert_cmd::logs;
+///
+/// let records = logs::parse(b"[2021-07-04T12:00:00Z WARN  app] careful\n");
+/// assert_eq!(records[0].level, logs::Level::Warn);
+/// assert_eq!(records[0].target.as_deref(), Some("app"));
+/// assert_eq!(records[0].message, "careful");
+/// ```
+pub fn parse(output: &[u8]) -> Vec<Record> {
+    let output = crate::ansi::strip(output);
+    let output = String::from_utf8_lossy(&output);
+    let mut records: Vec<Record> = Vec::new();
+    for line in output.lines() {
+        match parse_line(line) {
+            Some(record) => records.push(record),
+            None => {
+                if let Some(last) = records.last_mut() {
+                    last.message.push('\n');
+                    last.message.push_str(line);
+                }
+            }
+        }
+    }
+    records
+}
+
+fn parse_line(line: &str) -> Option<Record> {
+    // Only look at the start of the line so messages mentioning a level aren't mistaken for a
+    // record.
+    const MAX_PREFIX_TOKENS: usize = 3;
+
+    let bracketed = line.starts_with('[');
+    let mut rest = line.trim_start_matches('[');
+    for _ in 0..MAX_PREFIX_TOKENS {
+        let (token, remainder) = split_token(rest);
+        if token.is_empty() {
+            return None;
+        }
+        let level = token.trim_end_matches(']');
+        if let Ok(level) = level.parse::<Level>() {
+            if token.ends_with(']') {
+                return Some(Record {
+                    level,
+                    target: None,
+                    message: remainder.to_owned(),
+                });
+            }
+            return Some(parse_target(level, remainder, bracketed));
+        }
+        rest = remainder;
+    }
+    None
+}
+
+fn parse_target(level: Level, rest: &str, bracketed: bool) -> Record {
+    let (token, remainder) = split_token(rest);
+    let target = if bracketed {
+        token.strip_suffix(']')
+    } else {
+        token.strip_suffix(':')
+    };
+    match target {
+        Some(target) => Record {
+            level,
+            target: Some(target.to_owned()),
+            message: remainder.to_owned(),
+        },
+        None => Record {
+            level,
+            target: None,
+            message: rest.to_owned(),
+        },
+    }
+}
+
+fn split_token(s: &str) -> (&str, &str) {
+    let s = s.trim_start();
+    match s.find(char::is_whitespace) {
+        Some(i) => (&s[..i], s[i..].trim_start()),
+        None => (s, ""),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn record(level: Level, target: Option<&str>, message: &str) -> Record {
+        Record {
+            level,
+            target: target.map(|t| t.to_owned()),
+            message: message.to_owned(),
+        }
+    }
+
+    #[test]
+    fn env_logger() {
+        let records = parse(
+            b"[2021-07-04T12:00:00Z ERROR my_app::config] bad config\n[INFO  my_app] started\n",
+        );
+        assert_eq!(
+            records,
+            vec![
+                record(Level::Error, Some("my_app::config"), "bad config"),
+                record(Level::Info, Some("my_app"), "started"),
+            ]
+        );
+    }
+
+    #[test]
+    fn env_logger_without_target() {
+        let records = parse(b"[WARN] careful\n");
+        assert_eq!(records, vec![record(Level::Warn, None, "careful")]);
+    }
+
+    #[test]
+    fn tracing_subscriber() {
+        let records = parse(
+            b"2021-07-04T12:00:00.000000Z  WARN my_app::config: falling back\n\x1b[2m2021-07-04T12:00:00.000000Z\x1b[0m \x1b[32m INFO\x1b[0m \x1b[2mmy_app\x1b[0m\x1b[2m:\x1b[0m ready\n",
+        );
+        assert_eq!(
+            records,
+            vec![
+                record(Level::Warn, Some("my_app::config"), "falling back"),
+                record(Level::Info, Some("my_app"), "ready"),
+            ]
+        );
+    }
+
+    #[test]
+    fn continuation_lines() {
+        let records = parse(b"ignored preamble\nERROR app: first\n  second\n");
+        assert_eq!(
+            records,
+            vec![record(Level::Error, Some("app"), "first\n  second")]
+        );
+    }
+
+    #[test]
+    fn level_in_message_is_not_a_record() {
+        assert_eq!(parse(b"the quick brown fox saw an ERROR\n"), vec![]);
+    }
+}
@@ -0,0 +1,179 @@
+//! Match output against a regular expression.
+//!
+//! [`matches()`] works with [`Assert::stdout`] and [`Assert::stderr`] without depending on
+//! `predicates` directly:
+//!
+//! ```rust,no_run
+//! use assert_cmd::matches::matches;
+//! use assert_cmd::Command;
+//!
+//! Command::cargo_bin("bin_fixture")
+//!     .unwrap()
+//!     .env("stdout", "version 1.2.3")
+//!     .assert()
+//!     .stdout(matches(r"^version \d+\.\d+\.\d+$").multi_line(true));
+//! ```
+//!
+//! [`Assert::stdout`]: crate::assert::Assert::stdout()
+//! [`Assert::stderr`]: crate::assert::Assert::stderr()
+
+use std::fmt;
+
+use predicates_core::reflection;
+
+/// Match output against the regular expression `pattern`, anywhere in the output.
+///
+/// See the [`regex` crate](https://docs.rs/regex) for the syntax.
+///
+/// # Panics
+///
+/// If `pattern` is not a valid regular expression, naming the pattern.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use assert_cmd::matches::matches;
+/// use assert_cmd::Command;
+///
+/// Command::cargo_bin("bin_fixture")
+///     .unwrap()
+///     .env("stderr", "error: took 15ms")
+///     .assert()
+///     .stderr(matches(r"took \d+ms"));
+/// ```
+#[track_caller]
+pub fn matches<S: Into<String>>(pattern: S) -> MatchesPredicate {
+    let pattern = pattern.into();
+    let re = compile(&pattern, false, false);
+    MatchesPredicate {
+        pattern,
+        multi_line: false,
+        whole: false,
+        re,
+    }
+}
+
+/// [predicates_core::Predicate] for output matching a regular expression, see [`matches()`].
+#[derive(Debug, Clone)]
+pub struct MatchesPredicate {
+    pattern: String,
+    multi_line: bool,
+    whole: bool,
+    re: regex::bytes::Regex,
+}
+
+impl MatchesPredicate {
+    /// Whether `^` and `$` match at the start and end of each line, rather than only of the
+    /// whole output.
+    ///
+    /// Defaults to `false`.
+    pub fn multi_line(mut self, yes: bool) -> Self {
+        self.multi_line = yes;
+        self.recompile()
+    }
+
+    /// Whether the pattern must match all of the output, rather than any part of it.
+    ///
+    /// Defaults to `false`.
+    pub fn whole_output(mut self, yes: bool) -> Self {
+        self.whole = yes;
+        self.recompile()
+    }
+
+    fn recompile(mut self) -> Self {
+        self.re = compile(&self.pattern, self.multi_line, self.whole);
+        self
+    }
+}
+
+#[track_caller]
+pub(crate) fn compile(pattern: &str, multi_line: bool, whole: bool) -> regex::bytes::Regex {
+    let anchored;
+    let full = if whole {
+        anchored = format!(r"\A(?:{})\z", pattern);
+        &anchored
+    } else {
+        pattern
+    };
+    regex::bytes::RegexBuilder::new(full)
+        .multi_line(multi_line)
+        .build()
+        .unwrap_or_else(|err| panic!("Invalid regex `{}`: {}", pattern, err))
+}
+
+impl PartialEq for MatchesPredicate {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern
+            && self.multi_line == other.multi_line
+            && self.whole == other.whole
+    }
+}
+
+impl Eq for MatchesPredicate {}
+
+impl reflection::PredicateReflection for MatchesPredicate {}
+
+impl predicates_core::Predicate<[u8]> for MatchesPredicate {
+    fn eval(&self, variable: &[u8]) -> bool {
+        self.re.is_match(variable)
+    }
+
+    fn find_case<'a>(&'a self, expected: bool, variable: &[u8]) -> Option<reflection::Case<'a>> {
+        let found = self.re.find(variable);
+        if expected != found.is_some() {
+            return None;
+        }
+        let case = reflection::Case::new(Some(self), found.is_some());
+        Some(match found {
+            Some(found) => case.add_product(reflection::Product::new(
+                "matched",
+                String::from_utf8_lossy(found.as_bytes()).into_owned(),
+            )),
+            None => case,
+        })
+    }
+}
+
+impl fmt::Display for MatchesPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let what = if self.whole { "all of var" } else { "var" };
+        write!(f, "{} matches regex {:?}", what, self.pattern)?;
+        if self.multi_line {
+            write!(f, " (multi-line)")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use predicates_core::Predicate;
+
+    #[test]
+    fn options() {
+        let version = matches(r"^version \d+$");
+        assert!(version.eval(b"version 1" as &[u8]));
+        assert!(!version.eval(b"tool\nversion 1\n" as &[u8]));
+        assert!(version
+            .clone()
+            .multi_line(true)
+            .eval(b"tool\nversion 1\n" as &[u8]));
+
+        let digits = matches(r"\d+");
+        assert!(digits.eval(b"took 15ms" as &[u8]));
+        assert!(!digits
+            .clone()
+            .whole_output(true)
+            .eval(b"took 15ms" as &[u8]));
+        assert!(digits.whole_output(true).eval(b"15" as &[u8]));
+    }
+
+    #[test]
+    fn invalid_pattern() {
+        let err = std::panic::catch_unwind(|| matches("(unclosed")).unwrap_err();
+        let err = err.downcast_ref::<String>().unwrap();
+        assert!(err.starts_with("Invalid regex `(unclosed`"), "{}", err);
+    }
+}
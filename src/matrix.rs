@@ -0,0 +1,233 @@
+//! Run a command under every combination of arguments and environment variables.
+//!
+//! Rather than a near-identical test function per flag interaction, declare the values each
+//! option can take and check every combination:
+//!
+//! ```rust,no_run
+//! use assert_cmd::matrix::CommandMatrix;
+//! use assert_cmd::Command;
+//!
+//! CommandMatrix::new(|| Command::cargo_bin("bin_fixture").unwrap())
+//!     .args(&[&[], &["--verbose"], &["--quiet"]])
+//!     .args(&[&["--color=never"], &["--color=always"]])
+//!     .env("RUST_LOG", &["warn", "debug", "bogus"])
+//!     .expect_code(|combination| if combination.contains("bogus") { 2 } else { 0 })
+//!     .assert(|assert| assert);
+//! ```
+//!
+//! Every combination is run, and then any failures are reported together, each with its
+//! combination, after a table of every combination's expected and actual exit code.
+
+use std::ffi;
+use std::fmt::Write;
+use std::time;
+
+use crate::assert::Assert;
+use crate::cmd::Command;
+
+/// The combinations to run a [`Command`] under, see the
+/// [`matrix` module documentation][crate::matrix].
+pub struct CommandMatrix<'a> {
+    base: Box<dyn FnMut() -> Command + 'a>,
+    axes: Vec<Vec<Variant>>,
+    expected: Box<dyn FnMut(&str) -> i32 + 'a>,
+}
+
+/// One value of an axis of the matrix.
+#[derive(Clone, Debug)]
+struct Variant {
+    label: String,
+    args: Vec<ffi::OsString>,
+    envs: Vec<(ffi::OsString, ffi::OsString)>,
+}
+
+impl<'a> CommandMatrix<'a> {
+    /// Start with the `Command`s from `base`, called once per combination.
+    ///
+    /// With no axes, the matrix has one combination, the `Command` as `base` configured it.
+    pub fn new<F>(base: F) -> Self
+    where
+        F: FnMut() -> Command + 'a,
+    {
+        Self {
+            base: Box::new(base),
+            axes: Vec::new(),
+            expected: Box::new(|_| 0),
+        }
+    }
+
+    /// Run under each of the argument `sets`, appended after `base`'s arguments and those of
+    /// earlier axes.
+    ///
+    /// An empty set runs without any of them, labeled `(no args)`.
+    pub fn args(&mut self, sets: &[&[&str]]) -> &mut Self {
+        let axis = sets
+            .iter()
+            .map(|set| Variant {
+                label: if set.is_empty() {
+                    "(no args)".to_owned()
+                } else {
+                    set.join(" ")
+                },
+                args: set.iter().map(ffi::OsString::from).collect(),
+                envs: Vec::new(),
+            })
+            .collect();
+        self.axes.push(axis);
+        self
+    }
+
+    /// Run with the environment variable `key` set to each of the `values`.
+    pub fn env(&mut self, key: &str, values: &[&str]) -> &mut Self {
+        let axis = values
+            .iter()
+            .map(|value| Variant {
+                label: format!("{}={}", key, value),
+                args: Vec::new(),
+                envs: vec![(key.into(), value.into())],
+            })
+            .collect();
+        self.axes.push(axis);
+        self
+    }
+
+    /// Expect each combination to exit with the code `code` returns for its label, like
+    /// `--verbose RUST_LOG=debug`, rather than 0.
+    pub fn expect_code<F>(&mut self, code: F) -> &mut Self
+    where
+        F: FnMut(&str) -> i32 + 'a,
+    {
+        self.expected = Box::new(code);
+        self
+    }
+
+    /// The number of combinations, the product of the axes' lengths.
+    pub fn len(&self) -> usize {
+        self.axes.iter().map(Vec::len).product()
+    }
+
+    /// Whether there are no combinations, from an axis with no values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Run every combination, checking its exit code against [`CommandMatrix::expect_code`]
+    /// and then with `check`, panicking with each failure if any failed.
+    ///
+    /// Each run's failures report its combination as context, like `--verbose RUST_LOG=debug`.
+    /// The panic starts with a table of every combination's expected and actual exit code, and
+    /// how long it took.  A combination that failed `check` is also marked with the failure.
+    pub fn assert<F>(&mut self, mut check: F)
+    where
+        F: FnMut(Assert) -> Assert,
+    {
+        let combinations = combinations(&self.axes);
+        let total = combinations.len();
+        let mut rows = Vec::with_capacity(total);
+        let mut failures = Vec::new();
+        for combination in combinations {
+            let label = combination
+                .iter()
+                .map(|variant| variant.label.as_str())
+                .collect::<Vec<_>>()
+                .join(" ");
+            let mut cmd = (self.base)();
+            for variant in &combination {
+                cmd.args(&variant.args).envs(variant.envs.iter().cloned());
+            }
+            let expected = (self.expected)(&label);
+            let mut status = None;
+            let start = time::Instant::now();
+            let result = crate::aggregate::catch_all(|| {
+                let assert = cmd.assert().append_context("combination", label.clone());
+                status = Some(assert.get_output().status);
+                check(assert.code(expected))
+            });
+            let duration = start.elapsed();
+            let code = status.map(|status| match status.code() {
+                Some(code) => code.to_string(),
+                None => status.to_string(),
+            });
+            let actual = match result {
+                Ok(_) => code.unwrap_or_default(),
+                Err((signature, dump)) => {
+                    let headline = signature.lines().next().unwrap_or_default().to_owned();
+                    failures.push((label.clone(), signature, dump));
+                    match code {
+                        Some(code) if code != expected.to_string() => code,
+                        Some(code) => format!("{}, {}", code, headline),
+                        None => headline,
+                    }
+                }
+            };
+            rows.push([
+                label,
+                expected.to_string(),
+                actual,
+                format!("{:.1?}", duration),
+            ]);
+        }
+
+        if !failures.is_empty() {
+            let mut report = format!("{} of {} combinations failed\n", failures.len(), total);
+            report.push_str(&summary(&rows));
+            for (label, signature, dump) in &failures {
+                let _ = write!(report, "\n--- {} ---\n{}", label, signature);
+                if !dump.is_empty() {
+                    let _ = write!(report, "\n{}", dump);
+                }
+            }
+            panic!("{}", report);
+        }
+    }
+}
+
+/// A table of `rows`, under a header, with each column padded to its widest cell.
+fn summary(rows: &[[String; 4]]) -> String {
+    let header = ["combination", "expected", "actual", "duration"];
+    let mut widths = header.map(str::len);
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let mut table = String::new();
+    let lines = std::iter::once(header.map(str::to_owned)).chain(rows.iter().cloned());
+    for line in lines {
+        for (i, (cell, width)) in line.iter().zip(widths).enumerate() {
+            if i + 1 == line.len() {
+                table.push_str(cell);
+            } else {
+                let _ = write!(table, "{:<width$}  ", cell, width = width);
+            }
+        }
+        table.push('\n');
+    }
+    table
+}
+
+/// Every choice of one variant per axis, varying the last axis fastest.
+fn combinations(axes: &[Vec<Variant>]) -> Vec<Vec<&Variant>> {
+    let mut combinations = vec![Vec::new()];
+    for axis in axes {
+        combinations = combinations
+            .into_iter()
+            .flat_map(|combination: Vec<&Variant>| {
+                axis.iter().map(move |variant| {
+                    let mut combination = combination.clone();
+                    combination.push(variant);
+                    combination
+                })
+            })
+            .collect();
+    }
+    combinations
+}
+
+impl std::fmt::Debug for CommandMatrix<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CommandMatrix")
+            .field("axes", &self.axes)
+            .finish()
+    }
+}
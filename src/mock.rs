@@ -0,0 +1,245 @@
+//! Stand in for the programs a CLI runs, like `git` or `docker`.
+//!
+//! [`Mocks`] writes fake programs that reply with scripted output, one [`Reply`] per
+//! invocation, and record how they were called.  [`Command::mocks`] puts them first on the
+//! child's `PATH`:
+//!
+//! ```rust,no_run
+//! use assert_cmd::mock::{Mocks, Reply};
+//! use assert_cmd::Command;
+//!
+//! let mut mocks = Mocks::new();
+//! mocks
+//!     .program("git")
+//!     .reply(Reply::new().stdout("main\n"))
+//!     .reply(Reply::new().stderr("fatal: not a git repository\n").code(128));
+//!
+//! Command::cargo_bin("bin_fixture")
+//!     .unwrap()
+//!     .mocks(&mocks)
+//!     .assert()
+//!     .success();
+//!
+//! let calls = mocks.calls("git");
+//! assert_eq!(calls[0].args(), ["rev-parse", "--abbrev-ref", "HEAD"]);
+//! ```
+//!
+//! The fake programs are shell scripts, so this is only available on Unix.
+//!
+//! [`Command::mocks`]: crate::cmd::Command::mocks()
+
+use std::ffi;
+use std::fs;
+use std::os::unix::ffi::OsStringExt;
+use std::os::unix::fs::PermissionsExt;
+use std::path;
+
+/// Fake programs, see the [`mock` module documentation][crate::mock].
+///
+/// The programs are removed when `Mocks` is dropped.
+#[derive(Debug)]
+pub struct Mocks {
+    root: path::PathBuf,
+    programs: Vec<MockProgram>,
+}
+
+impl Mocks {
+    /// Start with no programs, in a fresh directory.
+    pub fn new() -> Self {
+        let root = crate::temp::create_dir("assert_cmd-mocks");
+        let _ = fs::create_dir_all(root.join("bin"));
+        Self {
+            root,
+            programs: Vec::new(),
+        }
+    }
+
+    /// The fake program `name`, created if needed.
+    ///
+    /// Until given a [`Reply`], it succeeds without output.
+    ///
+    /// # Panics
+    ///
+    /// If the program can't be written.
+    pub fn program(&mut self, name: &str) -> &mut MockProgram {
+        if let Some(i) = self.programs.iter().position(|p| p.name == name) {
+            return &mut self.programs[i];
+        }
+        let program = MockProgram::create(&self.root, name).unwrap_or_else(|err| {
+            panic!("Unable to write mock program `{}`: {}", name, err);
+        });
+        self.programs.push(program);
+        self.programs.last_mut().expect("just pushed")
+    }
+
+    /// The directory of fake programs, for `PATH`.
+    pub fn dir(&self) -> path::PathBuf {
+        self.root.join("bin")
+    }
+
+    /// How the program `name` was called so far, in order.
+    pub fn calls(&self, name: &str) -> Vec<Call> {
+        let state = self.root.join("state").join(name);
+        let count = fs::read_to_string(state.join("count"))
+            .ok()
+            .and_then(|count| count.trim().parse().ok())
+            .unwrap_or(0);
+        (1..=count)
+            .filter_map(|n: usize| {
+                let call = state.join(format!("call-{}", n));
+                let args = fs::read(call.join("args")).ok()?;
+                let cwd = fs::read_to_string(call.join("cwd")).ok()?;
+                let mut args: Vec<_> = args
+                    .split(|b| *b == 0)
+                    .map(|arg| ffi::OsString::from_vec(arg.to_owned()))
+                    .collect();
+                // Each argument is terminated, rather than separated.
+                args.pop();
+                Some(Call {
+                    args,
+                    cwd: cwd.trim_end_matches('\n').into(),
+                })
+            })
+            .collect()
+    }
+}
+
+impl Default for Mocks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Mocks {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+/// A fake program, see [`Mocks::program`].
+#[derive(Debug)]
+pub struct MockProgram {
+    name: String,
+    state: path::PathBuf,
+    replies: usize,
+}
+
+impl MockProgram {
+    fn create(root: &path::Path, name: &str) -> std::io::Result<Self> {
+        let state = root.join("state").join(name);
+        fs::create_dir_all(&state)?;
+        fs::write(state.join("count"), "0\n")?;
+        fs::write(state.join("replies"), "0\n")?;
+        let program = root.join("bin").join(name);
+        fs::write(&program, script(&state))?;
+        fs::set_permissions(&program, fs::Permissions::from_mode(0o755))?;
+        Ok(Self {
+            name: name.to_owned(),
+            state,
+            replies: 0,
+        })
+    }
+
+    /// Reply to the next invocation with `reply`.
+    ///
+    /// Replies are used in the order given, and the last is repeated for any further
+    /// invocations.
+    ///
+    /// # Panics
+    ///
+    /// If the reply can't be written.
+    pub fn reply(&mut self, reply: Reply) -> &mut Self {
+        let n = self.replies + 1;
+        let dir = self.state.join(format!("reply-{}", n));
+        let written = fs::create_dir_all(&dir)
+            .and_then(|_| fs::write(dir.join("stdout"), &reply.stdout))
+            .and_then(|_| fs::write(dir.join("stderr"), &reply.stderr))
+            .and_then(|_| fs::write(dir.join("code"), format!("{}\n", reply.code)))
+            .and_then(|_| fs::write(self.state.join("replies"), format!("{}\n", n)));
+        if let Err(err) = written {
+            panic!("Unable to write mock program `{}`: {}", self.name, err);
+        }
+        self.replies = n;
+        self
+    }
+}
+
+/// What a [`MockProgram`] does when invoked.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Reply {
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+    code: i32,
+}
+
+impl Reply {
+    /// Succeed without output.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write `stdout` to `stdout`.
+    pub fn stdout(mut self, stdout: impl Into<Vec<u8>>) -> Self {
+        self.stdout = stdout.into();
+        self
+    }
+
+    /// Write `stderr` to `stderr`.
+    pub fn stderr(mut self, stderr: impl Into<Vec<u8>>) -> Self {
+        self.stderr = stderr.into();
+        self
+    }
+
+    /// Exit with `code`.
+    pub fn code(mut self, code: i32) -> Self {
+        self.code = code;
+        self
+    }
+}
+
+/// An invocation of a [`MockProgram`], see [`Mocks::calls`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Call {
+    args: Vec<ffi::OsString>,
+    cwd: path::PathBuf,
+}
+
+impl Call {
+    /// The arguments, without the program.
+    pub fn args(&self) -> &[ffi::OsString] {
+        &self.args
+    }
+
+    /// The working directory.
+    pub fn cwd(&self) -> &path::Path {
+        &self.cwd
+    }
+}
+
+/// The fake program, keeping its calls and replies in `state`.
+///
+/// Invocations are numbered under a lock, so concurrent calls each get their own reply.
+fn script(state: &path::Path) -> String {
+    let state = state.to_string_lossy().replace('\'', "'\\''");
+    format!(
+        r#"#!/bin/sh
+state='{}'
+while ! mkdir "$state/lock" 2>/dev/null; do sleep 0.01; done
+n=$(( $(cat "$state/count") + 1 ))
+echo "$n" > "$state/count"
+rmdir "$state/lock"
+call="$state/call-$n"
+mkdir "$call"
+for arg in "$@"; do printf '%s\0' "$arg"; done > "$call/args"
+pwd > "$call/cwd"
+r=$n
+last=$(cat "$state/replies")
+[ "$r" -gt "$last" ] && r=$last
+[ "$r" -eq 0 ] && exit 0
+cat "$state/reply-$r/stdout"
+cat "$state/reply-$r/stderr" >&2
+exit "$(cat "$state/reply-$r/code")"
+"#,
+        state
+    )
+}
@@ -0,0 +1,232 @@
+//! Predicates for numeric output, like measurements or statistics.
+//!
+//! See [`Assert::stdout_parsed`] and [`Assert::stdout_duration`].
+//!
+//! [`Assert::stdout_parsed`]: crate::assert::Assert::stdout_parsed()
+//! [`Assert::stdout_duration`]: crate::assert::Assert::stdout_duration()
+
+use std::fmt;
+use std::time::Duration;
+
+use predicates_core::reflection;
+
+/// Create a predicate that a number is approximately `expected`.
+///
+/// Without a tolerance, this checks for exact equality.
+///
+/// # Examples
+///
+/// ```rust
+/// use assert_cmd::numeric::approx;
+/// use predicates::prelude::*;
+///
+/// assert!(approx(3.0).abs(0.1).eval(&3.05));
+/// assert!(!approx(3.0).abs(0.1).eval(&3.2));
+/// assert!(approx(1000.0).rel(0.01).eval(&1005.0));
+/// ```
+pub fn approx(expected: f64) -> ApproxPredicate {
+    ApproxPredicate {
+        expected,
+        abs: 0.0,
+        rel: 0.0,
+    }
+}
+
+/// Predicate that a number is within some tolerance of an expected value.
+///
+/// See [`approx`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ApproxPredicate {
+    expected: f64,
+    abs: f64,
+    rel: f64,
+}
+
+impl ApproxPredicate {
+    /// Accept values within `tolerance` of the expected value.
+    pub fn abs(mut self, tolerance: f64) -> Self {
+        self.abs = tolerance;
+        self
+    }
+
+    /// Accept values within `tolerance`, as a fraction of the larger magnitude, of the expected
+    /// value.
+    ///
+    /// When combined with [`ApproxPredicate::abs`], either tolerance is sufficient.
+    pub fn rel(mut self, tolerance: f64) -> Self {
+        self.rel = tolerance;
+        self
+    }
+}
+
+impl predicates_core::Predicate<f64> for ApproxPredicate {
+    fn eval(&self, variable: &f64) -> bool {
+        let diff = (variable - self.expected).abs();
+        let scale = variable.abs().max(self.expected.abs());
+        diff <= self.abs || diff <= self.rel * scale
+    }
+
+    fn find_case<'a>(&'a self, expected: bool, variable: &f64) -> Option<reflection::Case<'a>> {
+        let result = self.eval(variable);
+        if result == expected {
+            Some(
+                reflection::Case::new(Some(self), result)
+                    .add_product(reflection::Product::new("var", *variable)),
+            )
+        } else {
+            None
+        }
+    }
+}
+
+impl reflection::PredicateReflection for ApproxPredicate {
+    fn parameters<'a>(&'a self) -> Box<dyn Iterator<Item = reflection::Parameter<'a>> + 'a> {
+        let params = vec![
+            reflection::Parameter::new("abs", &self.abs),
+            reflection::Parameter::new("rel", &self.rel),
+        ];
+        Box::new(params.into_iter())
+    }
+}
+
+impl fmt::Display for ApproxPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "var ~= {}", self.expected)
+    }
+}
+
+/// Find the first human-readable duration in `text`, like `2.3s`, `150 ms`, or `1m 2s`.
+///
+/// Recognized units are `ns`, `us`/`µs`, `ms`, `s`/`sec`/`secs`/`seconds`,
+/// `m`/`min`/`mins`/`minutes`, and `h`/`hr`/`hours`.  Adjacent components are summed.
+///
+/// # Examples
+///
+/// ```rust
+/// use assert_cmd::numeric::find_duration;
+/// use std::time::Duration;
+///
+/// assert_eq!(
+///     find_duration("Finished in 2.5s"),
+///     Some(Duration::from_millis(2500))
+/// );
+/// assert_eq!(find_duration("took 1m 2s"), Some(Duration::from_secs(62)));
+/// assert_eq!(find_duration("no timing"), None);
+/// ```
+pub fn find_duration(text: &str) -> Option<Duration> {
+    let mut offset = 0;
+    while offset < text.len() {
+        let rest = &text[offset..];
+        let at_boundary = text[..offset]
+            .chars()
+            .next_back()
+            .map(|c| !c.is_alphanumeric() && c != '.')
+            .unwrap_or(true);
+        if at_boundary {
+            if let Some((mut total, mut len)) = duration_component(rest) {
+                while let Some((next, next_len)) = duration_component(rest[len..].trim_start()) {
+                    let skipped = rest[len..].len() - rest[len..].trim_start().len();
+                    total += next;
+                    len += skipped + next_len;
+                }
+                return Some(total);
+            }
+        }
+        offset += rest.chars().next().map(char::len_utf8).unwrap_or(1);
+    }
+    None
+}
+
+/// Parse a `<number> <unit>` prefix of `text`, returning the duration and bytes consumed.
+fn duration_component(text: &str) -> Option<(Duration, usize)> {
+    let number_len = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let number: f64 = text[..number_len].parse().ok()?;
+    let after_number = &text[number_len..];
+    let unit_start = after_number.len() - after_number.trim_start_matches(' ').len();
+    let unit_text = &after_number[unit_start..];
+    let unit_len = unit_text
+        .find(|c: char| !c.is_alphabetic())
+        .unwrap_or(unit_text.len());
+    let seconds = match &unit_text[..unit_len] {
+        "ns" => 1e-9,
+        "us" | "µs" => 1e-6,
+        "ms" => 1e-3,
+        "s" | "sec" | "secs" | "second" | "seconds" => 1.0,
+        "m" | "min" | "mins" | "minute" | "minutes" => 60.0,
+        "h" | "hr" | "hrs" | "hour" | "hours" => 3600.0,
+        _ => return None,
+    };
+    let duration = Duration::from_secs_f64(number * seconds);
+    Some((duration, number_len + unit_start + unit_len))
+}
+
+/// Find the first semver-like version, like `1.2.3` or `v1.2.3-rc.1+git.abc`, in `text`.
+///
+/// A leading `v` is not included.
+pub(crate) fn find_version(text: &str) -> Option<&str> {
+    let re = regex::Regex::new(
+        r"(?:^|[^0-9A-Za-z.])v?([0-9]+\.[0-9]+\.[0-9]+(?:-[0-9A-Za-z.-]+)?(?:\+[0-9A-Za-z.-]+)?)",
+    )
+    .unwrap();
+    re.captures(text).and_then(|c| c.get(1)).map(|m| m.as_str())
+}
+
+/// Whether `reported` is `expected`, ignoring build metadata like `+git.abc`.
+pub(crate) fn same_version(reported: &str, expected: &str) -> bool {
+    fn release(version: &str) -> &str {
+        version.split('+').next().unwrap_or(version)
+    }
+    release(reported) == release(expected)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use predicates_core::Predicate;
+
+    #[test]
+    fn exact_without_tolerance() {
+        assert!(approx(1.5).eval(&1.5));
+        assert!(!approx(1.5).eval(&1.500001));
+    }
+
+    #[test]
+    fn either_tolerance() {
+        let pred = approx(100.0).abs(0.5).rel(0.1);
+        assert!(pred.eval(&109.0));
+        assert!(!pred.eval(&112.0));
+        assert!(approx(0.0).abs(0.5).rel(0.1).eval(&0.4));
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(find_duration("150ms"), Some(Duration::from_millis(150)));
+        assert_eq!(
+            find_duration("in 1.5 seconds."),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(
+            find_duration("1h2m3s total"),
+            Some(Duration::from_secs(3723))
+        );
+        assert_eq!(find_duration("v1.2s"), None);
+        assert_eq!(find_duration("3 files"), None);
+        assert_eq!(find_duration("3 files in 2s"), Some(Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn versions() {
+        assert_eq!(find_version("tool 1.2.3\n"), Some("1.2.3"));
+        assert_eq!(
+            find_version("tool v1.2.3-rc.1+git.abc"),
+            Some("1.2.3-rc.1+git.abc")
+        );
+        assert_eq!(find_version("tool 1.2"), None);
+        assert_eq!(find_version("tool10.1.2.3"), None);
+        assert!(same_version("1.2.3+git.abc", "1.2.3"));
+        assert!(!same_version("1.2.3-rc.1", "1.2.3"));
+    }
+}
@@ -104,9 +104,12 @@ impl OutputOkExt for process::Output {
     }
 }
 
-impl<'c> OutputOkExt for &'c mut process::Command {
+impl OutputOkExt for &mut process::Command {
     fn ok(self) -> OutputResult {
-        let output = self.output().map_err(OutputError::with_cause)?;
+        let output = crate::budget::track(crate::timing::label(self), || {
+            crate::env_guard::spawning(|| self.output())
+        })
+        .map_err(|err| OutputError::with_cause(crate::exec::SpawnError::new(self, err)))?;
         if output.status.success() {
             Ok(output)
         } else {
@@ -236,6 +239,32 @@ impl OutputError {
             OutputCause::Unexpected(_) => None,
         }
     }
+
+    /// Access the [`SpawnError`], if the program couldn't be run at all.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// let err = Command::new("no-such-program")
+    ///     .unwrap_err();
+    /// let failure = err
+    ///     .spawn_error()
+    ///     .unwrap()
+    ///     .failure();
+    /// println!("{}", failure);
+    /// ```
+    ///
+    /// [`SpawnError`]: crate::exec::SpawnError
+    pub fn spawn_error(&self) -> Option<&crate::exec::SpawnError> {
+        match self.cause {
+            OutputCause::Expected(_) => None,
+            OutputCause::Unexpected(ref e) => e.downcast_ref(),
+        }
+    }
 }
 
 impl Error for OutputError {}
@@ -301,17 +330,26 @@ pub(crate) fn output_fmt(output: &process::Output, f: &mut fmt::Formatter<'_>) -
 #[derive(Debug)]
 pub(crate) struct DebugBytes<'a> {
     bytes: &'a [u8],
+    limit: Option<usize>,
 }
 
 impl<'a> DebugBytes<'a> {
     pub(crate) fn new(bytes: &'a [u8]) -> Self {
-        DebugBytes { bytes }
+        DebugBytes { bytes, limit: None }
+    }
+
+    /// Print at most `limit` bytes, rather than the default.
+    pub(crate) fn with_limit(bytes: &'a [u8], limit: Option<usize>) -> Self {
+        DebugBytes { bytes, limit }
     }
 }
 
 impl<'a> fmt::Display for DebugBytes<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        format_bytes(self.bytes, f)
+        match self.limit {
+            Some(limit) => format_bytes_limited(self.bytes, limit, f),
+            None => format_bytes(self.bytes, f),
+        }
     }
 }
 
@@ -340,7 +378,9 @@ fn format_bytes(data: &[u8], f: &mut fmt::Formatter<'_>) -> fmt::Result {
     const MAX_PRINTED: usize = MAX_START + MAX_END;
     assert!(MAX_PRINTED < MIN_OVERFLOW);
 
-    if data.len() >= MIN_OVERFLOW {
+    if is_binary(data) {
+        format_hex(data, MAX_HEX, f)
+    } else if data.len() >= MIN_OVERFLOW {
         write!(
             f,
             "<{} bytes total>{:?}...<{} bytes omitted>...{:?}",
@@ -353,3 +393,107 @@ fn format_bytes(data: &[u8], f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{:?}", data.as_bstr())
     }
 }
+
+fn format_bytes_limited(data: &[u8], limit: usize, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if is_binary(data) {
+        format_hex(data, limit, f)
+    } else if data.len() > limit {
+        let start = limit / 2;
+        let end = limit - start;
+        write!(
+            f,
+            "<{} bytes total>{:?}...<{} bytes omitted>...{:?}",
+            data.len(),
+            data[..start].as_bstr(),
+            data.len() - limit,
+            data[data.len() - end..].as_bstr(),
+        )
+    } else {
+        write!(f, "{:?}", data.as_bstr())
+    }
+}
+
+/// The most bytes of binary output dumped by default.
+const MAX_HEX: usize = 512;
+
+/// Bytes per hex dump line.
+const HEX_WIDTH: usize = 16;
+
+/// Whether `data` reads better as a hex dump than as escaped text.
+fn is_binary(data: &[u8]) -> bool {
+    data.to_str().is_err()
+}
+
+/// Dump at most `limit` bytes of `data`, from its start and end, as offsets, hex, and ASCII.
+fn format_hex(data: &[u8], limit: usize, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if data.len() > limit {
+        let start = (limit / 2).min(data.len());
+        let end = limit - start;
+        write!(f, "<{} bytes total, binary>", data.len())?;
+        format_hex_lines(&data[..start], 0, f)?;
+        write!(f, "\n...<{} bytes omitted>...", data.len() - limit)?;
+        let offset = data.len() - end;
+        format_hex_lines(&data[offset..], offset, f)?;
+    } else {
+        write!(f, "<{} bytes, binary>", data.len())?;
+        format_hex_lines(data, 0, f)?;
+    }
+    writeln!(f)
+}
+
+fn format_hex_lines(data: &[u8], offset: usize, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for (i, line) in data.chunks(HEX_WIDTH).enumerate() {
+        write!(f, "\n{:08x} ", offset + i * HEX_WIDTH)?;
+        for column in 0..HEX_WIDTH {
+            if column % 8 == 0 {
+                write!(f, " ")?;
+            }
+            match line.get(column) {
+                Some(byte) => write!(f, "{:02x} ", byte)?,
+                None => write!(f, "   ")?,
+            }
+        }
+        let ascii: String = line
+            .iter()
+            .map(|&b| {
+                if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        write!(f, " |{}|", ascii)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hex_dump() {
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\0\x01";
+        assert_eq!(
+            DebugBytes::new(png).to_string(),
+            "<18 bytes, binary>
+00000000  89 50 4e 47 0d 0a 1a 0a  00 00 00 0d 49 48 44 52  |.PNG........IHDR|
+00000010  00 01                                             |..|
+"
+        );
+        let big = [0xffu8; 100];
+        let dump = DebugBytes::with_limit(&big, Some(32)).to_string();
+        assert!(
+            dump.starts_with("<100 bytes total, binary>\n00000000 "),
+            "{}",
+            dump
+        );
+        assert!(
+            dump.contains("\n...<68 bytes omitted>...\n00000054 "),
+            "{}",
+            dump
+        );
+        assert_eq!(DebugBytes::new(b"text\n").to_string(), "\"text\\n\"");
+    }
+}
@@ -0,0 +1,135 @@
+//! Run several commands concurrently, like clients of a server under test.
+//!
+//! Each [`Command`] is run and checked on a pool of threads, and then any failures are reported
+//! together:
+//!
+//! ```rust,no_run
+//! use assert_cmd::parallel::Parallel;
+//! use assert_cmd::Command;
+//!
+//! let mut get = Command::cargo_bin("bin_fixture").unwrap();
+//! get.env("stdout", "value");
+//! let mut put = Command::cargo_bin("bin_fixture").unwrap();
+//! put.env("stdout", "stored");
+//!
+//! let outputs = Parallel::new()
+//!     .add(&mut get, |assert| assert.success().stdout("value\n"))
+//!     .add(&mut put, |assert| assert.success())
+//!     .assert();
+//! assert_eq!(outputs.len(), 2);
+//! ```
+
+use std::fmt::Write;
+use std::process;
+use std::sync::Mutex;
+use std::thread;
+
+use crate::assert::Assert;
+use crate::cmd::Command;
+
+type Check<'a> = Box<dyn FnOnce(Assert) -> Assert + Send + 'a>;
+
+/// [`Command`]s to run concurrently, see the [`parallel` module documentation][crate::parallel].
+pub struct Parallel<'a> {
+    runs: Vec<(&'a mut Command, Check<'a>)>,
+    threads: Option<usize>,
+}
+
+/// A run's result: its output if its check passed, else its failure's `(signature, dump)`.
+type Outcome = Result<process::Output, (String, String)>;
+
+impl<'a> Parallel<'a> {
+    /// Start with no commands.
+    pub fn new() -> Self {
+        Self {
+            runs: Vec::new(),
+            threads: None,
+        }
+    }
+
+    /// Run `cmd` and `check` it.
+    ///
+    /// The check runs on a pool thread, as soon as `cmd` exits.
+    pub fn add<F>(mut self, cmd: &'a mut Command, check: F) -> Self
+    where
+        F: FnOnce(Assert) -> Assert + Send + 'a,
+    {
+        self.runs.push((cmd, Box::new(check)));
+        self
+    }
+
+    /// How many commands run at once.
+    ///
+    /// Defaults to the available parallelism.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads.max(1));
+        self
+    }
+
+    /// Run every command, returning their outputs in the order they were added.
+    ///
+    /// The [`Assert`]s stay on the pool threads, so make any assertions in the checks.
+    ///
+    /// # Panics
+    ///
+    /// With each failure if any check failed, once every command has finished.
+    pub fn assert(self) -> Vec<process::Output> {
+        let total = self.runs.len();
+        let threads = self
+            .threads
+            .unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()))
+            .min(total);
+        let labels: Vec<_> = self.runs.iter().map(|(cmd, _)| cmd.label()).collect();
+        let queue = Mutex::new(self.runs.into_iter().enumerate());
+        let outcomes: Mutex<Vec<Option<Outcome>>> = Mutex::new((0..total).map(|_| None).collect());
+        thread::scope(|scope| {
+            for _ in 0..threads {
+                scope.spawn(|| loop {
+                    let next = queue.lock().unwrap().next();
+                    let (i, (cmd, check)) = match next {
+                        Some(run) => run,
+                        None => break,
+                    };
+                    let outcome = crate::aggregate::catch_all(|| check(cmd.assert()).into_output());
+                    outcomes.lock().unwrap()[i] = Some(outcome);
+                });
+            }
+        });
+
+        let mut outputs = Vec::with_capacity(total);
+        let mut failures = Vec::new();
+        for (i, outcome) in outcomes.into_inner().unwrap().into_iter().enumerate() {
+            match outcome.expect("every run finished") {
+                Ok(output) => outputs.push(output),
+                Err(failure) => failures.push((i, failure)),
+            }
+        }
+        if !failures.is_empty() {
+            let mut report = format!("{} of {} commands failed", failures.len(), total);
+            for (i, (signature, dump)) in &failures {
+                let _ = write!(report, "\n--- #{} {} ---\n{}", i, labels[*i], signature);
+                if !dump.is_empty() {
+                    let _ = write!(report, "\n{}", dump);
+                }
+            }
+            panic!("{}", report);
+        }
+        outputs
+    }
+}
+
+impl Default for Parallel<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for Parallel<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let commands: Vec<_> = self.runs.iter().map(|(cmd, _)| &**cmd).collect();
+        f.debug_struct("Parallel")
+            .field("commands", &commands)
+            .field("threads", &self.threads)
+            .finish()
+    }
+}
@@ -0,0 +1,300 @@
+//! Match output with elided, dynamic parts.
+//!
+//! Timestamps, paths, and versions make exact comparisons brittle.  In a [`pattern`], `[..]`
+//! matches any text within a line and a line of just `...` matches any number of lines:
+//!
+//! ```rust,no_run
+//! use assert_cmd::pattern::pattern;
+//! use assert_cmd::Command;
+//!
+//! Command::cargo_bin("bin_fixture")
+//!     .unwrap()
+//!     .env("stderr", "error: disk full at 12:03:44\nretrying\ngiving up")
+//!     .assert()
+//!     .stderr(pattern("error: disk full at [..]\n...\ngiving up\n"));
+//! ```
+//!
+//! [`in_order`] instead only checks that some snippets appear, one after another.
+
+use std::fmt;
+
+use predicates_core::reflection;
+
+/// Match output against `expected`, where `[..]` matches any text within a line and a line
+/// of just `...` matches any number of lines.
+///
+/// A missing or extra newline at the end is ignored, as are `\r`s before newlines.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use assert_cmd::pattern::pattern;
+/// use assert_cmd::Command;
+///
+/// Command::cargo_bin("bin_fixture")
+///     .unwrap()
+///     .env("stdout", "tool 1.2.3")
+///     .assert()
+///     .stdout(pattern("tool [..]"));
+/// ```
+pub fn pattern<S: Into<String>>(expected: S) -> PatternPredicate {
+    PatternPredicate {
+        expected: expected.into(),
+    }
+}
+
+/// [predicates_core::Predicate] for output matching a [`pattern`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternPredicate {
+    expected: String,
+}
+
+impl PatternPredicate {
+    fn matches(&self, actual: &str) -> bool {
+        let expected = normalize(&self.expected);
+        let actual = normalize(actual);
+        lines_match(&split_lines(&expected), &split_lines(&actual))
+    }
+
+    fn case(&self, expected: bool, actual: &str) -> Option<reflection::Case<'_>> {
+        let result = self.matches(actual);
+        if expected != result {
+            return None;
+        }
+        let case = reflection::Case::new(Some(self), result);
+        Some(if result {
+            case
+        } else {
+            case.add_product(crate::assert::diff_product(&self.expected, actual))
+        })
+    }
+}
+
+impl reflection::PredicateReflection for PatternPredicate {}
+
+impl predicates_core::Predicate<[u8]> for PatternPredicate {
+    fn eval(&self, variable: &[u8]) -> bool {
+        self.matches(&String::from_utf8_lossy(variable))
+    }
+
+    fn find_case<'a>(&'a self, expected: bool, variable: &[u8]) -> Option<reflection::Case<'a>> {
+        self.case(expected, &String::from_utf8_lossy(variable))
+    }
+}
+
+impl fmt::Display for PatternPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "var matches pattern {:?}", self.expected)
+    }
+}
+
+/// Match output containing each of `patterns`, in order, with anything between them.
+///
+/// Failures report which pattern wasn't found and where the search for it started.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use assert_cmd::pattern::in_order;
+/// use assert_cmd::Command;
+///
+/// Command::cargo_bin("bin_fixture")
+///     .unwrap()
+///     .env("stdout", "Loading config\nCompiling demo\nDone in 2s")
+///     .assert()
+///     .stdout(in_order(["Loading", "Compiling", "Done"]));
+/// ```
+pub fn in_order<I, S>(patterns: I) -> InOrderPredicate
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    InOrderPredicate {
+        patterns: patterns.into_iter().map(Into::into).collect(),
+    }
+}
+
+/// [predicates_core::Predicate] for output containing patterns in order, see [`in_order`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InOrderPredicate {
+    patterns: Vec<String>,
+}
+
+impl InOrderPredicate {
+    /// Where each pattern was found, stopping at the first one that wasn't.
+    fn search(&self, actual: &str) -> Vec<Option<usize>> {
+        let mut from = 0;
+        let mut found = Vec::new();
+        for pattern in &self.patterns {
+            match actual[from..].find(pattern.as_str()) {
+                Some(i) => {
+                    found.push(Some(from + i));
+                    from += i + pattern.len();
+                }
+                None => {
+                    found.push(None);
+                    break;
+                }
+            }
+        }
+        found
+    }
+
+    fn case(&self, expected: bool, actual: &str) -> Option<reflection::Case<'_>> {
+        let found = self.search(actual);
+        let result = found.iter().all(Option::is_some);
+        if expected != result {
+            return None;
+        }
+        let mut case = reflection::Case::new(Some(self), result);
+        let mut from = 0;
+        for (step, (pattern, at)) in self.patterns.iter().zip(&found).enumerate() {
+            let name = format!("step {}", step + 1);
+            case = match at {
+                Some(at) => {
+                    from = at + pattern.len();
+                    case.add_product(reflection::Product::new(
+                        name,
+                        format!("{:?} at {}", pattern, position(actual, *at)),
+                    ))
+                }
+                None => {
+                    let mut missing =
+                        format!("{:?} not found after {}", pattern, position(actual, from));
+                    if let Some(earlier) = actual[..from].find(pattern.as_str()) {
+                        missing
+                            .push_str(&format!(", only earlier at {}", position(actual, earlier)));
+                    }
+                    case.add_product(reflection::Product::new(name, missing))
+                }
+            };
+        }
+        Some(case)
+    }
+}
+
+/// Describe byte `at` of `text` by its line, counting from 1, and byte.
+fn position(text: &str, at: usize) -> String {
+    let line = text[..at].matches('\n').count() + 1;
+    format!("line {} (byte {})", line, at)
+}
+
+impl reflection::PredicateReflection for InOrderPredicate {}
+
+impl predicates_core::Predicate<[u8]> for InOrderPredicate {
+    fn eval(&self, variable: &[u8]) -> bool {
+        self.search(&String::from_utf8_lossy(variable))
+            .iter()
+            .all(Option::is_some)
+    }
+
+    fn find_case<'a>(&'a self, expected: bool, variable: &[u8]) -> Option<reflection::Case<'a>> {
+        self.case(expected, &String::from_utf8_lossy(variable))
+    }
+}
+
+impl fmt::Display for InOrderPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "var contains {:?} in order", self.patterns)
+    }
+}
+
+fn normalize(text: &str) -> String {
+    text.replace("\r\n", "\n")
+}
+
+fn split_lines(text: &str) -> Vec<&str> {
+    let text = text.strip_suffix('\n').unwrap_or(text);
+    if text.is_empty() {
+        Vec::new()
+    } else {
+        text.split('\n').collect()
+    }
+}
+
+/// Whether `actual` matches the `expected` lines, where a line of `...` matches any lines.
+pub(crate) fn lines_match(expected: &[&str], actual: &[&str]) -> bool {
+    match expected.split_first() {
+        None => actual.is_empty(),
+        Some((&"...", rest)) => (0..=actual.len()).any(|i| lines_match(rest, &actual[i..])),
+        Some((line, rest)) => match actual.split_first() {
+            Some((first, others)) => line_matches(line, first) && lines_match(rest, others),
+            None => false,
+        },
+    }
+}
+
+/// Whether `actual` matches `expected`, where `[..]` matches any text.
+pub(crate) fn line_matches(expected: &str, actual: &str) -> bool {
+    let mut parts = expected.split("[..]");
+    let first = parts.next().unwrap_or_default();
+    let mut rest = match actual.strip_prefix(first) {
+        Some(rest) => rest,
+        None => return false,
+    };
+    let parts: Vec<_> = parts.collect();
+    let (last, middle) = match parts.split_last() {
+        Some(split) => split,
+        None => return rest.is_empty(),
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use predicates_core::Predicate;
+
+    #[test]
+    fn wildcards() {
+        assert!(line_matches("took [..]ms", "took 15ms"));
+        assert!(line_matches("[..]", ""));
+        assert!(line_matches("a[..]b[..]c", "a1b2b3c"));
+        assert!(!line_matches("took [..]ms", "took 15s"));
+        assert!(!line_matches("exact", "exact!"));
+        assert!(lines_match(
+            &["first", "...", "last"],
+            &["first", "a", "b", "last"]
+        ));
+        assert!(lines_match(&["..."], &[]));
+        assert!(!lines_match(&["first"], &["first", "second"]));
+    }
+
+    #[test]
+    fn ordered() {
+        let pred = in_order(["Loading", "Compiling", "Done"]);
+        assert!(pred.eval(b"Loading\nCompiling a\nCompiling b\nDone\n" as &[u8]));
+        assert!(!pred.eval(b"Compiling\nLoading\nDone\n" as &[u8]));
+        assert!(in_order(Vec::<String>::new()).eval(b"" as &[u8]));
+
+        let case = pred
+            .find_case(false, b"Done early\nLoading\nCompiling\n" as &[u8])
+            .unwrap();
+        let products: Vec<_> = case.products().map(|p| p.to_string()).collect();
+        assert_eq!(
+            products,
+            [
+                "step 1: \"Loading\" at line 2 (byte 11)",
+                "step 2: \"Compiling\" at line 3 (byte 19)",
+                "step 3: \"Done\" not found after line 3 (byte 28), only earlier at line 1 (byte 0)",
+            ]
+        );
+    }
+
+    #[test]
+    fn whole_output() {
+        let pred = pattern("error: [..]\n...\n");
+        assert!(pred.eval(b"error: disk full\nat 12:03\nretrying\n" as &[u8]));
+        assert!(pred.eval(b"error: disk full\r\n" as &[u8]));
+        assert!(!pred.eval(b"warning: disk full\n" as &[u8]));
+        assert!(pattern("").eval(b"" as &[u8]));
+        assert!(!pattern("").eval(b"extra\n" as &[u8]));
+    }
+}
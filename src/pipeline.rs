@@ -0,0 +1,123 @@
+//! Run commands connected by pipes, like `producer | consumer` in a shell.
+//!
+//! [`Command::pipe`] connects each stage's `stdout` to the next stage's `stdin` with an OS pipe.
+//! The [`Assert`] is over the last stage; the other stages' statuses and `stderr` are part of its
+//! failure messages.
+//!
+//! ```rust,no_run
+//! use assert_cmd::Command;
+//!
+//! Command::cargo_bin("bin_fixture")
+//!     .unwrap()
+//!     .env("stdout", "hello")
+//!     .pipe(Command::new("tr").args(["a-z", "A-Z"]))
+//!     .pipefail(true)
+//!     .assert()
+//!     .success()
+//!     .stdout("HELLO\n");
+//! ```
+//!
+//! [`Command::pipe`]: crate::cmd::Command::pipe()
+
+use std::io::Read;
+use std::thread;
+
+use crate::assert::Assert;
+use crate::cmd::Command;
+use crate::output::DebugBytes;
+
+/// [`Command`]s whose `stdout` feeds the next's `stdin`.
+///
+/// See the [`pipeline` module documentation][crate::pipeline].
+///
+/// The first stage reads any [`Command::write_stdin`] input.  Each stage's
+/// [`Command::timeout`] applies to it alone; when the last stage exits, the others see their
+/// `stdout` close, like in a shell.
+///
+/// [`Command::write_stdin`]: crate::cmd::Command::write_stdin()
+/// [`Command::timeout`]: crate::cmd::Command::timeout()
+#[derive(Debug)]
+pub struct Pipeline<'a> {
+    stages: Vec<&'a mut Command>,
+    pipefail: bool,
+}
+
+impl<'a> Pipeline<'a> {
+    /// Start a pipeline with `first`, see [`Command::pipe`].
+    ///
+    /// [`Command::pipe`]: crate::cmd::Command::pipe()
+    pub fn new(first: &'a mut Command) -> Self {
+        Self {
+            stages: vec![first],
+            pipefail: false,
+        }
+    }
+
+    /// Pipe the last stage's `stdout` into `next`.
+    pub fn pipe(mut self, next: &'a mut Command) -> Self {
+        self.stages.push(next);
+        self
+    }
+
+    /// Whether any stage failing fails the [`Assert`], like `set -o pipefail` in a shell.
+    ///
+    /// Otherwise, only the last stage's status is asserted on.  Defaults to `false`.
+    pub fn pipefail(mut self, yes: bool) -> Self {
+        self.pipefail = yes;
+        self
+    }
+
+    /// Run every stage, returning an [`Assert`] over the last stage.
+    ///
+    /// # Panics
+    ///
+    /// If a stage can't be spawned.
+    pub fn assert(&mut self) -> Assert {
+        let (last, upstream) = self
+            .stages
+            .split_last_mut()
+            .expect("a pipeline has a stage");
+        let mut stdin = None;
+        let mut running = Vec::new();
+        for stage in upstream.iter_mut() {
+            let mut child = stage.spawn_stage(stdin.take());
+            stdin = child.take_stdout_pipe();
+            if stdin.is_none() {
+                panic!(
+                    "Unable to pipe {}'s stdout, it isn't captured",
+                    stage.label()
+                );
+            }
+            let stderr = child.take_stderr().map(|mut stderr| {
+                thread::spawn(move || {
+                    let mut data = Vec::new();
+                    let _ = stderr.read_to_end(&mut data);
+                    data
+                })
+            });
+            running.push((child, stderr));
+        }
+        let mut assert = last.assert_stage(stdin);
+
+        let mut failed = None;
+        for (n, (stage, (child, stderr))) in upstream.iter().zip(running).enumerate() {
+            let status = stage.finish_stage(child);
+            let stderr = stderr.and_then(|reader| reader.join().ok());
+            let summary = format!("{}, {}", stage.label(), status);
+            if !status.success() && failed.is_none() {
+                failed = Some((n + 1, summary.clone()));
+            }
+            assert = assert.append_context("stage", summary);
+            if let Some(stderr) = stderr.filter(|stderr| !stderr.is_empty()) {
+                assert = assert.append_context(
+                    "stage stderr",
+                    format!("```{}```", DebugBytes::new(&stderr)),
+                );
+            }
+        }
+        if let (true, Some((n, summary))) = (self.pipefail, failed) {
+            assert.fail(format_args!("Pipeline stage {} failed: {}", n, summary));
+        }
+        assert
+    }
+}
@@ -0,0 +1,262 @@
+//! Expectations that legitimately differ between platforms.
+//!
+//! [`PerOs`] picks the expected value for the platform the tests run on, so `cfg!` doesn't have
+//! to be scattered through test bodies.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use assert_cmd::platform::PerOs;
+//! use assert_cmd::Command;
+//!
+//! Command::cargo_bin("bin_fixture")
+//!     .unwrap()
+//!     .env("stdout", "config.toml")
+//!     .assert()
+//!     .stdout(
+//!         PerOs::new("config.toml\n")
+//!             .windows("config.toml\r\n")
+//!             .macos("config.toml\n"),
+//!     );
+//! ```
+//!
+//! Crashes are also reported differently: by a signal on Unix, and by an `NTSTATUS` exit code on
+//! Windows.  [`Assert::crashed`] and [`Assert::exit_nt_status`] understand both, with the
+//! codes below.
+//!
+//! [`Assert::crashed`]: crate::assert::Assert::crashed()
+//! [`Assert::exit_nt_status`]: crate::assert::Assert::exit_nt_status()
+
+use crate::assert::IntoOutputPredicate;
+
+/// An invalid memory access, `SIGSEGV` on Unix.
+pub const STATUS_ACCESS_VIOLATION: u32 = 0xC000_0005;
+
+/// An invalid instruction, `SIGILL` on Unix.
+pub const STATUS_ILLEGAL_INSTRUCTION: u32 = 0xC000_001D;
+
+/// An integer division by zero, `SIGFPE` on Unix.
+pub const STATUS_INTEGER_DIVIDE_BY_ZERO: u32 = 0xC000_0094;
+
+/// A stack overflow, `SIGSEGV` on Unix.
+pub const STATUS_STACK_OVERFLOW: u32 = 0xC000_00FD;
+
+/// A fast fail, like from [`std::process::abort`], `SIGABRT` on Unix.
+pub const STATUS_STACK_BUFFER_OVERRUN: u32 = 0xC000_0409;
+
+/// The Unix signal for the crash reported on Windows as `status`.
+#[cfg(unix)]
+pub(crate) fn nt_status_signal(status: u32) -> Option<i32> {
+    match status {
+        STATUS_ACCESS_VIOLATION | STATUS_STACK_OVERFLOW => Some(libc::SIGSEGV),
+        STATUS_ILLEGAL_INSTRUCTION => Some(libc::SIGILL),
+        STATUS_INTEGER_DIVIDE_BY_ZERO => Some(libc::SIGFPE),
+        STATUS_STACK_BUFFER_OVERRUN => Some(libc::SIGABRT),
+        _ => None,
+    }
+}
+
+/// Whether a process killed by `signal` crashed, rather than being asked to stop.
+#[cfg(unix)]
+pub(crate) fn is_crash_signal(signal: i32) -> bool {
+    [
+        libc::SIGSEGV,
+        libc::SIGBUS,
+        libc::SIGILL,
+        libc::SIGFPE,
+        libc::SIGABRT,
+        libc::SIGTRAP,
+        libc::SIGSYS,
+    ]
+    .contains(&signal)
+}
+
+/// Whether `code` is an error `NTSTATUS`, as from a crash.
+#[cfg(windows)]
+pub(crate) fn is_crash_code(code: u32) -> bool {
+    code & 0xC000_0000 == 0xC000_0000
+}
+
+/// A value keyed by platform, falling back to a default.
+///
+/// The most specific match wins: the OS (like [`PerOs::linux`]), then the family
+/// ([`PerOs::unix`] or [`PerOs::windows`]), then the default.
+///
+/// Output expectations, like `&str` or predicates, can be passed to [`Assert::stdout`] and
+/// [`Assert::stderr`] directly.  Otherwise, use [`PerOs::get`].
+///
+/// [`Assert::stdout`]: crate::assert::Assert::stdout()
+/// [`Assert::stderr`]: crate::assert::Assert::stderr()
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PerOs<T> {
+    default: T,
+    windows: Option<T>,
+    unix: Option<T>,
+    linux: Option<T>,
+    macos: Option<T>,
+}
+
+impl<T> PerOs<T> {
+    /// Use `default` unless a value for the current platform is given.
+    pub fn new(default: T) -> Self {
+        Self {
+            default,
+            windows: None,
+            unix: None,
+            linux: None,
+            macos: None,
+        }
+    }
+
+    /// Use `value` on Windows.
+    pub fn windows(mut self, value: T) -> Self {
+        self.windows = Some(value);
+        self
+    }
+
+    /// Use `value` on Unix-like platforms, unless the specific OS has its own.
+    pub fn unix(mut self, value: T) -> Self {
+        self.unix = Some(value);
+        self
+    }
+
+    /// Use `value` on Linux.
+    pub fn linux(mut self, value: T) -> Self {
+        self.linux = Some(value);
+        self
+    }
+
+    /// Use `value` on macOS.
+    pub fn macos(mut self, value: T) -> Self {
+        self.macos = Some(value);
+        self
+    }
+
+    /// The value for the current platform.
+    pub fn get(self) -> T {
+        let os = if cfg!(target_os = "linux") {
+            self.linux
+        } else if cfg!(target_os = "macos") {
+            self.macos
+        } else {
+            None
+        };
+        let family = if cfg!(windows) {
+            self.windows
+        } else if cfg!(unix) {
+            self.unix
+        } else {
+            None
+        };
+        os.or(family).unwrap_or(self.default)
+    }
+}
+
+impl<I, P> IntoOutputPredicate<P> for PerOs<I>
+where
+    I: IntoOutputPredicate<P>,
+    P: predicates_core::Predicate<[u8]>,
+{
+    type Predicate = P;
+
+    fn into_output(self) -> P {
+        self.get().into_output()
+    }
+}
+
+/// Quote `arg` as a single word for [`Command::shell`]'s shell: `sh` on Unix and `cmd` on
+/// Windows.
+///
+/// On Windows, the quoting is for programs parsing their command line like the C runtime does,
+/// and `%` still expands variables.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use assert_cmd::platform::shell_quote;
+/// use assert_cmd::Command;
+///
+/// let file = "report (final).txt";
+/// Command::shell(format!("mytool --input {} | sort", shell_quote(file)))
+///     .assert()
+///     .success();
+/// ```
+///
+/// [`Command::shell`]: crate::cmd::Command::shell()
+pub fn shell_quote(arg: &str) -> String {
+    if cfg!(windows) {
+        quote_cmd(arg)
+    } else {
+        quote_sh(arg)
+    }
+}
+
+fn quote_sh(arg: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c);
+    if !arg.is_empty() && arg.chars().all(plain) {
+        return arg.to_owned();
+    }
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+fn quote_cmd(arg: &str) -> String {
+    let special = |c: char| c.is_whitespace() || "\"&|<>()^%!,;=".contains(c);
+    if !arg.is_empty() && !arg.chars().any(special) {
+        return arg.to_owned();
+    }
+    // Backslashes are only escapes before a quote, including the closing one.
+    let mut quoted = String::from("\"");
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                quoted.extend(std::iter::repeat('\\').take(backslashes * 2 + 1));
+                quoted.push('"');
+                backslashes = 0;
+            }
+            c => {
+                quoted.extend(std::iter::repeat('\\').take(backslashes));
+                quoted.push(c);
+                backslashes = 0;
+            }
+        }
+    }
+    quoted.extend(std::iter::repeat('\\').take(backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn quotes() {
+        assert_eq!(quote_sh("plain-1.txt"), "plain-1.txt");
+        assert_eq!(quote_sh(""), "''");
+        assert_eq!(quote_sh("a b*"), "'a b*'");
+        assert_eq!(quote_sh("it's"), r"'it'\''s'");
+        assert_eq!(quote_cmd(r"C:\dir\file.txt"), r"C:\dir\file.txt");
+        assert_eq!(quote_cmd(""), r#""""#);
+        assert_eq!(quote_cmd("a & b"), r#""a & b""#);
+        assert_eq!(quote_cmd(r#"say "hi""#), r#""say \"hi\"""#);
+        assert_eq!(quote_cmd(r"C:\my dir\"), r#""C:\my dir\\""#);
+    }
+
+    #[test]
+    fn most_specific_wins() {
+        let expected = if cfg!(target_os = "linux") {
+            "linux"
+        } else if cfg!(unix) {
+            "unix"
+        } else {
+            "default"
+        };
+        assert_eq!(
+            PerOs::new("default").unix("unix").linux("linux").get(),
+            expected
+        );
+        assert_eq!(PerOs::new("default").get(), "default");
+    }
+}
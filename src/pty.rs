@@ -0,0 +1,89 @@
+//! Attach the child's `stdout` to a pseudo-terminal.
+//!
+//! See [`Command::pty`][crate::cmd::Command::pty()].
+
+use std::ffi;
+use std::fs;
+use std::io;
+use std::io::Read;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::io::FromRawFd;
+use std::sync;
+
+/// The terminal size reported to the child.
+const COLUMNS: u16 = 80;
+const ROWS: u16 = 24;
+
+/// `ptsname` isn't reentrant and `ptsname_r` isn't portable.
+static PTSNAME: sync::Mutex<()> = sync::Mutex::new(());
+
+/// Our end of the terminal, read like a pipe.
+#[derive(Debug)]
+pub(crate) struct Master(fs::File);
+
+impl Read for Master {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.0.read(buf) {
+            // Linux reports the child's end closing as an error rather than end-of-file.
+            Err(err) if err.raw_os_error() == Some(libc::EIO) => Ok(0),
+            result => result,
+        }
+    }
+}
+
+/// Open a terminal, returning our end and the child's.
+///
+/// Output processing is disabled so the child's `\n`s aren't translated to `\r\n`.
+pub(crate) fn open() -> io::Result<(Master, fs::File)> {
+    // SAFETY: `posix_openpt` returns a new descriptor, owned by the `File`
+    let master = unsafe {
+        let fd = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        fs::File::from_raw_fd(fd)
+    };
+    let fd = master.as_raw_fd();
+    // SAFETY: `fd` is an open terminal master
+    let name = unsafe {
+        check(libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC))?;
+        check(libc::grantpt(fd))?;
+        check(libc::unlockpt(fd))?;
+        let _lock = PTSNAME.lock().unwrap_or_else(sync::PoisonError::into_inner);
+        let name = libc::ptsname(fd);
+        if name.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        ffi::CStr::from_ptr(name).to_string_lossy().into_owned()
+    };
+    let slave = fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .custom_flags(libc::O_NOCTTY)
+        .open(name)?;
+    let fd = slave.as_raw_fd();
+    // SAFETY: `fd` is an open terminal and both structs outlive the calls
+    unsafe {
+        let mut termios: libc::termios = std::mem::zeroed();
+        check(libc::tcgetattr(fd, &mut termios))?;
+        termios.c_oflag &= !libc::OPOST;
+        check(libc::tcsetattr(fd, libc::TCSANOW, &termios))?;
+        let size = libc::winsize {
+            ws_row: ROWS,
+            ws_col: COLUMNS,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        };
+        check(libc::ioctl(fd, libc::TIOCSWINSZ, &size))?;
+    }
+    Ok((Master(master), slave))
+}
+
+fn check(result: libc::c_int) -> io::Result<()> {
+    if result < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
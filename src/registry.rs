@@ -0,0 +1,94 @@
+//! Name configured invocations so scenario tests read as domain steps.
+//!
+//! # Examples
+//!
+//! Define the steps once, in a fixture module:
+//!
+//! ```rust,no_run
+//! use assert_cmd::registry::Registry;
+//! use assert_cmd::Command;
+//!
+//! fn steps() -> Registry {
+//!     Registry::new()
+//!         .register("import", || {
+//!             let mut cmd = Command::cargo_bin("bin_fixture").unwrap();
+//!             cmd.env("stdout", "imported");
+//!             cmd
+//!         })
+//!         .register("report", || Command::cargo_bin("bin_fixture").unwrap())
+//! }
+//!
+//! let steps = steps();
+//! steps.run("import").success().stdout("imported\n");
+//! steps.run("report").success();
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::assert::Assert;
+use crate::cmd::Command;
+
+/// Configured invocations, by name.
+///
+/// See the [module documentation][crate::registry].
+#[derive(Default)]
+pub struct Registry {
+    commands: BTreeMap<String, Box<dyn Fn() -> Command>>,
+}
+
+impl Registry {
+    /// An empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Name the invocation created by `build`, replacing any previous registration of `name`.
+    ///
+    /// `build` is called for every use, so each run starts from a fresh [`Command`].
+    pub fn register<F>(mut self, name: impl Into<String>, build: F) -> Self
+    where
+        F: Fn() -> Command + 'static,
+    {
+        self.commands.insert(name.into(), Box::new(build));
+        self
+    }
+
+    /// A fresh [`Command`] for the invocation named `name`, for further configuration.
+    ///
+    /// # Panics
+    ///
+    /// If nothing is registered as `name`.
+    pub fn command(&self, name: &str) -> Command {
+        match self.commands.get(name) {
+            Some(build) => build(),
+            None => {
+                let known: Vec<_> = self.commands.keys().map(String::as_str).collect();
+                panic!(
+                    "No command registered as `{}`; registered: {}",
+                    name,
+                    known.join(", ")
+                );
+            }
+        }
+    }
+
+    /// Run the invocation named `name`.
+    ///
+    /// Failure messages include the step name.
+    ///
+    /// # Panics
+    ///
+    /// If nothing is registered as `name`.
+    pub fn run(&self, name: &str) -> Assert {
+        self.command(name)
+            .assert()
+            .append_context("step", name.to_owned())
+    }
+}
+
+impl fmt::Debug for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.commands.keys()).finish()
+    }
+}
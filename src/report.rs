@@ -0,0 +1,274 @@
+//! Record every asserted command in a machine-readable file, like for CI dashboards.
+//!
+//! Once enabled, each [`Assert`] adds an entry when it fails, or when it is dropped with all of
+//! its assertions passing.  An entry has the test's name, the command, how long it took, its
+//! exit status, and the start of its `stdout` and `stderr`.
+//!
+//! Enable it with [`report_to`] or by setting `ASSERT_CMD_REPORT` to the file's path.  Paths
+//! ending in `.xml` get a JUnit `<testsuite>`, with a `<testcase>` per command, and anything
+//! else gets a JSON object per line:
+//!
+//! ```text
+//! {"test":"cli::import","command":"\"tool\" \"import\"","passed":false,"code":1,"duration":0.25,"failure":"Unexpected return code...","stdout":"","stderr":"error: ..."}
+//! ```
+//!
+//! Entries are appended, so remove the file between CI runs.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use assert_cmd::Command;
+//!
+//! assert_cmd::report::report_to("target/assert_cmd.xml");
+//!
+//! Command::cargo_bin("bin_fixture").unwrap().assert().success();
+//! ```
+//!
+//! [`Assert`]: crate::assert::Assert
+
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::io::Write as _;
+use std::path;
+use std::process;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// Set to the report's path to enable reports without code changes.
+pub const ENV_VAR: &str = "ASSERT_CMD_REPORT";
+
+/// How much of each stream an entry keeps.
+const OUTPUT_LIMIT: usize = 4096;
+
+const JUNIT_HEADER: &str =
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuite name=\"assert_cmd\">\n";
+const JUNIT_FOOTER: &str = "</testsuite>\n";
+
+static PATH: Mutex<Option<path::PathBuf>> = Mutex::new(None);
+
+/// Record every asserted command in the file at `path`.
+///
+/// This applies to every test in the process and takes precedence over `ASSERT_CMD_REPORT`.
+pub fn report_to<P: Into<path::PathBuf>>(path: P) {
+    *PATH.lock().unwrap_or_else(|err| err.into_inner()) = Some(path.into());
+}
+
+fn path() -> Option<path::PathBuf> {
+    let path = PATH.lock().unwrap_or_else(|err| err.into_inner()).clone();
+    path.or_else(|| {
+        env::var_os(ENV_VAR)
+            .filter(|path| !path.is_empty())
+            .map(Into::into)
+    })
+}
+
+/// A command to report once the outcome of its assertions is known.
+#[derive(Debug)]
+pub(crate) struct Entry {
+    test: String,
+    command: String,
+    duration: Option<Duration>,
+}
+
+/// Describe `cmd`'s run, taking `duration` if it was run rather than replayed, if reports are
+/// enabled.
+pub(crate) fn entry(cmd: &dyn std::fmt::Debug, duration: Option<Duration>) -> Option<Entry> {
+    path()?;
+    Some(Entry {
+        test: thread::current().name().unwrap_or("<unnamed>").to_owned(),
+        command: format!("{:?}", cmd),
+        duration,
+    })
+}
+
+/// Add `entry` to the report, with `failure` as the failed assertion, if any.
+pub(crate) fn record(entry: Entry, output: &process::Output, failure: Option<&str>) {
+    let path = match path() {
+        Some(path) => path,
+        None => return,
+    };
+    // Serializes appending, and rewriting the JUnit footer, across threads.
+    static WRITING: Mutex<()> = Mutex::new(());
+    let _writing = WRITING.lock().unwrap_or_else(|err| err.into_inner());
+    let result = if path.extension().map_or(false, |ext| ext == "xml") {
+        append_junit(&path, &junit(&entry, output, failure))
+    } else {
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| writeln!(file, "{}", json(&entry, output, failure)))
+    };
+    if let Err(err) = result {
+        let _ = writeln!(
+            io::stderr(),
+            "assert_cmd: failed to write report `{}`: {}",
+            path.display(),
+            err
+        );
+    }
+}
+
+fn append_junit(path: &path::Path, testcase: &str) -> io::Result<()> {
+    let mut report = match fs::read_to_string(path) {
+        Ok(report) => report,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err),
+    };
+    match report.strip_suffix(JUNIT_FOOTER) {
+        Some(body) => report.truncate(body.len()),
+        None => report = JUNIT_HEADER.to_owned(),
+    }
+    report.push_str(testcase);
+    report.push_str(JUNIT_FOOTER);
+    fs::write(path, report)
+}
+
+fn junit(entry: &Entry, output: &process::Output, failure: Option<&str>) -> String {
+    let mut testcase = format!(
+        "  <testcase name=\"{}\" classname=\"{}\"",
+        xml_escape(&entry.test),
+        xml_escape(&entry.command)
+    );
+    if let Some(duration) = entry.duration {
+        let _ = write!(testcase, " time=\"{:.3}\"", duration.as_secs_f64());
+    }
+    let _ = writeln!(
+        testcase,
+        ">\n    <properties><property name=\"status\" value=\"{}\"/></properties>",
+        xml_escape(&output.status.to_string())
+    );
+    if let Some(failure) = failure {
+        let first = failure.lines().next().unwrap_or_default();
+        let _ = writeln!(
+            testcase,
+            "    <failure message=\"{}\">{}</failure>",
+            xml_escape(first),
+            xml_escape(failure)
+        );
+    }
+    let _ = writeln!(
+        testcase,
+        "    <system-out>{}</system-out>\n    <system-err>{}</system-err>",
+        xml_escape(&truncated(&output.stdout)),
+        xml_escape(&truncated(&output.stderr))
+    );
+    testcase.push_str("  </testcase>\n");
+    testcase
+}
+
+fn json(entry: &Entry, output: &process::Output, failure: Option<&str>) -> String {
+    let optional = |value: Option<String>| value.unwrap_or_else(|| "null".to_owned());
+    format!(
+        "{{\"test\":{},\"command\":{},\"passed\":{},\"code\":{},\"duration\":{},\"failure\":{},\"stdout\":{},\"stderr\":{}}}",
+        json_string(&entry.test),
+        json_string(&entry.command),
+        failure.is_none(),
+        optional(output.status.code().map(|code| code.to_string())),
+        optional(entry.duration.map(|d| d.as_secs_f64().to_string())),
+        optional(failure.map(json_string)),
+        json_string(&truncated(&output.stdout)),
+        json_string(&truncated(&output.stderr))
+    )
+}
+
+/// The start of `data`, as text.
+fn truncated(data: &[u8]) -> String {
+    let text = String::from_utf8_lossy(data);
+    if text.len() <= OUTPUT_LIMIT {
+        return text.into_owned();
+    }
+    let mut end = OUTPUT_LIMIT;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}...<{} bytes omitted>", &text[..end], text.len() - end)
+}
+
+fn json_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(quoted, "\\u{:04x}", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn xml_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\n' | '\r' | '\t' => escaped.push(c),
+            // Not allowed in XML 1.0, even escaped.
+            c if c.is_control() => escaped.push('\u{fffd}'),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+
+    fn sample() -> (Entry, process::Output) {
+        let entry = Entry {
+            test: "cli::import".to_owned(),
+            command: "\"tool\" \"<in>\"".to_owned(),
+            duration: Some(Duration::from_millis(250)),
+        };
+        let output = process::Output {
+            status: process::ExitStatus::from_raw(1 << 8),
+            stdout: b"ok\n".to_vec(),
+            stderr: b"bad\x01".to_vec(),
+        };
+        (entry, output)
+    }
+
+    #[test]
+    fn formats() {
+        let (entry, output) = sample();
+        assert_eq!(
+            json(&entry, &output, Some("Unexpected code")),
+            r#"{"test":"cli::import","command":"\"tool\" \"<in>\"","passed":false,"code":1,"duration":0.25,"failure":"Unexpected code","stdout":"ok\n","stderr":"bad\u0001"}"#
+        );
+        assert_eq!(
+            junit(&entry, &output, None),
+            "  <testcase name=\"cli::import\" classname=\"&quot;tool&quot; &quot;&lt;in&gt;&quot;\" time=\"0.250\">\n    <properties><property name=\"status\" value=\"exit status: 1\"/></properties>\n    <system-out>ok\n</system-out>\n    <system-err>bad\u{fffd}</system-err>\n  </testcase>\n"
+        );
+    }
+
+    #[test]
+    fn appends_junit() {
+        let dir = crate::temp::TempDir::new("assert_cmd-report");
+        let path = dir.path().join("report.xml");
+        append_junit(&path, "  <testcase name=\"a\"/>\n").unwrap();
+        append_junit(&path, "  <testcase name=\"b\"/>\n").unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            format!(
+                "{}  <testcase name=\"a\"/>\n  <testcase name=\"b\"/>\n{}",
+                JUNIT_HEADER, JUNIT_FOOTER
+            )
+        );
+    }
+}
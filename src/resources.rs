@@ -0,0 +1,189 @@
+//! Sample the child's resource usage while it runs, and report its total once it exits.
+//!
+//! See [`Command::sample_resources`] and [`Assert::get_resource_usage`].
+//!
+//! Sampling is only supported on Linux; elsewhere no samples are collected.  Totals are
+//! supported on Unix.
+//!
+//! [`Command::sample_resources`]: crate::cmd::Command::sample_resources()
+//! [`Assert::get_resource_usage`]: crate::assert::Assert::get_resource_usage()
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The child's resource usage at a point in time.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Sample {
+    /// When the sample was taken, relative to spawning the child.
+    pub elapsed: Duration,
+    /// Resident set size, in bytes.
+    pub rss: u64,
+    /// CPU time consumed so far, user and system combined.
+    pub cpu: Duration,
+}
+
+/// The child's total resource usage, as reported by `wait4` when it exited.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ResourceUsage {
+    /// Peak resident set size, in bytes.
+    pub max_rss: u64,
+    /// CPU time spent in user mode.
+    pub user_time: Duration,
+    /// CPU time spent in the kernel.
+    pub system_time: Duration,
+}
+
+impl ResourceUsage {
+    #[cfg(unix)]
+    pub(crate) fn from_rusage(usage: &libc::rusage) -> Self {
+        // macOS reports bytes, everything else kilobytes.
+        let unit = if cfg!(any(target_os = "macos", target_os = "ios")) {
+            1
+        } else {
+            1024
+        };
+        let time = |time: libc::timeval| {
+            Duration::from_secs(time.tv_sec as u64) + Duration::from_micros(time.tv_usec as u64)
+        };
+        Self {
+            max_rss: (usage.ru_maxrss as u64).saturating_mul(unit),
+            user_time: time(usage.ru_utime),
+            system_time: time(usage.ru_stime),
+        }
+    }
+
+    /// CPU time spent, user and system combined.
+    pub fn cpu_time(&self) -> Duration {
+        self.user_time + self.system_time
+    }
+}
+
+/// Samples a child on a background thread until stopped.
+pub(crate) struct Sampler {
+    stop: Arc<AtomicBool>,
+    handle: thread::JoinHandle<Vec<Sample>>,
+}
+
+impl Sampler {
+    pub(crate) fn start(pid: u32, start: Instant, interval: Duration) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let handle = thread::spawn(move || {
+            let mut samples = Vec::new();
+            while !thread_stop.load(Ordering::SeqCst) {
+                match sample(pid) {
+                    Some((rss, cpu)) => samples.push(Sample {
+                        elapsed: start.elapsed(),
+                        rss,
+                        cpu,
+                    }),
+                    None => break,
+                }
+                thread::park_timeout(interval);
+            }
+            samples
+        });
+        Self { stop, handle }
+    }
+
+    pub(crate) fn finish(self) -> Vec<Sample> {
+        self.stop.store(true, Ordering::SeqCst);
+        self.handle.thread().unpark();
+        self.handle.join().unwrap_or_default()
+    }
+}
+
+/// Resident set size and CPU time of `pid`, if it's still running.
+#[cfg(target_os = "linux")]
+fn sample(pid: u32) -> Option<(u64, Duration)> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    parse_stat(&stat, page_size(), clock_ticks())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn sample(_pid: u32) -> Option<(u64, Duration)> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn page_size() -> u64 {
+    // SAFETY: `sysconf` has no preconditions.
+    unsafe { libc::sysconf(libc::_SC_PAGESIZE) as u64 }
+}
+
+#[cfg(target_os = "linux")]
+fn clock_ticks() -> u64 {
+    // SAFETY: `sysconf` has no preconditions.
+    unsafe { libc::sysconf(libc::_SC_CLK_TCK) as u64 }
+}
+
+/// Parse `/proc/<pid>/stat`, see `proc(5)`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_stat(stat: &str, page_size: u64, clock_ticks: u64) -> Option<(u64, Duration)> {
+    // The command name may contain spaces or parentheses, so start after its closing `)`.
+    let fields: Vec<_> = stat[stat.rfind(')')? + 1..].split_whitespace().collect();
+    let state = fields.first()?;
+    if *state == "Z" || *state == "X" {
+        return None;
+    }
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    let rss: u64 = fields.get(21)?.parse().ok()?;
+    let cpu = Duration::from_secs_f64((utime + stime) as f64 / clock_ticks.max(1) as f64);
+    Some((rss * page_size, cpu))
+}
+
+/// Whether the RSS never shrank and was still growing through the second half of `samples`.
+///
+/// Startup allocations level off quickly, so a plateau after an initial jump isn't growth.
+pub(crate) fn grows_monotonically(samples: &[Sample]) -> bool {
+    let mid = match samples.get(samples.len() / 2) {
+        Some(mid) => mid.rss,
+        None => return false,
+    };
+    samples.windows(2).all(|w| w[0].rss <= w[1].rss)
+        && samples.last().map(|last| last.rss) > Some(mid)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_proc_stat() {
+        let stat = "1234 (my (app)) S 1 1234 1234 0 -1 4194560 100 0 0 0 150 50 0 0 20 0 1 0 100 1000000 256 18446744073709551615";
+        assert_eq!(
+            parse_stat(stat, 4096, 100),
+            Some((256 * 4096, Duration::from_secs(2)))
+        );
+        let zombie =
+            "1234 (app) Z 1 1234 1234 0 -1 4194560 100 0 0 0 150 50 0 0 20 0 1 0 100 0 0 0";
+        assert_eq!(parse_stat(zombie, 4096, 100), None);
+    }
+
+    fn samples(rss: &[u64]) -> Vec<Sample> {
+        rss.iter()
+            .map(|rss| Sample {
+                elapsed: Duration::default(),
+                rss: *rss,
+                cpu: Duration::default(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn monotonic_growth() {
+        assert!(grows_monotonically(&samples(&[1, 2, 2, 3])));
+        assert!(!grows_monotonically(&samples(&[1, 3, 2])));
+        assert!(!grows_monotonically(&samples(&[2, 2, 2])));
+        assert!(!grows_monotonically(&samples(&[1, 5, 5, 5, 5])));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn samples_self() {
+        assert!(sample(std::process::id()).is_some());
+    }
+}
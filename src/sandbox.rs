@@ -0,0 +1,109 @@
+//! Keep a CLI away from the developer's real config and caches.
+//!
+//! A [`Sandbox`] is a fresh home directory, with the per-user config, cache, and data
+//! directories inside it.  [`Command::sandbox_home`] points the child at it, so tests can
+//! pre-seed config without touching the real one:
+//!
+//! ```rust,no_run
+//! use assert_cmd::sandbox::Sandbox;
+//! use assert_cmd::Command;
+//!
+//! let sandbox = Sandbox::new();
+//! std::fs::create_dir_all(sandbox.config_dir().join("mytool")).unwrap();
+//! std::fs::write(sandbox.config_dir().join("mytool").join("config.toml"), "color = false\n")
+//!     .unwrap();
+//!
+//! Command::cargo_bin("bin_fixture")
+//!     .unwrap()
+//!     .sandbox_home(&sandbox)
+//!     .assert()
+//!     .success();
+//! ```
+//!
+//! [`Command::sandbox_home`]: crate::cmd::Command::sandbox_home()
+
+use std::fs;
+use std::path;
+
+/// A fresh home directory, see the [`sandbox` module documentation][crate::sandbox].
+///
+/// The directory is removed when the `Sandbox` is dropped.
+#[derive(Debug)]
+pub struct Sandbox {
+    root: path::PathBuf,
+}
+
+impl Sandbox {
+    /// Create a home directory, with empty config, cache, and data directories.
+    pub fn new() -> Self {
+        let root = crate::temp::create_dir("assert_cmd-sandbox");
+        for (_, dir) in vars(&root) {
+            let _ = fs::create_dir_all(dir);
+        }
+        Self { root }
+    }
+
+    /// The home directory, for `HOME` (and `USERPROFILE` on Windows).
+    pub fn home(&self) -> &path::Path {
+        &self.root
+    }
+
+    /// The config directory: `XDG_CONFIG_HOME` on Unix, and `APPDATA` on Windows.
+    pub fn config_dir(&self) -> path::PathBuf {
+        if cfg!(windows) {
+            self.root.join("AppData").join("Roaming")
+        } else {
+            self.root.join(".config")
+        }
+    }
+
+    /// The cache directory: `XDG_CACHE_HOME` on Unix, and `LOCALAPPDATA` on Windows.
+    pub fn cache_dir(&self) -> path::PathBuf {
+        if cfg!(windows) {
+            self.root.join("AppData").join("Local")
+        } else {
+            self.root.join(".cache")
+        }
+    }
+
+    /// The data directory: `XDG_DATA_HOME` on Unix, and `APPDATA` on Windows.
+    pub fn data_dir(&self) -> path::PathBuf {
+        if cfg!(windows) {
+            self.root.join("AppData").join("Roaming")
+        } else {
+            self.root.join(".local").join("share")
+        }
+    }
+
+    pub(crate) fn vars(&self) -> Vec<(&'static str, path::PathBuf)> {
+        vars(&self.root)
+    }
+}
+
+impl Default for Sandbox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        crate::temp::remove_dir(&self.root);
+    }
+}
+
+/// The per-user directory variables for the home directory `root`.
+pub(crate) fn vars(root: &path::Path) -> Vec<(&'static str, path::PathBuf)> {
+    let mut vars = vec![("HOME", root.to_owned())];
+    if cfg!(windows) {
+        vars.push(("USERPROFILE", root.to_owned()));
+        vars.push(("APPDATA", root.join("AppData").join("Roaming")));
+        vars.push(("LOCALAPPDATA", root.join("AppData").join("Local")));
+    } else {
+        vars.push(("XDG_CACHE_HOME", root.join(".cache")));
+        vars.push(("XDG_CONFIG_HOME", root.join(".config")));
+        vars.push(("XDG_DATA_HOME", root.join(".local").join("share")));
+        vars.push(("XDG_STATE_HOME", root.join(".local").join("state")));
+    }
+    vars
+}
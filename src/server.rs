@@ -0,0 +1,176 @@
+//! Run a long-lived program, like a server, in the background of a test.
+//!
+//! [`Command::spawn_server`] starts it; wait for it to be ready, exercise it, and it is killed
+//! when the test is done, even if the test panics:
+//!
+//! ```rust,no_run
+//! use assert_cmd::Command;
+//! use predicates::prelude::*;
+//!
+//! use std::time::Duration;
+//!
+//! let mut cmd = Command::cargo_bin("bin_fixture").unwrap();
+//! cmd.env("stderr", "listening").env("sleep", "60");
+//! let mut server = cmd.spawn_server().unwrap();
+//! server
+//!     .ready_when_stderr(predicate::str::contains("listening"), Duration::from_secs(5))
+//!     .on_shutdown(|assert| assert.stderr(predicate::str::contains("panicked").not()));
+//!
+//! Command::cargo_bin("bin_fixture")
+//!     .unwrap()
+//!     .assert()
+//!     .success();
+//! ```
+//!
+//! [`Command::spawn_server`]: crate::cmd::Command::spawn_server()
+
+use std::fmt;
+use std::net;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::assert::Assert;
+use crate::assert::IntoOutputPredicate;
+use crate::interactive::AssertChild;
+
+/// How often [`Server::ready_when_listening`] tries to connect.
+const CONNECT_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A program running in the background, see the [`server` module documentation][crate::server].
+///
+/// When the `Server` is [shut down][Server::shutdown] or dropped, the program is killed and its
+/// output captured.  Dropped while the test is panicking, its output is printed to help explain
+/// the failure; otherwise any [`Server::on_shutdown`] check is run.
+pub struct Server<'a> {
+    session: Option<AssertChild<'a>>,
+    on_shutdown: Option<Box<dyn FnOnce(Assert) -> Assert + 'a>>,
+}
+
+impl<'a> Server<'a> {
+    pub(crate) fn new(session: AssertChild<'a>) -> Self {
+        Self {
+            session: Some(session),
+            on_shutdown: None,
+        }
+    }
+
+    /// Wait up to `timeout` for the program's `stdout` to match `pred`, like a "listening"
+    /// message.
+    ///
+    /// See [`AssertChild::expect_stdout`].
+    pub fn ready_when_stdout<I, P>(&mut self, pred: I, timeout: Duration) -> &mut Self
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        self.session().expect_stdout(pred, timeout);
+        self
+    }
+
+    /// Wait up to `timeout` for the program's `stderr` to match `pred`.
+    ///
+    /// See [`AssertChild::expect_stderr`].
+    pub fn ready_when_stderr<I, P>(&mut self, pred: I, timeout: Duration) -> &mut Self
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        self.session().expect_stderr(pred, timeout);
+        self
+    }
+
+    /// Wait up to `timeout` for `addr` to accept TCP connections.
+    ///
+    /// Fails if the program exits first.
+    pub fn ready_when_listening<A: net::ToSocketAddrs + fmt::Debug>(
+        &mut self,
+        addr: A,
+        timeout: Duration,
+    ) -> &mut Self {
+        let deadline = Instant::now() + timeout;
+        let session = self.session();
+        let addrs: Vec<_> = match addr.to_socket_addrs() {
+            Ok(addrs) => addrs.collect(),
+            Err(err) => session.fail(format_args!("Invalid address {:?}: {}", addr, err)),
+        };
+        loop {
+            let listening = addrs
+                .iter()
+                .any(|addr| net::TcpStream::connect_timeout(addr, CONNECT_INTERVAL).is_ok());
+            if listening {
+                return self;
+            }
+            if session.has_exited() {
+                session.fail(format_args!("Exited before listening on {:?}", addr));
+            }
+            if deadline <= Instant::now() {
+                session.fail(format_args!(
+                    "Timed out after {:?} waiting for a listener on {:?}",
+                    timeout, addr
+                ));
+            }
+            thread::sleep(CONNECT_INTERVAL);
+        }
+    }
+
+    /// Check the program's whole run when it is shut down.
+    ///
+    /// This isn't run if the `Server` is dropped while the test is panicking.
+    pub fn on_shutdown<F>(&mut self, check: F) -> &mut Self
+    where
+        F: FnOnce(Assert) -> Assert + 'a,
+    {
+        self.on_shutdown = Some(Box::new(check));
+        self
+    }
+
+    /// The running program, like to send it input.
+    pub fn session(&mut self) -> &mut AssertChild<'a> {
+        self.session
+            .as_mut()
+            .expect("server is running until shut down")
+    }
+
+    /// Kill the program and [`Assert`] on its whole run, after any
+    /// [`Server::on_shutdown`] check.
+    pub fn shutdown(mut self) -> Assert {
+        self.stop()
+    }
+
+    fn stop(&mut self) -> Assert {
+        let session = self.session.take().expect("server is shut down once");
+        let assert = session.kill();
+        match self.on_shutdown.take() {
+            Some(check) => check(assert),
+            None => assert,
+        }
+    }
+}
+
+impl Drop for Server<'_> {
+    fn drop(&mut self) {
+        match self.session {
+            // A failed expectation already reported the program's run.
+            Some(ref session) if session.is_finished() => {}
+            Some(_) if thread::panicking() => {
+                let assert = self.session.take().expect("checked above").kill();
+                eprintln!(
+                    "Background program, killed after the test panicked:\n{}",
+                    assert
+                );
+            }
+            Some(_) => {
+                self.stop();
+            }
+            None => {}
+        }
+    }
+}
+
+impl fmt::Debug for Server<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Server")
+            .field("session", &self.session)
+            .finish()
+    }
+}
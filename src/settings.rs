@@ -0,0 +1,467 @@
+//! Share filters and display settings across many [`Assert`]s.
+//!
+//! See [`Assert::with_settings`].
+//!
+//! [`Assert`]: crate::assert::Assert
+//! [`Assert::with_settings`]: crate::assert::Assert::with_settings()
+
+use std::borrow::Cow;
+use std::env;
+use std::ffi;
+use std::fmt;
+use std::sync::Arc;
+
+/// Set to show every stream in full in failure messages, as if by
+/// [`AssertSettings::full_output`].
+const FULL_OUTPUT_ENV: &str = "ASSERT_CMD_FULL_OUTPUT";
+
+type Filter = Arc<dyn Fn(&[u8]) -> Vec<u8> + Send + Sync>;
+
+/// Suite-wide normalization and display rules for [`Assert`].
+///
+/// Build this once, for example in a test helper, and apply it with [`Assert::with_settings`].
+///
+/// Filters rewrite `stdout` and `stderr`, in the order they were added, before any later
+/// assertion sees them.  They are also reflected in failure messages.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use assert_cmd::prelude::*;
+/// use assert_cmd::settings::AssertSettings;
+///
+/// use std::process::Command;
+///
+/// let settings = AssertSettings::new()
+///     .filter(r"\d+ms", "[DURATION]")
+///     .truncate(1024)
+///     .color(false);
+///
+/// Command::cargo_bin("bin_fixture")
+///     .unwrap()
+///     .env("stdout", "done in 15ms")
+///     .assert()
+///     .with_settings(&settings)
+///     .stdout("done in [DURATION]\n");
+/// ```
+///
+/// [`Assert`]: crate::assert::Assert
+/// [`Assert::with_settings`]: crate::assert::Assert::with_settings()
+#[derive(Clone)]
+pub struct AssertSettings {
+    filters: Vec<Filter>,
+    pub(crate) truncate: Option<usize>,
+    pub(crate) color: bool,
+}
+
+impl AssertSettings {
+    /// Settings that leave output untouched.
+    pub fn new() -> Self {
+        Self {
+            filters: Vec::new(),
+            truncate: None,
+            color: true,
+        }
+    }
+
+    /// Replace every match of the regular expression `pattern` with `replacement`.
+    ///
+    /// `replacement` may refer to capture groups, like `$1`.
+    ///
+    /// # Panics
+    ///
+    /// If `pattern` is not a valid regular expression.
+    pub fn filter(self, pattern: &str, replacement: &str) -> Self {
+        let re = regex::bytes::Regex::new(pattern)
+            .unwrap_or_else(|err| panic!("Invalid filter pattern: {}", err));
+        let replacement = replacement.to_owned();
+        self.normalize(move |data| re.replace_all(data, replacement.as_bytes()).into_owned())
+    }
+
+    /// Replace every occurrence of `value`, like the path of a temp dir, with `placeholder`.
+    ///
+    /// # Panics
+    ///
+    /// If `value` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    /// use assert_cmd::settings::AssertSettings;
+    ///
+    /// use std::process::Command;
+    ///
+    /// let temp = std::env::temp_dir();
+    /// let settings = AssertSettings::new().redact("[TEMPDIR]", &temp);
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", temp.join("out.txt"))
+    ///     .assert()
+    ///     .with_settings(&settings)
+    ///     .stdout(format!("[TEMPDIR]{}out.txt\n", std::path::MAIN_SEPARATOR));
+    /// ```
+    pub fn redact<V: AsRef<ffi::OsStr>>(self, placeholder: &str, value: V) -> Self {
+        use bstr::ByteSlice;
+        let value = value.as_ref().to_string_lossy().into_owned();
+        if value.is_empty() {
+            panic!("Empty value to redact as `{}`", placeholder);
+        }
+        let placeholder = placeholder.to_owned();
+        self.normalize(move |data| data.replace(value.as_bytes(), placeholder.as_bytes()))
+    }
+
+    /// Replace every match of the regular expression `pattern` with `placeholder`.
+    ///
+    /// Unlike [`AssertSettings::filter`], `placeholder` is inserted as-is, without expanding
+    /// capture groups.
+    ///
+    /// # Panics
+    ///
+    /// If `pattern` is not a valid regular expression.
+    pub fn redact_regex(self, placeholder: &str, pattern: &str) -> Self {
+        let re = regex::bytes::Regex::new(pattern)
+            .unwrap_or_else(|err| panic!("Invalid redaction pattern: {}", err));
+        let placeholder = placeholder.to_owned();
+        self.normalize(move |data| {
+            re.replace_all(data, regex::bytes::NoExpand(placeholder.as_bytes()))
+                .into_owned()
+        })
+    }
+
+    /// Rewrite output with `normalizer`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use assert_cmd::settings::AssertSettings;
+    ///
+    /// let settings = AssertSettings::new().normalize(|data| data.to_ascii_lowercase());
+    /// ```
+    pub fn normalize<F>(mut self, normalizer: F) -> Self
+    where
+        F: Fn(&[u8]) -> Vec<u8> + Send + Sync + 'static,
+    {
+        self.filters.push(Arc::new(normalizer));
+        self
+    }
+
+    /// Rewrite JSON output with sorted keys and consistent formatting.
+    ///
+    /// See [`json::canonicalize`].  Requires the `json` feature.
+    ///
+    /// [`json::canonicalize`]: crate::json::canonicalize()
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    /// use assert_cmd::settings::AssertSettings;
+    ///
+    /// use std::process::Command;
+    ///
+    /// let settings = AssertSettings::new().canonical_json();
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", r#"{"b":1,"a":2}"#)
+    ///     .assert()
+    ///     .with_settings(&settings)
+    ///     .matches_expected();
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn canonical_json(self) -> Self {
+        self.normalize(crate::json::canonicalize)
+    }
+
+    /// Rewrite `\r\n` line endings to `\n`, so expectations hold on every platform.
+    ///
+    /// See also [`Assert::stdout_normalized`].
+    ///
+    /// [`Assert::stdout_normalized`]: crate::assert::Assert::stdout_normalized()
+    pub fn normalize_line_endings(self) -> Self {
+        self.normalize(|data| crlf_to_lf(data).into_owned())
+    }
+
+    /// Remove ANSI escape sequences, like colors, from the output.
+    ///
+    /// Unlike [`AssertSettings::color`], this also affects assertions, for CLIs that colorize
+    /// output even when not writing to a terminal.
+    ///
+    /// See also [`Command::strip_ansi`].
+    ///
+    /// [`Command::strip_ansi`]: crate::cmd::Command::strip_ansi()
+    pub fn strip_ansi(self) -> Self {
+        self.normalize(|data| crate::ansi::strip(data).into_owned())
+    }
+
+    /// Show at most `max_bytes` of each stream in failure messages.
+    ///
+    /// The start and end of the output are kept, around a note of how much was omitted.  By
+    /// default, streams of 8 KiB or more are cut down to their first and last 2 KiB.
+    pub fn truncate(mut self, max_bytes: usize) -> Self {
+        self.truncate = Some(max_bytes);
+        self
+    }
+
+    /// Show each stream in full in failure messages, however large.
+    ///
+    /// Set `ASSERT_CMD_FULL_OUTPUT=1` to do this for every failure without code changes.
+    pub fn full_output(self) -> Self {
+        self.truncate(usize::MAX)
+    }
+
+    /// How much of each stream failure messages show, if not the default.
+    pub(crate) fn limit(&self) -> Option<usize> {
+        match env::var_os(FULL_OUTPUT_ENV) {
+            Some(v) if !v.is_empty() && v != "0" => Some(usize::MAX),
+            _ => self.truncate,
+        }
+    }
+
+    /// Whether failure messages keep ANSI escape sequences, like colors, from the output.
+    ///
+    /// Defaults to `true`.
+    pub fn color(mut self, yes: bool) -> Self {
+        self.color = yes;
+        self
+    }
+
+    pub(crate) fn apply(&self, data: Vec<u8>) -> Vec<u8> {
+        self.filters.iter().fold(data, |data, filter| filter(&data))
+    }
+
+    pub(crate) fn display<'a>(&self, data: &'a [u8]) -> Cow<'a, [u8]> {
+        if self.color {
+            Cow::Borrowed(data)
+        } else {
+            crate::ansi::strip(data)
+        }
+    }
+}
+
+/// `data` with `\r\n` line endings rewritten to `\n`.
+pub(crate) fn crlf_to_lf(data: &[u8]) -> Cow<'_, [u8]> {
+    use bstr::ByteSlice;
+    if data.find(b"\r\n").is_some() {
+        Cow::Owned(data.replace(b"\r\n", b"\n"))
+    } else {
+        Cow::Borrowed(data)
+    }
+}
+
+impl Default for AssertSettings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for AssertSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AssertSettings")
+            .field("filters", &self.filters.len())
+            .field("truncate", &self.truncate)
+            .field("color", &self.color)
+            .finish()
+    }
+}
+
+/// Insignificant differences to overlook when comparing text, per assertion.
+///
+/// Both the expected and the actual text are normalized before comparing, see
+/// [`Assert::stdout_tolerant`] and [`Assert::stdout_matches_path_tolerant`].
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use assert_cmd::settings::Tolerance;
+/// use assert_cmd::Command;
+///
+/// Command::cargo_bin("bin_fixture")
+///     .unwrap()
+///     .env("stdout", "a  \n\n\nb")
+///     .assert()
+///     .stdout_tolerant(
+///         "a\n\nb",
+///         Tolerance::new().trailing_whitespace().blank_lines().final_newline(),
+///     );
+/// ```
+///
+/// [`Assert::stdout_tolerant`]: crate::assert::Assert::stdout_tolerant()
+/// [`Assert::stdout_matches_path_tolerant`]: crate::assert::Assert::stdout_matches_path_tolerant()
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Tolerance {
+    trailing_whitespace: bool,
+    final_newline: bool,
+    blank_lines: bool,
+}
+
+impl Tolerance {
+    /// Overlook nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Overlook spaces and tabs at the end of each line.
+    pub fn trailing_whitespace(mut self) -> Self {
+        self.trailing_whitespace = true;
+        self
+    }
+
+    /// Overlook whether the text ends with a newline, or with several.
+    pub fn final_newline(mut self) -> Self {
+        self.final_newline = true;
+        self
+    }
+
+    /// Treat each run of blank lines as one blank line.
+    pub fn blank_lines(mut self) -> Self {
+        self.blank_lines = true;
+        self
+    }
+
+    /// `data` with the overlooked differences normalized away.
+    pub fn apply(&self, data: &[u8]) -> Vec<u8> {
+        let ends_with_newline = data.ends_with(b"\n");
+        let data = data.strip_suffix(b"\n").unwrap_or(data);
+        let mut lines: Vec<&[u8]> = Vec::new();
+        if !data.is_empty() || ends_with_newline {
+            for line in data.split(|b| *b == b'\n') {
+                let line = if self.trailing_whitespace {
+                    let end = line
+                        .iter()
+                        .rposition(|b| *b != b' ' && *b != b'\t')
+                        .map_or(0, |i| i + 1);
+                    &line[..end]
+                } else {
+                    line
+                };
+                let repeated_blank =
+                    line.is_empty() && lines.last().map_or(false, |l| l.is_empty());
+                if !(self.blank_lines && repeated_blank) {
+                    lines.push(line);
+                }
+            }
+        }
+        let mut out = lines.join(&b'\n');
+        if self.final_newline {
+            while out.last() == Some(&b'\n') {
+                out.pop();
+            }
+            if !out.is_empty() {
+                out.push(b'\n');
+            }
+        } else if ends_with_newline {
+            out.push(b'\n');
+        }
+        out
+    }
+}
+
+impl fmt::Display for Tolerance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = [
+            (self.trailing_whitespace, "trailing whitespace"),
+            (self.final_newline, "final newline"),
+            (self.blank_lines, "blank lines"),
+        ];
+        let names: Vec<_> = names
+            .iter()
+            .filter(|(on, _)| *on)
+            .map(|(_, name)| *name)
+            .collect();
+        if names.is_empty() {
+            f.write_str("nothing")
+        } else {
+            f.write_str(&names.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tolerance() {
+        let all = Tolerance::new()
+            .trailing_whitespace()
+            .final_newline()
+            .blank_lines();
+        assert_eq!(all.apply(b"a \t\n  \n\n\nb\n\n"), b"a\n\nb\n");
+        assert_eq!(all.apply(b"a\n\nb"), b"a\n\nb\n");
+        assert_eq!(all.apply(b""), b"");
+        assert_eq!(all.apply(b"\n\n"), b"");
+        let none = Tolerance::new();
+        assert_eq!(none.apply(b"a \n\n\nb"), b"a \n\n\nb");
+        assert_eq!(none.apply(b"\n"), b"\n");
+        assert_eq!(Tolerance::new().blank_lines().apply(b"a\n\n\n"), b"a\n\n");
+        assert_eq!(
+            all.to_string(),
+            "trailing whitespace, final newline, blank lines"
+        );
+    }
+
+    #[test]
+    fn filters_apply_in_order() {
+        let settings = AssertSettings::new()
+            .filter(r"\d+", "N")
+            .filter("N+", "[NUM]");
+        assert_eq!(
+            settings.apply(b"took 15ms, 3 retries".to_vec()),
+            b"took [NUM]ms, [NUM] retries"
+        );
+    }
+
+    #[test]
+    fn redactions() {
+        let settings = AssertSettings::new()
+            .redact("[TEMPDIR]", "/tmp/x")
+            .redact_regex("[$1]", r"\d+ms");
+        assert_eq!(
+            settings.apply(b"wrote /tmp/x/out in 15ms".to_vec()),
+            b"wrote [TEMPDIR]/out in [$1]"
+        );
+    }
+
+    #[test]
+    fn strips_ansi() {
+        let settings = AssertSettings::new().strip_ansi();
+        assert_eq!(
+            settings.apply(b"\x1b[1;31merror\x1b[0m: x".to_vec()),
+            b"error: x"
+        );
+    }
+
+    #[test]
+    fn display_strips_color() {
+        let settings = AssertSettings::new().color(false);
+        assert_eq!(settings.display(b"\x1b[31mred\x1b[0m").as_ref(), b"red");
+    }
+
+    #[test]
+    fn full_output() {
+        assert_eq!(AssertSettings::new().truncate(10).truncate, Some(10));
+        assert_eq!(
+            AssertSettings::new().truncate(10).full_output().truncate,
+            Some(usize::MAX)
+        );
+    }
+
+    #[test]
+    fn line_endings() {
+        let settings = AssertSettings::new().normalize_line_endings();
+        assert_eq!(settings.apply(b"a\r\nb\rc\n".to_vec()), b"a\nb\rc\n");
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn canonical_json() {
+        let settings = AssertSettings::new().canonical_json();
+        assert_eq!(
+            settings.apply(b"{\"b\":1,\"a\":2}".to_vec()),
+            b"{\n  \"a\": 2,\n  \"b\": 1\n}\n".to_vec()
+        );
+    }
+}
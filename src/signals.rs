@@ -0,0 +1,246 @@
+//! Verify the signal mask and dispositions that reached the child.
+//!
+//! Test harnesses can leave signals blocked or ignored, which children inherit, changing how a
+//! CLI reacts to `SIGPIPE`, `SIGINT`, etc.  [`Command::reset_signals`] restores the defaults
+//! before `exec`.
+//!
+//! To verify what the child actually got, enable [`Command::signal_dump`] and call [`dump`] early
+//! in the child, like with [`env_dump`][crate::env_dump].  The state is then available via
+//! [`Assert::child_signals_default`] and [`Assert::child_signals`].
+//!
+//! # Examples
+//!
+//! In the program under test:
+//! ```rust
+//! // Early in `main`
+//! #[cfg(unix)]
+//! assert_cmd::signals::dump().unwrap();
+//! ```
+//!
+//! In the test:
+//! ```rust,no_run
+//! use assert_cmd::Command;
+//!
+//! Command::cargo_bin("bin_fixture")
+//!     .unwrap()
+//!     .reset_signals()
+//!     .signal_dump(true)
+//!     .assert()
+//!     .child_signals_default();
+//! ```
+//!
+//! [`Command::reset_signals`]: crate::cmd::Command::reset_signals()
+//! [`Command::signal_dump`]: crate::cmd::Command::signal_dump()
+//! [`Assert::child_signals_default`]: crate::assert::Assert::child_signals_default()
+//! [`Assert::child_signals`]: crate::assert::Assert::child_signals()
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::mem;
+use std::path;
+use std::ptr;
+
+/// Environment variable holding the path the child should dump its signal state to.
+pub const ENV_VAR: &str = "ASSERT_CMD_SIGNAL_DUMP";
+
+/// The standard (non-realtime) signals.
+const SIGNALS: std::ops::Range<libc::c_int> = 1..32;
+
+/// Which signals a process had blocked or ignored.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SignalState {
+    /// Signals in the process's signal mask.
+    pub blocked: Vec<i32>,
+    /// Signals whose disposition is `SIG_IGN`.
+    pub ignored: Vec<i32>,
+}
+
+impl SignalState {
+    /// The current process's signal state.
+    pub fn current() -> Self {
+        let mut state = Self::default();
+        // SAFETY: The out-parameters are valid and queries don't change the process's state.
+        unsafe {
+            let mut mask: libc::sigset_t = mem::zeroed();
+            libc::sigemptyset(&mut mask);
+            libc::pthread_sigmask(libc::SIG_BLOCK, ptr::null(), &mut mask);
+            for signal in SIGNALS {
+                if libc::sigismember(&mask, signal) == 1 {
+                    state.blocked.push(signal);
+                }
+                let mut action: libc::sigaction = mem::zeroed();
+                if libc::sigaction(signal, ptr::null(), &mut action) == 0
+                    && action.sa_sigaction == libc::SIG_IGN
+                {
+                    state.ignored.push(signal);
+                }
+            }
+        }
+        state
+    }
+
+    /// Whether no signals are blocked or ignored.
+    pub fn is_default(&self) -> bool {
+        self.blocked.is_empty() && self.ignored.is_empty()
+    }
+
+    fn parse(data: &str) -> Self {
+        let mut state = Self::default();
+        for line in data.lines() {
+            let (name, signals) = match line.split_once(':') {
+                Some(split) => split,
+                None => continue,
+            };
+            let signals = signals
+                .split_whitespace()
+                .filter_map(|s| s.parse().ok())
+                .collect();
+            match name {
+                "blocked" => state.blocked = signals,
+                "ignored" => state.ignored = signals,
+                _ => {}
+            }
+        }
+        state
+    }
+}
+
+impl fmt::Display for SignalState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |signals: &[i32]| -> String {
+            signals
+                .iter()
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        writeln!(f, "blocked: {}", join(&self.blocked))?;
+        writeln!(f, "ignored: {}", join(&self.ignored))
+    }
+}
+
+/// The name of `signal`, like `SIGSEGV`, for the signals POSIX defines.
+pub fn name(signal: i32) -> Option<&'static str> {
+    let names = [
+        (libc::SIGHUP, "SIGHUP"),
+        (libc::SIGINT, "SIGINT"),
+        (libc::SIGQUIT, "SIGQUIT"),
+        (libc::SIGILL, "SIGILL"),
+        (libc::SIGTRAP, "SIGTRAP"),
+        (libc::SIGABRT, "SIGABRT"),
+        (libc::SIGBUS, "SIGBUS"),
+        (libc::SIGFPE, "SIGFPE"),
+        (libc::SIGKILL, "SIGKILL"),
+        (libc::SIGUSR1, "SIGUSR1"),
+        (libc::SIGSEGV, "SIGSEGV"),
+        (libc::SIGUSR2, "SIGUSR2"),
+        (libc::SIGPIPE, "SIGPIPE"),
+        (libc::SIGALRM, "SIGALRM"),
+        (libc::SIGTERM, "SIGTERM"),
+        (libc::SIGCHLD, "SIGCHLD"),
+        (libc::SIGCONT, "SIGCONT"),
+        (libc::SIGSTOP, "SIGSTOP"),
+        (libc::SIGTSTP, "SIGTSTP"),
+        (libc::SIGTTIN, "SIGTTIN"),
+        (libc::SIGTTOU, "SIGTTOU"),
+        (libc::SIGURG, "SIGURG"),
+        (libc::SIGXCPU, "SIGXCPU"),
+        (libc::SIGXFSZ, "SIGXFSZ"),
+        (libc::SIGVTALRM, "SIGVTALRM"),
+        (libc::SIGPROF, "SIGPROF"),
+        (libc::SIGWINCH, "SIGWINCH"),
+        (libc::SIGSYS, "SIGSYS"),
+    ];
+    names
+        .iter()
+        .find(|(number, _)| *number == signal)
+        .map(|(_, name)| *name)
+}
+
+/// `signal` for people, like `SIGSEGV (11)`.
+pub(crate) fn describe(signal: i32) -> String {
+    match name(signal) {
+        Some(name) => format!("{} ({})", name, signal),
+        None => format!("signal {}", signal),
+    }
+}
+
+/// Dump the current process's signal state, if requested by [`ENV_VAR`].
+///
+/// The Rust runtime ignores `SIGPIPE` before `main` runs, so whether it was inherited can't be
+/// known and it is left out of the dump.
+///
+/// Returns whether a dump was written.
+pub fn dump() -> io::Result<bool> {
+    let path = match env::var_os(ENV_VAR) {
+        Some(path) => path,
+        None => return Ok(false),
+    };
+    let mut state = SignalState::current();
+    state.ignored.retain(|signal| *signal != libc::SIGPIPE);
+    fs::write(path, state.to_string())?;
+    Ok(true)
+}
+
+/// Reset the calling process's signal mask and dispositions to the defaults.
+///
+/// This is async-signal-safe, for use between `fork` and `exec`.
+pub(crate) fn reset() -> io::Result<()> {
+    // SAFETY: `sigemptyset`, `sigprocmask`, and `signal` are async-signal-safe.
+    unsafe {
+        let mut mask: libc::sigset_t = mem::zeroed();
+        libc::sigemptyset(&mut mask);
+        if libc::sigprocmask(libc::SIG_SETMASK, &mask, ptr::null_mut()) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        for signal in SIGNALS {
+            if signal != libc::SIGKILL && signal != libc::SIGSTOP {
+                // Not every number is a valid signal on every platform; those just fail.
+                libc::signal(signal, libc::SIG_DFL);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Read and remove the dump written by [`dump`].
+pub(crate) fn take(path: &path::Path) -> Option<SignalState> {
+    let data = fs::read_to_string(path).ok()?;
+    let _ = fs::remove_file(path);
+    Some(SignalState::parse(&data))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let state = SignalState {
+            blocked: vec![10, 13],
+            ignored: vec![],
+        };
+        assert_eq!(SignalState::parse(&state.to_string()), state);
+        assert_eq!(state.to_string(), "blocked: 10 13\nignored: \n");
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(name(libc::SIGSEGV), Some("SIGSEGV"));
+        assert_eq!(name(0), None);
+        assert_eq!(describe(libc::SIGKILL), "SIGKILL (9)");
+        assert_eq!(describe(0), "signal 0");
+    }
+
+    #[test]
+    fn take_removes_dump() {
+        let path = crate::temp::unique_path("assert_cmd-signals");
+        fs::write(&path, "blocked: 13\nignored: 1\n").unwrap();
+        let state = take(&path).unwrap();
+        assert_eq!(state.blocked, vec![13]);
+        assert_eq!(state.ignored, vec![1]);
+        assert!(!path.exists());
+    }
+}
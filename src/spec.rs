@@ -0,0 +1,207 @@
+//! Share a base invocation across many tests.
+//!
+//! [`std::process::Command`] can't be cloned, so a [`CommandSpec`] holds the program, common
+//! arguments, environment, working directory, and `stdin`, and stamps out a fresh [`Command`]
+//! for each test to add to:
+//!
+//! ```rust,no_run
+//! use assert_cmd::spec::CommandSpec;
+//!
+//! fn base() -> CommandSpec {
+//!     let mut spec = CommandSpec::cargo_bin("bin_fixture").unwrap();
+//!     spec.arg("--color=never").env("LANG", "C");
+//!     spec
+//! }
+//!
+//! base().command().arg("--version").assert().success();
+//!
+//! let mut quiet = base();
+//! quiet.arg("--quiet");
+//! quiet.assert().success().stdout("");
+//! ```
+//!
+//! [`Command`]: crate::cmd::Command
+
+use std::ffi;
+use std::path;
+use std::process;
+use std::time::Duration;
+
+use crate::assert::Assert;
+use crate::cmd::Command;
+
+/// A cloneable template for [`Command`]s, see the [`spec` module documentation][crate::spec].
+///
+/// [`Command`]: crate::cmd::Command
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommandSpec {
+    program: ffi::OsString,
+    args: Vec<ffi::OsString>,
+    env: Vec<(ffi::OsString, Option<ffi::OsString>)>,
+    env_cleared: bool,
+    cwd: Option<path::PathBuf>,
+    stdin: Option<Vec<u8>>,
+    timeout: Option<Duration>,
+}
+
+impl CommandSpec {
+    /// Run `program`.
+    pub fn new<S: AsRef<ffi::OsStr>>(program: S) -> Self {
+        Self {
+            program: program.as_ref().to_owned(),
+            args: Vec::new(),
+            env: Vec::new(),
+            env_cleared: false,
+            cwd: None,
+            stdin: None,
+            timeout: None,
+        }
+    }
+
+    /// Run a binary of the current crate, like [`Command::cargo_bin`].
+    ///
+    /// [`Command::cargo_bin`]: crate::cmd::Command::cargo_bin()
+    pub fn cargo_bin<S: AsRef<str>>(name: S) -> Result<Self, crate::cargo::CargoError> {
+        let cmd = crate::cargo::cargo_bin_cmd(name)?;
+        Ok(Self::from_std(&cmd))
+    }
+
+    /// Capture `cmd`'s program, arguments, environment changes, and working directory.
+    fn from_std(cmd: &process::Command) -> Self {
+        let mut spec = Self::new(cmd.get_program());
+        spec.args = cmd.get_args().map(|arg| arg.to_owned()).collect();
+        spec.env = cmd
+            .get_envs()
+            .map(|(key, value)| (key.to_owned(), value.map(|value| value.to_owned())))
+            .collect();
+        spec.cwd = cmd.get_current_dir().map(|cwd| cwd.to_owned());
+        spec
+    }
+
+    /// Add an argument, see [`Command::arg`].
+    ///
+    /// [`Command::arg`]: crate::cmd::Command::arg()
+    pub fn arg<S: AsRef<ffi::OsStr>>(&mut self, arg: S) -> &mut Self {
+        self.args.push(arg.as_ref().to_owned());
+        self
+    }
+
+    /// Add arguments, see [`Command::args`].
+    ///
+    /// [`Command::args`]: crate::cmd::Command::args()
+    pub fn args<I, S>(&mut self, args: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<ffi::OsStr>,
+    {
+        self.args
+            .extend(args.into_iter().map(|arg| arg.as_ref().to_owned()));
+        self
+    }
+
+    /// Set an environment variable, see [`Command::env`].
+    ///
+    /// [`Command::env`]: crate::cmd::Command::env()
+    pub fn env<K, V>(&mut self, key: K, val: V) -> &mut Self
+    where
+        K: AsRef<ffi::OsStr>,
+        V: AsRef<ffi::OsStr>,
+    {
+        self.env
+            .push((key.as_ref().to_owned(), Some(val.as_ref().to_owned())));
+        self
+    }
+
+    /// Set environment variables, see [`Command::envs`].
+    ///
+    /// [`Command::envs`]: crate::cmd::Command::envs()
+    pub fn envs<I, K, V>(&mut self, vars: I) -> &mut Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<ffi::OsStr>,
+        V: AsRef<ffi::OsStr>,
+    {
+        for (key, val) in vars {
+            self.env(key, val);
+        }
+        self
+    }
+
+    /// Remove an environment variable, see [`Command::env_remove`].
+    ///
+    /// [`Command::env_remove`]: crate::cmd::Command::env_remove()
+    pub fn env_remove<K: AsRef<ffi::OsStr>>(&mut self, key: K) -> &mut Self {
+        self.env.push((key.as_ref().to_owned(), None));
+        self
+    }
+
+    /// Clear the environment, including variables set so far, see [`Command::env_clear`].
+    ///
+    /// [`Command::env_clear`]: crate::cmd::Command::env_clear()
+    pub fn env_clear(&mut self) -> &mut Self {
+        self.env.clear();
+        self.env_cleared = true;
+        self
+    }
+
+    /// Set the working directory, see [`Command::current_dir`].
+    ///
+    /// [`Command::current_dir`]: crate::cmd::Command::current_dir()
+    pub fn current_dir<P: AsRef<path::Path>>(&mut self, dir: P) -> &mut Self {
+        self.cwd = Some(dir.as_ref().to_owned());
+        self
+    }
+
+    /// Write `buffer` to `stdin`, see [`Command::write_stdin`].
+    ///
+    /// [`Command::write_stdin`]: crate::cmd::Command::write_stdin()
+    pub fn write_stdin<S>(&mut self, buffer: S) -> &mut Self
+    where
+        S: Into<Vec<u8>>,
+    {
+        self.stdin = Some(buffer.into());
+        self
+    }
+
+    /// Limit how long each run may take, see [`Command::timeout`].
+    ///
+    /// [`Command::timeout`]: crate::cmd::Command::timeout()
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// A fresh [`Command`] from this template, for further configuration.
+    ///
+    /// [`Command`]: crate::cmd::Command
+    pub fn command(&self) -> Command {
+        let mut cmd = Command::new(&self.program);
+        cmd.args(&self.args);
+        if self.env_cleared {
+            cmd.env_clear();
+        }
+        for (key, val) in &self.env {
+            match val {
+                Some(val) => cmd.env(key, val),
+                None => cmd.env_remove(key),
+            };
+        }
+        if let Some(cwd) = &self.cwd {
+            cmd.current_dir(cwd);
+        }
+        if let Some(stdin) = &self.stdin {
+            cmd.write_stdin(stdin.clone());
+        }
+        if let Some(timeout) = self.timeout {
+            cmd.timeout(timeout);
+        }
+        cmd
+    }
+
+    /// Run a fresh [`Command`] from this template, see [`CommandSpec::command`].
+    ///
+    /// [`Command`]: crate::cmd::Command
+    pub fn assert(&self) -> Assert {
+        self.command().assert()
+    }
+}
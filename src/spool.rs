@@ -0,0 +1,228 @@
+//! Output written to files rather than memory, see [`Command::capture_to_files`].
+//!
+//! [`Command::capture_to_files`]: crate::cmd::Command::capture_to_files()
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::io::{Read, Seek};
+use std::path;
+
+/// How much of each end of a file to show on failure.
+const EXCERPT_BYTES: u64 = 2048;
+
+/// The files the child's output was written to, removed once the last [`Assert`] using them is
+/// dropped.
+///
+/// [`Assert`]: crate::assert::Assert
+#[derive(Debug)]
+pub(crate) struct Spool {
+    dir: crate::temp::TempDir,
+}
+
+impl Spool {
+    pub(crate) fn new() -> Self {
+        Self {
+            dir: crate::temp::TempDir::new("assert_cmd-output"),
+        }
+    }
+
+    /// The file for `stream`, `stdout` or `stderr`.
+    pub(crate) fn path(&self, stream: &str) -> path::PathBuf {
+        self.dir.path().join(stream)
+    }
+
+    /// Create the file for `stream`, for the child to write to.
+    pub(crate) fn create(&self, stream: &str) -> io::Result<fs::File> {
+        fs::File::create(self.path(stream))
+    }
+
+    /// What to show of `stream` on failure.
+    pub(crate) fn excerpt(&self, stream: &str) -> Excerpt {
+        let path = self.path(stream);
+        let read = || -> io::Result<Contents> {
+            let mut file = fs::File::open(&path)?;
+            let len = file.metadata()?.len();
+            let mut start = Vec::new();
+            (&mut file).take(EXCERPT_BYTES).read_to_end(&mut start)?;
+            let end = if EXCERPT_BYTES * 2 < len {
+                let mut end = Vec::new();
+                file.seek(io::SeekFrom::End(-(EXCERPT_BYTES as i64)))?;
+                file.read_to_end(&mut end)?;
+                Some(end)
+            } else {
+                file.read_to_end(&mut start)?;
+                None
+            };
+            Ok(Contents { len, start, end })
+        };
+        Excerpt {
+            contents: read().map_err(|err| err.to_string()),
+            path,
+        }
+    }
+}
+
+/// The start and end of a [`Spool`] file.
+#[derive(Debug)]
+pub(crate) struct Excerpt {
+    path: path::PathBuf,
+    contents: Result<Contents, String>,
+}
+
+#[derive(Debug)]
+struct Contents {
+    len: u64,
+    start: Vec<u8>,
+    /// The end, when not all shown in `start`.
+    end: Option<Vec<u8>>,
+}
+
+impl fmt::Display for Excerpt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.contents {
+            Ok(Contents {
+                len,
+                start,
+                end: None,
+            }) => write!(
+                f,
+                "{} ({} bytes): {:?}",
+                self.path.display(),
+                len,
+                String::from_utf8_lossy(start)
+            ),
+            Ok(Contents {
+                len,
+                start,
+                end: Some(end),
+            }) => write!(
+                f,
+                "{} ({} bytes): {:?}...<{} bytes omitted>...{:?}",
+                self.path.display(),
+                len,
+                String::from_utf8_lossy(start),
+                *len - start.len() as u64 - end.len() as u64,
+                String::from_utf8_lossy(end)
+            ),
+            Err(err) => write!(f, "{} (unreadable: {})", self.path.display(), err),
+        }
+    }
+}
+
+/// The SHA-256 of everything `input` reads, in lowercase hex.
+pub(crate) fn sha256(mut input: impl Read) -> io::Result<String> {
+    let mut state = SHA256_INIT;
+    let mut block = [0; 64];
+    let mut filled = 0;
+    let mut total: u64 = 0;
+    loop {
+        let len = match input.read(&mut block[filled..]) {
+            Ok(0) => break,
+            Ok(len) => len,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        filled += len;
+        total += len as u64;
+        if filled == block.len() {
+            sha256_block(&mut state, &block);
+            filled = 0;
+        }
+    }
+    // Pad with a 1 bit, zeros, and the length in bits, into one or two final blocks.
+    block[filled] = 0x80;
+    block[filled + 1..].fill(0);
+    if 56 <= filled {
+        sha256_block(&mut state, &block);
+        block = [0; 64];
+    }
+    block[56..].copy_from_slice(&(total * 8).to_be_bytes());
+    sha256_block(&mut state, &block);
+    Ok(state.iter().map(|word| format!("{:08x}", word)).collect())
+}
+
+const SHA256_INIT: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+fn sha256_block(state: &mut [u32; 8], block: &[u8; 64]) {
+    let mut w = [0u32; 64];
+    for (i, word) in block.chunks_exact(4).enumerate() {
+        w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+    }
+    for i in 16..64 {
+        let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+        let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+        w[i] = w[i - 16]
+            .wrapping_add(s0)
+            .wrapping_add(w[i - 7])
+            .wrapping_add(s1);
+    }
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for i in 0..64 {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = h
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(SHA256_K[i])
+            .wrapping_add(w[i]);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(maj);
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+    for (word, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *word = word.wrapping_add(value);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn digests() {
+        let cases: [(&[u8], &str); 3] = [
+            (
+                b"",
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            ),
+            (
+                b"abc",
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            ),
+            (
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+            ),
+        ];
+        for (data, expected) in cases.iter() {
+            assert_eq!(sha256(*data).unwrap(), *expected, "{:?}", data);
+        }
+        let million = io::repeat(b'a').take(1_000_000);
+        assert_eq!(
+            sha256(million).unwrap(),
+            "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! Scratch locations for communicating with and sandboxing the child.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::path;
+use std::process;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// A path within the system temp dir that is unique to this process and call.
+///
+/// The current test's name is included so leftovers are easy to correlate with the test that
+/// created them.  Nothing is created on disk.
+pub(crate) fn unique_path(prefix: &str) -> path::PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let name = match namespace() {
+        Some(test) => format!("{}-{}-{}-{}", prefix, test, process::id(), count),
+        None => format!("{}-{}-{}", prefix, process::id(), count),
+    };
+    env::temp_dir().join(name)
+}
+
+/// Create a fresh, empty directory for the child to use as its temp dir.
+pub(crate) fn create_dir(prefix: &str) -> path::PathBuf {
+    let path = unique_path(prefix);
+    // If this fails, the child will report it more clearly than we could.
+    let _ = fs::create_dir_all(&path);
+    path
+}
+
+/// A directory removed once the last [`Command`] or [`Assert`] using it is dropped, unless kept
+/// on failure.
+///
+/// [`Command`]: crate::cmd::Command
+/// [`Assert`]: crate::assert::Assert
+#[derive(Debug)]
+pub(crate) struct TempDir {
+    path: path::PathBuf,
+    kept: AtomicBool,
+}
+
+impl TempDir {
+    pub(crate) fn new(prefix: &str) -> Self {
+        Self {
+            path: create_dir(prefix),
+            kept: AtomicBool::new(false),
+        }
+    }
+
+    pub(crate) fn path(&self) -> &path::Path {
+        &self.path
+    }
+
+    /// Leave the directory behind for debugging.
+    pub(crate) fn keep(&self) {
+        self.kept.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        if !self.kept.load(Ordering::Relaxed) {
+            remove_dir(&self.path);
+        }
+    }
+}
+
+/// List everything left in `dir`, relative to it, and then remove it.
+pub(crate) fn take_dir(dir: &path::Path) -> Vec<path::PathBuf> {
+    fn walk(root: &path::Path, dir: &path::Path, found: &mut Vec<path::PathBuf>) {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path();
+            found.push(path.strip_prefix(root).unwrap_or(&path).to_owned());
+            if entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+                walk(root, &path, found);
+            }
+        }
+    }
+
+    let mut found = Vec::new();
+    walk(dir, dir, &mut found);
+    found.sort();
+    remove_dir(dir);
+    found
+}
+
+/// Remove `dir` and its contents, tolerating files that are still locked.
+///
+/// On Windows, a just-exited child's files can stay locked for a moment, e.g. by antivirus or a
+/// delayed handle close, so removal is retried with backoff.  If that still fails, a warning is
+/// printed and the directory is left behind rather than failing the test.
+pub(crate) fn remove_dir(dir: &path::Path) {
+    let retries = if cfg!(windows) { 5 } else { 0 };
+    let mut delay = Duration::from_millis(10);
+    let mut attempt = 0;
+    loop {
+        match fs::remove_dir_all(dir) {
+            Ok(()) => return,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return,
+            Err(_) if attempt < retries => {
+                thread::sleep(delay);
+                delay *= 2;
+                attempt += 1;
+            }
+            Err(err) => {
+                let _ = writeln!(
+                    io::stderr(),
+                    "assert_cmd: leaving `{}` behind, couldn't remove it: {}",
+                    dir.display(),
+                    err
+                );
+                return;
+            }
+        }
+    }
+}
+
+/// A file-name-safe label for the current test, derived from the thread name.
+///
+/// The test harness names each test's thread after the test, e.g. `tests::cli::help`.
+pub(crate) fn namespace() -> Option<String> {
+    let thread = thread::current();
+    let name = thread.name()?;
+    if name == "main" {
+        return None;
+    }
+    Some(sanitize(name))
+}
+
+fn sanitize(name: &str) -> String {
+    // Keep paths well under common `PATH_MAX` / `MAX_PATH` limits.
+    const MAX_LEN: usize = 64;
+
+    let name = name.replace("::", "-");
+    let sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    // Keep the end, where the test function's name is.
+    let skip = sanitized.len().saturating_sub(MAX_LEN);
+    sanitized[skip..].to_owned()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn namespace_from_thread_name() {
+        thread::Builder::new()
+            .name("suite::case".to_owned())
+            .spawn(|| {
+                assert_eq!(namespace().as_deref(), Some("suite-case"));
+                let path = unique_path("prefix");
+                let name = path.file_name().unwrap().to_str().unwrap();
+                assert!(name.starts_with("prefix-suite-case-"));
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn leftovers() {
+        let dir = create_dir("assert_cmd-test");
+        fs::create_dir(dir.join("sub")).unwrap();
+        fs::write(dir.join("sub").join("file"), "").unwrap();
+        assert_eq!(
+            take_dir(&dir),
+            vec![
+                path::PathBuf::from("sub"),
+                path::Path::new("sub").join("file")
+            ]
+        );
+        assert!(!dir.exists());
+        assert!(take_dir(&dir).is_empty());
+        remove_dir(&dir);
+    }
+
+    #[test]
+    fn temp_dir_kept() {
+        let removed = TempDir::new("assert_cmd-test");
+        let path = removed.path().to_owned();
+        assert!(path.is_dir());
+        drop(removed);
+        assert!(!path.exists());
+
+        let kept = TempDir::new("assert_cmd-test");
+        let path = kept.path().to_owned();
+        kept.keep();
+        drop(kept);
+        assert!(path.is_dir());
+        remove_dir(&path);
+    }
+
+    #[test]
+    fn sanitize_names() {
+        assert_eq!(sanitize("cli::help <long>"), "cli-help__long_");
+        assert_eq!(sanitize(&"a".repeat(100)).len(), 64);
+    }
+}
@@ -0,0 +1,183 @@
+//! Find the invocations that make a test slow.
+//!
+//! Once enabled, every command run through this crate is timed.  When a test's thread exits
+//! after spending more than the threshold in commands, a summary, slowest commands first, is
+//! printed to stderr:
+//!
+//! ```text
+//! assert_cmd: `cli::import` spent 3.2s in 4 commands
+//!   2.1s `"tool" "import" "big.csv"`
+//!   ...
+//! ```
+//!
+//! Enable it with [`report_slower_than`] or by setting `ASSERT_CMD_TIMING_REPORT` to the
+//! threshold in seconds.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use assert_cmd::Command;
+//!
+//! assert_cmd::timing::report_slower_than(std::time::Duration::from_secs(2));
+//!
+//! Command::cargo_bin("bin_fixture").unwrap().assert().success();
+//! let timings = assert_cmd::timing::timings();
+//! assert_eq!(timings.len(), 1);
+//! ```
+
+use std::cell::RefCell;
+use std::env;
+use std::fmt;
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// Set to the threshold, in seconds, to enable reports without code changes.
+pub const ENV_VAR: &str = "ASSERT_CMD_TIMING_REPORT";
+
+/// The most commands listed in a summary.
+const MAX_LISTED: usize = 10;
+
+const UNCONFIGURED: u64 = u64::MAX;
+
+static THRESHOLD: AtomicU64 = AtomicU64::new(UNCONFIGURED);
+
+thread_local! {
+    static TIMINGS: RefCell<Report> = const {
+        RefCell::new(Report {
+            test: None,
+            timings: Vec::new(),
+        })
+    };
+}
+
+/// Report on tests whose commands take longer than `threshold` in total.
+///
+/// This applies to every test in the process and takes precedence over `ASSERT_CMD_TIMING_REPORT`.
+pub fn report_slower_than(threshold: Duration) {
+    let nanos = threshold.as_nanos();
+    let nanos = if nanos < u128::from(UNCONFIGURED) {
+        nanos as u64
+    } else {
+        UNCONFIGURED - 1
+    };
+    THRESHOLD.store(nanos, Ordering::SeqCst);
+}
+
+/// The commands timed so far on the current thread.
+pub fn timings() -> Vec<Timing> {
+    TIMINGS.with(|report| report.borrow().timings.clone())
+}
+
+/// How long one command took.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Timing {
+    /// The command, as formatted for failure messages.
+    pub command: String,
+    /// From spawning to having collected all of the output.
+    pub duration: Duration,
+}
+
+fn threshold() -> Option<Duration> {
+    match THRESHOLD.load(Ordering::SeqCst) {
+        UNCONFIGURED => env::var(ENV_VAR)
+            .ok()
+            .and_then(|secs| secs.trim().parse::<f64>().ok())
+            .filter(|secs| secs.is_finite() && 0.0 <= *secs)
+            .map(Duration::from_secs_f64),
+        nanos => Some(Duration::from_nanos(nanos)),
+    }
+}
+
+/// Describe `cmd` for the report, if reports are enabled.
+pub(crate) fn label(cmd: &dyn fmt::Debug) -> Option<String> {
+    threshold().map(|_| format!("{:?}", cmd))
+}
+
+/// Record that `label`'s run took `duration`.
+pub(crate) fn record(label: Option<String>, duration: Duration) {
+    if let Some(command) = label {
+        // Ignore recording during thread teardown.
+        let _ = TIMINGS.try_with(|report| {
+            let mut report = report.borrow_mut();
+            if report.test.is_none() {
+                // Looked up now since `thread::current` may be unavailable during teardown.
+                report.test = Some(thread::current().name().unwrap_or("<unnamed>").to_owned());
+            }
+            report.timings.push(Timing { command, duration })
+        });
+    }
+}
+
+#[derive(Default)]
+struct Report {
+    test: Option<String>,
+    timings: Vec<Timing>,
+}
+
+impl Report {
+    fn summary(&self, test: &str) -> String {
+        let total = self.total();
+        let mut slowest: Vec<_> = self.timings.iter().collect();
+        slowest.sort_by_key(|t| std::cmp::Reverse(t.duration));
+        let mut summary = format!(
+            "assert_cmd: `{}` spent {:.1?} in {} commands",
+            test,
+            total,
+            self.timings.len()
+        );
+        for timing in slowest.iter().take(MAX_LISTED) {
+            summary.push_str(&format!("\n  {:.1?} `{}`", timing.duration, timing.command));
+        }
+        if MAX_LISTED < slowest.len() {
+            summary.push_str(&format!("\n  ... {} more", slowest.len() - MAX_LISTED));
+        }
+        summary
+    }
+
+    fn total(&self) -> Duration {
+        self.timings.iter().map(|t| t.duration).sum()
+    }
+}
+
+impl Drop for Report {
+    fn drop(&mut self) {
+        let threshold = match threshold() {
+            Some(threshold) => threshold,
+            None => return,
+        };
+        if self.timings.is_empty() || self.total() <= threshold {
+            return;
+        }
+        let test = self.test.as_deref().unwrap_or("<unnamed>");
+        let _ = writeln!(std::io::stderr(), "{}", self.summary(test));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn summary_slowest_first() {
+        let report = Report {
+            test: None,
+            timings: vec![
+                Timing {
+                    command: "\"fast\"".to_owned(),
+                    duration: Duration::from_millis(100),
+                },
+                Timing {
+                    command: "\"slow\"".to_owned(),
+                    duration: Duration::from_millis(2000),
+                },
+            ],
+        };
+        assert_eq!(
+            report.summary("cli::import"),
+            "assert_cmd: `cli::import` spent 2.1s in 2 commands\n  2.0s `\"slow\"`\n  100.0ms `\"fast\"`"
+        );
+        std::mem::forget(report);
+    }
+}
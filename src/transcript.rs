@@ -0,0 +1,472 @@
+//! A complete record of a command run, for snapshot testing.
+//!
+//! See [`Assert::transcript`].
+//!
+//! [`Assert::transcript`]: crate::assert::Assert::transcript()
+
+use std::fmt;
+use std::process;
+use std::time::Duration;
+
+/// An output stream of the child.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Stream {
+    /// The child's `stdout`.
+    Stdout,
+    /// The child's `stderr`.
+    Stderr,
+}
+
+impl fmt::Display for Stream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stream::Stdout => "stdout".fmt(f),
+            Stream::Stderr => "stderr".fmt(f),
+        }
+    }
+}
+
+/// A read from one of the child's streams.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Chunk {
+    pub(crate) stream: Stream,
+    pub(crate) elapsed: Duration,
+    pub(crate) len: usize,
+}
+
+/// What `Command` knows about a run, beyond its `Output`.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Recording {
+    pub(crate) program: String,
+    pub(crate) args: Vec<String>,
+    pub(crate) env: Vec<(String, Option<String>)>,
+    pub(crate) stdin: Option<Vec<u8>>,
+    pub(crate) chunks: Vec<Chunk>,
+    pub(crate) spawn: Option<Duration>,
+    pub(crate) duration: Option<Duration>,
+}
+
+impl Recording {
+    pub(crate) fn new(cmd: &process::Command) -> Self {
+        let program = std::path::Path::new(cmd.get_program())
+            .file_stem()
+            .unwrap_or_else(|| cmd.get_program())
+            .to_string_lossy()
+            .into_owned();
+        let args = cmd
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        let env = cmd
+            .get_envs()
+            .filter(|(key, _)| !is_internal_var(key))
+            .map(|(key, value)| {
+                (
+                    key.to_string_lossy().into_owned(),
+                    value.map(|v| v.to_string_lossy().into_owned()),
+                )
+            })
+            .collect();
+        Self {
+            program,
+            args,
+            env,
+            ..Default::default()
+        }
+    }
+}
+
+/// How a child was invoked, as resolved when it was spawned.
+///
+/// See [`Assert::invocation`].
+///
+/// [`Assert::invocation`]: crate::assert::Assert::invocation()
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Invocation {
+    program: std::path::PathBuf,
+    /// Whether the program was found on `PATH`, rather than given as a path.
+    pub(crate) found: bool,
+    args: Vec<std::ffi::OsString>,
+    cwd: std::path::PathBuf,
+    env: Vec<(std::ffi::OsString, Option<std::ffi::OsString>)>,
+    env_cleared: bool,
+}
+
+impl Invocation {
+    pub(crate) fn new(cmd: &process::Command, env_cleared: bool) -> Self {
+        let test_cwd = std::env::current_dir().unwrap_or_default();
+        let cwd = match cmd.get_current_dir() {
+            Some(cwd) => test_cwd.join(cwd),
+            None => test_cwd,
+        };
+        let mut env: Vec<_> = cmd
+            .get_envs()
+            .filter(|(key, _)| !is_internal_var(key))
+            .map(|(key, value)| (key.to_owned(), value.map(|v| v.to_owned())))
+            .collect();
+        env.sort();
+        let program = crate::cache::resolve(cmd).unwrap_or_else(|| cmd.get_program().into());
+        Self {
+            found: program.as_os_str() != cmd.get_program(),
+            program,
+            args: cmd.get_args().map(|arg| arg.to_owned()).collect(),
+            cwd,
+            env,
+            env_cleared,
+        }
+    }
+
+    /// The program run, found on `PATH` for a bare name.
+    ///
+    /// A program not found is as given.
+    pub fn program(&self) -> &std::path::Path {
+        &self.program
+    }
+
+    /// The arguments, without the program.
+    pub fn args(&self) -> &[std::ffi::OsString] {
+        &self.args
+    }
+
+    /// The working directory, absolute.
+    pub fn cwd(&self) -> &std::path::Path {
+        &self.cwd
+    }
+
+    /// How the child's environment differed from the test's, sorted: each variable set, or
+    /// removed as `None`.
+    pub fn env(&self) -> &[(std::ffi::OsString, Option<std::ffi::OsString>)] {
+        &self.env
+    }
+
+    /// Whether the child's environment started empty, rather than from the test's, like after
+    /// [`Command::env_clear`].
+    ///
+    /// This is only known for [`Command`].
+    ///
+    /// [`Command`]: crate::cmd::Command
+    /// [`Command::env_clear`]: crate::cmd::Command::env_clear()
+    pub fn env_cleared(&self) -> bool {
+        self.env_cleared
+    }
+
+    /// The environment, as reported in failures.
+    pub(crate) fn env_summary(&self) -> Option<String> {
+        if self.env.is_empty() && !self.env_cleared {
+            return None;
+        }
+        let vars = self
+            .env
+            .iter()
+            .map(|(key, value)| match value {
+                Some(value) => format!("{}={}", key.to_string_lossy(), value.to_string_lossy()),
+                None => format!("{} removed", key.to_string_lossy()),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        Some(match (self.env_cleared, vars.is_empty()) {
+            (true, true) => "cleared".to_owned(),
+            (true, false) => format!("cleared, then {}", vars),
+            (false, _) => vars,
+        })
+    }
+}
+
+/// Variables `Command` sets on the child for its own bookkeeping.
+fn is_internal_var(key: &std::ffi::OsStr) -> bool {
+    #[cfg(unix)]
+    {
+        if key == crate::signals::ENV_VAR {
+            return true;
+        }
+    }
+    key == crate::env_dump::ENV_VAR
+}
+
+/// A contiguous piece of output from one stream.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Event<'a> {
+    /// The stream written to.
+    pub stream: Stream,
+    /// When the output was read, relative to spawning the child, if known.
+    pub elapsed: Option<Duration>,
+    /// What was written.
+    pub data: &'a [u8],
+}
+
+/// When a command run reached each milestone, for checking startup latency.
+///
+/// Output and exit are relative to the child having been spawned.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Timings {
+    /// How long spawning the child took.
+    pub spawn: Duration,
+    /// When the first byte of `stdout` was read, if any was written.
+    pub first_stdout: Option<Duration>,
+    /// When the first byte of `stderr` was read, if any was written.
+    pub first_stderr: Option<Duration>,
+    /// When the child had exited and all of its output was read.
+    pub exit: Duration,
+}
+
+impl Timings {
+    /// When the first byte on either stream was read, if any was written.
+    pub fn first_output(&self) -> Option<Duration> {
+        match (self.first_stdout, self.first_stderr) {
+            (Some(stdout), Some(stderr)) => Some(stdout.min(stderr)),
+            (stdout, stderr) => stdout.or(stderr),
+        }
+    }
+}
+
+/// The full transcript of a command run: invocation, environment changes, `stdin`, the
+/// interleaved output, and exit status.
+///
+/// The `Display` format is stable and free of timing so it can be fed into any snapshot tool.
+/// The alternate format (`{:#}`) adds timing.
+///
+/// Output is interleaved in the order it was read from the child.  Interleaving is only
+/// available for [`Command`]; otherwise `stdout` is followed by `stderr`.
+///
+/// [`Command`]: crate::cmd::Command
+#[derive(Copy, Clone, Debug)]
+pub struct Transcript<'a> {
+    recording: Option<&'a Recording>,
+    output: &'a process::Output,
+}
+
+impl<'a> Transcript<'a> {
+    pub(crate) fn new(recording: Option<&'a Recording>, output: &'a process::Output) -> Self {
+        Self { recording, output }
+    }
+
+    /// How long the command ran, if known.
+    pub fn duration(&self) -> Option<Duration> {
+        self.recording.and_then(|r| r.duration)
+    }
+
+    /// When the command reached each milestone, if known.
+    ///
+    /// This is only available for [`Command`].
+    ///
+    /// [`Command`]: crate::cmd::Command
+    pub fn timings(&self) -> Option<Timings> {
+        let recording = self.recording?;
+        let first = |stream| {
+            recording
+                .chunks
+                .iter()
+                .find(|c| c.stream == stream)
+                .map(|c| c.elapsed)
+        };
+        Some(Timings {
+            spawn: recording.spawn?,
+            first_stdout: first(Stream::Stdout),
+            first_stderr: first(Stream::Stderr),
+            exit: recording.duration?,
+        })
+    }
+
+    /// The command's exit status.
+    pub fn status(&self) -> process::ExitStatus {
+        self.output.status
+    }
+
+    /// The command's output, in the order it was read.
+    pub fn events(&self) -> Vec<Event<'a>> {
+        let output = self.output;
+        let chunks = self.recording.map(|r| r.chunks.as_slice()).unwrap_or(&[]);
+        let chunked_len = |stream| -> usize {
+            chunks
+                .iter()
+                .filter(|c| c.stream == stream)
+                .map(|c| c.len)
+                .sum()
+        };
+        let complete = !chunks.is_empty()
+            && chunked_len(Stream::Stdout) == output.stdout.len()
+            && chunked_len(Stream::Stderr) == output.stderr.len();
+        if !complete {
+            return [
+                (Stream::Stdout, &output.stdout),
+                (Stream::Stderr, &output.stderr),
+            ]
+            .iter()
+            .filter(|(_, data)| !data.is_empty())
+            .map(|(stream, data)| Event {
+                stream: *stream,
+                elapsed: None,
+                data,
+            })
+            .collect();
+        }
+
+        let mut events: Vec<Event<'a>> = Vec::new();
+        let (mut stdout, mut stderr) = (0, 0);
+        for chunk in chunks {
+            let (data, offset) = match chunk.stream {
+                Stream::Stdout => (&output.stdout, &mut stdout),
+                Stream::Stderr => (&output.stderr, &mut stderr),
+            };
+            let start = *offset;
+            *offset += chunk.len;
+            match events.last_mut() {
+                Some(last) if last.stream == chunk.stream => {
+                    last.data = &data[start - last.data.len()..*offset];
+                }
+                _ => events.push(Event {
+                    stream: chunk.stream,
+                    elapsed: Some(chunk.elapsed),
+                    data: &data[start..*offset],
+                }),
+            }
+        }
+        events
+    }
+}
+
+impl<'a> fmt::Display for Transcript<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(recording) = self.recording {
+            write!(f, "$ {}", recording.program)?;
+            for arg in &recording.args {
+                if arg.is_empty() || arg.contains(|c: char| c.is_whitespace() || c == '"') {
+                    write!(f, " {:?}", arg)?;
+                } else {
+                    write!(f, " {}", arg)?;
+                }
+            }
+            writeln!(f)?;
+            for (key, value) in &recording.env {
+                match value {
+                    Some(value) => writeln!(f, "env: {}={}", key, value)?,
+                    None => writeln!(f, "env: -{}", key)?,
+                }
+            }
+            if let Some(ref stdin) = recording.stdin {
+                for line in String::from_utf8_lossy(stdin).lines() {
+                    writeln!(f, "[stdin] {}", line)?;
+                }
+            }
+        }
+        for event in self.events() {
+            let prefix = match (f.alternate(), event.elapsed) {
+                (true, Some(elapsed)) => format!("+{:?} ", elapsed),
+                _ => String::new(),
+            };
+            for line in String::from_utf8_lossy(event.data).lines() {
+                writeln!(f, "{}[{}] {}", prefix, event.stream, line)?;
+            }
+        }
+        write!(f, "status: {}", StatusDisplay(&self.output.status))?;
+        if f.alternate() {
+            if let Some(duration) = self.duration() {
+                write!(f, "\nduration: {:?}", duration)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+struct StatusDisplay<'a>(&'a process::ExitStatus);
+
+impl<'a> fmt::Display for StatusDisplay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(code) = self.0.code() {
+            return write!(f, "exit {}", code);
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            if let Some(signal) = self.0.signal() {
+                return write!(f, "signal {}", signal);
+            }
+        }
+        write!(f, "interrupted")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn output(stdout: &[u8], stderr: &[u8]) -> process::Output {
+        #[cfg(unix)]
+        use std::os::unix::process::ExitStatusExt;
+        #[cfg(windows)]
+        use std::os::windows::process::ExitStatusExt;
+
+        process::Output {
+            status: process::ExitStatus::from_raw(0),
+            stdout: stdout.to_vec(),
+            stderr: stderr.to_vec(),
+        }
+    }
+
+    fn chunk(stream: Stream, len: usize) -> Chunk {
+        Chunk {
+            stream,
+            elapsed: Duration::from_millis(len as u64),
+            len,
+        }
+    }
+
+    #[test]
+    fn interleaves_chunks() {
+        let output = output(b"one\ntwo\n", b"oops\n");
+        let recording = Recording {
+            program: "tool".to_owned(),
+            args: vec!["a b".to_owned()],
+            env: vec![
+                ("LEVEL".to_owned(), Some("1".to_owned())),
+                ("HOME".to_owned(), None),
+            ],
+            chunks: vec![
+                chunk(Stream::Stdout, 2),
+                chunk(Stream::Stdout, 2),
+                chunk(Stream::Stderr, 5),
+                chunk(Stream::Stdout, 4),
+            ],
+            ..Default::default()
+        };
+        let transcript = Transcript::new(Some(&recording), &output);
+        let events = transcript.events();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].data, b"one\n");
+        assert_eq!(events[1].data, b"oops\n");
+        assert_eq!(events[2].data, b"two\n");
+        assert_eq!(
+            transcript.to_string(),
+            "$ tool \"a b\"\nenv: LEVEL=1\nenv: -HOME\n[stdout] one\n[stderr] oops\n[stdout] two\nstatus: exit 0"
+        );
+    }
+
+    #[test]
+    fn timings_from_chunks() {
+        let output = output(b"one\n", b"oops\n");
+        let recording = Recording {
+            chunks: vec![chunk(Stream::Stderr, 5), chunk(Stream::Stdout, 4)],
+            spawn: Some(Duration::from_millis(1)),
+            duration: Some(Duration::from_millis(10)),
+            ..Default::default()
+        };
+        let timings = Transcript::new(Some(&recording), &output)
+            .timings()
+            .unwrap();
+        assert_eq!(timings.first_stdout, Some(Duration::from_millis(4)));
+        assert_eq!(timings.first_stderr, Some(Duration::from_millis(5)));
+        assert_eq!(timings.first_output(), Some(Duration::from_millis(4)));
+        assert_eq!(timings.exit, Duration::from_millis(10));
+        assert_eq!(Transcript::new(None, &output).timings(), None);
+    }
+
+    #[test]
+    fn falls_back_without_chunks() {
+        let output = output(b"out\n", b"err\n");
+        let transcript = Transcript::new(None, &output);
+        assert_eq!(
+            transcript.to_string(),
+            "[stdout] out\n[stderr] err\nstatus: exit 0"
+        );
+    }
+}
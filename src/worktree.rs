@@ -0,0 +1,135 @@
+//! Catch commands that modify the source checkout they run in.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path;
+use std::process;
+
+/// The modified tracked files of a git working tree, with hashes of their contents.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Snapshot {
+    root: path::PathBuf,
+    files: Vec<(String, Option<u64>)>,
+}
+
+impl Snapshot {
+    /// Snapshot the working tree containing `dir`, if it is in one.
+    pub(crate) fn new(dir: &path::Path) -> Option<Self> {
+        let root = git(dir, &["rev-parse", "--show-toplevel"])?;
+        let root = path::PathBuf::from(String::from_utf8_lossy(&root).trim_end());
+        let status = git(
+            &root,
+            &["status", "--porcelain", "-z", "--untracked-files=no"],
+        )?;
+        let mut entries = status.split(|b| *b == 0).filter(|e| !e.is_empty());
+        let mut files = Vec::new();
+        while let Some(entry) = entries.next() {
+            let entry = String::from_utf8_lossy(entry);
+            if entry.len() < 4 {
+                continue;
+            }
+            // Renames and copies are followed by their original path
+            if entry.starts_with('R') || entry.starts_with('C') {
+                entries.next();
+            }
+            let file = entry[3..].to_owned();
+            let hash = fs::read(root.join(&file)).ok().map(|data| {
+                let mut hasher = DefaultHasher::new();
+                data.hash(&mut hasher);
+                hasher.finish()
+            });
+            files.push((file, hash));
+        }
+        files.sort();
+        Some(Self { root, files })
+    }
+
+    /// The files that changed between `self` and `after`.
+    pub(crate) fn changes(&self, after: &Self) -> Vec<String> {
+        let mut changes: Vec<_> = self
+            .files
+            .iter()
+            .filter(|file| !after.files.contains(file))
+            .chain(after.files.iter().filter(|file| !self.files.contains(file)))
+            .map(|(file, _)| file.clone())
+            .collect();
+        changes.sort();
+        changes.dedup();
+        changes
+    }
+
+    pub(crate) fn root(&self) -> &path::Path {
+        &self.root
+    }
+}
+
+fn git(dir: &path::Path, args: &[&str]) -> Option<Vec<u8>> {
+    let output = process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .stdin(process::Stdio::null())
+        .stderr(process::Stdio::null())
+        .output()
+        .ok()?;
+    if output.status.success() {
+        Some(output.stdout)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn detects_modified_files() {
+        let dir = crate::temp::create_dir("assert_cmd-worktree");
+        let init = || -> Option<()> {
+            git(&dir, &["init", "-q"])?;
+            fs::write(dir.join("tracked"), "one\n").ok()?;
+            fs::write(dir.join("dirty"), "one\n").ok()?;
+            git(&dir, &["add", "tracked", "dirty"])?;
+            git(
+                &dir,
+                &[
+                    "-c",
+                    "user.name=test",
+                    "-c",
+                    "user.email=test@example.com",
+                    "commit",
+                    "-qm",
+                    "initial",
+                ],
+            )?;
+            Some(())
+        };
+        if init().is_none() {
+            // No usable `git`
+            let _ = fs::remove_dir_all(&dir);
+            return;
+        }
+        fs::write(dir.join("dirty"), "two\n").unwrap();
+
+        let before = Snapshot::new(&dir).unwrap();
+        fs::write(dir.join("untracked"), "new\n").unwrap();
+        assert_eq!(
+            before.changes(&Snapshot::new(&dir).unwrap()),
+            Vec::<String>::new()
+        );
+        fs::write(dir.join("tracked"), "two\n").unwrap();
+        fs::write(dir.join("dirty"), "three\n").unwrap();
+        assert_eq!(
+            before.changes(&Snapshot::new(&dir).unwrap()),
+            ["dirty", "tracked"]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn outside_worktree() {
+        assert_eq!(Snapshot::new(path::Path::new("/")), None);
+    }
+}
@@ -1,4 +1,5 @@
 use std::process::Command;
+use std::time::Duration;
 
 use assert_cmd::prelude::*;
 use predicates::prelude::*;
@@ -154,3 +155,613 @@ fn stderr_example() {
         .assert()
         .stderr("world\n");
 }
+
+#[test]
+#[cfg(unix)]
+fn raw_code_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("exit", "42")
+        .assert()
+        .raw_code(42 << 8);
+}
+
+#[test]
+fn ansi_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "\x1b[1;31mhello\x1b[0m")
+        .env("stderr", "world")
+        .assert()
+        .stdout_has_ansi()
+        .stdout_has_sgr("1;31")
+        .stderr_no_ansi();
+}
+
+#[test]
+#[should_panic(expected = "Expected SGR sequence `ESC[32m` in stdout, found `1;31`, `0`")]
+fn ansi_missing_sgr() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "\x1b[1;31mhello\x1b[0m")
+        .assert()
+        .stdout_has_sgr("32");
+}
+
+#[test]
+#[should_panic(expected = "1 line in stdout exceeded 4 columns:\n  line 2 (5 columns): \"world\"")]
+fn stdout_max_width_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "\x1b[1mhell\x1b[0m\nworld")
+        .assert()
+        .stdout_max_width(4);
+}
+
+#[test]
+#[should_panic(expected = "config reload should keep serving\nUnexpected failure.\ncode=1\n")]
+fn with_message_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("exit", "1")
+        .assert()
+        .with_message("config reload should keep serving")
+        .success();
+}
+
+#[test]
+fn stderr_logs_example() {
+    use assert_cmd::logs::Level;
+
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stderr", "[WARN  app] careful\n[INFO  app] done")
+        .assert()
+        .stderr_no_logs(Level::Error)
+        .stderr_log_count(Level::Warn, predicate::str::contains("care"), 1)
+        .stderr_log_count(Level::Info, "done", 1);
+}
+
+#[test]
+#[should_panic(expected = "Unexpected ERROR records in stderr:\n  ERROR app: broken")]
+fn stderr_no_logs_failure() {
+    use assert_cmd::logs::Level;
+
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stderr", "[ERROR app] broken")
+        .assert()
+        .stderr_no_logs(Level::Error);
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn stderr_jsonl_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env(
+            "stderr",
+            "{\"level\":\"info\",\"event\":\"started\"}\n{\"level\":\"info\",\"event\":\"stopped\"}",
+        )
+        .assert()
+        .stderr_jsonl(|logs| logs.all("level", "info") && logs.in_order("event", &["started", "stopped"]));
+}
+
+#[test]
+#[cfg(feature = "json")]
+#[should_panic(expected = "Invalid JSON lines in stderr, line 2 is not valid JSON")]
+fn stderr_jsonl_invalid() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stderr", "{\"level\":\"info\"}\noops")
+        .assert()
+        .stderr_jsonl(|_| true);
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn stdout_json_example() {
+    use assert_cmd::json::Value;
+
+    let assert = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", r#"{"package":{"name":"tool","version":"1.2.3"}}"#)
+        .assert()
+        .stdout_json(|value| value["package"]["name"] == "tool")
+        .stdout_json_at("/package/version", predicate::eq(Value::from("1.2.3")));
+
+    let err = assert
+        .try_assert(|assert| assert.stdout_json_at("/package/license", predicate::always()))
+        .unwrap_err();
+    assert!(
+        err.headline()
+            .starts_with("No `/package/license` in stdout JSON"),
+        "{}",
+        err.headline()
+    );
+}
+
+#[test]
+#[cfg(feature = "json")]
+#[should_panic(expected = "Invalid JSON in stdout, expected value at line 1 column 1")]
+fn stdout_json_invalid() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "oops")
+        .assert()
+        .stdout_json(|_| true);
+}
+
+#[test]
+fn progress_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "done")
+        .env("stderr", "10%\r50%\r\x1b[2K100%")
+        .assert()
+        .stdout_no_progress()
+        .stderr_rendered("100%\n");
+}
+
+#[test]
+fn extract_example() {
+    let assert = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "created id: 42")
+        .env("stderr", "wrote 3 files to out/")
+        .assert()
+        .success();
+    assert_eq!(assert.stdout_extract(r"id: (\d+)"), "42");
+    assert_eq!(assert.stdout_extract(r"\d+"), "42");
+    assert_eq!(
+        assert.stderr_extract_all(r"wrote (\d+) files to (\S+)"),
+        ["3", "out/"]
+    );
+}
+
+#[test]
+#[should_panic(expected = "Pattern `id: (\\d+)` not found in stdout")]
+fn extract_missing() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "created")
+        .assert()
+        .stdout_extract(r"id: (\d+)");
+}
+
+#[test]
+fn parsed_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "2.504")
+        .env("stderr", "42")
+        .assert()
+        .stdout_parsed::<f64, _>(assert_cmd::numeric::approx(2.5).abs(0.01))
+        .stderr_parsed::<u32, _>(predicate::gt(40));
+}
+
+#[test]
+#[should_panic(expected = "Unparseable stdout as `f64`")]
+fn parsed_invalid() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "fast")
+        .assert()
+        .stdout_parsed::<f64, _>(assert_cmd::numeric::approx(2.5));
+}
+
+#[test]
+fn duration_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "finished 3 jobs in 2.3s")
+        .assert()
+        .stdout_duration(predicate::lt(Duration::from_secs(5)))
+        .stdout_duration(predicate::gt(Duration::from_secs(2)));
+}
+
+#[test]
+#[should_panic(expected = "No duration found in stderr")]
+fn duration_missing() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stderr", "finished")
+        .assert()
+        .stderr_duration(predicate::lt(Duration::from_secs(5)));
+}
+
+#[test]
+fn strict_stderr_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stderr", "deprecated flag")
+        .assert()
+        .strict_stderr()
+        .success()
+        .stderr(predicate::str::contains("deprecated"));
+}
+
+#[test]
+#[should_panic(expected = "Unexpected stderr (strict mode)")]
+fn strict_stderr_unasserted() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stderr", "deprecated flag")
+        .assert()
+        .strict_stderr()
+        .success();
+}
+
+#[cfg(unix)]
+#[test]
+fn code_maps_from_child_example() {
+    let inner = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("exit", "42")
+        .unwrap_err();
+    let inner = inner.as_output().unwrap().status;
+
+    let fixture = assert_cmd::cargo::cargo_bin("bin_fixture");
+    Command::new("sh")
+        .args(["-c", "\"$0\""])
+        .arg(&fixture)
+        .env("exit", "42")
+        .assert()
+        .code_maps_from_child(&inner);
+}
+
+#[cfg(unix)]
+#[test]
+fn shell_conventions() {
+    Command::new("sh")
+        .args(["-c", "no-such-program-for-assert-cmd"])
+        .assert()
+        .code_not_found();
+    Command::new("sh")
+        .args(["-c", "kill -TERM $$"])
+        .assert()
+        .interrupted();
+    Command::new("sh")
+        .args(["-c", "sh -c 'kill -TERM $$'; exit $?"])
+        .assert()
+        .code_from_signal(15);
+}
+
+#[cfg(unix)]
+#[test]
+fn terminating_signal() {
+    Command::new("sh")
+        .args(["-c", "kill -SEGV $$"])
+        .assert()
+        .interrupted_by(11)
+        .signal(predicate::in_iter(vec![6, 11]));
+    let err = Command::new("sh")
+        .args(["-c", "kill -TERM $$"])
+        .assert()
+        .try_assert(|assert| assert.interrupted_by(9))
+        .unwrap_err();
+    assert_eq!(
+        err.headline(),
+        "Unexpected signal, expected SIGKILL (9), got SIGTERM (15)"
+    );
+    assert!(err.context().contains("signal=SIGTERM (15)\n"));
+    let err = Command::new("true")
+        .assert()
+        .try_assert(|assert| assert.signal(15))
+        .unwrap_err();
+    assert!(err.headline().contains("exited with code 0"));
+}
+
+#[test]
+fn with_settings_example() {
+    let settings = assert_cmd::settings::AssertSettings::new()
+        .filter(r"\d+ms", "[DURATION]")
+        .filter("/tmp/[a-z]+", "[TEMP]");
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "done in 15ms")
+        .env("stderr", "wrote /tmp/abc")
+        .assert()
+        .with_settings(&settings)
+        .stdout("done in [DURATION]\n")
+        .stderr("wrote [TEMP]\n");
+}
+
+#[test]
+#[should_panic(expected = "stdout=```<12 bytes total>\"he\"...<8 bytes omitted>...\"d\\n\"```")]
+fn with_settings_truncate() {
+    let settings = assert_cmd::settings::AssertSettings::new()
+        .truncate(4)
+        .color(false);
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello \x1b[1mworld\x1b[0m")
+        .assert()
+        .with_settings(&settings)
+        .failure();
+}
+
+#[test]
+fn matches_expected_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello")
+        .env("stderr", "world")
+        .env("exit", "3")
+        .assert()
+        .matches_expected();
+}
+
+#[test]
+fn matches_snapshot_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello")
+        .env("stderr", "world")
+        .env("exit", "3")
+        .assert()
+        .matches_snapshot("hello");
+}
+
+#[test]
+fn printable_only() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "\x1b[1mhello\x1b[0m\tworld")
+        .env("stderr", "plain")
+        .assert()
+        .stdout_printable_only()
+        .stderr_printable_only();
+}
+
+#[test]
+#[should_panic(expected = "Unexpected control character '\\u{7}' in stdout at byte 5")]
+fn printable_only_bell() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello\x07")
+        .assert()
+        .stdout_printable_only();
+}
+
+#[test]
+fn try_assertions() {
+    let assert = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello")
+        .env("exit", "3")
+        .assert()
+        .try_failure()
+        .unwrap()
+        .try_code(3)
+        .unwrap();
+    let err = assert
+        .with_message("greets")
+        .try_stdout("goodbye\n")
+        .unwrap_err();
+    assert!(err.headline().starts_with("greets\nUnexpected stdout"));
+    assert!(err.context().contains("code=3\n"));
+    assert_eq!(err.get_output().stdout, b"hello\n");
+    assert_eq!(
+        err.to_string(),
+        format!("{}\n{}", err.headline(), err.context())
+    );
+}
+
+#[test]
+fn per_os() {
+    use assert_cmd::platform::PerOs;
+
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello")
+        .env("stderr", "world")
+        .assert()
+        .stdout_per_os(predicate::str::contains("hello"), "hello\n")
+        .stderr(PerOs::new("world\n").windows("world\n"));
+}
+
+#[test]
+#[should_panic(
+    expected = "Debug output in stderr, line 1 looks like `dbg!` output: \"[src/main.rs:4:5] count = 3\""
+)]
+fn no_debug_leaks_dbg() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello")
+        .env("stderr", "[src/main.rs:4:5] count = 3")
+        .assert()
+        .no_debug_leaks();
+}
+
+#[cfg(unix)]
+#[test]
+fn missing_library_context() {
+    let err = Command::new("sh")
+        .args([
+            "-c",
+            "echo 'tool: error while loading shared libraries: libfoo.so.1: cannot open shared object file' >&2; exit 127",
+        ])
+        .assert()
+        .try_success()
+        .unwrap_err();
+    assert!(err
+        .context()
+        .contains("exec failure=`missing shared library libfoo.so.1`"));
+}
+
+#[test]
+fn stdout_mismatch_diff() {
+    let expected: String = (0..30).map(|i| format!("line {}\n", i)).collect();
+    let actual = expected.replace("line 15\n", "line fifteen\n");
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", actual.trim_end())
+        .assert()
+        .try_stdout(expected)
+        .unwrap_err();
+    assert!(
+        err.headline().contains("line fifteen"),
+        "{}",
+        err.headline()
+    );
+    assert!(
+        err.headline().contains("@@ -13,7 +13,7 @@"),
+        "{}",
+        err.headline()
+    );
+    assert!(!err.headline().contains("line 0\n"), "{}", err.headline());
+}
+
+#[test]
+fn str_assertions() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "héllo")
+        .env("stderr", "wörld")
+        .assert()
+        .stdout_str(predicate::str::starts_with("hé"))
+        .stderr_str(predicate::str::contains("ö"));
+
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "tab\there")
+        .assert()
+        .try_assert(|assert| assert.stdout_str(predicate::str::contains("nope")))
+        .unwrap_err();
+    assert!(
+        err.headline().ends_with("stdout=```\ntab\there\n```"),
+        "{}",
+        err.headline()
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn str_assertions_invalid_utf8() {
+    let err = Command::new("printf")
+        .arg("ok\\n\\377")
+        .assert()
+        .try_assert(|assert| assert.stdout_str(predicate::always()))
+        .unwrap_err();
+    assert!(
+        err.headline()
+            .starts_with("stdout is not valid UTF-8, from byte 3 (line 2)"),
+        "{}",
+        err.headline()
+    );
+}
+
+#[test]
+fn normalized_line_endings() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "a\r\nb")
+        .env("stderr", "c\n")
+        .assert()
+        .stdout_normalized("a\nb\n")
+        .stderr_normalized("c\r\n\r\n");
+
+    let settings = assert_cmd::settings::AssertSettings::new().normalize_line_endings();
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "a\r\nb")
+        .assert()
+        .with_settings(&settings)
+        .stdout("a\nb\n");
+}
+
+#[test]
+fn matches_path() {
+    let dir = std::env::temp_dir().join(format!("assert_cmd-matches-path-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let stdout = dir.join("hello.stdout");
+    let stderr = dir.join("hello.stderr");
+    std::fs::write(&stdout, "hello\n").unwrap();
+    std::fs::write(&stderr, "world\n").unwrap();
+
+    let assert = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello")
+        .env("stderr", "world")
+        .assert()
+        .stdout_matches_path(&stdout)
+        .stderr_matches_path(&stderr);
+
+    let err = assert
+        .try_assert(|assert| assert.stdout_matches_path(&stderr))
+        .unwrap_err();
+    assert!(
+        err.headline().starts_with("Output doesn't match `"),
+        "{}",
+        err.headline()
+    );
+    assert!(err.to_string().contains("@@ -1 +1 @@"), "{}", err);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn code_range_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("exit", "3")
+        .assert()
+        .code(2..10)
+        .code(2..=3)
+        .code([2, 3, 4]);
+
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("exit", "12")
+        .assert()
+        .try_code(2..10)
+        .unwrap_err();
+    assert!(
+        err.headline().contains("var in 2..10"),
+        "{}",
+        err.headline()
+    );
+    assert!(err.headline().contains("var: 12"), "{}", err.headline());
+}
+
+#[test]
+fn composite_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "done")
+        .assert()
+        .success_with_empty_stderr();
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("exit", "2")
+        .assert()
+        .failure_with_code(2);
+
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stderr", "warning: deprecated")
+        .assert()
+        .try_assert(|assert| assert.success_with_empty_stderr())
+        .unwrap_err();
+    assert!(
+        err.headline().starts_with("Unexpected stderr"),
+        "{}",
+        err.headline()
+    );
+}
+
+#[test]
+fn into_output_example() {
+    let assert = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello")
+        .env("stderr", "world")
+        .assert()
+        .strict_stderr()
+        .success();
+    assert_eq!(assert.stdout_lossy(), "hello\n");
+    assert_eq!(assert.stderr_lossy(), "world\n");
+    let output = assert.into_output();
+    assert_eq!(output.stdout, b"hello\n");
+    assert_eq!(output.stderr, b"world\n");
+}
@@ -52,3 +52,72 @@ fn cargo_bin_example_2() {
     let output = cmd.unwrap();
     println!("{:?}", output);
 }
+
+#[test]
+fn cargo_bin_suggests_close_names() {
+    let err = Command::cargo_bin("bin-fixture").unwrap_err();
+    assert!(
+        err.to_string().contains("Did you mean `bin_fixture`?"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn cargo_bin_lists_available() {
+    let err = Command::cargo_bin("completely-different").unwrap_err();
+    let err = err.to_string();
+    assert!(err.contains("Available binaries: "), "{}", err);
+    assert!(err.contains("bin_fixture"), "{}", err);
+}
+
+#[test]
+fn cargo_example() {
+    assert_cmd::Command::cargo_example("example_fixture")
+        .unwrap()
+        .env("stdout", "42")
+        .assert()
+        .success()
+        .stdout("42\n");
+}
+
+#[test]
+fn cargo_bin_macro() {
+    assert!(assert_cmd::cargo_bin!("bin_fixture").is_file());
+    assert_cmd::Command::new(assert_cmd::cargo_bin!("bin_fixture"))
+        .env("stdout", "42")
+        .assert()
+        .stdout("42\n");
+}
+
+#[test]
+fn cargo_bin_with_profile() {
+    assert_eq!(
+        assert_cmd::cargo::cargo_bin_with_profile("bin_fixture", "dev"),
+        assert_cmd::cargo_bin!("bin_fixture")
+    );
+    assert_cmd::Command::cargo_bin_with_profile("bin_fixture", "dev")
+        .unwrap()
+        .env("stdout", "42")
+        .assert()
+        .stdout("42\n");
+}
+
+#[test]
+fn cargo_bin_with_profile_says_how_to_build() {
+    let err = assert_cmd::Command::cargo_bin_with_profile("bin_fixture", "unbuilt").unwrap_err();
+    let err = err.to_string();
+    assert!(
+        err.contains("Build it with `cargo build --profile unbuilt --bin bin_fixture`"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn workspace_bin_reports_build_errors() {
+    let err = assert_cmd::Command::workspace_bin("no-such-member-bin").unwrap_err();
+    let err = err.to_string();
+    assert!(err.contains("Unable to build with "), "{}", err);
+    assert!(err.contains("no-such-member-bin"), "{}", err);
+}
@@ -26,3 +26,2282 @@ fn timeout_example() {
         .assert();
     assert.failure();
 }
+
+#[test]
+fn capture_example() {
+    use assert_cmd::cmd::Streams;
+
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello")
+        .env("stderr", "world")
+        .capture(Streams::StderrOnly)
+        .assert()
+        .stdout("")
+        .stderr("world\n");
+}
+
+#[test]
+fn env_dump_example() {
+    use predicates::prelude::*;
+
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("LEVEL", "debug")
+        .env_remove("UNSET")
+        .env_dump(true)
+        .assert()
+        .child_env("LEVEL", "debug")
+        .child_env("PATH", predicate::str::is_empty().not())
+        .child_env_unset("UNSET");
+}
+
+#[cfg(unix)]
+#[test]
+fn env_dump_per_run() {
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", "echo ${ASSERT_CMD_ENV_DUMP-unset}"]);
+    cmd.env_dump(true).assert().success();
+    cmd.env_dump(false).assert().stdout("unset\n");
+}
+
+#[test]
+fn stays_alive_for_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "listening")
+        .env("sleep", "100")
+        .stays_alive_for(std::time::Duration::from_millis(500))
+        .stdout("listening\n");
+}
+
+#[test]
+#[should_panic(expected = "Process exited within 10s\ncommand=")]
+fn stays_alive_for_early_exit() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("exit", "3")
+        .stays_alive_for(std::time::Duration::from_secs(10));
+}
+
+#[test]
+fn audit_example() {
+    let rules = assert_cmd::hermetic::Rules::new()
+        .allow_env("PATH")
+        .allow_env("stdout")
+        .allow_path("/tmp");
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env_clear()
+        .env("PATH", "/usr/bin")
+        .env("stdout", "wrote /tmp/out.txt")
+        .env_dump(true)
+        .assert()
+        .audit(&rules);
+}
+
+#[test]
+#[should_panic(expected = "child was given unlisted env var `HOME`")]
+fn audit_leaked_env() {
+    let rules = assert_cmd::hermetic::Rules::new().allow_env("PATH");
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env_clear()
+        .env("HOME", "/root")
+        .env_dump(true)
+        .assert()
+        .audit(&rules);
+}
+
+#[test]
+fn on_timeout_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "started")
+        .env("sleep", "100")
+        .timeout(std::time::Duration::from_secs(1))
+        .assert()
+        .on_timeout(|assert| assert.stdout("started\n"))
+        .failure();
+}
+
+#[test]
+#[should_panic(expected = "Command timed out after 1s\ncommand=")]
+fn timeout_fails_success() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "started")
+        .env("sleep", "100")
+        .timeout(std::time::Duration::from_secs(1))
+        .assert()
+        .success();
+}
+
+#[test]
+fn timeout_unread_stdin() {
+    let start = std::time::Instant::now();
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("sleep", "30")
+        .write_stdin(vec![b'x'; 1 << 20])
+        .timeout(std::time::Duration::from_secs(1))
+        .assert()
+        .interrupted();
+    assert!(start.elapsed() < std::time::Duration::from_secs(20));
+}
+
+#[test]
+fn on_timeout_skipped() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .timeout(std::time::Duration::from_secs(10))
+        .assert()
+        .on_timeout(|assert| assert.stdout("never checked"))
+        .success();
+}
+
+#[cfg(unix)]
+#[test]
+fn invocation_example() {
+    let assert = Command::new("sh")
+        .args(["-c", "exit 0"])
+        .current_dir("src")
+        .env_clear()
+        .env("LANG", "C")
+        .assert();
+    let invocation = assert.invocation().unwrap();
+    assert!(invocation.program().is_absolute());
+    assert!(invocation.program().ends_with("sh"));
+    assert_eq!(invocation.args(), ["-c", "exit 0"]);
+    assert_eq!(
+        invocation.cwd(),
+        std::env::current_dir().unwrap().join("src")
+    );
+    assert_eq!(invocation.env(), [("LANG".into(), Some("C".into()))]);
+    assert!(invocation.env_cleared());
+}
+
+#[cfg(unix)]
+#[test]
+#[should_panic(
+    expected = "program=`/bin/sh`\ncwd=`/`\nenv=`HOME removed, LANG=C, PATH=/bin`\ncode=3"
+)]
+fn invocation_reported() {
+    Command::new("sh")
+        .args(["-c", "exit 3"])
+        .current_dir("/")
+        .env("LANG", "C")
+        .env_remove("HOME")
+        .env("PATH", "/bin")
+        .assert()
+        .success();
+}
+
+#[test]
+fn transcript_example() {
+    let assert = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .arg("-A")
+        .env("stdout", "hello")
+        .env("exit", "42")
+        .write_stdin("42")
+        .assert();
+    assert_eq!(
+        assert.transcript().to_string(),
+        "$ bin_fixture -A\nenv: exit=42\nenv: stdout=hello\n[stdin] 42\n[stdout] hello\nstatus: exit 42"
+    );
+    assert!(assert.transcript().duration().is_some());
+}
+
+#[test]
+fn write_stdin_line_example() {
+    Command::new("cat")
+        .write_stdin_line("yes")
+        .write_stdin_lines(["no\n", "maybe\r\n"])
+        .assert()
+        .stdout("yes\nno\nmaybe\n");
+
+    Command::new("cat")
+        .stdin_line_ending(assert_cmd::cmd::LineEnding::CrLf)
+        .write_stdin_line("yes")
+        .assert()
+        .stdout("yes\r\n");
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn sample_resources_example() {
+    use predicates::prelude::*;
+
+    let assert = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("sleep", "1")
+        .sample_resources(std::time::Duration::from_millis(50))
+        .assert()
+        .success()
+        .peak_rss(predicate::lt(200 * 1024 * 1024))
+        .no_monotonic_rss_growth();
+    assert!(assert.resource_samples().len() >= 3);
+}
+
+#[cfg(unix)]
+#[test]
+fn resource_usage_example() {
+    let assert = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .assert()
+        .success()
+        .max_rss_below(1024 * 1024 * 1024);
+    let usage = assert.get_resource_usage().unwrap();
+    assert!(0 < usage.max_rss);
+
+    // Reaped after timing out, too
+    let assert = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("sleep", "100")
+        .timeout(std::time::Duration::from_millis(500))
+        .assert()
+        .failure();
+    assert!(assert.get_resource_usage().is_some());
+}
+
+#[cfg(unix)]
+#[test]
+#[should_panic(expected = "bytes, expected below 1024 bytes")]
+fn max_rss_below_exceeded() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .assert()
+        .max_rss_below(1024);
+}
+
+#[test]
+fn lines_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "name: demo\nversion: 1.0")
+        .env("stderr", "warning: unused")
+        .assert()
+        .stdout_line_count(2)
+        .stdout_has_line(predicates::str::starts_with("version:"))
+        .stdout_line(1, predicates::ord::eq("name: demo"))
+        .stderr_has_line(predicates::str::contains("unused"));
+}
+
+#[test]
+#[should_panic(expected = "  2 | b\n  3 | c\n> 4 | d\n  5 | e\n  6 | f\n    | ... 1 more")]
+fn line_mismatch() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "a\nb\nc\nd\ne\nf\ng")
+        .assert()
+        .stdout_line(4, predicates::ord::eq("D"));
+}
+
+#[test]
+fn forbidden_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "Compiling\nFinished")
+        .env("stderr", "warning: unused")
+        .assert()
+        .stdout_does_not_contain("panicked")
+        .stderr_does_not_match(r"(?i)\berror\b");
+}
+
+#[test]
+#[should_panic(
+    expected = "Unexpected \"error\" in stderr (2 times), first at line 2 (byte 8)\n  1 | warning\n> 2 | error: one\n  3 | error: two"
+)]
+fn forbidden_found() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stderr", "warning\nerror: one\nerror: two")
+        .assert()
+        .stderr_does_not_contain("error");
+}
+
+#[test]
+fn exit_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("exit", "64")
+        .assert()
+        .code(assert_cmd::exit::USAGE);
+}
+
+#[test]
+#[should_panic(expected = "Unexpected return code, failed EX_CONFIG (78)")]
+fn exit_mismatch() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("exit", "65")
+        .assert()
+        .code(assert_cmd::exit::CONFIG);
+}
+
+#[test]
+fn empty_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello")
+        .assert()
+        .success()
+        .stderr_empty();
+}
+
+#[test]
+#[should_panic(expected = "Expected no output on stderr, got 5 bytes\n  1 | oops")]
+fn empty_written() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stderr", "oops")
+        .assert()
+        .stderr_empty();
+}
+
+#[cfg(unix)]
+#[test]
+fn signal_dump_example() {
+    const SIGINT: i32 = 2;
+
+    let fixture = assert_cmd::cargo::cargo_bin("bin_fixture");
+    Command::new("sh")
+        .args(["-c", "trap '' INT; exec \"$0\""])
+        .arg(&fixture)
+        .signal_dump(true)
+        .assert()
+        .child_signals(|state| state.ignored.contains(&SIGINT));
+
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .reset_signals()
+        .signal_dump(true)
+        .assert()
+        .child_signals_default();
+}
+
+#[test]
+fn crashed_example() {
+    use assert_cmd::platform::STATUS_STACK_BUFFER_OVERRUN;
+
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("abort", "1")
+        .assert()
+        .crashed()
+        .exit_nt_status(STATUS_STACK_BUFFER_OVERRUN);
+}
+
+#[cfg(unix)]
+#[test]
+#[should_panic(expected = "Expected the command to crash, but it was killed by SIGTERM (15)")]
+fn crashed_killed() {
+    Command::new("sh")
+        .args(["-c", "kill -TERM $$"])
+        .assert()
+        .crashed();
+}
+
+#[cfg(unix)]
+#[test]
+fn stdout_closed_after_example() {
+    Command::new("yes")
+        .timeout(std::time::Duration::from_secs(10))
+        .stdout_closed_after(10)
+        .killed_by_sigpipe()
+        .stdout("y\ny\ny\ny\ny\n");
+}
+
+#[test]
+fn stdout_closed_after_clean_exit() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello")
+        .stdout_closed_after(2)
+        .success()
+        .stdout("he");
+}
+
+#[test]
+#[should_panic(
+    expected = "3 of 4 checks failed (1 distinct)\n--- 3 times, first at check #1 ---\nUnexpected failure.\ncode=1"
+)]
+fn aggregate_example() {
+    let mut failures = assert_cmd::aggregate::Failures::new();
+    for exit in &["0", "1", "1", "1"] {
+        failures.check(|| {
+            Command::cargo_bin("bin_fixture")
+                .unwrap()
+                .env("exit", exit)
+                .assert()
+                .success()
+        });
+    }
+    failures.finish();
+}
+
+#[test]
+fn stdout_reports_crate_version_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env(
+            "stdout",
+            format!("bin_fixture {}+git.abc", env!("CARGO_PKG_VERSION")),
+        )
+        .assert()
+        .stdout_reports_crate_version();
+}
+
+#[test]
+#[should_panic(expected = "Reported version `0.1.0` doesn't match expected `1.2.3`")]
+fn stdout_reports_version_mismatch() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "bin_fixture 0.1.0")
+        .assert()
+        .stdout_reports_version("1.2.3");
+}
+
+#[test]
+fn leaves_no_temp_files_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .isolated_temp_dir(true)
+        .assert()
+        .leaves_no_temp_files();
+}
+
+#[cfg(unix)]
+#[test]
+#[should_panic(expected = "Child left 1 entries in its temp dir\n  leak\n")]
+fn leaves_no_temp_files_leak() {
+    Command::new("sh")
+        .args(["-c", "touch \"$TMPDIR/leak\""])
+        .isolated_temp_dir(true)
+        .assert()
+        .success()
+        .leaves_no_temp_files();
+}
+
+#[cfg(unix)]
+#[test]
+fn in_temp_dir_example() {
+    let mut cmd = Command::new("cat");
+    cmd.arg("input.txt").in_temp_dir();
+    let dir = cmd.temp_cwd().unwrap().to_owned();
+    std::fs::write(dir.join("input.txt"), "hello").unwrap();
+    let assert = cmd.assert().success().stdout("hello");
+    drop(cmd);
+    assert!(dir.is_dir());
+    drop(assert);
+    assert!(!dir.exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn keep_temp_cwd_on_failure_example() {
+    let mut cmd = Command::new("cat");
+    cmd.arg("missing.txt")
+        .in_temp_dir()
+        .keep_temp_cwd_on_failure(true);
+    let dir = cmd.temp_cwd().unwrap().to_owned();
+    let err = cmd.assert().try_success().unwrap_err();
+    drop(cmd);
+    assert!(err.to_string().contains("kept on failure"), "{}", err);
+    assert!(dir.is_dir());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn file_example() {
+    let assert = Command::new("sh")
+        .args(["-c", "mkdir -p out && echo done > out/report.txt"])
+        .in_temp_dir()
+        .assert()
+        .success()
+        .file("out/report.txt", "done\n")
+        .dir_exists("out");
+
+    let err = assert
+        .try_assert(|assert| assert.file("out/report.txt", "pending\n"))
+        .unwrap_err();
+    assert!(
+        err.headline().starts_with("Unexpected contents of `"),
+        "{}",
+        err
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn pipe_example() {
+    Command::new("cat")
+        .write_stdin("b\na\n")
+        .pipe(&mut Command::new("sort"))
+        .assert()
+        .success()
+        .stdout("a\nb\n");
+
+    // `yes` is stopped by `head` closing the pipe
+    Command::new("yes")
+        .pipe(Command::new("head").args(["-n", "1"]))
+        .assert()
+        .success()
+        .stdout("y\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn pipefail_example() {
+    let err = std::panic::catch_unwind(|| {
+        Command::new("sh")
+            .args(["-c", "echo partial; echo broken >&2; exit 3"])
+            .pipe(&mut Command::new("cat"))
+            .pipefail(true)
+            .assert()
+    })
+    .unwrap_err();
+    let err = err.downcast_ref::<String>().unwrap();
+    assert!(err.starts_with("Pipeline stage 1 failed: "), "{}", err);
+    assert!(err.contains("broken"), "{}", err);
+}
+
+#[cfg(unix)]
+#[test]
+fn combined_output_example() {
+    let assert = Command::new("sh")
+        .args(["-c", "echo result; echo progress >&2; echo done"])
+        .capture(assert_cmd::cmd::Streams::Combined)
+        .assert()
+        .success()
+        .combined_output("result\nprogress\ndone\n")
+        .stdout("")
+        .stderr("");
+    let dump = assert.to_string();
+    assert!(dump.contains("combined=```\"result\\nprogress"), "{}", dump);
+
+    let err = Command::new("true")
+        .assert()
+        .try_assert(|assert| assert.combined_output(""))
+        .unwrap_err();
+    assert_eq!(
+        err.headline(),
+        "Output wasn't combined; see `Streams::Combined`"
+    );
+}
+
+#[test]
+fn assert_cwd_independent_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello")
+        .assert_cwd_independent()
+        .stdout("hello\n");
+}
+
+#[test]
+fn assert_from_dirs_filtered() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "\x1b[32mok\x1b[0m")
+        .strip_ansi(true)
+        .assert_from_dirs(["src"])
+        .stdout("ok\n");
+}
+
+#[cfg(unix)]
+#[test]
+#[should_panic(expected = "Output differs when run from `/`\nstdout=```\"/\\n\"```")]
+fn assert_from_dirs_differs() {
+    Command::new("pwd").assert_from_dirs(["/"]);
+}
+
+#[test]
+fn assert_each_home_example() {
+    let mut homes = Vec::new();
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("HOME", "/root")
+        .assert_each_home(|assert| {
+            homes.push(assert.to_string());
+            assert.success()
+        });
+    assert_eq!(homes.len(), 3);
+    assert!(homes[0].contains("home=`unset`"));
+    assert!(homes[2].contains("home=`read-only dir`"));
+}
+
+#[test]
+#[should_panic(expected = "home=`unset`")]
+fn assert_each_home_failure() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("exit", "1")
+        .assert_each_home(|assert| assert.success());
+}
+
+#[cfg(unix)]
+#[test]
+fn stdin_consumed_example() {
+    Command::new("cat")
+        .write_stdin(vec![b'y'; 1024 * 1024])
+        .assert()
+        .stdin_consumed()
+        .success();
+}
+
+#[cfg(unix)]
+#[test]
+#[should_panic(expected = "stdin unread=`child exited after")]
+fn stdin_consumed_ignored() {
+    Command::new("true")
+        .write_stdin(vec![b'y'; 1024 * 1024])
+        .assert()
+        .stdin_consumed();
+}
+
+#[cfg(unix)]
+#[test]
+fn expect_early_exit_example() {
+    let err = Command::new("true")
+        .write_stdin(vec![b'y'; 1024 * 1024])
+        .assert()
+        .expect_early_exit()
+        .success()
+        .try_failure()
+        .unwrap_err();
+    assert!(!err.context().contains("stdin unread"), "{}", err);
+
+    let err = Command::new("sleep")
+        .arg("100")
+        .timeout(std::time::Duration::from_millis(100))
+        .assert()
+        .try_assert(|assert| assert.expect_early_exit())
+        .unwrap_err();
+    assert!(err.headline().contains("rather than exiting early"));
+}
+
+#[test]
+fn registry_example() {
+    let steps = assert_cmd::registry::Registry::new()
+        .register("greet", || {
+            let mut cmd = Command::cargo_bin("bin_fixture").unwrap();
+            cmd.env("stdout", "hello");
+            cmd
+        })
+        .register("fail", || {
+            let mut cmd = Command::cargo_bin("bin_fixture").unwrap();
+            cmd.env("exit", "1");
+            cmd
+        });
+    steps.run("greet").success().stdout("hello\n");
+    steps
+        .command("greet")
+        .env("stdout", "bye")
+        .assert()
+        .stdout("bye\n");
+    assert!(steps.run("fail").to_string().contains("step=`fail`"));
+}
+
+#[test]
+#[should_panic(expected = "No command registered as `missing`; registered: greet")]
+fn registry_missing() {
+    assert_cmd::registry::Registry::new()
+        .register("greet", || Command::cargo_bin("bin_fixture").unwrap())
+        .run("missing");
+}
+
+#[test]
+fn timing_example() {
+    std::thread::Builder::new()
+        .name("timing_example".to_owned())
+        .spawn(|| {
+            assert_cmd::timing::report_slower_than(std::time::Duration::from_secs(3600));
+            Command::cargo_bin("bin_fixture")
+                .unwrap()
+                .env("stdout", "hello")
+                .assert()
+                .success();
+            let timings = assert_cmd::timing::timings();
+            assert_eq!(timings.len(), 1);
+            assert!(timings[0].command.contains("bin_fixture"));
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn allow_flaky_example() {
+    let marker = std::env::temp_dir().join(format!("assert_cmd-flaky-{}", std::process::id()));
+    let _ = std::fs::remove_file(&marker);
+    // Fails the first time only.
+    let flakiness = Command::new("sh")
+        .args(["-c", "test -e \"$0\" || { touch \"$0\"; exit 1; }"])
+        .arg(&marker)
+        .allow_flaky(2, |assert| assert.success());
+    std::fs::remove_file(&marker).unwrap();
+    assert_eq!(flakiness.runs, 3);
+    assert_eq!(flakiness.passed, 2);
+    assert!(!flakiness.is_stable());
+}
+
+#[test]
+#[should_panic(expected = "3 of 3 checks failed (1 distinct)")]
+fn allow_flaky_always_fails() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("exit", "1")
+        .allow_flaky(2, |assert| assert.success());
+}
+
+#[cfg(unix)]
+#[test]
+fn write_stdin_chunks_example() {
+    let assert = Command::new("sh")
+        .args(["-c", "while read -r line; do echo \"got $line\"; done"])
+        .write_stdin_chunks(
+            ["one\n", "two\n", "three\n"],
+            std::time::Duration::from_millis(200),
+        )
+        .assert()
+        .success()
+        .stdout("got one\ngot two\ngot three\n");
+    assert!(assert.get_duration().unwrap() >= std::time::Duration::from_millis(400));
+}
+
+#[cfg(unix)]
+#[test]
+fn write_stdin_chunks_timeout() {
+    let assert = Command::new("sleep")
+        .arg("30")
+        .write_stdin_chunks(
+            ["one\n", "two\n", "three\n"],
+            std::time::Duration::from_secs(5),
+        )
+        .timeout(std::time::Duration::from_millis(500))
+        .assert()
+        .interrupted();
+    assert!(assert.get_duration().unwrap() < std::time::Duration::from_secs(4));
+}
+
+#[cfg(unix)]
+#[test]
+fn hold_stdin_open_example() {
+    // Without input, `cat` sees the end of it right away...
+    Command::new("cat")
+        .timeout(std::time::Duration::from_secs(10))
+        .assert()
+        .success();
+    // ...unless `stdin` is held open.
+    Command::new("cat")
+        .hold_stdin_open(true)
+        .timeout(std::time::Duration::from_millis(300))
+        .assert()
+        .interrupted();
+    // Writing input the child never reads counts toward the timeout too.
+    let assert = Command::new("sleep")
+        .arg("30")
+        .write_stdin(vec![b'x'; 1 << 20])
+        .hold_stdin_open(true)
+        .timeout(std::time::Duration::from_millis(300))
+        .assert()
+        .interrupted();
+    assert!(assert.get_duration().unwrap() < std::time::Duration::from_secs(10));
+}
+
+#[cfg(unix)]
+#[test]
+fn retries_example() {
+    let marker = std::env::temp_dir().join(format!("assert_cmd-retries-{}", std::process::id()));
+    let _ = std::fs::remove_file(&marker);
+    // Fails the first time only.
+    Command::new("sh")
+        .args(["-c", "test -e \"$0\" || { touch \"$0\"; exit 1; }"])
+        .arg(&marker)
+        .retries(2)
+        .assert()
+        .success();
+    std::fs::remove_file(&marker).unwrap();
+
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("exit", "1")
+        .env("stderr", "unreachable")
+        .retries(2)
+        .retry_if(|run| run.get_output().stderr.starts_with(b"unreachable"))
+        .assert()
+        .try_success()
+        .unwrap_err();
+    let context = err.context();
+    assert!(context.contains("attempt=`1 of 3, "), "{}", context);
+    assert!(context.contains("attempt=`2 of 3, "), "{}", context);
+    assert!(context.contains("stderr=```\"unreachable"), "{}", context);
+}
+
+#[cfg(unix)]
+#[test]
+fn cached_example() {
+    let cwd = std::env::temp_dir().join(format!("assert_cmd-cached-{}", std::process::id()));
+    std::fs::create_dir_all(&cwd).unwrap();
+    let counter = cwd.with_extension("count");
+    let _ = std::fs::remove_file(&counter);
+    let run = || {
+        Command::new("sh")
+            .args(["-c", "echo run >> \"$0\"; cat \"$0\""])
+            .arg(&counter)
+            .current_dir(&cwd)
+            .cached(true)
+            .assert()
+            .success()
+            .stdout("run\n");
+    };
+    run();
+    run();
+    std::fs::remove_file(&counter).unwrap();
+    std::fs::remove_dir(&cwd).unwrap();
+}
+
+#[test]
+fn cassette_example() {
+    let cassette = std::env::temp_dir().join(format!(
+        "assert_cmd-cassette-{}.cassette",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&cassette);
+    let run = |stdout: &str| {
+        Command::cargo_bin("bin_fixture")
+            .unwrap()
+            .arg("fetch")
+            .env("stdout", stdout)
+            .cassette(&cassette)
+            .assert()
+            .success()
+            .stdout("recorded\n");
+    };
+    run("recorded");
+    // The environment isn't part of the key, so this replays the first run.
+    run("live");
+    let recorded = std::fs::read_to_string(&cassette).unwrap();
+    assert!(recorded.contains("program \"bin_fixture\"\narg \"fetch\"\nstatus 0\n"));
+    std::fs::remove_file(&cassette).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn assert_concurrent_example() {
+    use assert_cmd::cmd::Contention;
+
+    let lock = std::env::temp_dir().join(format!("assert_cmd-lock-{}", std::process::id()));
+    let _ = std::fs::remove_dir(&lock);
+    let instances = Command::new("mkdir")
+        .arg(&lock)
+        .assert_concurrent(3, Contention::OneSucceeds);
+    std::fs::remove_dir(&lock).unwrap();
+    assert_eq!(instances.len(), 3);
+}
+
+#[test]
+#[should_panic(
+    expected = "Concurrent instances didn't match `exactly one succeeds`, 2 of 2 succeeded"
+)]
+fn assert_concurrent_violated() {
+    use assert_cmd::cmd::Contention;
+
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .assert_concurrent(2, Contention::OneSucceeds);
+}
+
+#[cfg(unix)]
+#[test]
+fn assert_cold_warm_example() {
+    Command::new("sh")
+        .args([
+            "-c",
+            "test -e \"$XDG_CACHE_HOME/seen\" && echo hit || { mkdir -p \"$XDG_CACHE_HOME\" && touch \"$XDG_CACHE_HOME/seen\" && echo miss; }",
+        ])
+        .assert_cold_warm(|cold| cold.stdout("miss\n"), |warm| warm.stdout("hit\n"));
+}
+
+assert_cmd::console_test! {
+    /// ```console
+    /// $ stdout=hello bin_fixture
+    /// hello
+    /// $ stdout="[1] two words" exit=3 bin_fixture
+    /// [..] two words
+    /// ? 3
+    /// ```
+    fn console_test_example() {}
+}
+
+#[test]
+fn harness_example() {
+    assert_cmd::harness::TestCases::new()
+        .case("tests/cmd/*.toml")
+        .case("tests/cmd/*.trycmd");
+}
+
+#[test]
+#[should_panic(expected = "1 of 1 cases failed\n--- ")]
+fn harness_failure() {
+    let dir = std::env::temp_dir().join(format!("assert_cmd-harness-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("wrong.toml"),
+        "bin = \"bin_fixture\"\nstdout = \"goodbye\"\n[env]\nstdout = \"hello\"\n",
+    )
+    .unwrap();
+    let mut cases = assert_cmd::harness::TestCases::new();
+    cases.case(dir.join("*.toml"));
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| cases.run()));
+    std::fs::remove_dir_all(&dir).unwrap();
+    std::panic::resume_unwind(result.unwrap_err());
+}
+
+#[test]
+fn matrix_example() {
+    let mut matrix =
+        assert_cmd::matrix::CommandMatrix::new(|| Command::cargo_bin("bin_fixture").unwrap());
+    matrix
+        .env("exit", &["0", "1", "2"])
+        .env("stdout", &["hello", "bye"])
+        .expect_code(|combination| i32::from(combination.contains("exit=1")));
+    assert_eq!(matrix.len(), 6);
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        matrix.assert(|assert| assert.stdout("hello\n"))
+    }));
+    let err = result.unwrap_err();
+    let err = err.downcast_ref::<String>().unwrap();
+    assert!(
+        err.starts_with("4 of 6 combinations failed\ncombination "),
+        "{}",
+        err
+    );
+    assert!(
+        err.contains("\nexit=1 stdout=hello  1         1 "),
+        "{}",
+        err
+    );
+    assert!(
+        err.contains("\nexit=2 stdout=hello  0         2 "),
+        "{}",
+        err
+    );
+    assert!(
+        err.contains("\nexit=0 stdout=bye    0         0, Unexpected stdout"),
+        "{}",
+        err
+    );
+    assert!(err.contains("--- exit=2 stdout=hello ---"), "{}", err);
+    assert!(err.contains("combination=`exit=1 stdout=bye`"), "{}", err);
+    assert!(
+        !err.contains("stdout=hello ---\nUnexpected stdout"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn server_example() {
+    let mut cmd = Command::cargo_bin("bin_fixture").unwrap();
+    cmd.env("stdout", "listening").env("sleep", "60");
+    let start = std::time::Instant::now();
+    let mut server = cmd.spawn_server().unwrap();
+    server.ready_when_stdout("listening\n", std::time::Duration::from_secs(30));
+    server.shutdown().interrupted().stdout("listening\n");
+    assert!(start.elapsed() < std::time::Duration::from_secs(30));
+}
+
+#[test]
+fn server_ready_when_listening() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let mut cmd = Command::cargo_bin("bin_fixture").unwrap();
+    cmd.env("sleep", "60");
+    let mut server = cmd.spawn_server().unwrap();
+    server
+        .ready_when_listening(addr, std::time::Duration::from_secs(5))
+        .on_shutdown(|assert| assert.interrupted());
+}
+
+#[test]
+#[should_panic(expected = "Exited before listening on 127.0.0.1:")]
+fn server_exits_before_listening() {
+    let addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let mut cmd = Command::cargo_bin("bin_fixture").unwrap();
+    cmd.env("exit", "3");
+    cmd.spawn_server()
+        .unwrap()
+        .ready_when_listening(addr, std::time::Duration::from_secs(30));
+}
+
+#[test]
+fn on_failure_example() {
+    let seen = std::rc::Rc::new(std::cell::RefCell::new(None));
+    let handled = seen.clone();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        Command::cargo_bin("bin_fixture")
+            .unwrap()
+            .env("stdout", "hello")
+            .write_stdin("input")
+            .assert()
+            .on_failure(move |failure| *handled.borrow_mut() = Some(failure.clone()))
+            .stdout("goodbye\n");
+    }));
+    assert!(result.is_err());
+    let failure = seen.borrow_mut().take().unwrap();
+    assert_eq!(failure.kind(), assert_cmd::failure::FailureKind::Stdout);
+    assert!(failure.headline().starts_with("Unexpected stdout"));
+    assert!(failure.expected().unwrap().contains("goodbye"));
+    assert_eq!(failure.actual(), Some(&b"hello\n"[..]));
+    assert!(failure.command().unwrap().contains("bin_fixture"));
+    assert_eq!(failure.stdin(), Some(&b"input"[..]));
+}
+
+#[test]
+fn soft_example() {
+    let result = std::panic::catch_unwind(|| {
+        Command::cargo_bin("bin_fixture")
+            .unwrap()
+            .env("stdout", "hello")
+            .env("stderr", "oops")
+            .env("exit", "2")
+            .assert()
+            .soft()
+            .check(|assert| assert.code(0))
+            .check(|assert| assert.stdout("goodbye\n"))
+            .check(|assert| assert.stderr("oops\n"))
+            .check(|assert| assert.stderr(""))
+            .verify();
+    });
+    let err = result.unwrap_err();
+    let err = err.downcast_ref::<String>().unwrap();
+    assert!(
+        err.starts_with("3 of 4 checks failed (3 distinct)"),
+        "{}",
+        err
+    );
+    assert!(err.contains("Unexpected return code"), "{}", err);
+    assert!(err.contains("Unexpected stdout"), "{}", err);
+    assert!(err.contains("Unexpected stderr"), "{}", err);
+}
+
+#[test]
+fn first_mismatch_example() {
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "one\ntoo")
+        .assert()
+        .try_stdout("one\ntwo\n")
+        .unwrap_err();
+    assert!(
+        err.headline()
+            .contains("first mismatch: line 2, column 2: expected 'w', found 'o'"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn parallel_example() {
+    let mut cmds: Vec<_> = (0..3)
+        .map(|i| {
+            let mut cmd = Command::cargo_bin("bin_fixture").unwrap();
+            cmd.env("sleep", "1").env("stdout", i.to_string());
+            cmd
+        })
+        .collect();
+    let start = std::time::Instant::now();
+    let outputs = cmds
+        .iter_mut()
+        .fold(
+            assert_cmd::parallel::Parallel::new().threads(3),
+            |runs, cmd| runs.add(cmd, |assert| assert.success()),
+        )
+        .assert();
+    assert!(start.elapsed() < std::time::Duration::from_millis(2500));
+    let stdouts: Vec<_> = outputs.iter().map(|output| &output.stdout[..]).collect();
+    assert_eq!(stdouts, [b"0\n", b"1\n", b"2\n"]);
+}
+
+#[test]
+#[should_panic(expected = "1 of 2 commands failed\n--- #1 ")]
+fn parallel_failure() {
+    let mut ok = Command::cargo_bin("bin_fixture").unwrap();
+    let mut failing = Command::cargo_bin("bin_fixture").unwrap();
+    failing.env("exit", "1");
+    assert_cmd::parallel::Parallel::new()
+        .add(&mut ok, |assert| assert.success())
+        .add(&mut failing, |assert| assert.success())
+        .assert();
+}
+
+#[test]
+#[should_panic(expected = "Unexpected stdout, expected:\ngoodbye")]
+fn console_transcript_mismatch() {
+    assert_cmd::console::assert_transcript("```console\n$ stdout=hello bin_fixture\ngoodbye\n```");
+}
+
+#[cfg(unix)]
+#[test]
+fn follow_process_group_timeout() {
+    let start = std::time::Instant::now();
+    let err = Command::new("sh")
+        .args(["-c", "sleep 100 >/dev/null 2>&1 &"])
+        .follow_process_group(true)
+        .timeout(std::time::Duration::from_secs(1))
+        .assert()
+        .try_success()
+        .unwrap_err();
+    assert!(err.headline().starts_with("Command timed out"));
+    assert!(start.elapsed() < std::time::Duration::from_secs(50));
+}
+
+#[cfg(unix)]
+#[test]
+fn kill_process_group_timeout() {
+    let start = std::time::Instant::now();
+    let err = Command::new("sh")
+        .args(["-c", "sleep 100 & sleep 100"])
+        .kill_process_group(true)
+        .timeout(std::time::Duration::from_millis(500))
+        .assert()
+        .try_success()
+        .unwrap_err();
+    assert!(err.headline().starts_with("Command timed out"));
+    assert!(start.elapsed() < std::time::Duration::from_secs(50));
+}
+
+#[cfg(unix)]
+#[test]
+fn env_deterministic_example() {
+    let mut env = assert_cmd::env_guard::EnvGuard::new();
+    env.set("TZ", "America/New_York")
+        .set("ENV_DETERMINISTIC_EXAMPLE", "kept");
+    let mut cmd = Command::new("sh");
+    cmd.args([
+        "-c",
+        "echo $TZ $LC_ALL $ENV_DETERMINISTIC_EXAMPLE ${HOME:-unset}",
+    ])
+    .env_deterministic()
+    .env_inherit("ENV_DETERMINISTIC_EXAMPLE");
+    drop(env);
+    cmd.assert().success().stdout("UTC C kept unset\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn coverage_example() {
+    let mut env = assert_cmd::env_guard::EnvGuard::new();
+    env.set("LLVM_PROFILE_FILE", "cov/run.profraw")
+        .set("ASAN_OPTIONS", "detect_leaks=0");
+    let profile = std::env::current_dir().unwrap().join("cov/run-%p.profraw");
+    Command::new("sh")
+        .args(["-c", "echo $LLVM_PROFILE_FILE $ASAN_OPTIONS"])
+        .env_deterministic()
+        .coverage(true)
+        .assert()
+        .success()
+        .stdout(format!("{} detect_leaks=0\n", profile.display()));
+}
+
+#[cfg(unix)]
+#[test]
+fn mocks_example() {
+    use assert_cmd::mock::{Mocks, Reply};
+
+    let mut mocks = Mocks::new();
+    mocks
+        .program("git")
+        .reply(Reply::new().stdout("main\n"))
+        .reply(Reply::new().stderr("fatal: no remote\n").code(128));
+    Command::new("sh")
+        .args([
+            "-c",
+            "git rev-parse --abbrev-ref HEAD; git fetch 'my origin' || echo $?; git fetch",
+        ])
+        .mocks(&mocks)
+        .assert()
+        .code(128)
+        .stdout("main\n128\n")
+        .stderr("fatal: no remote\nfatal: no remote\n");
+    let calls = mocks.calls("git");
+    assert_eq!(calls.len(), 3);
+    assert_eq!(calls[0].args(), ["rev-parse", "--abbrev-ref", "HEAD"]);
+    assert_eq!(calls[1].args(), ["fetch", "my origin"]);
+    assert_eq!(calls[2].cwd(), std::env::current_dir().unwrap());
+}
+
+#[cfg(unix)]
+#[test]
+fn runner_wraps_program() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .runner(["env", "stdout=wrapped"])
+        .env("exit", "0")
+        .assert()
+        .success()
+        .stdout("wrapped\n");
+}
+
+#[test]
+fn spawn_error_classes() {
+    use assert_cmd::exec::ExecFailure;
+
+    let err = Command::new("no-such-program-for-assert-cmd").unwrap_err();
+    let spawn = err.spawn_error().unwrap();
+    assert_eq!(*spawn.failure(), ExecFailure::NotFound);
+    assert!(!spawn.failure().is_build_problem());
+
+    #[cfg(unix)]
+    {
+        let err = Command::new("./Cargo.toml").unwrap_err();
+        assert_eq!(
+            *err.spawn_error().unwrap().failure(),
+            ExecFailure::PermissionDenied
+        );
+    }
+}
+
+#[test]
+#[should_panic(expected = "program not found")]
+fn spawn_failure_message() {
+    Command::new("no-such-program-for-assert-cmd").assert();
+}
+
+#[test]
+fn assert_env_matrix_example() {
+    let runs = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .assert_env_matrix(&[("stdout", &["a", "b"]), ("exit", &["0", "3"])]);
+    let labels: Vec<_> = runs.iter().map(|(label, _)| label.as_str()).collect();
+    assert_eq!(
+        labels,
+        [
+            "stdout=a exit=0",
+            "stdout=a exit=3",
+            "stdout=b exit=0",
+            "stdout=b exit=3"
+        ]
+    );
+    for (label, assert) in runs {
+        let stdout = if label.contains("stdout=a") {
+            "a\n"
+        } else {
+            "b\n"
+        };
+        let code = if label.ends_with("exit=3") { 3 } else { 0 };
+        let assert = assert.code(code).stdout(stdout);
+        assert!(assert.to_string().contains(&format!("env=`{}`", label)));
+    }
+}
+
+#[test]
+fn command_context() {
+    let assert = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .append_context("fixture", "cases/empty.toml")
+        .assert()
+        .success();
+    assert!(assert.to_string().contains("fixture=`cases/empty.toml`\n"));
+}
+
+#[test]
+#[cfg(unix)]
+fn guard_worktree_example() {
+    let dir = std::env::temp_dir().join(format!("assert_cmd-worktree-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("tracked"), "one\n").unwrap();
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(&dir)
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    };
+    let ready = git(&["init", "-q"])
+        && git(&["add", "tracked"])
+        && git(&[
+            "-c",
+            "user.name=test",
+            "-c",
+            "user.email=test@example.com",
+            "commit",
+            "-qm",
+            "initial",
+        ]);
+    if ready {
+        Command::new("true")
+            .current_dir(&dir)
+            .guard_worktree(true)
+            .assert()
+            .success();
+        let err = std::panic::catch_unwind(|| {
+            Command::new("sh")
+                .args(["-c", "echo two >> tracked"])
+                .current_dir(&dir)
+                .guard_worktree(true)
+                .assert();
+        })
+        .unwrap_err();
+        let err = err.downcast_ref::<String>().unwrap();
+        assert!(
+            err.starts_with("Command modified tracked files in `"),
+            "{}",
+            err
+        );
+        assert!(err.contains("`:\n  tracked\n"), "{}", err);
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn comparison_example() {
+    let run = |stdout: &str, exit: &str| {
+        Command::cargo_bin("bin_fixture")
+            .unwrap()
+            .env("stdout", stdout)
+            .env("exit", exit)
+            .assert()
+    };
+    let mut runs = assert_cmd::compare::Comparison::new();
+    runs.insert("before", run("rows=3", "0"))
+        .insert("after", run("rows=3", "1"))
+        .insert("changed", run("rows=4", "0"));
+    runs.same_stdout("before", "after")
+        .codes_differ("before", "after")
+        .same_code("before", "changed");
+
+    let err = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        runs.same_stdout("before", "changed");
+    }))
+    .unwrap_err();
+    let err = err.downcast_ref::<String>().unwrap();
+    assert!(
+        err.starts_with("Unexpected stdout, `before` and `changed` differ\n"),
+        "{}",
+        err
+    );
+    assert!(err.contains("run=`before`\n"), "{}", err);
+    assert!(err.contains("run=`changed`\n"), "{}", err);
+}
+
+#[test]
+fn nul_records_example() {
+    Command::new("printf")
+        .arg("a b\\0c\\nd\\0")
+        .assert()
+        .stdout_nul_records(["a b", "c\nd"])
+        .stdout_nul_records_unordered(["c\nd", "a b"]);
+    Command::new("printf")
+        .arg("\\0")
+        .assert()
+        .stdout_nul_records([""]);
+    Command::new("true")
+        .assert()
+        .stdout_nul_records(Vec::<&str>::new());
+
+    let err = Command::new("printf")
+        .arg("a\\0b")
+        .assert()
+        .try_assert(|assert| assert.stdout_nul_records(["a", "c"]))
+        .unwrap_err();
+    assert_eq!(
+        err.headline(),
+        "Unexpected record 1 in stdout, expected \"c\", got \"b\"\nrecords=[\"a\", \"b\"]"
+    );
+    let err = Command::new("printf")
+        .arg("a\\0b")
+        .assert()
+        .try_assert(|assert| assert.stdout_nul_records_unordered(["b", "c"]))
+        .unwrap_err();
+    assert!(
+        err.headline().starts_with(
+            "Unexpected NUL-delimited records in stdout, missing [\"c\"], unexpected [\"a\"]"
+        ),
+        "{}",
+        err.headline()
+    );
+}
+
+#[test]
+fn pattern_example() {
+    use assert_cmd::pattern::pattern;
+
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "tool 1.2.3\nbuilt at 12:03:44\nready")
+        .assert()
+        .stdout(pattern("tool [..]\n...\nready"));
+
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "tool 1.2.3")
+        .assert()
+        .try_stdout(pattern("cli [..]"))
+        .unwrap_err();
+    assert!(
+        err.headline().contains("var matches pattern \"cli [..]\""),
+        "{}",
+        err.headline()
+    );
+}
+
+#[test]
+fn in_order_example() {
+    use assert_cmd::pattern::in_order;
+
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "Loading config\nCompiling demo\nDone in 2s")
+        .assert()
+        .stdout(in_order(["Loading", "Compiling", "Done"]));
+
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "Loading config\nDone in 2s")
+        .assert()
+        .try_stdout(in_order(["Loading", "Compiling", "Done"]))
+        .unwrap_err();
+    assert!(
+        err.headline()
+            .contains("step 2: \"Compiling\" not found after line 1 (byte 7)"),
+        "{}",
+        err.headline()
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn decode_example() {
+    use assert_cmd::encoding::Encoding;
+
+    // `\xFF\xFE` marks little-endian UTF-16
+    let assert = Command::new("printf")
+        .arg("\\377\\376o\\000k\\000")
+        .assert()
+        .decode_stdout(Encoding::Auto)
+        .stdout("ok");
+    assert!(
+        assert
+            .to_string()
+            .contains("decoded=`stdout from UTF-16LE`"),
+        "{}",
+        assert
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn shell_example() {
+    use assert_cmd::platform::shell_quote;
+
+    Command::shell("echo hello | tr a-z A-Z")
+        .assert()
+        .success()
+        .stdout("HELLO\n");
+
+    let arg = "it's a *";
+    Command::shell(format!("printf '%s' {}", shell_quote(arg)))
+        .assert()
+        .stdout(arg);
+}
+
+#[cfg(unix)]
+#[test]
+fn sandbox_example() {
+    use assert_cmd::sandbox::Sandbox;
+
+    let sandbox = Sandbox::new();
+    let config = sandbox.config_dir().join("tool");
+    std::fs::create_dir_all(&config).unwrap();
+    std::fs::write(config.join("config"), "color = false\n").unwrap();
+
+    Command::new("sh")
+        .args(["-c", "cat \"$XDG_CONFIG_HOME/tool/config\"; echo \"$HOME\""])
+        .sandbox_home(&sandbox)
+        .assert()
+        .success()
+        .stdout(format!("color = false\n{}\n", sandbox.home().display()));
+
+    let home = sandbox.home().to_owned();
+    assert!(sandbox.cache_dir().is_dir());
+    drop(sandbox);
+    assert!(!home.exists());
+}
+
+#[test]
+fn matches_example() {
+    use assert_cmd::matches::matches;
+
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "tool\nversion 1.2.3")
+        .assert()
+        .stdout(matches(r"^version \d+\.\d+\.\d+$").multi_line(true))
+        .stdout(matches(r"tool\n.*\n").whole_output(true));
+
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "version 1.2")
+        .assert()
+        .try_stdout(matches(r"^version \d+\.\d+\.\d+$").multi_line(true))
+        .unwrap_err();
+    assert!(
+        err.headline().contains("var matches regex"),
+        "{}",
+        err.headline()
+    );
+}
+
+#[test]
+fn assert_requires_tty_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stderr", "error: a terminal is required")
+        .env("exit", "2")
+        .assert_requires_tty()
+        .code(2);
+
+    let err = std::panic::catch_unwind(|| {
+        Command::new("sleep")
+            .arg("100")
+            .timeout(std::time::Duration::from_millis(100))
+            .assert_requires_tty();
+    })
+    .unwrap_err();
+    let err = err.downcast_ref::<String>().unwrap();
+    assert!(err.contains("rather than failing fast"), "{}", err);
+    assert!(err.contains("terminal=`none`"), "{}", err);
+
+    let err = std::panic::catch_unwind(|| {
+        Command::cargo_bin("bin_fixture")
+            .unwrap()
+            .env("exit", "1")
+            .assert_requires_tty();
+    })
+    .unwrap_err();
+    let err = err.downcast_ref::<String>().unwrap();
+    assert!(err.contains("didn't say why on stderr"), "{}", err);
+}
+
+#[test]
+fn redact_example() {
+    let temp = std::env::temp_dir();
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", format!("wrote {} in 15ms", temp.display()))
+        .redact("[TEMPDIR]", &temp)
+        .redact_regex("[DURATION]", r"\d+ms")
+        .assert()
+        .stdout("wrote [TEMPDIR] in [DURATION]\n")
+        .try_stdout("other\n")
+        .unwrap_err();
+    let err = err.to_string();
+    assert!(
+        err.contains("stdout=```\"wrote [TEMPDIR] in [DURATION]\\n\"```"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn strip_ansi_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "\x1b[1;32mok\x1b[0m in 15ms")
+        .env("stderr", "\x1b[33mwarning\x1b[0m")
+        .strip_ansi(true)
+        .redact_regex("[DURATION]", r"\d+ms")
+        .assert()
+        .stdout("ok in [DURATION]\n")
+        .stderr("warning\n");
+}
+
+#[test]
+fn env_guard_example() {
+    let mut env = assert_cmd::env_guard::EnvGuard::new();
+    env.set("stdout", "inherited");
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .assert()
+        .stdout("inherited\n");
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "overridden")
+        .assert()
+        .stdout("overridden\n");
+    drop(env);
+
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .assert()
+        .stdout("");
+}
+
+#[cfg(unix)]
+#[test]
+fn spawn_example() {
+    use predicates::prelude::*;
+    use std::time::Duration;
+
+    let timeout = Duration::from_secs(10);
+    let mut cmd = Command::new("sh");
+    cmd.args([
+        "-c",
+        "echo ready; read a; echo \"got $a\"; read b; echo \"got $b\" >&2",
+    ]);
+    let mut session = cmd.spawn().unwrap();
+    session
+        .expect_stdout("ready\n", timeout)
+        .send_line("one")
+        .expect_stdout(predicate::str::contains("got one"), timeout)
+        .send_line("two")
+        .expect_stderr("got two\n", timeout)
+        .close_stdin();
+    session
+        .assert()
+        .success()
+        .stdout("ready\ngot one\n")
+        .stderr("got two\n");
+
+    let err = std::panic::catch_unwind(|| {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "echo bye"]);
+        let mut session = cmd.spawn().unwrap();
+        session.expect_stdout("hello\n", timeout);
+    })
+    .unwrap_err();
+    let err = err.downcast_ref::<String>().unwrap();
+    assert!(err.starts_with("stdout closed before matching"), "{}", err);
+    assert!(err.contains("unmatched=```\"bye\\n\"```"), "{}", err);
+}
+
+#[cfg(unix)]
+#[test]
+fn send_signal_example() {
+    use predicates::prelude::*;
+    use std::time::Duration;
+
+    const SIGTERM: i32 = 15;
+
+    let timeout = Duration::from_secs(10);
+    let mut cmd = Command::new("sh");
+    cmd.args([
+        "-c",
+        "trap 'echo shutting down; exit 0' TERM; echo ready; while :; do sleep 0.1; done",
+    ]);
+    let mut session = cmd.spawn().unwrap();
+    assert!(0 < session.pid());
+    session
+        .expect_stdout("ready\n", timeout)
+        .send_signal(SIGTERM);
+    let assert = session
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("shutting down"));
+    let report = assert.to_string();
+    assert!(
+        report.contains("signals sent=`SIGTERM (15) at "),
+        "{}",
+        report
+    );
+
+    let err = std::panic::catch_unwind(|| {
+        let mut cmd = Command::new("true");
+        let mut session = cmd.spawn().unwrap();
+        session.expect_stdout("", timeout);
+        std::thread::sleep(Duration::from_millis(500));
+        session.send_signal(SIGTERM);
+    })
+    .unwrap_err();
+    let err = err.downcast_ref::<String>().unwrap();
+    assert!(
+        err.starts_with("Unable to send SIGTERM (15): the child already exited"),
+        "{}",
+        err
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn terminate_after_example() {
+    use std::time::Duration;
+
+    const SIGTERM: i32 = 15;
+
+    Command::new("sh")
+        .args([
+            "-c",
+            "trap 'echo shutting down; exit 0' TERM; echo ready; while :; do sleep 0.1; done",
+        ])
+        .terminate_after(Duration::from_millis(300), SIGTERM)
+        .assert()
+        .success()
+        .stdout("ready\nshutting down\n");
+
+    // Exiting first, the signal isn't sent
+    let assert = Command::new("true")
+        .terminate_after(Duration::from_secs(5), SIGTERM)
+        .assert()
+        .success();
+    assert!(!assert.to_string().contains("terminated="), "{}", assert);
+}
+
+#[cfg(unix)]
+#[test]
+fn terminate_after_paced_stdin() {
+    use std::time::Duration;
+
+    const SIGTERM: i32 = 15;
+
+    let assert = Command::new("sh")
+        .args([
+            "-c",
+            "trap 'echo shutting down; exit 0' TERM; while :; do sleep 0.1; done",
+        ])
+        .write_stdin_chunks(["one\n", "two\n", "three\n"], Duration::from_secs(5))
+        .terminate_after(Duration::from_millis(300), SIGTERM)
+        .assert()
+        .success()
+        .stdout("shutting down\n");
+    assert!(assert.get_duration().unwrap() < Duration::from_secs(4));
+}
+
+#[cfg(unix)]
+#[test]
+fn terminate_after_killed() {
+    use std::time::Duration;
+
+    const SIGTERM: i32 = 15;
+
+    let assert = Command::new("sh")
+        .args(["-c", "trap '' TERM; while :; do sleep 0.1; done"])
+        .terminate_after(Duration::from_millis(200), SIGTERM)
+        .timeout(Duration::from_secs(1))
+        .assert()
+        .interrupted();
+    let report = assert.to_string();
+    assert!(
+        report.contains("terminated=`SIGTERM (15) after 200ms, then killed`"),
+        "{}",
+        report
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn wait_for_example() {
+    use predicates::prelude::*;
+    use std::time::Duration;
+
+    let mut cmd = Command::new("sh");
+    cmd.args([
+        "-c",
+        "sleep 1; echo 'listening on 8080' >&2; cat >/dev/null",
+    ]);
+    let mut session = cmd.spawn().unwrap();
+    assert!(!session.wait_for_stderr(
+        predicate::str::contains("listening on"),
+        Duration::from_millis(100)
+    ));
+    assert!(session.wait_for_stderr(
+        predicate::str::contains("listening on"),
+        Duration::from_secs(10)
+    ));
+    session.close_stdin();
+    assert!(!session.wait_for_stdout(predicate::str::contains("never"), Duration::from_secs(10)));
+    session.assert().success().stderr("listening on 8080\n");
+}
+
+#[test]
+fn timings_example() {
+    use std::time::Duration;
+
+    let assert = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "ready")
+        .env("sleep", "1")
+        .assert()
+        .success()
+        .first_stdout_within(Duration::from_secs(1))
+        .first_output_within(Duration::from_secs(1));
+    let timings = assert.transcript().timings().unwrap();
+    assert!(
+        timings.first_stdout.unwrap() < timings.exit,
+        "{:?}",
+        timings
+    );
+    assert_eq!(timings.first_stderr, None);
+
+    let err = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        assert.first_stderr_within(Duration::from_secs(5));
+    }))
+    .unwrap_err();
+    let err = err.downcast_ref::<String>().unwrap();
+    assert!(
+        err.starts_with("Unexpected first stderr latency, nothing was written"),
+        "{}",
+        err
+    );
+}
+
+#[cfg(all(unix, feature = "pty"))]
+#[test]
+fn pty_example() {
+    Command::new("sh")
+        .args(["-c", "test -t 1 && echo tty; test -t 2 || echo piped >&2"])
+        .pty(true)
+        .assert()
+        .success()
+        .stdout("tty\n")
+        .stderr("piped\n");
+
+    Command::new("sh")
+        .args(["-c", "test -t 1 || echo piped"])
+        .pty(false)
+        .assert()
+        .stdout("piped\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn stream_stdout_example() {
+    use std::sync::{Arc, Mutex};
+
+    let counted = Arc::new(Mutex::new(0));
+    let sink = counted.clone();
+    let err = Command::new("sh")
+        .args(["-c", "head -c 200000 /dev/zero"])
+        .stream_stdout(move |chunk| *sink.lock().unwrap() += chunk.len())
+        .assert()
+        .success()
+        .try_stdout("")
+        .unwrap_err();
+    assert_eq!(*counted.lock().unwrap(), 200000);
+    assert_eq!(err.get_output().stdout.len(), assert_cmd::cmd::STREAM_TAIL);
+    assert!(
+        err.to_string()
+            .contains("streamed=`stdout, kept the last 65536 of 200000 bytes`"),
+        "{}",
+        err.context()
+    );
+}
+
+#[test]
+fn tee_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "mirrored")
+        .env("stderr", "also mirrored")
+        .tee(true)
+        .assert()
+        .success()
+        .stdout("mirrored\n")
+        .stderr("also mirrored\n");
+}
+
+#[test]
+fn duration_example() {
+    use predicates::prelude::*;
+    use std::time::Duration;
+
+    let assert = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("sleep", "1")
+        .assert()
+        .success()
+        .duration(predicate::ge(Duration::from_secs(1)))
+        .completes_within(Duration::from_secs(30));
+    assert!(Duration::from_secs(1) <= assert.get_duration().unwrap());
+
+    let err = assert
+        .try_assert(|assert| assert.completes_within(Duration::from_millis(100)))
+        .unwrap_err();
+    assert!(
+        err.headline().starts_with("Command took "),
+        "{}",
+        err.headline()
+    );
+}
+
+#[test]
+fn timeout_env_example() {
+    // The default is read once per process, so check it in a fresh one.
+    if std::env::var_os("TIMEOUT_ENV_EXAMPLE").is_some() {
+        let err = Command::cargo_bin("bin_fixture")
+            .unwrap()
+            .env("sleep", "100")
+            .assert()
+            .try_success()
+            .unwrap_err();
+        assert!(
+            err.headline().starts_with("Command timed out after 500ms"),
+            "{}",
+            err.headline()
+        );
+        return;
+    }
+    Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", "timeout_env_example"])
+        .env("TIMEOUT_ENV_EXAMPLE", "1")
+        .env(assert_cmd::cmd::TIMEOUT_ENV, "0.5")
+        .timeout(std::time::Duration::from_secs(60))
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("1 passed"));
+}
+
+#[test]
+fn matches_path_example() {
+    let path = std::env::temp_dir().join(format!("assert_cmd-result-{}.toml", std::process::id()));
+    std::fs::write(
+        &path,
+        "status.code = 2\nstdout = '''\nhello\n'''\nstderr = \"\"\n",
+    )
+    .unwrap();
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello")
+        .env("exit", "2")
+        .assert()
+        .failure()
+        .matches_path(&path);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn capture_to_files_example() {
+    use std::io::BufRead;
+
+    let assert = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello")
+        .env("stderr", "world")
+        .capture_to_files(true)
+        .assert()
+        .success()
+        .stdout("")
+        .stdout_file_size(predicates::ord::eq(6))
+        .stdout_file_sha256("5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03")
+        .stderr_file_check(|reader| reader.lines().map(Result::unwrap).eq(["world"]));
+    let path = assert.stdout_path().unwrap();
+    assert!(path.exists());
+    drop(assert);
+    assert!(!path.exists());
+}
+
+#[test]
+#[should_panic(expected = "Unexpected size of stdout file")]
+fn capture_to_files_size() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello")
+        .capture_to_files(true)
+        .assert()
+        .stdout_file_size(predicates::ord::gt(100));
+}
+
+#[test]
+fn hooks_example() {
+    let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let spawned = seen.clone();
+    let exited = seen.clone();
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .before_spawn(move |cmd| {
+            spawned.lock().unwrap().push("spawn".to_owned());
+            cmd.env("stdout", "from hook");
+        })
+        .after_output(move |assert| {
+            let stdout = String::from_utf8_lossy(&assert.get_output().stdout).into_owned();
+            exited.lock().unwrap().push(stdout);
+        })
+        .assert()
+        .success()
+        .stdout("from hook\n");
+    assert_eq!(*seen.lock().unwrap(), ["spawn", "from hook\n"]);
+}
+
+#[test]
+fn global_hooks_example() {
+    // Hooks apply to every command in the process, so check them in a fresh one.
+    if std::env::var_os("GLOBAL_HOOKS_EXAMPLE").is_some() {
+        assert_cmd::hooks::before_spawn(|cmd| {
+            cmd.env("stdout", "global");
+        });
+        assert_cmd::hooks::after_output(|assert| {
+            assert!(assert.get_output().status.success());
+        });
+        Command::cargo_bin("bin_fixture")
+            .unwrap()
+            .assert()
+            .stdout("global\n");
+        assert_cmd::hooks::reset();
+        Command::cargo_bin("bin_fixture")
+            .unwrap()
+            .assert()
+            .stdout("");
+        return;
+    }
+    Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", "global_hooks_example"])
+        .env("GLOBAL_HOOKS_EXAMPLE", "1")
+        .timeout(std::time::Duration::from_secs(60))
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("1 passed"));
+}
+
+#[test]
+fn idempotent_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "formatted")
+        .assert_idempotent(3)
+        .stdout("formatted\n");
+}
+
+#[cfg(unix)]
+#[test]
+#[should_panic(expected = "Unexpected stdout on run 2 of 3, differs from run 1")]
+fn idempotent_diverges() {
+    let marker = std::env::temp_dir().join(format!("assert_cmd-idempotent-{}", std::process::id()));
+    let _ = std::fs::remove_file(&marker);
+    Command::new("sh")
+        .args([
+            "-c",
+            "if [ -e \"$0\" ]; then echo unchanged; else touch \"$0\"; echo reformatted; fi",
+        ])
+        .arg(&marker)
+        .assert_idempotent(3);
+}
+
+#[test]
+fn spec_example() {
+    use assert_cmd::spec::CommandSpec;
+
+    let mut base = CommandSpec::cargo_bin("bin_fixture").unwrap();
+    base.env("stdout", "base").env("exit", "0");
+    let mut failing = base.clone();
+    failing.env("exit", "3");
+
+    base.assert().success().stdout("base\n");
+    base.command()
+        .env("stdout", "changed")
+        .assert()
+        .stdout("changed\n");
+    failing.assert().code(3).stdout("base\n");
+    base.assert().success();
+}
+
+#[test]
+fn cargo_bin_as_example() {
+    use assert_cmd::cargo::Stage;
+
+    for stage in [Stage::Link, Stage::Copy] {
+        let mut cmd = Command::cargo_bin_as("bin_fixture", "fixture-alias", stage).unwrap();
+        let assert = cmd
+            .env("stdout", "staged")
+            .assert()
+            .success()
+            .stdout("staged\n");
+        let program = assert.invocation().unwrap().program().to_owned();
+        assert_eq!(
+            program.file_name().unwrap(),
+            format!("fixture-alias{}", std::env::consts::EXE_SUFFIX).as_str()
+        );
+        drop(cmd);
+        assert!(!program.exists());
+    }
+}
+
+#[test]
+fn tolerant_example() {
+    use assert_cmd::settings::Tolerance;
+
+    let path = std::env::temp_dir().join(format!("assert_cmd-tolerant-{}.txt", std::process::id()));
+    std::fs::write(&path, "a\n\nb").unwrap();
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "a \t\n\n\n\nb\n")
+        .assert()
+        .success()
+        .stdout_tolerant(
+            "a\n\nb",
+            Tolerance::new()
+                .trailing_whitespace()
+                .blank_lines()
+                .final_newline(),
+        )
+        .stdout_matches_path_tolerant(
+            &path,
+            Tolerance::new()
+                .trailing_whitespace()
+                .blank_lines()
+                .final_newline(),
+        );
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+#[should_panic(expected = "Unexpected stdout (tolerating final newline)")]
+fn tolerant_mismatch() {
+    use assert_cmd::settings::Tolerance;
+
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "a  ")
+        .assert()
+        .stdout_tolerant("a", Tolerance::new().final_newline());
+}
+
+#[test]
+fn report_example() {
+    // The report applies to every command in the process, so check it in a fresh one.
+    if std::env::var_os(assert_cmd::report::ENV_VAR).is_some() {
+        Command::cargo_bin("bin_fixture")
+            .unwrap()
+            .env("stdout", "hello")
+            .assert()
+            .success();
+        let failed = std::panic::catch_unwind(|| {
+            Command::cargo_bin("bin_fixture")
+                .unwrap()
+                .env("exit", "2")
+                .assert()
+                .success();
+        });
+        assert!(failed.is_err());
+        return;
+    }
+    let path = std::env::temp_dir().join(format!("assert_cmd-report-{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", "report_example"])
+        .env(assert_cmd::report::ENV_VAR, &path)
+        .timeout(std::time::Duration::from_secs(60))
+        .assert()
+        .success();
+    let report = std::fs::read_to_string(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    let lines: Vec<_> = report.lines().collect();
+    assert_eq!(lines.len(), 2, "{}", report);
+    assert!(
+        lines[0].starts_with(r#"{"test":"report_example","#),
+        "{}",
+        report
+    );
+    assert!(
+        lines[0].contains(r#""passed":true,"code":0,"#),
+        "{}",
+        report
+    );
+    assert!(
+        lines[0].ends_with(r#""stdout":"hello\n","stderr":""}"#),
+        "{}",
+        report
+    );
+    assert!(
+        lines[1].contains(r#""passed":false,"code":2,"#),
+        "{}",
+        report
+    );
+    assert!(
+        lines[1].contains(r#""failure":"Unexpected failure.\ncode=2\n"#),
+        "{}",
+        report
+    );
+}
+
+#[test]
+fn echo_example() {
+    use predicates::prelude::*;
+
+    if std::env::var_os(assert_cmd::cmd::ECHO_ENV).is_some() {
+        Command::cargo_bin("bin_fixture")
+            .unwrap()
+            .arg("--flag")
+            .env("stdout", "hello")
+            .write_stdin("input")
+            .assert()
+            .success();
+        Command::cargo_bin("bin_fixture")
+            .unwrap()
+            .arg("--quiet")
+            .echo(false)
+            .assert()
+            .success();
+        return;
+    }
+    Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", "echo_example", "--nocapture"])
+        .env(assert_cmd::cmd::ECHO_ENV, "1")
+        .timeout(std::time::Duration::from_secs(60))
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "bin_fixture\" \"--flag\"\n  cwd: ",
+        ))
+        .stderr(predicate::str::contains(
+            "\n  env: stdout=hello\n  stdin: \"input\"\n",
+        ))
+        .stderr(predicate::str::contains("--quiet").not());
+}
+
+#[cfg(unix)]
+#[test]
+fn strict_stderr_internal_runs() {
+    // Strict mode applies to every `Assert` in the process, so check it in a fresh one.
+    if std::env::var_os("STRICT_STDERR_INTERNAL_RUNS").is_some() {
+        let marker =
+            std::env::temp_dir().join(format!("assert_cmd-strict-retries-{}", std::process::id()));
+        let _ = std::fs::remove_file(&marker);
+        // The failed attempt's `stderr` is discarded with it.
+        Command::new("sh")
+            .args([
+                "-c",
+                "test -e \"$0\" || { touch \"$0\"; echo flaky >&2; exit 1; }",
+            ])
+            .arg(&marker)
+            .retries(1)
+            .assert()
+            .success()
+            .stderr("");
+        std::fs::remove_file(&marker).unwrap();
+
+        Command::cargo_bin("bin_fixture")
+            .unwrap()
+            .env("stderr", "warning")
+            .assert_idempotent(3)
+            .stderr("warning\n");
+        return;
+    }
+    Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", "strict_stderr_internal_runs"])
+        .env("STRICT_STDERR_INTERNAL_RUNS", "1")
+        .env("ASSERT_CMD_STRICT_STDERR", "1")
+        .timeout(std::time::Duration::from_secs(60))
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("1 passed"));
+}
+
+#[test]
+fn spawn_dropped() {
+    let start = std::time::Instant::now();
+    let mut cmd = Command::cargo_bin("bin_fixture").unwrap();
+    cmd.env("sleep", "100");
+    let session = cmd.spawn().unwrap();
+    drop(session);
+    assert!(start.elapsed() < std::time::Duration::from_secs(50));
+}